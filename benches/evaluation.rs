@@ -0,0 +1,22 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use taz::bench::{workloads, Strategy};
+
+/// Benchmark each workload of Taz with each strategy of evaluation
+fn bench_workloads(criterion: &mut Criterion) {
+    for workload in workloads() {
+        let mut group = criterion.benchmark_group(workload.name);
+
+        for strategy in Strategy::ALL {
+            group.bench_with_input(
+                BenchmarkId::from_parameter(strategy.name()),
+                &strategy,
+                |bencher, &strategy| bencher.iter(|| workload.run(strategy)),
+            );
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_workloads);
+criterion_main!(benches);
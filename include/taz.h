@@ -0,0 +1,54 @@
+/*
+ * C interface of Taz, built with ffi feature:
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ *     cargo rustc --release --features ffi --crate-type staticlib
+ *
+ * Functions returning an int give 0 on success and -1 on failure,
+ * the message of the failure being given by taz_last_error.
+ * A panic inside Taz is reported as a failure instead of unwinding into C code.
+ */
+
+#ifndef TAZ_H
+#define TAZ_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Expression compiled by taz_compile */
+typedef struct TazExpression TazExpression;
+
+/* Message of last error occured on current thread, or NULL if no error occured.
+ * Message stays valid until next failing call on current thread. */
+const char *taz_last_error(void);
+
+/* Evaluate expression with nb_variables variables whose names and values are given by arrays,
+ * storing its value in result. */
+int taz_evaluate(const char *expression, const char *const *names, const double *values,
+                 size_t nb_variables, double *result);
+
+/* Compile expression, giving NULL on failure. Compiled expression is released with taz_compiled_free. */
+TazExpression *taz_compile(const char *expression);
+
+/* Number of variables of compiled expression */
+size_t taz_compiled_nb_variables(const TazExpression *compiled);
+
+/* Name of variable of compiled expression at slot, or NULL if slot is out of range.
+ * Name stays valid until compiled expression is released. */
+const char *taz_compiled_variable(const TazExpression *compiled, size_t slot);
+
+/* Evaluate compiled expression with nb_values values of variables ordered by slot, storing its value in result. */
+int taz_compiled_evaluate(const TazExpression *compiled, const double *values, size_t nb_values,
+                          double *result);
+
+/* Release compiled expression. Nothing is done if it is NULL. */
+void taz_compiled_free(TazExpression *compiled);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TAZ_H */
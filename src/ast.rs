@@ -0,0 +1,783 @@
+#![allow(clippy::needless_return)]
+
+use super::canonical;
+use super::converter;
+use super::differentiation;
+use super::functions::Function;
+use super::limits::Limits;
+use super::operators::{BinaryOperator, UnaryOperator};
+use super::simplification::{self, SimplificationRules};
+use super::token::Token;
+use super::tokenizer::Infix;
+
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Node of abstract syntax tree of an expression
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum Node {
+    Number(f64),
+    Constant(String, f64),
+    Variable(String),
+    Array(Vec<f64>),
+    Unary(UnaryOperator, Box<Node>),
+    Binary(BinaryOperator, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+    Tuple(Vec<Node>),
+}
+
+/// Names referenced by an expression, sorted by name
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Uses {
+    /// Predefined functions called by expression
+    pub functions: BTreeSet<String>,
+    /// Predefined constants used by expression
+    pub constants: BTreeSet<String>,
+    /// Functions defined by user called by expression
+    pub custom_functions: BTreeSet<String>,
+    /// Variables whose values are needed to evaluate expression
+    pub variables: BTreeSet<String>,
+}
+
+/// Expression simplified by rules, with the assumptions made by rules on values of its subexpressions
+#[derive(Debug, PartialEq, Clone)]
+pub struct Simplification {
+    /// Simplified expression
+    pub ast: Ast,
+    /// Assumptions made by rules, like "x is not 0" when x / x is simplified to 1
+    pub assumptions: Vec<String>,
+}
+
+/// Abstract syntax tree of an expression, giving the structure of expression
+/// without evaluating it
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ast {
+    pub(crate) root: Node,
+}
+
+impl Ast {
+    /// Parse expression given in argument into abstract syntax tree.
+    /// Each word which is neither a constant nor a function is a variable.
+    /// Expression is parsed with default limits, and depth of tree cannot exceed their nesting of parenthesis,
+    /// so methods walking the tree cannot overflow the stack.
+    ///
+    /// If expression is not valid, an error message is stored in string contained in Result output
+    pub fn new(expression: &str) -> Result<Ast, String> {
+        let limits: Limits = Limits::default();
+        let mut names: Vec<String> = Vec::new();
+        let mut constant_names: VecDeque<String> = VecDeque::new();
+        let mut tokens: Vec<Token> = Vec::new();
+
+        let mut infix = Infix::new(expression, |name: &str| {
+            match names.iter().position(|slot_name| slot_name == name) {
+                Some(slot) => Some(Token::Variable(slot)),
+                None => {
+                    names.push(String::from(name));
+                    Some(Token::Variable(names.len() - 1))
+                }
+            }
+        })
+        .with_limits(limits);
+
+        while let Some(token) = infix.next() {
+            let token: Token = token?;
+
+            // Constants keep their relative order in postfix expression
+            if let Token::Constant(_) = token {
+                constant_names.push_back(String::from(infix.last_word()));
+            }
+
+            tokens.push(token);
+        }
+
+        drop(infix);
+
+        let (postfix_tokens, nb_elements) = converter::tuple_to_postfix(tokens)?;
+        let error: String = String::from("Missing operand to build syntax tree");
+        let mut stack: Vec<Node> = Vec::with_capacity(postfix_tokens.len());
+        let mut depths: Vec<usize> = Vec::with_capacity(postfix_tokens.len());
+
+        // Number of arguments of aggregation function, given by array token preceding it
+        let mut nb_args: Option<usize> = None;
+
+        for (index, &token) in postfix_tokens.iter().enumerate() {
+            let node: Node = match token {
+                Token::Number(number) => Node::Number(number),
+                Token::Constant(value) => {
+                    Node::Constant(constant_names.pop_front().ok_or(error.clone())?, value)
+                }
+                Token::Variable(slot) => Node::Variable(names[slot].clone()),
+                Token::Array(length) if matches!(postfix_tokens.get(index + 1), Some(Token::Function(fun)) if fun.is_variadic()) =>
+                {
+                    nb_args = Some(length);
+                    continue;
+                }
+                Token::Array(length) => {
+                    if stack.len() < length {
+                        return Err(error);
+                    }
+
+                    let values: Vec<f64> = stack
+                        .drain(stack.len() - length..)
+                        .map(|node| match node {
+                            Node::Number(number) => Ok(number),
+                            _ => Err(String::from("Cannot parse breakpoint array")),
+                        })
+                        .collect::<Result<Vec<f64>, String>>()?;
+
+                    Node::Array(values)
+                }
+                Token::UnaryOperator(ops) => {
+                    let operand: Node = stack.pop().ok_or(error.clone())?;
+                    Node::Unary(ops, Box::new(operand))
+                }
+                Token::BinaryOperator(ops) => {
+                    let right: Node = stack.pop().ok_or(error.clone())?;
+                    let left: Node = stack.pop().ok_or(error.clone())?;
+                    Node::Binary(ops, Box::new(left), Box::new(right))
+                }
+                Token::Function(fun) => {
+                    let arity: usize = nb_args.take().unwrap_or(fun.arity());
+
+                    if stack.len() < arity {
+                        return Err(error);
+                    }
+
+                    Node::Call(fun, stack.split_off(stack.len() - arity))
+                }
+                // Conditional operator is represented by select function taking the same values
+                Token::Question | Token::Colon | Token::ShortCircuit(_) => continue,
+                Token::EndConditional => {
+                    if stack.len() < 3 {
+                        return Err(error);
+                    }
+
+                    Node::Call(Function::Select, stack.split_off(stack.len() - 3))
+                }
+                _ => return Err(String::from("Token non-accepted in syntax tree")),
+            };
+
+            // Depth of node is one more than the deepest of its operands, taken from stack
+            let depth: usize = depths.drain(stack.len()..).max().unwrap_or(0) + 1;
+
+            if depth > limits.max_nesting {
+                return Err(format!(
+                    "Expression exceeds limit of {} nested operations",
+                    limits.max_nesting
+                ));
+            }
+
+            depths.push(depth);
+            stack.push(node);
+        }
+
+        if stack.len() != nb_elements || stack.is_empty() {
+            return Err(String::from(
+                "Expression does not reduce to a single value per element",
+            ));
+        }
+
+        let root: Node = match nb_elements {
+            1 => stack.pop().unwrap(),
+            _ => Node::Tuple(stack),
+        };
+
+        return Ok(Ast { root });
+    }
+
+    /// Get names of functions, constants and variables referenced by expression,
+    /// so only the inputs needed by expression can be computed
+    pub fn uses(&self) -> Uses {
+        let mut uses: Uses = Uses::default();
+        collect_uses(&self.root, &mut uses);
+
+        return uses;
+    }
+
+    /// Write syntax tree as a Mermaid flowchart, which can be rendered natively
+    /// by Markdown documents and issue trackers supporting Mermaid diagrams
+    pub fn to_mermaid(&self) -> String {
+        let mut lines: Vec<String> = vec![String::from("flowchart TD")];
+        let mut nb_nodes: usize = 0;
+        write_mermaid_node(&self.root, &mut lines, &mut nb_nodes);
+
+        return lines.join("\n");
+    }
+}
+
+impl Ast {
+    /// Create expression applying binary operation on two expressions given in argument
+    fn binary(ops: BinaryOperator, left: Ast, right: Ast) -> Ast {
+        return Ast {
+            root: Node::Binary(ops, Box::new(left.root), Box::new(right.root)),
+        };
+    }
+
+    /// Create expression raising this expression to the power given in argument
+    pub fn pow<T>(self, exponent: T) -> Ast
+    where
+        T: Into<Ast>,
+    {
+        return Ast::binary(BinaryOperator::Power, self, exponent.into());
+    }
+
+    /// Create expression where each occurrence of variable whose name is given in argument
+    /// is replaced by expression given in argument
+    pub fn compose(&self, name: &str, other: &Ast) -> Ast {
+        return self.substitute(name, other.clone());
+    }
+
+    /// Create expression where each occurrence of variable whose name is given in argument
+    /// is replaced by expression or number given in argument, like a template formula
+    /// whose parameter is bound before compilation
+    pub fn substitute<T>(&self, name: &str, other: T) -> Ast
+    where
+        T: Into<Ast>,
+    {
+        return self.substitute_all(&[(name, other.into())]);
+    }
+
+    /// Create expression where each occurrence of variables whose names are given in argument
+    /// is replaced by its expression. Replacements are simultaneous, so x and y can be swapped
+    /// and replacing expressions are never substituted again
+    pub fn substitute_all(&self, replacements: &[(&str, Ast)]) -> Ast {
+        let nodes: Vec<(&str, &Node)> = replacements
+            .iter()
+            .map(|(name, other)| (*name, &other.root))
+            .collect();
+
+        return Ast {
+            root: substitute(&self.root, &nodes),
+        };
+    }
+
+    /// Get derivative of expression with respect to variable whose name is given in argument,
+    /// computed symbolically and simplified: derivative of x^2 + sin(x) is 2 * x + cos(x).
+    /// If expression uses boolean operators or functions which cannot be differentiated,
+    /// an error message is stored in string contained in Result output
+    pub fn derivative(&self, name: &str) -> Result<Ast, String> {
+        return Ok(Ast {
+            root: differentiation::derivative(&self.root, name)?,
+        });
+    }
+
+    /// Simplify expression by rules given in argument, like x + 2 * x giving 3 * x, x / x giving 1
+    /// or sin(x)^2 + cos(x)^2 giving 1. Some rules hold only for some values of subexpressions,
+    /// like x / x giving 1 which assumes x is not 0: these assumptions are given with simplified expression
+    pub fn simplify(&self, rules: &SimplificationRules) -> Simplification {
+        let (root, assumptions): (Node, Vec<String>) = simplification::simplify(&self.root, rules);
+
+        return Simplification {
+            ast: Ast { root },
+            assumptions,
+        };
+    }
+
+    /// Expand expression by distributing products over sums and expanding powers of sums
+    /// to positive integer exponents, like x^2 + 2 * x + 1 for (x + 1)^2.
+    /// Numbers are folded and like terms are combined, while quotients are only distributed over terms of dividends
+    pub fn expand(&self) -> Ast {
+        return Ast {
+            root: simplification::expand(&self.root),
+        };
+    }
+
+    /// Get canonical form of expression, where operands of additions, multiplications, min, max, gcd and lcm
+    /// are ordered and constants are replaced by their values, like 2 + x * y for y * x + 2.
+    /// Operations are never regrouped, so canonical form gives exactly the same results as expression
+    pub fn canonical(&self) -> Ast {
+        return Ast {
+            root: canonical::canonicalize(&self.root),
+        };
+    }
+
+    /// Get hash of canonical form of expression, equal for expressions with the same canonical form.
+    /// Hash is stable across runs, platforms and versions of Rust, so it can key persistent caches
+    pub fn structural_hash(&self) -> u64 {
+        return canonical::stable_hash(&self.canonical().to_string());
+    }
+
+    /// Get Taylor polynomial of expression with respect to variable whose name is given in argument,
+    /// at point x0 up to order given in argument: Taylor polynomial of exp(x) at 0 up to order 2
+    /// is 1 + x + 0.5 * x^2. Order can be at most 20.
+    /// If expression cannot be differentiated or order is too large,
+    /// an error message is stored in string contained in Result output
+    pub fn taylor(&self, name: &str, x0: f64, order: usize) -> Result<Ast, String> {
+        return Ok(Ast {
+            root: differentiation::taylor(&self.root, name, x0, order)?,
+        });
+    }
+}
+
+/// Create expression made of a number
+impl From<f64> for Ast {
+    fn from(number: f64) -> Ast {
+        return Ast {
+            root: Node::Number(number),
+        };
+    }
+}
+
+impl<T: Into<Ast>> Add<T> for Ast {
+    type Output = Ast;
+
+    fn add(self, right: T) -> Ast {
+        return Ast::binary(BinaryOperator::Plus, self, right.into());
+    }
+}
+
+impl<T: Into<Ast>> Sub<T> for Ast {
+    type Output = Ast;
+
+    fn sub(self, right: T) -> Ast {
+        return Ast::binary(BinaryOperator::Minus, self, right.into());
+    }
+}
+
+impl<T: Into<Ast>> Mul<T> for Ast {
+    type Output = Ast;
+
+    fn mul(self, right: T) -> Ast {
+        return Ast::binary(BinaryOperator::Multiply, self, right.into());
+    }
+}
+
+impl<T: Into<Ast>> Div<T> for Ast {
+    type Output = Ast;
+
+    fn div(self, right: T) -> Ast {
+        return Ast::binary(BinaryOperator::Divide, self, right.into());
+    }
+}
+
+impl Neg for Ast {
+    type Output = Ast;
+
+    fn neg(self) -> Ast {
+        return Ast {
+            root: Node::Unary(UnaryOperator::Minus, Box::new(self.root)),
+        };
+    }
+}
+
+/// Expression is written with parenthesis only where they are needed,
+/// so it can be parsed again into the same syntax tree
+impl fmt::Display for Ast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_node(f, &self.root)
+    }
+}
+
+/// Node is written as the subexpression it represents
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_node(f, self)
+    }
+}
+
+/// Copy node given in argument where each variable named in replacements given in argument
+/// is replaced by its node. Replacements are simultaneous, so replacing nodes are never substituted again
+pub(crate) fn substitute(node: &Node, replacements: &[(&str, &Node)]) -> Node {
+    let substitute_all = |nodes: &[Node]| -> Vec<Node> {
+        nodes
+            .iter()
+            .map(|node| substitute(node, replacements))
+            .collect()
+    };
+
+    match node {
+        Node::Variable(variable) => match replacements.iter().find(|(name, _)| name == variable) {
+            Some(&(_, replacement)) => replacement.clone(),
+            None => node.clone(),
+        },
+        Node::Unary(ops, operand) => Node::Unary(*ops, Box::new(substitute(operand, replacements))),
+        Node::Binary(ops, left, right) => Node::Binary(
+            *ops,
+            Box::new(substitute(left, replacements)),
+            Box::new(substitute(right, replacements)),
+        ),
+        Node::Call(fun, args) => Node::Call(*fun, substitute_all(args)),
+        Node::Tuple(elements) => Node::Tuple(substitute_all(elements)),
+        _ => node.clone(),
+    }
+}
+
+/// Check if node given in argument starts with a sign once written,
+/// so it must be enclosed in parenthesis when it is an operand
+fn is_signed(node: &Node) -> bool {
+    match node {
+        Node::Number(number) => number.is_sign_negative(),
+        Node::Unary(ops, _) => *ops != UnaryOperator::Not,
+        _ => false,
+    }
+}
+
+/// Write operand of binary operation given in argument, enclosed in parenthesis if needed
+fn write_operand(
+    f: &mut fmt::Formatter<'_>,
+    operand: &Node,
+    parent: BinaryOperator,
+    is_right: bool,
+) -> fmt::Result {
+    let needs_parenthesis: bool = match operand {
+        Node::Binary(ops, _, _) => {
+            let (precedence, parent_precedence): (u8, u8) = (ops.precedence(), parent.precedence());
+
+            // Operand with same precedence is grouped by associativity of parent operator
+            precedence < parent_precedence
+                || (precedence == parent_precedence && is_right == parent.is_left_associative())
+        }
+        _ => is_signed(operand),
+    };
+
+    if needs_parenthesis {
+        write!(f, "(")?;
+        write_node(f, operand)?;
+        return write!(f, ")");
+    }
+
+    return write_node(f, operand);
+}
+
+/// Write elements given in argument separated by commas
+fn write_list(f: &mut fmt::Formatter<'_>, nodes: &[Node]) -> fmt::Result {
+    for (index, node) in nodes.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+
+        write_node(f, node)?;
+    }
+
+    return Ok(());
+}
+
+/// Write node given in argument as an expression
+fn write_node(f: &mut fmt::Formatter<'_>, node: &Node) -> fmt::Result {
+    match node {
+        Node::Number(number) => write!(f, "{number}"),
+        Node::Constant(name, _) | Node::Variable(name) => write!(f, "{name}"),
+        Node::Array(values) => {
+            let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+            write!(f, "[{}]", values.join(", "))
+        }
+        Node::Unary(ops, operand) => {
+            write!(f, "{}", ops.symbol())?;
+
+            // Unary operator binds tighter than binary operators
+            if matches!(**operand, Node::Binary(_, _, _)) || is_signed(operand) {
+                write!(f, "(")?;
+                write_node(f, operand)?;
+                return write!(f, ")");
+            }
+
+            return write_node(f, operand);
+        }
+        Node::Binary(ops, left, right) => {
+            write_operand(f, left, *ops, false)?;
+
+            match ops {
+                BinaryOperator::Power => write!(f, "^")?,
+                _ => write!(f, " {} ", ops.notation())?,
+            }
+
+            return write_operand(f, right, *ops, true);
+        }
+        Node::Call(fun, args) => {
+            write!(f, "{}(", fun.name())?;
+            write_list(f, args)?;
+            write!(f, ")")
+        }
+        Node::Tuple(elements) => {
+            write!(f, "(")?;
+            write_list(f, elements)?;
+            write!(f, ")")
+        }
+    }
+}
+
+/// Write node given in argument and its children as lines of Mermaid flowchart,
+/// nodes being identified by their order of writing. The identifier of node is returned
+fn write_mermaid_node(node: &Node, lines: &mut Vec<String>, nb_nodes: &mut usize) -> usize {
+    let id: usize = *nb_nodes;
+    *nb_nodes += 1;
+
+    let label: String = match node {
+        Node::Number(number) => number.to_string(),
+        Node::Constant(name, _) | Node::Variable(name) => name.clone(),
+        Node::Array(_) => node.to_string(),
+        Node::Unary(ops, _) => ops.symbol().to_string(),
+        Node::Binary(ops, _, _) => String::from(ops.notation()),
+        Node::Call(fun, _) => String::from(fun.name()),
+        Node::Tuple(_) => String::from("tuple"),
+    };
+
+    lines.push(format!("    n{id}[\"{label}\"]"));
+
+    let children: Vec<&Node> = match node {
+        Node::Unary(_, operand) => vec![operand],
+        Node::Binary(_, left, right) => vec![left, right],
+        Node::Call(_, nodes) | Node::Tuple(nodes) => nodes.iter().collect(),
+        _ => Vec::new(),
+    };
+
+    for child in children {
+        let child_id: usize = write_mermaid_node(child, lines, nb_nodes);
+        lines.push(format!("    n{id} --> n{child_id}"));
+    }
+
+    return id;
+}
+
+/// Insert names referenced by node given in argument and its children in uses
+fn collect_uses(node: &Node, uses: &mut Uses) {
+    match node {
+        Node::Number(_) | Node::Array(_) => (),
+        Node::Constant(name, _) => {
+            uses.constants.insert(name.clone());
+        }
+        Node::Variable(name) => {
+            uses.variables.insert(name.clone());
+        }
+        Node::Unary(_, operand) => collect_uses(operand, uses),
+        Node::Binary(_, left, right) => {
+            collect_uses(left, uses);
+            collect_uses(right, uses);
+        }
+        Node::Call(fun, args) => {
+            uses.functions.insert(String::from(fun.name()));
+            args.iter().for_each(|arg| collect_uses(arg, uses));
+        }
+        Node::Tuple(elements) => elements
+            .iter()
+            .for_each(|element| collect_uses(element, uses)),
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> BTreeSet<String> {
+        return names.iter().map(|&name| String::from(name)).collect();
+    }
+
+    #[test]
+    fn test_ast_structure() {
+        match Ast::new("-x + 2 * cos(pi)") {
+            Ok(ast) => assert_eq!(
+                ast.root,
+                Node::Binary(
+                    BinaryOperator::Plus,
+                    Box::new(Node::Unary(
+                        UnaryOperator::Minus,
+                        Box::new(Node::Variable(String::from("x")))
+                    )),
+                    Box::new(Node::Binary(
+                        BinaryOperator::Multiply,
+                        Box::new(Node::Number(2.0)),
+                        Box::new(Node::Call(
+                            Function::Cos,
+                            vec![Node::Constant(String::from("pi"), std::f64::consts::PI)]
+                        ))
+                    ))
+                )
+            ),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_ast_uses() {
+        match Ast::new("(sqrt(x^2 + y^2) * c, clamp(e, x, rad(theta)), lookup(t, [0, pi], [1, 2]))")
+        {
+            Ok(ast) => {
+                let uses: Uses = ast.uses();
+
+                assert_eq!(
+                    uses.functions,
+                    names(&["clamp", "deg2rad", "lookup", "sqrt"])
+                );
+                assert_eq!(uses.constants, names(&["c", "e"]));
+                assert_eq!(uses.variables, names(&["t", "theta", "x", "y"]));
+                assert!(uses.custom_functions.is_empty());
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_ast_invalid_expression() {
+        assert!(Ast::new("2 * (x + 1").is_err());
+        assert!(Ast::new("2 +").is_err());
+        assert!(Ast::new("").is_err());
+    }
+
+    #[test]
+    fn test_ast_deep_expression() {
+        let nested: String = "(".repeat(20_000) + "1" + &")".repeat(20_000);
+        assert_eq!(
+            Ast::new(&nested),
+            Err(String::from(
+                "Expression exceeds limit of 256 nested parenthesis at position 256"
+            ))
+        );
+
+        let negations: String = "-(".repeat(200) + "x" + &")".repeat(200);
+        assert!(Ast::new(&negations).is_ok());
+
+        let sum: String = vec!["x"; 5_000].join(" + ");
+        assert_eq!(
+            Ast::new(&sum),
+            Err(String::from(
+                "Expression exceeds limit of 256 nested operations"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_ast_display() {
+        for (expression, written) in [
+            ("1+2*x", "1 + 2 * x"),
+            ("(1 + 2) * x", "(1 + 2) * x"),
+            ("a - (b - c)", "a - (b - c)"),
+            ("(a - b) - c", "a - b - c"),
+            ("(a ^ b) ^ c", "(a^b)^c"),
+            ("a ^ (b ^ c)", "a^b^c"),
+            ("-(x^2) + 2 * (-y)", "(-(x^2)) + 2 * (-y)"),
+            ("clamp(x, -1, 1) * pi", "clamp(x, -1, 1) * pi"),
+            ("lookup(x, [0, 1], [2, 3])", "lookup(x, [0, 1], [2, 3])"),
+            ("(sin(t), rad(t))", "(sin(t), deg2rad(t))"),
+        ] {
+            match Ast::new(expression) {
+                Ok(ast) => {
+                    assert_eq!(ast.to_string(), written);
+                    assert_eq!(Ast::new(written).map(|parsed| parsed.root), Ok(ast.root));
+                }
+                Err(_) => assert!(false),
+            }
+        }
+    }
+
+    #[test]
+    fn test_ast_combination() {
+        match (Ast::new("x + 1"), Ast::new("y - 2"), Ast::new("t^2")) {
+            (Ok(left), Ok(right), Ok(square)) => {
+                assert_eq!(
+                    (left.clone() * right.clone()).to_string(),
+                    "(x + 1) * (y - 2)"
+                );
+                assert_eq!((left.clone() - right).to_string(), "x + 1 - (y - 2)");
+                assert_eq!(left.clone().pow(2.0).to_string(), "(x + 1)^2");
+                assert_eq!((-left.clone() / -2.0).to_string(), "(-(x + 1)) / (-2)");
+                assert_eq!(left.compose("x", &square).to_string(), "t^2 + 1");
+            }
+            _ => assert!(false),
+        }
+
+        match (Ast::new("sin(x) * x"), Ast::new("rad(t) - 1")) {
+            (Ok(ast), Ok(angle)) => {
+                let composed: Ast = ast.compose("x", &angle);
+
+                assert_eq!(
+                    composed.to_string(),
+                    "sin(deg2rad(t) - 1) * (deg2rad(t) - 1)"
+                );
+            }
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_ast_substitution() {
+        match (
+            Ast::new("a * x^2 + b * y"),
+            Ast::new("t - 1"),
+            Ast::new("2 * z"),
+        ) {
+            (Ok(template), Ok(shift), Ok(double)) => {
+                assert_eq!(
+                    template.substitute("x", shift.clone()).to_string(),
+                    "a * (t - 1)^2 + b * y"
+                );
+                assert_eq!(template.substitute("a", 3.0).to_string(), "3 * x^2 + b * y");
+                assert_eq!(
+                    template.substitute("c", shift.clone()).to_string(),
+                    "a * x^2 + b * y"
+                );
+                assert_eq!(
+                    template
+                        .substitute_all(&[("x", Ast::from(-1.0)), ("y", double)])
+                        .to_string(),
+                    "a * (-1)^2 + b * (2 * z)"
+                );
+            }
+            _ => assert!(false),
+        }
+
+        match (
+            Ast::new("x - y"),
+            Ast::new("x"),
+            Ast::new("y"),
+            Ast::new("rad(t)"),
+        ) {
+            (Ok(ast), Ok(x), Ok(y), Ok(angle)) => {
+                assert_eq!(
+                    ast.substitute_all(&[("x", y), ("y", x)]).to_string(),
+                    "y - x"
+                );
+
+                let substituted: Ast = ast.substitute("y", angle);
+                assert_eq!(substituted.to_string(), "x - deg2rad(t)");
+            }
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_ast_canonical() {
+        match (
+            Ast::new("y * x + 2 * pi"),
+            Ast::new("2 * 3.141592653589793 + x * y"),
+            Ast::new("x * y + 2 * pi + 0"),
+        ) {
+            (Ok(first), Ok(second), Ok(third)) => {
+                assert_eq!(
+                    first.canonical().to_string(),
+                    "2 * 3.141592653589793 + x * y"
+                );
+                assert_eq!(first.canonical(), second.canonical());
+                assert_eq!(first.structural_hash(), second.structural_hash());
+                assert_ne!(first.structural_hash(), third.structural_hash());
+            }
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_ast_to_mermaid() {
+        match Ast::new("2 * x + sin(pi)") {
+            Ok(ast) => assert_eq!(
+                ast.to_mermaid(),
+                [
+                    "flowchart TD",
+                    "    n0[\"+\"]",
+                    "    n1[\"*\"]",
+                    "    n2[\"2\"]",
+                    "    n1 --> n2",
+                    "    n3[\"x\"]",
+                    "    n1 --> n3",
+                    "    n0 --> n1",
+                    "    n4[\"sin\"]",
+                    "    n5[\"pi\"]",
+                    "    n4 --> n5",
+                    "    n0 --> n4",
+                ]
+                .join("\n")
+            ),
+            Err(_) => assert!(false),
+        }
+    }
+}
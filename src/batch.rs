@@ -0,0 +1,480 @@
+#![allow(clippy::needless_return)]
+
+use super::converter;
+use super::evaluate;
+use super::evaluator;
+use super::token::Token;
+use super::tokenizer;
+use super::units::{self, Unit};
+
+use std::collections::HashMap;
+
+/// Result of evaluation of one expression over several sets of variables.
+/// Each element keeps its own outcome, so a failing element does not prevent
+/// the evaluation of the following ones.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BatchResult {
+    results: Vec<Result<f64, String>>,
+}
+
+impl BatchResult {
+    /// Number of evaluated elements
+    pub fn len(&self) -> usize {
+        return self.results.len();
+    }
+
+    /// Check if batch does not contain any element
+    pub fn is_empty(&self) -> bool {
+        return self.results.is_empty();
+    }
+
+    /// Get result of element at index given in argument
+    pub fn get(&self, index: usize) -> Option<&Result<f64, String>> {
+        return self.results.get(index);
+    }
+
+    /// Check if all elements have been successfully evaluated
+    pub fn is_ok(&self) -> bool {
+        return self.results.iter().all(|result| result.is_ok());
+    }
+
+    /// Number of elements whose evaluation failed
+    pub fn error_count(&self) -> usize {
+        return self.results.iter().filter(|result| result.is_err()).count();
+    }
+
+    /// Iterate over successful values with their index in batch
+    pub fn values(&self) -> impl Iterator<Item = (usize, f64)> + '_ {
+        return self
+            .results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, result)| result.as_ref().ok().map(|&value| (index, value)));
+    }
+
+    /// Iterate over error messages with their index in batch
+    pub fn errors(&self) -> impl Iterator<Item = (usize, &str)> + '_ {
+        return self
+            .results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, result)| {
+                result
+                    .as_ref()
+                    .err()
+                    .map(|message| (index, message.as_str()))
+            });
+    }
+
+    /// Convert batch into vector of values if all elements have been successfully evaluated.
+    /// Otherwise, all errors with their index are stored in Result output
+    pub fn into_values(self) -> Result<Vec<f64>, Vec<(usize, String)>> {
+        if self.is_ok() {
+            return Ok(self.results.into_iter().flatten().collect());
+        }
+
+        return Err(self
+            .results
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, result)| result.err().map(|message| (index, message)))
+            .collect());
+    }
+}
+
+/// Bitmask telling which elements of a batch have been successfully evaluated.
+/// Bits are packed in 64-bits words: bit i % 64 of word i / 64 is set when element i is valid.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValidityMask {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl ValidityMask {
+    /// Create a mask of given length where all elements are invalid
+    fn new(len: usize) -> ValidityMask {
+        return ValidityMask {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        };
+    }
+
+    /// Mark element at index given in argument as valid
+    fn set_valid(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    /// Number of elements covered by mask
+    pub fn len(&self) -> usize {
+        return self.len;
+    }
+
+    /// Check if mask does not cover any element
+    pub fn is_empty(&self) -> bool {
+        return self.len == 0;
+    }
+
+    /// Check if element at index given in argument is valid.
+    /// An index out of mask is considered as invalid.
+    pub fn is_valid(&self, index: usize) -> bool {
+        if index >= self.len {
+            return false;
+        }
+
+        return (self.words[index / 64] >> (index % 64)) & 1 == 1;
+    }
+
+    /// Number of valid elements
+    pub fn count_valid(&self) -> usize {
+        return self
+            .words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum();
+    }
+
+    /// Packed representation of mask
+    pub fn as_words(&self) -> &[u64] {
+        return &self.words;
+    }
+}
+
+/// Result of masked evaluation of one expression over several sets of variables.
+/// Elements whose evaluation failed contain NaN and are marked as invalid in the validity mask.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MaskedBatchResult {
+    pub values: Vec<f64>,
+    pub validity: ValidityMask,
+}
+
+/// Values of a variable sharing the same unit, like a column of measurements in a dataset.
+/// The unit is given once for the whole column.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Measurements<'a> {
+    pub unit: Unit,
+    pub values: &'a [f64],
+}
+
+/// Result of evaluation of one expression over columns of measurements.
+/// All elements of results share the same unit.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MeasuredBatchResult {
+    pub unit: Unit,
+    pub results: BatchResult,
+}
+
+/// Evaluate an expression for each set of variables given in argument.
+/// Unlike evaluate function, an error on one element does not abort the evaluation:
+/// the error is stored with the index of element in the BatchResult output
+/// and the evaluation continues with the next element.
+///
+/// # Example
+/// ```
+/// use taz;
+/// use std::collections::HashMap;
+///
+/// let rows: Vec<HashMap<String, f64>> = vec![
+///     HashMap::from([(String::from("x"), 4.0)]),
+///     HashMap::from([(String::from("x"), -1.0)]),
+///     HashMap::from([(String::from("x"), 9.0)]),
+/// ];
+///
+/// let batch: taz::BatchResult = taz::evaluate_batch("sqrt(x)", &rows);
+/// assert_eq!(batch.error_count(), 1);
+///
+/// for (index, message) in batch.errors() {
+///     println!("Row {index}: {message}");
+/// }
+/// ```
+pub fn evaluate_batch(expression: &str, variables: &[HashMap<String, f64>]) -> BatchResult {
+    let results: Vec<Result<f64, String>> = variables
+        .iter()
+        .map(|row| evaluate(expression, row))
+        .collect();
+
+    return BatchResult { results };
+}
+
+/// Evaluate an expression for each set of variables given in argument
+/// without keeping the error of failing elements.
+/// A failing element (domain error, missing variable, ...) contains NaN in values
+/// and is marked as invalid in validity mask, which is convenient to feed plots with large arrays.
+///
+/// # Example
+/// ```
+/// use taz;
+/// use std::collections::HashMap;
+///
+/// let rows: Vec<HashMap<String, f64>> = vec![
+///     HashMap::from([(String::from("x"), 4.0)]),
+///     HashMap::from([(String::from("x"), -1.0)]),
+/// ];
+///
+/// let batch: taz::MaskedBatchResult = taz::evaluate_batch_masked("sqrt(x)", &rows);
+/// assert_eq!(batch.values[0], 2.0);
+/// assert!(batch.values[1].is_nan());
+/// assert!(!batch.validity.is_valid(1));
+/// ```
+pub fn evaluate_batch_masked(
+    expression: &str,
+    variables: &[HashMap<String, f64>],
+) -> MaskedBatchResult {
+    let mut values: Vec<f64> = Vec::with_capacity(variables.len());
+    let mut validity: ValidityMask = ValidityMask::new(variables.len());
+
+    for (index, row) in variables.iter().enumerate() {
+        match evaluate(expression, row) {
+            Ok(value) => {
+                values.push(value);
+                validity.set_valid(index);
+            }
+            Err(_) => values.push(f64::NAN),
+        }
+    }
+
+    return MaskedBatchResult { values, validity };
+}
+
+/// Evaluate an expression for each row of columns of measurements given in argument.
+/// The expression is parsed and its units are checked only once,
+/// then it is evaluated on raw values of each row.
+///
+/// If expression cannot be parsed, if its units are not consistent
+/// or if columns have different lengths, an error message is stored in string contained in Result output.
+/// Otherwise, errors occurring on a row are collected in results like evaluate_batch function.
+///
+/// # Example
+/// ```
+/// use taz;
+/// use taz::{Measurements, Unit};
+/// use std::collections::HashMap;
+///
+/// let distances: Vec<f64> = vec![100.0, 200.0];
+/// let durations: Vec<f64> = vec![10.0, 40.0];
+///
+/// let columns: HashMap<String, Measurements> = HashMap::from([
+///     (String::from("d"), Measurements { unit: Unit::meter(), values: &distances }),
+///     (String::from("t"), Measurements { unit: Unit::second(), values: &durations }),
+/// ]);
+///
+/// let speeds: taz::MeasuredBatchResult = taz::evaluate_batch_with_units("d / t", &columns).unwrap();
+/// assert_eq!(speeds.unit, Unit::meter() / Unit::second());
+/// assert_eq!(speeds.results.into_values(), Ok(vec![10.0, 5.0]));
+///
+/// assert!(taz::evaluate_batch_with_units("d + t", &columns).is_err());
+/// ```
+pub fn evaluate_batch_with_units(
+    expression: &str,
+    columns: &HashMap<String, Measurements>,
+) -> Result<MeasuredBatchResult, String> {
+    let mut names: Vec<&str> = columns.keys().map(|name| name.as_str()).collect();
+    names.sort_unstable();
+
+    let measurements: Vec<Measurements> = names.iter().map(|&name| columns[name]).collect();
+    let len: usize = measurements.first().map_or(0, |column| column.values.len());
+
+    if measurements.iter().any(|column| column.values.len() != len) {
+        return Err(String::from(
+            "Columns of measurements have different lengths",
+        ));
+    }
+
+    let tokens: Vec<Token> = tokenizer::tokenize_with_slots(expression, &names)?;
+    let postfix_tokens: Vec<Token> = converter::infix_to_postfix(tokens)?;
+
+    let column_units: Vec<Unit> = measurements.iter().map(|column| column.unit).collect();
+    let unit: Unit = units::check_postfix_units(&postfix_tokens, &column_units)?;
+
+    let mut row: Vec<f64> = vec![0.0; measurements.len()];
+    let mut results: Vec<Result<f64, String>> = Vec::with_capacity(len);
+
+    for index in 0..len {
+        for (value, column) in row.iter_mut().zip(measurements.iter()) {
+            *value = column.values[index];
+        }
+
+        results.push(evaluator::postfix_evaluation_with_values(
+            &postfix_tokens,
+            &row,
+        ));
+    }
+
+    return Ok(MeasuredBatchResult {
+        unit,
+        results: BatchResult { results },
+    });
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(name: &str, values: &[f64]) -> Vec<HashMap<String, f64>> {
+        return values
+            .iter()
+            .map(|&value| HashMap::from([(String::from(name), value)]))
+            .collect();
+    }
+
+    #[test]
+    fn test_evaluate_batch_without_error() {
+        let batch: BatchResult = evaluate_batch("2.0 * x", &rows("x", &[1.0, 2.0, 3.0]));
+
+        assert_eq!(batch.len(), 3);
+        assert!(batch.is_ok());
+        assert_eq!(batch.error_count(), 0);
+        assert_eq!(batch.into_values(), Ok(vec![2.0, 4.0, 6.0]));
+    }
+
+    #[test]
+    fn test_evaluate_batch_collect_all_errors() {
+        let batch: BatchResult = evaluate_batch("sqrt(x)", &rows("x", &[-1.0, 4.0, -9.0, 16.0]));
+
+        assert_eq!(batch.len(), 4);
+        assert!(!batch.is_ok());
+        assert_eq!(batch.error_count(), 2);
+
+        let values: Vec<(usize, f64)> = batch.values().collect();
+        assert_eq!(values, vec![(1, 2.0), (3, 4.0)]);
+
+        let indices: Vec<usize> = batch.errors().map(|(index, _)| index).collect();
+        assert_eq!(indices, vec![0, 2]);
+
+        match batch.into_values() {
+            Ok(_) => assert!(false),
+            Err(errors) => {
+                assert_eq!(errors.len(), 2);
+                assert_eq!(errors[0].0, 0);
+                assert_eq!(errors[1].0, 2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_batch_missing_variable() {
+        let mut variables: Vec<HashMap<String, f64>> = rows("x", &[1.0, 2.0]);
+        variables.push(HashMap::new());
+
+        let batch: BatchResult = evaluate_batch("x + 1.0", &variables);
+
+        assert_eq!(batch.error_count(), 1);
+        assert_eq!(batch.get(0), Some(&Ok(2.0)));
+        assert!(batch.get(2).unwrap().is_err());
+        assert!(batch.get(3).is_none());
+    }
+
+    #[test]
+    fn test_evaluate_batch_empty() {
+        let batch: BatchResult = evaluate_batch("1.0 + 1.0", &[]);
+
+        assert!(batch.is_empty());
+        assert!(batch.is_ok());
+    }
+
+    #[test]
+    fn test_evaluate_batch_masked() {
+        let batch: MaskedBatchResult =
+            evaluate_batch_masked("ln(x)", &rows("x", &[1.0, -1.0, 0.0, 1.0]));
+
+        assert_eq!(batch.values.len(), 4);
+        assert_eq!(batch.values[0], 0.0);
+        assert!(batch.values[1].is_nan());
+        assert!(batch.values[2].is_nan());
+        assert_eq!(batch.values[3], 0.0);
+
+        assert_eq!(batch.validity.len(), 4);
+        assert_eq!(batch.validity.count_valid(), 2);
+        assert!(batch.validity.is_valid(0));
+        assert!(!batch.validity.is_valid(1));
+        assert!(!batch.validity.is_valid(2));
+        assert!(batch.validity.is_valid(3));
+        assert!(!batch.validity.is_valid(4));
+        assert_eq!(batch.validity.as_words(), &[0b1001]);
+    }
+
+    #[test]
+    fn test_validity_mask_several_words() {
+        let values: Vec<f64> = (0..130).map(|index| index as f64 - 65.0).collect();
+        let batch: MaskedBatchResult = evaluate_batch_masked("sqrt(x)", &rows("x", &values));
+
+        assert_eq!(batch.validity.as_words().len(), 3);
+        assert_eq!(batch.validity.count_valid(), 65);
+        assert!(!batch.validity.is_valid(64));
+        assert!(batch.validity.is_valid(65));
+        assert!(batch.validity.is_valid(129));
+    }
+
+    #[test]
+    fn test_evaluate_batch_with_units() {
+        let masses: Vec<f64> = vec![1.0, 2.0, 0.0];
+        let speeds: Vec<f64> = vec![2.0, 3.0, 4.0];
+
+        let columns: HashMap<String, Measurements> = HashMap::from([
+            (
+                String::from("m"),
+                Measurements {
+                    unit: Unit::kilogram(),
+                    values: &masses,
+                },
+            ),
+            (
+                String::from("v"),
+                Measurements {
+                    unit: Unit::meter() / Unit::second(),
+                    values: &speeds,
+                },
+            ),
+        ]);
+
+        match evaluate_batch_with_units("0.5 * m * v^2", &columns) {
+            Ok(energies) => {
+                let joule: Unit =
+                    Unit::kilogram() * (Unit::meter() / Unit::second()).powi(2).unwrap();
+                assert_eq!(energies.unit, joule);
+                assert_eq!(energies.results.into_values(), Ok(vec![2.0, 9.0, 0.0]));
+            }
+            Err(_) => assert!(false),
+        }
+
+        match evaluate_batch_with_units("1.0 / m", &columns) {
+            Ok(inverses) => {
+                assert_eq!(inverses.unit, Unit::dimensionless() / Unit::kilogram());
+                assert_eq!(inverses.results.error_count(), 1);
+                assert!(inverses.results.get(2).unwrap().is_err());
+            }
+            Err(_) => assert!(false),
+        }
+
+        assert!(evaluate_batch_with_units("m + v", &columns).is_err());
+        assert!(evaluate_batch_with_units("m + w", &columns).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_batch_with_units_different_lengths() {
+        let first: Vec<f64> = vec![1.0, 2.0];
+        let second: Vec<f64> = vec![1.0];
+
+        let columns: HashMap<String, Measurements> = HashMap::from([
+            (
+                String::from("x"),
+                Measurements {
+                    unit: Unit::meter(),
+                    values: &first,
+                },
+            ),
+            (
+                String::from("y"),
+                Measurements {
+                    unit: Unit::meter(),
+                    values: &second,
+                },
+            ),
+        ]);
+
+        match evaluate_batch_with_units("x + y", &columns) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(message, "Columns of measurements have different lengths"),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+#![allow(clippy::needless_return)]
+
+use super::compiler::CompiledExpression;
+
+use std::collections::HashMap;
+use std::hint::black_box;
+
+/// Strategy of evaluation of a workload, so configurations of Taz can be compared on the same workload
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Strategy {
+    /// Expression is tokenized, converted and evaluated for each input, like evaluate function
+    Interpreted,
+    /// Expression is compiled once to bytecode, then evaluated for each input one at a time
+    Bytecode,
+    /// Expression is compiled once to bytecode, then evaluated by lanes of inputs
+    /// whose arithmetic operations can be vectorized, like eval_slice method
+    Lanes,
+}
+
+impl Strategy {
+    /// All strategies of evaluation
+    pub const ALL: [Strategy; 3] = [Strategy::Interpreted, Strategy::Bytecode, Strategy::Lanes];
+
+    /// Get name of strategy, like bytecode
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Interpreted => "interpreted",
+            Strategy::Bytecode => "bytecode",
+            Strategy::Lanes => "lanes",
+        }
+    }
+}
+
+/// Representative workload of evaluation: an expression of variable x evaluated for each input
+#[derive(Debug, PartialEq, Clone)]
+pub struct Workload {
+    /// Name of workload, like short_arithmetic
+    pub name: &'static str,
+    /// Expression evaluated, whose only variable is x
+    pub expression: String,
+    /// Values of x for which expression is evaluated
+    pub inputs: Vec<f64>,
+}
+
+impl Workload {
+    /// Create workload from its name, expression and number of inputs, spread over [0, 1)
+    fn new(name: &'static str, expression: String, nb_inputs: usize) -> Workload {
+        let inputs: Vec<f64> = (0..nb_inputs)
+            .map(|index| index as f64 / nb_inputs as f64)
+            .collect();
+
+        return Workload {
+            name,
+            expression,
+            inputs,
+        };
+    }
+
+    /// Evaluate expression of workload for each input with strategy given in argument,
+    /// giving the sum of values so the evaluation cannot be optimized away.
+    /// If error occurs during evaluation, an error message is stored in string contained in Result output
+    pub fn run(&self, strategy: Strategy) -> Result<f64, String> {
+        let mut sum: f64 = 0.0;
+
+        match strategy {
+            Strategy::Interpreted => {
+                let mut variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 0.0)]);
+
+                for &input in self.inputs.iter() {
+                    variables.insert(String::from("x"), input);
+                    sum += super::evaluate(black_box(&self.expression), &variables)?;
+                }
+            }
+            Strategy::Bytecode => {
+                let compiled: CompiledExpression = CompiledExpression::new(&self.expression)?;
+
+                for &input in self.inputs.iter() {
+                    sum += compiled.evaluate(&[black_box(input)])?;
+                }
+            }
+            Strategy::Lanes => {
+                let compiled: CompiledExpression = CompiledExpression::new(&self.expression)?;
+                let mut outputs: Vec<f64> = vec![0.0; self.inputs.len()];
+
+                compiled.eval_slice(black_box(&self.inputs), &mut outputs)?;
+                sum = outputs.iter().sum();
+            }
+        }
+
+        return Ok(sum);
+    }
+}
+
+/// Get representative workloads of evaluation: short arithmetic, transcendental-heavy,
+/// deeply nested and batch of many inputs.
+/// Each workload can be run with each strategy, like in the following example.
+///
+/// # Example
+/// ```
+/// use taz::bench::{workloads, Strategy};
+/// use std::time::Instant;
+///
+/// for workload in workloads() {
+///     for strategy in Strategy::ALL {
+///         let start: Instant = Instant::now();
+///         workload.run(strategy).unwrap();
+///         println!("{} {}: {:?}", workload.name, strategy.name(), start.elapsed());
+///     }
+/// }
+/// ```
+pub fn workloads() -> Vec<Workload> {
+    let deep_nesting: String = (1..=32).fold(String::from("x"), |expression, depth| {
+        format!("({expression} + {depth}) * 0.5")
+    });
+
+    return vec![
+        Workload::new("short_arithmetic", String::from("2 * x + 1"), 64),
+        Workload::new(
+            "transcendental",
+            String::from("sin(x)^2 + cos(x)^2 + exp(-x) * ln(x + 1) + atan(sqrt(x))"),
+            64,
+        ),
+        Workload::new("deep_nesting", deep_nesting, 64),
+        Workload::new(
+            "batch",
+            String::from("((0.5 * x - 1.5) * x + 2) * x - 0.25"),
+            4096,
+        ),
+    ];
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workloads_agree_on_strategies() {
+        for workload in workloads() {
+            match workload.run(Strategy::Interpreted) {
+                Ok(reference) => {
+                    for strategy in [Strategy::Bytecode, Strategy::Lanes] {
+                        match workload.run(strategy) {
+                            Ok(sum) => assert!((sum - reference).abs() <= 1e-9 * reference.abs()),
+                            Err(_) => assert!(false),
+                        }
+                    }
+                }
+                Err(_) => assert!(false),
+            }
+        }
+    }
+}
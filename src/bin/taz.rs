@@ -0,0 +1,422 @@
+#![allow(clippy::needless_return)]
+#![cfg_attr(test, allow(clippy::assertions_on_constants))]
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, IsTerminal};
+use std::process::ExitCode;
+
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+use taz::{AngleUnit, EvaluationOptions, Session};
+
+const USAGE: &str = "Usage: taz [OPTIONS] [EXPRESSION]...
+
+Evaluate each expression given in argument, or each line of standard input if no expression is given.
+A line like x = 2 * pi assigns the value of an expression to a variable usable by following expressions,
+a line like f(x) = x^2 + 1 defines a function, statements of a line being separated by semicolons,
+and ans holds the value of the last expression. Without expression nor pipe, an interactive session starts.
+
+Options:
+  --precision <N>   Number of digits after the decimal point of results
+  --degrees         Angles of trigonometric functions are in degrees
+  --format <FORMAT> Format of results: plain (default), scientific or json
+  --help            Print this help";
+
+/// Format of results printed by calculator
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Format {
+    /// Value alone, like 0.5
+    Plain,
+    /// Value in scientific notation, like 5e-1
+    Scientific,
+    /// JSON object holding expression and value, like {"expression": "1 / 2", "value": 0.5}
+    Json,
+}
+
+/// Configuration of calculator given by arguments of command line
+#[derive(Debug, PartialEq)]
+struct Config {
+    help: bool,
+    precision: Option<usize>,
+    format: Format,
+    options: EvaluationOptions,
+    expressions: Vec<String>,
+}
+
+/// Parse arguments of command line given in argument, without name of program.
+/// If arguments are invalid, an error message is stored in string contained in Result output
+fn parse_args<I>(args: I) -> Result<Config, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut config: Config = Config {
+        help: false,
+        precision: None,
+        format: Format::Plain,
+        options: EvaluationOptions::default(),
+        expressions: Vec::new(),
+    };
+
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--precision" => match args.next().map(|value| value.parse::<usize>()) {
+                Some(Ok(precision)) => config.precision = Some(precision),
+                _ => return Err(String::from("--precision expects a number of digits")),
+            },
+            "--help" => config.help = true,
+            "--degrees" => config.options.angle = AngleUnit::Degrees,
+            "--format" => {
+                config.format = match args.next().as_deref() {
+                    Some("plain") => Format::Plain,
+                    Some("scientific") => Format::Scientific,
+                    Some("json") => Format::Json,
+                    _ => return Err(String::from("--format expects plain, scientific or json")),
+                }
+            }
+            // Expressions starting with a minus sign, like -2 * x, are not options
+            option if option.starts_with("--") => {
+                return Err(format!("Unknown option {option}"));
+            }
+            _ => config.expressions.push(arg),
+        }
+    }
+
+    return Ok(config);
+}
+
+/// Escape string given in argument to be written in a JSON document
+fn escape_json(string: &str) -> String {
+    let mut escaped: String = String::with_capacity(string.len());
+
+    for character in string.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            character if character.is_control() => {
+                escaped.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => escaped.push(character),
+        }
+    }
+
+    return escaped;
+}
+
+/// Format value of expression given in argument according to configuration
+fn format_value(expression: &str, value: f64, config: &Config) -> String {
+    let number: String = match (config.format, config.precision) {
+        (Format::Scientific, Some(precision)) => format!("{value:.precision$e}"),
+        (Format::Scientific, None) => format!("{value:e}"),
+        (_, Some(precision)) => format!("{value:.precision$}"),
+        (_, None) => value.to_string(),
+    };
+
+    match config.format {
+        Format::Json => {
+            // Non-finite values have no JSON representation
+            let number: String = if value.is_finite() {
+                number
+            } else {
+                String::from("null")
+            };
+
+            return format!(
+                "{{\"expression\": \"{}\", \"value\": {number}}}",
+                escape_json(expression)
+            );
+        }
+        _ => return number,
+    }
+}
+
+/// Evaluate line given in argument, which is an expression, an assignment like x = 2
+/// or a definition of function like f(x) = x^2, in session given in argument, which keeps
+/// assigned variables, defined functions and ans, holding the value of expression.
+/// An empty string is given for a line which only defines functions.
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+fn run_line(line: &str, session: &mut Session, config: &Config) -> Result<String, String> {
+    match session.run(line)? {
+        Some(value) => Ok(format_value(line.trim(), value, config)),
+        None => Ok(String::new()),
+    }
+}
+
+const COMMANDS: &str = "Commands:
+  :vars   List variables and their values
+  :funcs  List functions and constants
+  :help   Print this help
+  :quit   Quit session";
+
+/// Outcome of a line of interactive session
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    /// Text to print
+    Reply(String),
+    /// End of session
+    Quit,
+}
+
+/// Run line of interactive session given in argument, which is a command like :vars or an expression.
+/// If error occurs during evaluation or command is unknown, an error message is stored
+/// in string contained in Result output
+fn run_session_line(line: &str, session: &mut Session, config: &Config) -> Result<Outcome, String> {
+    match line.trim() {
+        ":quit" | ":q" => Ok(Outcome::Quit),
+        ":help" => Ok(Outcome::Reply(String::from(COMMANDS))),
+        ":vars" => {
+            let variables: &HashMap<String, f64> = session.variables();
+            let mut names: Vec<&String> = variables.keys().collect();
+            names.sort_unstable();
+
+            let lines: Vec<String> = names
+                .iter()
+                .map(|&name| format!("{name} = {}", format_value(name, variables[name], config)))
+                .collect();
+
+            return Ok(Outcome::Reply(lines.join("\n")));
+        }
+        ":funcs" => {
+            let mut reply: String = format!(
+                "Functions: {}\nConstants: {}",
+                taz::function_names().join(", "),
+                taz::constant_names().join(", ")
+            );
+
+            for definition in session.functions() {
+                reply.push_str(&format!("\n{definition}"));
+            }
+
+            return Ok(Outcome::Reply(reply));
+        }
+        command if command.starts_with(':') => Err(format!("Unknown command {command}")),
+        _ => run_line(line, session, config).map(Outcome::Reply),
+    }
+}
+
+/// Run interactive session with line editing and history, until :quit command or end of input
+fn run_session(config: &Config) -> ExitCode {
+    let mut editor: DefaultEditor = match DefaultEditor::new() {
+        Ok(editor) => editor,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut session: Session = Session::new().with_options(config.options);
+    println!(
+        "Taz {} - type :help for commands",
+        env!("CARGO_PKG_VERSION")
+    );
+
+    loop {
+        match editor.readline("> ") {
+            Ok(line) if line.trim().is_empty() => (),
+            Ok(line) => {
+                let _ = editor.add_history_entry(line.as_str());
+
+                match run_session_line(&line, &mut session, config) {
+                    Ok(Outcome::Reply(reply)) if reply.is_empty() => (),
+                    Ok(Outcome::Reply(reply)) => println!("{reply}"),
+                    Ok(Outcome::Quit) => return ExitCode::SUCCESS,
+                    Err(message) => eprintln!("error: {message}"),
+                }
+            }
+            // Ctrl-C abandons the line being edited
+            Err(ReadlineError::Interrupted) => (),
+            Err(ReadlineError::Eof) => return ExitCode::SUCCESS,
+            Err(error) => {
+                eprintln!("error: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let config: Config = match parse_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if config.help {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    if config.expressions.is_empty() && io::stdin().is_terminal() {
+        return run_session(&config);
+    }
+
+    let lines: Vec<String> = if config.expressions.is_empty() {
+        // Pipe mode: each line of standard input is evaluated
+        match io::stdin()
+            .lock()
+            .lines()
+            .collect::<Result<Vec<String>, io::Error>>()
+        {
+            Ok(lines) => lines,
+            Err(error) => {
+                eprintln!("error: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        config.expressions.clone()
+    };
+
+    let mut session: Session = Session::new().with_options(config.options);
+    let mut status: ExitCode = ExitCode::SUCCESS;
+
+    for line in lines.iter().filter(|line| !line.trim().is_empty()) {
+        match run_line(line, &mut session, &config) {
+            Ok(result) if result.is_empty() => (),
+            Ok(result) => println!("{result}"),
+            Err(message) => {
+                eprintln!("error: {message}");
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+
+    return status;
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        return args.iter().map(|&arg| String::from(arg)).collect();
+    }
+
+    #[test]
+    fn test_parse_args() {
+        match parse_args(args(&[
+            "--precision",
+            "3",
+            "--degrees",
+            "--format",
+            "json",
+            "sin(30)",
+        ])) {
+            Ok(config) => {
+                assert_eq!(config.precision, Some(3));
+                assert_eq!(config.format, Format::Json);
+                assert_eq!(config.options.angle, AngleUnit::Degrees);
+                assert_eq!(config.expressions, args(&["sin(30)"]));
+            }
+            Err(_) => assert!(false),
+        }
+
+        match parse_args(args(&["-2 * 3"])) {
+            Ok(config) => assert_eq!(config.expressions, args(&["-2 * 3"])),
+            Err(_) => assert!(false),
+        }
+
+        assert!(parse_args(args(&["--precision", "many"])).is_err());
+        assert!(parse_args(args(&["--format", "xml"])).is_err());
+        assert!(parse_args(args(&["--radians"])).is_err());
+    }
+
+    #[test]
+    fn test_run_line() {
+        match parse_args(args(&["--precision", "2", "--degrees"])) {
+            Ok(config) => {
+                let mut session: Session = Session::new().with_options(config.options);
+
+                assert_eq!(
+                    run_line("sin(30)", &mut session, &config),
+                    Ok(String::from("0.50"))
+                );
+                assert_eq!(
+                    run_line("x = 2 * 3", &mut session, &config),
+                    Ok(String::from("6.00"))
+                );
+                assert_eq!(
+                    run_line("x / 4", &mut session, &config),
+                    Ok(String::from("1.50"))
+                );
+                assert!(run_line("y + 1", &mut session, &config).is_err());
+                assert!(run_line("2 = 2", &mut session, &config).is_err());
+                assert_eq!(
+                    run_line("f(x) = x + 1", &mut session, &config),
+                    Ok(String::new())
+                );
+                assert_eq!(
+                    run_line("f(x)", &mut session, &config),
+                    Ok(String::from("7.00"))
+                );
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_format_value() {
+        match parse_args(args(&["--format", "json"])) {
+            Ok(config) => {
+                assert_eq!(
+                    format_value("1 / 2", 0.5, &config),
+                    "{\"expression\": \"1 / 2\", \"value\": 0.5}"
+                );
+                assert_eq!(
+                    format_value("ln(0)", f64::NEG_INFINITY, &config),
+                    "{\"expression\": \"ln(0)\", \"value\": null}"
+                );
+            }
+            Err(_) => assert!(false),
+        }
+
+        match parse_args(args(&["--format", "scientific", "--precision", "2"])) {
+            Ok(config) => assert_eq!(format_value("x", 1234.5, &config), "1.23e3"),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_run_session_line() {
+        let mut session: Session = Session::new();
+
+        match parse_args(args(&[])) {
+            Ok(config) => {
+                assert_eq!(
+                    run_session_line("r = 2", &mut session, &config),
+                    Ok(Outcome::Reply(String::from("2")))
+                );
+                assert_eq!(
+                    run_session_line("ans * 3", &mut session, &config),
+                    Ok(Outcome::Reply(String::from("6")))
+                );
+                assert_eq!(
+                    run_session_line(":vars", &mut session, &config),
+                    Ok(Outcome::Reply(String::from("ans = 6\nr = 2")))
+                );
+
+                match run_session_line(":funcs", &mut session, &config) {
+                    Ok(Outcome::Reply(reply)) => {
+                        assert!(reply.contains("sqrt") && reply.contains("pi"))
+                    }
+                    _ => assert!(false),
+                }
+
+                assert_eq!(
+                    run_session_line(":quit", &mut session, &config),
+                    Ok(Outcome::Quit)
+                );
+                assert_eq!(
+                    run_session_line(":save", &mut session, &config),
+                    Err(String::from("Unknown command :save"))
+                );
+            }
+            Err(_) => assert!(false),
+        }
+    }
+}
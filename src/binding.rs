@@ -0,0 +1,169 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::{substitute, Ast};
+use super::constants::is_constant;
+use super::functions::Function;
+
+use std::collections::HashMap;
+
+/// Local binding of an expression like let t = x / 2 in sin(t) + cos(t), naming a subexpression
+/// whose value is computed once. Expressions of binding are blanked before their position
+/// in expression, so positions of errors are given in whole expression
+#[derive(Debug, PartialEq)]
+pub(crate) struct LetBinding<'a> {
+    /// Name bound to value
+    pub(crate) name: &'a str,
+    /// Expression of value bound to name
+    pub(crate) value: String,
+    /// Expression evaluated with binding, which can hold other bindings
+    pub(crate) body: String,
+}
+
+/// Get position of word given in argument in expression given in argument, outside parenthesis.
+/// None is given if word is not found
+fn find_word(expression: &str, word: &str) -> Option<usize> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut depth: usize = 0;
+
+    for (index, c) in expression.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && expression[index..].starts_with(word) => {
+                let before: Option<char> = expression[..index].chars().next_back();
+                let after: Option<char> = expression[index + word.len()..].chars().next();
+
+                if !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char) {
+                    return Some(index);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    return None;
+}
+
+/// Get local binding of expression given in argument, if it starts with let name = value in.
+/// None is given if expression does not start with a binding.
+/// If binding is not valid, an error message is stored in string contained in Result output
+pub(crate) fn let_binding(expression: &str) -> Result<Option<LetBinding<'_>>, String> {
+    let start: usize = expression.len() - expression.trim_start().len();
+    let after_let: &str = match expression[start..].strip_prefix("let") {
+        Some(after_let) if after_let.starts_with(char::is_whitespace) => after_let,
+        _ => return Ok(None),
+    };
+
+    let (name, value) = match after_let.split_once('=') {
+        Some((name, value)) => (name.trim(), value),
+        None => return Ok(None),
+    };
+
+    if !name.starts_with(char::is_alphabetic)
+        || !name.chars().all(|c| c.is_alphanumeric() || c == '_')
+    {
+        return Ok(None);
+    }
+
+    if is_constant(name) || Function::is_fun(name) {
+        return Err(format!(
+            "Cannot bind {name}, which is not a variable, at position {start}"
+        ));
+    }
+
+    let value_start: usize = expression.len() - value.len();
+    let value_length: usize = find_word(value, "in")
+        .ok_or_else(|| format!("Missing in after binding of {name} at position {start}"))?;
+    let body_start: usize = value_start + value_length + "in".len();
+
+    return Ok(Some(LetBinding {
+        name,
+        value: " ".repeat(value_start) + &value[..value_length],
+        body: " ".repeat(body_start) + &expression[body_start..],
+    }));
+}
+
+/// Evaluate expression with local binding given in argument, value of binding being computed once
+/// by evaluation function given in argument with variables given in argument, then body being evaluated
+/// with the same variables and the bound name.
+/// If error occurs during evaluation, an error is given in Result output
+pub(crate) fn evaluate_binding<E, F>(
+    binding: &LetBinding<'_>,
+    variables: &HashMap<String, f64>,
+    mut evaluate: F,
+) -> Result<f64, E>
+where
+    F: FnMut(&str, &HashMap<String, f64>) -> Result<f64, E>,
+{
+    let value: f64 = evaluate(&binding.value, variables)?;
+
+    let mut scope: HashMap<String, f64> = variables.clone();
+    scope.insert(String::from(binding.name), value);
+
+    return evaluate(&binding.body, &scope);
+}
+
+/// Expand local bindings of expression given in argument, each bound name being replaced by its value
+/// in syntax tree of body, like calls of functions defined by user in scripts, so expression can be compiled.
+/// Value is then computed at each use of bound name.
+/// None is given if expression does not start with a binding.
+/// If a binding is not valid, an error message is stored in string contained in Result output
+pub(crate) fn expand_bindings(expression: &str) -> Result<Option<String>, String> {
+    let binding: LetBinding<'_> = match let_binding(expression)? {
+        Some(binding) => binding,
+        None => return Ok(None),
+    };
+
+    // Bindings of body are expanded first, so a name bound again in body hides this binding
+    let body: String = expand_bindings(&binding.body)?.unwrap_or(binding.body);
+    let value: Ast = Ast::new(&binding.value)?;
+
+    return Ok(Some(
+        substitute(&Ast::new(&body)?.root, &[(binding.name, &value.root)]).to_string(),
+    ));
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_let_binding() {
+        match let_binding(" let t = x / (2 in 3) in sin(t) + input") {
+            Ok(Some(binding)) => {
+                assert_eq!(binding.name, "t");
+                assert_eq!(binding.value, "         x / (2 in 3) ");
+                assert_eq!(binding.body, format!("{} sin(t) + input", " ".repeat(24)));
+            }
+            _ => assert!(false),
+        }
+
+        assert_eq!(let_binding("letter + 1"), Ok(None));
+        assert_eq!(let_binding("let * 2"), Ok(None));
+        assert_eq!(
+            let_binding("let e = 1 in e"),
+            Err(String::from(
+                "Cannot bind e, which is not a variable, at position 0"
+            ))
+        );
+        assert_eq!(
+            let_binding("let t = x + 1"),
+            Err(String::from("Missing in after binding of t at position 0"))
+        );
+    }
+
+    #[test]
+    fn test_expand_bindings() {
+        assert_eq!(
+            expand_bindings("let t = x / 2 in sin(t) + t"),
+            Ok(Some(String::from("sin(x / 2) + x / 2")))
+        );
+        assert_eq!(
+            expand_bindings("let x = x * 2 in let y = x + 1 in let x = x - y in x * y"),
+            Ok(Some(String::from("(x * 2 - (x * 2 + 1)) * (x * 2 + 1)")))
+        );
+        assert_eq!(expand_bindings("x + 1"), Ok(None));
+        assert!(expand_bindings("let t = 2 * in t").is_err());
+    }
+}
@@ -0,0 +1,373 @@
+#![allow(clippy::needless_return)]
+
+use super::functions::Function;
+use super::library::{Formula, FormulaLibrary, LibraryOptions};
+use super::units::Unit;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Version of bundle format written by this version of library.
+/// Bundles written with a greater format version are rejected.
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Options of library as stored in bundle
+#[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize)]
+struct BundleOptions {
+    #[serde(default)]
+    require_units: bool,
+}
+
+/// Formula as stored in bundle, units being written as strings like "m.s^-1"
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+struct BundleFormula {
+    name: String,
+    version: u32,
+    expression: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    variables: Vec<String>,
+    #[serde(default)]
+    units: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result_unit: Option<String>,
+}
+
+/// Portable representation of a formula library
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+struct Bundle {
+    format_version: u32,
+    #[serde(default)]
+    taz_version: String,
+    #[serde(default)]
+    required_functions: Vec<String>,
+    #[serde(default)]
+    options: BundleOptions,
+    #[serde(default)]
+    constants: BTreeMap<String, f64>,
+    #[serde(default)]
+    aliases: BTreeMap<String, String>,
+    #[serde(default)]
+    formulas: Vec<BundleFormula>,
+}
+
+impl Bundle {
+    /// Create a bundle from a formula library
+    fn from_library(library: &FormulaLibrary) -> Bundle {
+        let mut required_functions: Vec<String> = Vec::new();
+        let mut formulas: Vec<BundleFormula> = Vec::with_capacity(library.len());
+
+        for (name, version, formula) in library.iter() {
+            for fun_name in library.functions(name, version) {
+                required_functions.push(String::from(fun_name));
+            }
+
+            formulas.push(BundleFormula {
+                name: String::from(name),
+                version,
+                expression: formula.expression.clone(),
+                description: formula.description.clone(),
+                variables: formula.variables.clone(),
+                units: formula
+                    .units
+                    .iter()
+                    .map(|(variable, unit)| (variable.clone(), unit.to_string()))
+                    .collect(),
+                result_unit: formula.result_unit.map(|unit| unit.to_string()),
+            });
+        }
+
+        required_functions.sort_unstable();
+        required_functions.dedup();
+
+        return Bundle {
+            format_version: BUNDLE_FORMAT_VERSION,
+            taz_version: String::from(env!("CARGO_PKG_VERSION")),
+            required_functions,
+            options: BundleOptions {
+                require_units: library.options().require_units,
+            },
+            constants: library
+                .constants()
+                .map(|(name, value)| (String::from(name), value))
+                .collect(),
+            aliases: library
+                .aliases()
+                .map(|(alias, name)| (String::from(alias), String::from(name)))
+                .collect(),
+            formulas,
+        };
+    }
+
+    /// Check that bundle can be loaded by this version of library
+    fn check_compatibility(&self) -> Result<(), String> {
+        if self.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(format!(
+                "Bundle format version {} is not supported, this version of taz supports up to version {}",
+                self.format_version, BUNDLE_FORMAT_VERSION
+            ));
+        }
+
+        let missing_functions: Vec<&str> = self
+            .required_functions
+            .iter()
+            .map(|name| name.as_str())
+            .filter(|&name| !Function::is_fun(name))
+            .collect();
+
+        if !missing_functions.is_empty() {
+            return Err(format!(
+                "Bundle requires functions not available in this version of taz: {}",
+                missing_functions.join(", ")
+            ));
+        }
+
+        return Ok(());
+    }
+
+    /// Create a formula library from bundle, all formulas being validated.
+    /// If bundle is not compatible or if a formula is not valid,
+    /// an error message is stored in string contained in Result output
+    fn into_library(self) -> Result<FormulaLibrary, String> {
+        self.check_compatibility()?;
+
+        let mut library: FormulaLibrary = FormulaLibrary::with_options(LibraryOptions {
+            require_units: self.options.require_units,
+        });
+
+        for (name, value) in self.constants.iter() {
+            library.set_constant(name, *value)?;
+        }
+
+        for bundle_formula in self.formulas.into_iter() {
+            let name: String = bundle_formula.name;
+            let version: u32 = bundle_formula.version;
+
+            let mut units: HashMap<String, Unit> =
+                HashMap::with_capacity(bundle_formula.units.len());
+
+            for (variable, unit) in bundle_formula.units.iter() {
+                units.insert(variable.clone(), unit.parse()?);
+            }
+
+            let formula: Formula = Formula {
+                expression: bundle_formula.expression,
+                description: bundle_formula.description,
+                variables: bundle_formula.variables,
+                units,
+                result_unit: bundle_formula
+                    .result_unit
+                    .map(|unit| unit.parse())
+                    .transpose()?,
+            };
+
+            library
+                .add(&name, version, formula)
+                .map_err(|message| format!("Formula {name} version {version}: {message}"))?;
+        }
+
+        for (alias, name) in self.aliases.iter() {
+            library.add_alias(alias, name)?;
+        }
+
+        return Ok(library);
+    }
+}
+
+impl FormulaLibrary {
+    /// Export library (formulas, constants, aliases and options) as a JSON bundle.
+    /// If error occurs during serialization, an error message is stored
+    /// in string contained in Result output
+    pub fn to_json(&self) -> Result<String, String> {
+        return serde_json::to_string_pretty(&Bundle::from_library(self))
+            .map_err(|error| error.to_string());
+    }
+
+    /// Load library from a JSON bundle.
+    /// The bundle is checked against capabilities of this version of library
+    /// (bundle format and available functions) and all its formulas are validated.
+    /// If error occurs, an error message is stored in string contained in Result output
+    pub fn from_json(bundle: &str) -> Result<FormulaLibrary, String> {
+        let bundle: Bundle = serde_json::from_str(bundle).map_err(|error| error.to_string())?;
+        return bundle.into_library();
+    }
+
+    /// Export library (formulas, constants, aliases and options) as a TOML bundle.
+    /// If error occurs during serialization, an error message is stored
+    /// in string contained in Result output
+    pub fn to_toml(&self) -> Result<String, String> {
+        return toml::to_string(&Bundle::from_library(self)).map_err(|error| error.to_string());
+    }
+
+    /// Load library from a TOML bundle.
+    /// The bundle is checked against capabilities of this version of library
+    /// (bundle format and available functions) and all its formulas are validated.
+    /// If error occurs, an error message is stored in string contained in Result output
+    pub fn from_toml(bundle: &str) -> Result<FormulaLibrary, String> {
+        let bundle: Bundle = toml::from_str(bundle).map_err(|error| error.to_string())?;
+        return bundle.into_library();
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library() -> FormulaLibrary {
+        let mut library: FormulaLibrary = FormulaLibrary::new();
+
+        let mut speed: Formula = Formula::new("d / t", &["d", "t"]);
+        speed.description = String::from("Average speed");
+        speed.units = HashMap::from([
+            (String::from("d"), Unit::meter()),
+            (String::from("t"), Unit::second()),
+        ]);
+        speed.result_unit = Some(Unit::meter() / Unit::second());
+
+        assert!(library.set_constant("vat", 0.2).is_ok());
+        assert!(library.add("speed", 1, speed).is_ok());
+        assert!(library
+            .add(
+                "gross",
+                2,
+                Formula::new("round2(net) * (1 + vat)", &["net"])
+            )
+            .is_err());
+        assert!(library
+            .add("gross", 2, Formula::new("abs(net) * (1 + vat)", &["net"]))
+            .is_ok());
+        assert!(library.add_alias("velocity", "speed").is_ok());
+
+        return library;
+    }
+
+    #[test]
+    fn test_bundle_json_round_trip() {
+        let library: FormulaLibrary = library();
+
+        match library.to_json() {
+            Ok(bundle) => {
+                assert!(bundle.contains("\"required_functions\""));
+                assert_eq!(FormulaLibrary::from_json(&bundle), Ok(library));
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_bundle_deterministic_output() {
+        let mut formula: Formula = Formula::new("a * b * c * d", &["d", "c", "b", "a"]);
+        formula.units = ["d", "a", "c", "b"]
+            .iter()
+            .map(|&name| (String::from(name), Unit::second()))
+            .collect();
+
+        // Each hash map iterates its keys in its own random order, but bundles are the same
+        let bundles: Vec<Result<String, String>> = (0..8)
+            .map(|_| {
+                let mut library: FormulaLibrary = library();
+                let mut formula: Formula = formula.clone();
+                formula.units = formula.units.into_iter().collect();
+
+                assert!(library.add("volume", 1, formula).is_ok());
+                return library.to_json();
+            })
+            .collect();
+
+        match &bundles[0] {
+            Ok(bundle) => {
+                assert!(bundles.iter().all(|other| other.as_ref() == Ok(bundle)));
+
+                let positions: Vec<Option<usize>> =
+                    ["\"a\": \"s\"", "\"b\": \"s\"", "\"d\": \"s\""]
+                        .iter()
+                        .map(|unit| bundle.find(unit))
+                        .collect();
+
+                assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_bundle_toml_round_trip() {
+        let library: FormulaLibrary = library();
+
+        match library.to_toml() {
+            Ok(bundle) => {
+                assert!(bundle.contains("result_unit = \"m.s^-1\""));
+                assert_eq!(FormulaLibrary::from_toml(&bundle), Ok(library));
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_bundle_from_toml_written_by_hand() {
+        let bundle: &str = r#"
+            format_version = 1
+
+            [constants]
+            g = 9.81
+
+            [[formulas]]
+            name = "weight"
+            version = 1
+            expression = "m * g"
+            variables = ["m"]
+        "#;
+
+        match FormulaLibrary::from_toml(bundle) {
+            Ok(library) => {
+                let variables: HashMap<String, f64> = HashMap::from([(String::from("m"), 2.0)]);
+                assert_eq!(library.evaluate("weight", 1, &variables), Ok(19.62));
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_bundle_incompatible_format_version() {
+        let bundle: &str = r#"{ "format_version": 99 }"#;
+
+        match FormulaLibrary::from_json(bundle) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(
+                message,
+                "Bundle format version 99 is not supported, this version of taz supports up to version 1"
+            ),
+        }
+    }
+
+    #[test]
+    fn test_bundle_missing_functions() {
+        let bundle: &str = r#"{
+            "format_version": 1,
+            "required_functions": ["sqrt", "erf", "gamma"]
+        }"#;
+
+        match FormulaLibrary::from_json(bundle) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(
+                message,
+                "Bundle requires functions not available in this version of taz: erf, gamma"
+            ),
+        }
+    }
+
+    #[test]
+    fn test_bundle_invalid_formula() {
+        let bundle: &str = r#"{
+            "format_version": 1,
+            "formulas": [{ "name": "f", "version": 3, "expression": "x +", "variables": ["y"] }]
+        }"#;
+
+        match FormulaLibrary::from_json(bundle) {
+            Ok(_) => assert!(false),
+            Err(message) => assert!(message.starts_with("Formula f version 3: ")),
+        }
+    }
+}
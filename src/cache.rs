@@ -0,0 +1,269 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::Ast;
+use super::compiler::CompiledExpression;
+use super::evaluate;
+
+use std::collections::HashMap;
+
+/// Cache of compiled expressions keyed by expression string.
+/// When cache is full, the least recently used expression is removed to store a new one,
+/// so repeated evaluations of the same formulas skip their parsing.
+#[derive(Debug, Clone)]
+pub struct ExpressionCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<String, (CompiledExpression, u64)>,
+}
+
+impl ExpressionCache {
+    /// Create an empty cache storing at most the number of expressions given in argument,
+    /// a cache storing at least one expression
+    pub fn new(capacity: usize) -> ExpressionCache {
+        let capacity: usize = capacity.max(1);
+
+        ExpressionCache {
+            capacity,
+            tick: 0,
+            entries: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Get maximal number of expressions stored in cache
+    pub fn capacity(&self) -> usize {
+        return self.capacity;
+    }
+
+    /// Get number of expressions stored in cache
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    /// Check if cache does not store any expression
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    /// Check if expression given in argument is stored in cache
+    pub fn contains(&self, expression: &str) -> bool {
+        return self.entries.contains_key(expression);
+    }
+
+    /// Remove all expressions stored in cache
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Get compiled expression from cache, compiling and storing it if it is not already stored.
+    /// Expressions which cannot be compiled are not stored.
+    ///
+    /// If error occurs during compilation, an error message is stored in string contained in Result output
+    pub fn get_or_compile(&mut self, expression: &str) -> Result<&CompiledExpression, String> {
+        self.tick += 1;
+
+        if !self.entries.contains_key(expression) {
+            let compiled: CompiledExpression = CompiledExpression::new(expression)?;
+
+            if self.entries.len() >= self.capacity {
+                self.remove_least_recently_used();
+            }
+
+            self.entries
+                .insert(String::from(expression), (compiled, self.tick));
+        }
+
+        let (compiled, last_use) = self.entries.get_mut(expression).unwrap();
+        *last_use = self.tick;
+
+        return Ok(compiled);
+    }
+
+    /// Get compiled canonical form of expression from cache, compiling and storing it if it is not already stored.
+    /// Expressions with the same canonical form, like x * y + 1 and 1 + y * x, share the same entry,
+    /// stored under their canonical form. Variables of compiled expression are those of canonical form,
+    /// so they should be bound by name.
+    ///
+    /// If error occurs during parsing or compilation, an error message is stored in string contained in Result output
+    pub fn get_or_compile_canonical(
+        &mut self,
+        expression: &str,
+    ) -> Result<&CompiledExpression, String> {
+        let canonical: String = Ast::new(expression)?.canonical().to_string();
+        return self.get_or_compile(&canonical);
+    }
+
+    /// Evaluate expression with values of variables stored in hash map given in argument,
+    /// expression being parsed only if it is not already stored in cache.
+    /// Expressions which cannot be compiled, like calls of calculus functions,
+    /// are evaluated by evaluate function each time.
+    /// Errors are always given by evaluate function, a compiled expression failing being evaluated again by it,
+    /// so cache gives the same results and the same error messages as this function.
+    ///
+    /// If error occurs during evaluation, an error message is stored in string contained in Result output
+    pub fn evaluate(
+        &mut self,
+        expression: &str,
+        variables: &HashMap<String, f64>,
+    ) -> Result<f64, String> {
+        match self.get_or_compile(expression) {
+            Ok(compiled) => compiled
+                .evaluate_with_variables(variables)
+                .or_else(|_| evaluate(expression, variables)),
+            Err(_) => evaluate(expression, variables),
+        }
+    }
+
+    /// Remove expression whose last use is the oldest
+    fn remove_least_recently_used(&mut self) {
+        let oldest: Option<String> = self
+            .entries
+            .iter()
+            .min_by_key(|(_, &(_, last_use))| last_use)
+            .map(|(expression, _)| expression.clone());
+
+        if let Some(expression) = oldest {
+            self.entries.remove(&expression);
+        }
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expression_cache_evaluate() {
+        let mut cache: ExpressionCache = ExpressionCache::new(4);
+        let mut variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 2.0)]);
+
+        assert_eq!(cache.evaluate("x^2 + 1", &variables), Ok(5.0));
+        assert_eq!(cache.len(), 1);
+
+        variables.insert(String::from("x"), 3.0);
+        assert_eq!(cache.evaluate("x^2 + 1", &variables), Ok(10.0));
+        assert_eq!(cache.len(), 1);
+
+        assert!(cache.evaluate("x + y", &variables).is_err());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_expression_cache_invalid_expression_not_stored() {
+        let mut cache: ExpressionCache = ExpressionCache::new(4);
+
+        assert!(cache.evaluate("2 * (x + 1", &HashMap::new()).is_err());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_expression_cache_eviction() {
+        let mut cache: ExpressionCache = ExpressionCache::new(2);
+        let variables: HashMap<String, f64> = HashMap::new();
+
+        assert_eq!(cache.evaluate("1 + 1", &variables), Ok(2.0));
+        assert_eq!(cache.evaluate("2 + 2", &variables), Ok(4.0));
+        assert_eq!(cache.evaluate("1 + 1", &variables), Ok(2.0));
+        assert_eq!(cache.evaluate("3 + 3", &variables), Ok(6.0));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains("1 + 1"));
+        assert!(!cache.contains("2 + 2"));
+        assert!(cache.contains("3 + 3"));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(ExpressionCache::new(0).capacity(), 1);
+    }
+
+    #[test]
+    fn test_expression_cache_canonical() {
+        let mut cache: ExpressionCache = ExpressionCache::new(4);
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("x"), 2.0), (String::from("y"), 5.0)]);
+
+        for expression in ["x * y + 1", "1 + y * x", "+y * x + 1"] {
+            match cache.get_or_compile_canonical(expression) {
+                Ok(compiled) => assert_eq!(compiled.evaluate_with_variables(&variables), Ok(11.0)),
+                Err(_) => assert!(false),
+            }
+        }
+
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains("1 + x * y"));
+
+        assert!(cache.get_or_compile_canonical("x * (y + 1)").is_ok());
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_expression_cache_evaluate_like_evaluate_function() {
+        let mut cache: ExpressionCache = ExpressionCache::new(4);
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("x"), -2.0), (String::from("y"), 0.0)]);
+
+        // Expressions which cannot be compiled are evaluated without being stored
+        for expression in [
+            "integrate(t^2, t, 0, 3)",
+            "deriv(t^3, t, x)",
+            "series(k, 1, 4, k * x)",
+            "2 * (x + 1",
+        ] {
+            assert_eq!(
+                cache.evaluate(expression, &variables),
+                evaluate(expression, &variables)
+            );
+        }
+
+        assert!(cache.is_empty());
+
+        // Conditional and boolean operators are compiled with jumps and local bindings are expanded
+        for expression in [
+            "let t = x^2 in t + 1",
+            "let t = y in let t = 1 / t in t",
+            "x ? 1 : 2",
+            "if(y, 1 / y, 3)",
+            "x && !y",
+            "x < y || x == 2",
+            "piecewise(x < 0, -x, x)",
+            "y ? 1 / y : ln(y)",
+        ] {
+            assert_eq!(
+                cache.evaluate(expression, &variables),
+                evaluate(expression, &variables)
+            );
+        }
+
+        assert!(cache.contains("y ? 1 / y : ln(y)"));
+        assert_eq!(
+            cache.evaluate("let t = y in let t = 1 / t in t", &variables),
+            Err(String::from("Division by zero at position 27"))
+        );
+        cache.clear();
+
+        assert_eq!(
+            cache.get_or_compile("1 + integrate(t, t, 0, 1)").err(),
+            Some(String::from(
+                "Function integrate cannot be compiled at position 4"
+            ))
+        );
+        assert_eq!(cache.evaluate("x^2 + y", &variables), Ok(4.0));
+        assert_eq!(cache.len(), 1);
+
+        // Errors of compiled expressions are given by evaluate function, with their positions
+        for expression in ["1 + ln(y)", "x / y", "x + z", "sqrt(x) * 2"] {
+            assert_eq!(
+                cache.evaluate(expression, &variables),
+                evaluate(expression, &variables)
+            );
+        }
+
+        assert_eq!(
+            cache.evaluate("1 + ln(y)", &variables),
+            Err(String::from(
+                "Argument of ln function is negative or null at position 8"
+            ))
+        );
+        assert_eq!(cache.len(), 4);
+    }
+}
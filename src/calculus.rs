@@ -0,0 +1,960 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::Ast;
+use super::compiler::CompiledExpression;
+
+use std::collections::HashMap;
+
+/// Names of functions of calculus taking a subexpression and its variable, with their minimal and maximal
+/// numbers of arguments: integrate(expr, var, a, b) giving integral of subexpression from a to b,
+/// deriv(expr, var, x0) giving its derivative at x0 and solve(expr, var, guess, tolerance, max_iterations)
+/// giving one of its roots, tolerance and maximal number of iterations being optional.
+/// Summation series(i, a, b, expr), also written sum(i, a, b, expr), and product prod(i, a, b, expr) give the sum
+/// and the product of subexpression for i from a to b, their variable coming first. Function taylor(expr, var, x0, order) gives the value
+/// of Taylor polynomial of subexpression at x0 up to order, at the value of its variable, and limit(expr, var, x0)
+/// gives its limit at x0. Function minimize(expr, var, a, b) gives a vector, so it is only evaluated with vectors
+const CALCULUS_FUNCTIONS: [(&str, usize, usize); 9] = [
+    ("integrate", 4, 4),
+    ("deriv", 3, 3),
+    ("solve", 3, 5),
+    ("series", 4, 4),
+    ("sum", 4, 4),
+    ("prod", 4, 4),
+    ("taylor", 4, 4),
+    ("limit", 3, 3),
+    ("minimize", 4, 4),
+];
+
+/// Tolerance on absolute error of numerical integration
+const TOLERANCE: f64 = 1e-10;
+
+/// Maximal depth of subdivisions of interval of integration
+const MAX_DEPTH: usize = 50;
+
+/// Step of central differences relative to magnitude of point of derivation
+const DERIVATION_STEP: f64 = 1e-3;
+
+/// Default tolerance on root given by solve function, relative to magnitude of root
+const DEFAULT_ROOT_TOLERANCE: f64 = 1e-12;
+
+/// Default maximal number of iterations of solve function
+const DEFAULT_MAX_ITERATIONS: f64 = 100.0;
+
+/// Number of points approaching point of limit on each side, each one ten times closer than the previous one
+const LIMIT_STEPS: i32 = 8;
+
+/// Tolerance on agreement of values approaching limit, relative to magnitude of limit
+const LIMIT_TOLERANCE: f64 = 1e-6;
+
+/// Fraction of interval where golden section search puts its points, which is (3 - sqrt(5)) / 2
+const GOLDEN_SECTION: f64 = 0.381_966_011_250_105_1;
+
+/// Tolerance on minimum given by minimize function, relative to its magnitude
+const MINIMUM_TOLERANCE: f64 = 1.5e-8;
+
+/// Maximal number of iterations of minimize function
+const MAX_MINIMIZATION_ITERATIONS: usize = 500;
+
+/// Maximal number of terms of summations and products, so a large range cannot hang evaluation
+const MAX_SERIES_TERMS: f64 = 1_000_000.0;
+
+/// Variable of a call of calculus function and positions of its subexpression, with values of its points
+type CalculusCall<'a> = ((usize, usize), &'a str, Vec<f64>);
+
+/// Expression whose calls of calculus functions are replaced by variables,
+/// with copy of variables holding values of calls
+type ReplacedCalls = (String, HashMap<String, f64>);
+
+/// Check if a char can be part of a name
+fn is_word_char(c: char) -> bool {
+    return c.is_alphanumeric() || c == '_';
+}
+
+/// Integrate function given in argument from a to b by adaptive Simpson quadrature,
+/// values of function at a, b and middle of interval and Simpson estimate on whole interval being given in argument.
+/// Interval is split in two halves until estimate on halves agrees with estimate on whole interval.
+/// If error occurs during evaluation of function, an error message is stored in string contained in Result output
+#[allow(clippy::too_many_arguments)]
+fn adaptive_simpson<F>(
+    f: &mut F,
+    a: f64,
+    b: f64,
+    fa: f64,
+    fm: f64,
+    fb: f64,
+    whole: f64,
+    tolerance: f64,
+    depth: usize,
+) -> Result<f64, String>
+where
+    F: FnMut(f64) -> Result<f64, String>,
+{
+    let m: f64 = (a + b) / 2.0;
+    let (lm, rm): (f64, f64) = ((a + m) / 2.0, (m + b) / 2.0);
+    let (flm, frm): (f64, f64) = (f(lm)?, f(rm)?);
+    let left: f64 = (m - a) / 6.0 * (fa + 4.0 * flm + fm);
+    let right: f64 = (b - m) / 6.0 * (fm + 4.0 * frm + fb);
+    let delta: f64 = left + right - whole;
+
+    if depth == 0 || delta.abs() <= 15.0 * tolerance {
+        return Ok(left + right + delta / 15.0);
+    }
+
+    return Ok(
+        adaptive_simpson(f, a, m, fa, flm, fm, left, tolerance / 2.0, depth - 1)?
+            + adaptive_simpson(f, m, b, fm, frm, fb, right, tolerance / 2.0, depth - 1)?,
+    );
+}
+
+/// Integrate function given in argument from a to b by adaptive Simpson quadrature.
+/// If error occurs during evaluation of function, an error message is stored in string contained in Result output
+fn integrate<F>(mut f: F, a: f64, b: f64) -> Result<f64, String>
+where
+    F: FnMut(f64) -> Result<f64, String>,
+{
+    let m: f64 = (a + b) / 2.0;
+    let (fa, fm, fb): (f64, f64, f64) = (f(a)?, f(m)?, f(b)?);
+    let whole: f64 = (b - a) / 6.0 * (fa + 4.0 * fm + fb);
+
+    return adaptive_simpson(&mut f, a, b, fa, fm, fb, whole, TOLERANCE, MAX_DEPTH);
+}
+
+/// Differentiate function given in argument at point x0 by central differences,
+/// refined by Richardson extrapolation so error decreases like fourth power of step.
+/// If error occurs during evaluation of function, an error message is stored in string contained in Result output
+fn differentiate<F>(mut f: F, x0: f64) -> Result<f64, String>
+where
+    F: FnMut(f64) -> Result<f64, String>,
+{
+    let h: f64 = DERIVATION_STEP * x0.abs().max(1.0);
+    let mut difference = |h: f64| -> Result<f64, String> {
+        return Ok((f(x0 + h)? - f(x0 - h)?) / (2.0 * h));
+    };
+
+    let (coarse, fine): (f64, f64) = (difference(h)?, difference(h / 2.0)?);
+    return Ok((4.0 * fine - coarse) / 3.0);
+}
+
+/// Find a root of function given in argument from guess given in argument by Newton method,
+/// derivatives being computed by central differences. Once two iterates enclose a change of sign,
+/// the root is kept in this bracket and an iterate leaving it is replaced by bisection.
+/// Iterations stop when an iterate moves less than tolerance relative to its magnitude.
+/// If root is not found within maximal number of iterations or if derivative vanishes outside bracket,
+/// an error message is stored in string contained in Result output
+fn find_root<F>(mut f: F, guess: f64, tolerance: f64, max_iterations: usize) -> Result<f64, String>
+where
+    F: FnMut(f64) -> Result<f64, String>,
+{
+    let (mut x, mut fx): (f64, f64) = (guess, f(guess)?);
+    let mut bracket: Option<[(f64, f64); 2]> = None;
+
+    for _ in 0..max_iterations {
+        if fx == 0.0 {
+            return Ok(x);
+        }
+
+        let mut next: f64 = x - fx / differentiate(&mut f, x)?;
+
+        match bracket {
+            Some([(a, _), (b, _)]) if !(next > a.min(b) && next < a.max(b)) => {
+                next = (a + b) / 2.0;
+            }
+            None if !next.is_finite() => {
+                return Err(format!(
+                    "Derivative vanishes at {x}, so no root is found from guess {guess}"
+                ));
+            }
+            _ => (),
+        }
+
+        let f_next: f64 = f(next)?;
+
+        bracket = match bracket {
+            Some([low, high]) if f_next.signum() == low.1.signum() => Some([(next, f_next), high]),
+            Some([low, _]) => Some([low, (next, f_next)]),
+            None if f_next.signum() != fx.signum() => Some([(x, fx), (next, f_next)]),
+            None => None,
+        };
+
+        if (next - x).abs() <= tolerance * next.abs().max(1.0) {
+            return Ok(next);
+        }
+
+        (x, fx) = (next, f_next);
+    }
+
+    return Err(format!(
+        "No root found from guess {guess} after {max_iterations} iterations"
+    ));
+}
+
+/// Sum values of function given in argument for each integer from a to b included, or multiply them
+/// if product is asked, giving 0 for an empty summation and 1 for an empty product, when b is lower than a.
+/// If bounds are not integers, if range has more than a million terms or if error occurs during evaluation
+/// of function, an error message is stored in string contained in Result output
+fn series<F>(mut f: F, a: f64, b: f64, is_product: bool) -> Result<f64, String>
+where
+    F: FnMut(f64) -> Result<f64, String>,
+{
+    let kind: &str = if is_product { "product" } else { "summation" };
+
+    if a.fract() != 0.0 || b.fract() != 0.0 {
+        return Err(format!("Bounds {a} and {b} of {kind} must be integers"));
+    }
+
+    if b - a >= MAX_SERIES_TERMS {
+        return Err(format!(
+            "Range of {kind} from {a} to {b} exceeds {MAX_SERIES_TERMS} terms"
+        ));
+    }
+
+    let mut total: f64 = if is_product { 1.0 } else { 0.0 };
+
+    // Terms are counted by an integer, since adding 1 to a bound beyond 2^53 does not change it
+    for k in 0..(b - a + 1.0).max(0.0) as u64 {
+        match is_product {
+            true => total *= f(a + k as f64)?,
+            false => total += f(a + k as f64)?,
+        }
+    }
+
+    return Ok(total);
+}
+
+/// Get limit of function given in argument when approaching x0 from one side, from the right if direction is 1
+/// and from the left if direction is -1. Function is evaluated at points ten times closer to x0 each time,
+/// and the limit is the value with the least change from the previous one, so rounding errors near x0 are avoided.
+/// None is given if values do not settle, like 1 / x near 0.
+/// If error occurs during evaluation of function, an error message is stored in string contained in Result output
+fn one_sided_limit<F>(f: &mut F, x0: f64, direction: f64) -> Result<Option<f64>, String>
+where
+    F: FnMut(f64) -> Result<f64, String>,
+{
+    let scale: f64 = x0.abs().max(1.0);
+    let mut previous: f64 = f(x0 + direction * scale / 10.0)?;
+    let mut best: Option<(f64, f64)> = None;
+
+    for k in 2..=LIMIT_STEPS {
+        let value: f64 = f(x0 + direction * scale * 10f64.powi(-k))?;
+        let change: f64 = (value - previous).abs();
+
+        if change.is_finite() && best.is_none_or(|(_, least)| change < least) {
+            best = Some((value, change));
+        }
+
+        previous = value;
+    }
+
+    return Ok(best.and_then(|(value, change)| {
+        (change <= LIMIT_TOLERANCE * value.abs().max(1.0)).then_some(value)
+    }));
+}
+
+/// Get limit of function given in argument at x0, approached from both sides, like 1 for sin(x) / x at 0.
+/// If limit diverges on a side or differs by side or if error occurs during evaluation of function,
+/// an error message is stored in string contained in Result output
+fn limit<F>(mut f: F, x0: f64) -> Result<f64, String>
+where
+    F: FnMut(f64) -> Result<f64, String>,
+{
+    let left: Option<f64> = one_sided_limit(&mut f, x0, -1.0)?;
+    let right: Option<f64> = one_sided_limit(&mut f, x0, 1.0)?;
+
+    return match (left, right) {
+        (None, _) => Err(format!("Limit at {x0} diverges on left side")),
+        (_, None) => Err(format!("Limit at {x0} diverges on right side")),
+        (Some(left), Some(right))
+            if (left - right).abs() > LIMIT_TOLERANCE * left.abs().max(right.abs()).max(1.0) =>
+        {
+            Err(format!(
+                "Limit at {x0} differs by side, being {left} on left side and {right} on right side"
+            ))
+        }
+        (Some(left), Some(right)) => Ok((left + right) / 2.0),
+    };
+}
+
+/// Find minimum of function given in argument between a and b by Brent method, giving the point of minimum
+/// and the value of function at this point. Steps of parabolic interpolation through three best points are taken
+/// when they stay in interval and shrink it fast enough, otherwise golden section search is used.
+/// As other local searches, a local minimum is given if function has many of them.
+/// If error occurs during evaluation of function, an error message is stored in string contained in Result output
+fn minimize<F>(mut f: F, a: f64, b: f64) -> Result<(f64, f64), String>
+where
+    F: FnMut(f64) -> Result<f64, String>,
+{
+    let (mut a, mut b): (f64, f64) = (a.min(b), a.max(b));
+    let mut x: f64 = a + GOLDEN_SECTION * (b - a);
+    let mut fx: f64 = f(x)?;
+    let (mut w, mut fw, mut v, mut fv): (f64, f64, f64, f64) = (x, fx, x, fx);
+
+    // Step of last iteration and step of the iteration before
+    let (mut step, mut previous_step): (f64, f64) = (0.0, 0.0);
+
+    for _ in 0..MAX_MINIMIZATION_ITERATIONS {
+        let middle: f64 = (a + b) / 2.0;
+        let tolerance: f64 = MINIMUM_TOLERANCE * x.abs() + f64::EPSILON;
+
+        if (x - middle).abs() <= 2.0 * tolerance - (b - a) / 2.0 {
+            break;
+        }
+
+        let mut is_golden: bool = true;
+
+        if previous_step.abs() > tolerance {
+            let r: f64 = (x - w) * (fx - fv);
+            let q: f64 = (x - v) * (fx - fw);
+            let mut p: f64 = (x - v) * q - (x - w) * r;
+            let mut q: f64 = 2.0 * (q - r);
+
+            if q > 0.0 {
+                p = -p;
+            }
+            q = q.abs();
+
+            if p.abs() < (q * previous_step / 2.0).abs() && p > q * (a - x) && p < q * (b - x) {
+                previous_step = step;
+                step = p / q;
+
+                // Function is not evaluated too close to bounds
+                if x + step - a < 2.0 * tolerance || b - x - step < 2.0 * tolerance {
+                    step = if x < middle { tolerance } else { -tolerance };
+                }
+
+                is_golden = false;
+            }
+        }
+
+        if is_golden {
+            previous_step = if x < middle { b - x } else { a - x };
+            step = GOLDEN_SECTION * previous_step;
+        }
+
+        let u: f64 = match step.abs() >= tolerance {
+            true => x + step,
+            false => x + tolerance.copysign(step),
+        };
+        let fu: f64 = f(u)?;
+
+        if fu <= fx {
+            if u < x {
+                b = x;
+            } else {
+                a = x;
+            }
+
+            (v, fv, w, fw, x, fx) = (w, fw, x, fx, u, fu);
+        } else {
+            if u < x {
+                a = u;
+            } else {
+                b = u;
+            }
+
+            if fu <= fw || w == x {
+                (v, fv, w, fw) = (w, fw, u, fu);
+            } else if fu <= fv || v == x || v == w {
+                (v, fv) = (u, fu);
+            }
+        }
+    }
+
+    return Ok((x, fx));
+}
+
+/// Compile subexpression given by its positions in expression given in argument as a function of variable
+/// whose name is given in argument, other variables taking their values in variables given in argument.
+/// Subexpression is compiled once, then evaluated with the compiled pipeline for each value of variable.
+/// If subexpression cannot be compiled, an error message is stored in string contained in Result output
+fn compile_function(
+    expression: &str,
+    (start, end): (usize, usize),
+    name: &str,
+    variables: &HashMap<String, f64>,
+) -> Result<impl FnMut(f64) -> Result<f64, String>, String> {
+    let compiled: CompiledExpression =
+        CompiledExpression::new(&(" ".repeat(start) + &expression[start..end]))?;
+    let mut values: Vec<f64> = Vec::with_capacity(compiled.variables().len());
+    let mut slot: Option<usize> = None;
+
+    for (index, variable) in compiled.variables().iter().enumerate() {
+        if variable == name {
+            slot = Some(index);
+            values.push(0.0);
+            continue;
+        }
+
+        match variables.get(variable) {
+            Some(&value) => values.push(value),
+            None => {
+                return Err(format!(
+                    "Missing value of variable {variable} at position {start}"
+                ))
+            }
+        }
+    }
+
+    return Ok(move |x: f64| {
+        if let Some(slot) = slot {
+            values[slot] = x;
+        }
+
+        return compiled.evaluate(&values);
+    });
+}
+
+/// Evaluate Taylor polynomial of subexpression given by its positions in expression given in argument,
+/// with respect to variable whose name is given in argument, at x0 up to order given in argument.
+/// The polynomial is built symbolically, then evaluated at value of variable in variables given in argument.
+/// If subexpression cannot be differentiated or a variable has no value,
+/// an error message is stored in string contained in Result output
+fn evaluate_taylor(
+    expression: &str,
+    (start, end): (usize, usize),
+    name: &str,
+    (x0, order): (f64, f64),
+    position: usize,
+    variables: &HashMap<String, f64>,
+) -> Result<f64, String> {
+    let ast: Ast = Ast::new(&(" ".repeat(start) + &expression[start..end]))?;
+
+    if let Some(variable) = ast
+        .uses()
+        .variables
+        .iter()
+        .find(|&variable| variable != name && !variables.contains_key(variable))
+    {
+        return Err(format!(
+            "Missing value of variable {variable} at position {start}"
+        ));
+    }
+
+    let x: f64 = match variables.get(name) {
+        Some(&x) => x,
+        None => {
+            return Err(format!(
+                "Missing value of variable {name} at position {position}"
+            ))
+        }
+    };
+
+    if order.is_nan() || order < 0.0 || order.fract() != 0.0 {
+        return Err(format!(
+            "Order of taylor function must be a non-negative integer at position {position}"
+        ));
+    }
+
+    let polynomial: String = ast
+        .taylor(name, x0, order as usize)
+        .map_err(|message| format!("{message} at position {position}"))?
+        .to_string();
+
+    let mut f = compile_function(&polynomial, (0, polynomial.len()), name, variables)?;
+
+    return f(x).map_err(|message| format!("{message} at position {position}"));
+}
+
+/// Check arguments of call of calculus function whose name is given in argument and whose arguments are given
+/// by their positions in expression given in argument, giving the positions of its subexpression, its variable
+/// and the values of its points, which are evaluated by evaluation function given in argument.
+/// If arguments are not valid or if error occurs during evaluation of points,
+/// an error message is stored in string contained in Result output
+fn check_calculus_call<'a, E>(
+    name: &str,
+    expression: &'a str,
+    args: &[(usize, usize)],
+    position: usize,
+    variables: &HashMap<String, f64>,
+    evaluate: &mut E,
+) -> Result<CalculusCall<'a>, String>
+where
+    E: FnMut(&str, &HashMap<String, f64>) -> Result<f64, String>,
+{
+    let (min_args, max_args): (usize, usize) = CALCULUS_FUNCTIONS
+        .iter()
+        .find_map(|&(function, min_args, max_args)| {
+            (function == name).then_some((min_args, max_args))
+        })
+        .unwrap_or_default();
+
+    if args.len() < min_args || args.len() > max_args {
+        let expected: String = match min_args == max_args {
+            true => min_args.to_string(),
+            false => format!("{min_args} to {max_args}"),
+        };
+
+        return Err(format!(
+            "Function {name} expects {expected} arguments but got {} at position {position}",
+            args.len()
+        ));
+    }
+
+    // Summation and product give their variable first and their subexpression last
+    let variable_first: bool = matches!(name, "series" | "sum" | "prod");
+    let (subexpression, variable, point_args) = match variable_first {
+        true => (args[3], args[0], &args[1..3]),
+        false => (args[0], args[1], &args[2..]),
+    };
+    let variable: &str = expression[variable.0..variable.1].trim();
+
+    if !variable.starts_with(char::is_alphabetic) || !variable.chars().all(is_word_char) {
+        let ordinal: &str = if variable_first { "First" } else { "Second" };
+        return Err(format!(
+            "{ordinal} argument of {name} function must be a variable at position {position}"
+        ));
+    }
+
+    let mut points: Vec<f64> = Vec::with_capacity(point_args.len());
+
+    for &(start, end) in point_args.iter() {
+        points.push(evaluate(
+            &(" ".repeat(start) + &expression[start..end]),
+            variables,
+        )?);
+    }
+
+    return Ok((subexpression, variable, points));
+}
+
+/// Evaluate call of minimize function whose arguments are given by their positions in expression given in argument,
+/// like minimize(x^2 - 2 * x, x, 0, 3), giving the point of minimum of subexpression between bounds and its value there.
+/// Bounds are evaluated by evaluation function given in argument, while subexpression is compiled.
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+pub(crate) fn evaluate_minimization<E>(
+    expression: &str,
+    args: &[(usize, usize)],
+    position: usize,
+    variables: &HashMap<String, f64>,
+    mut evaluate: E,
+) -> Result<(f64, f64), String>
+where
+    E: FnMut(&str, &HashMap<String, f64>) -> Result<f64, String>,
+{
+    let (subexpression, variable, points) = check_calculus_call(
+        "minimize",
+        expression,
+        args,
+        position,
+        variables,
+        &mut evaluate,
+    )?;
+    let f = compile_function(expression, subexpression, variable, variables)?;
+
+    return minimize(f, points[0], points[1])
+        .map_err(|message| format!("{message} at position {position}"));
+}
+
+/// Evaluate call of calculus function whose name is given in argument and whose arguments are given
+/// by their positions in expression given in argument, like integrate(x^2, x, 0, 1), deriv(x^2, x, 1)
+/// solve(x^2 - 2, x, 1), series(i, 1, 10, 1 / i^2) or prod(i, 1, 5, i).
+/// Its subexpression is compiled as a function of its variable, while its points are evaluated
+/// by evaluation function given in argument.
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+fn evaluate_calculus_call<E>(
+    name: &str,
+    expression: &str,
+    args: &[(usize, usize)],
+    position: usize,
+    variables: &HashMap<String, f64>,
+    evaluate: &mut E,
+) -> Result<f64, String>
+where
+    E: FnMut(&str, &HashMap<String, f64>) -> Result<f64, String>,
+{
+    let (subexpression, variable, points) =
+        check_calculus_call(name, expression, args, position, variables, evaluate)?;
+
+    if name == "minimize" {
+        return Err(format!(
+            "Function minimize gives a vector, so it must be evaluated with vectors at position {position}"
+        ));
+    }
+
+    if let ("taylor", &[x0, order]) = (name, points.as_slice()) {
+        return evaluate_taylor(
+            expression,
+            subexpression,
+            variable,
+            (x0, order),
+            position,
+            variables,
+        );
+    }
+
+    let f = compile_function(expression, subexpression, variable, variables)?;
+
+    let result: Result<f64, String> = match (name, points.as_slice()) {
+        ("integrate", &[a, b]) => integrate(f, a, b),
+        ("deriv", &[x0]) => differentiate(f, x0),
+        ("series" | "sum", &[a, b]) => series(f, a, b, false),
+        ("prod", &[a, b]) => series(f, a, b, true),
+        ("limit", &[x0]) => limit(f, x0),
+        ("solve", &[guess, ref options @ ..]) => {
+            let tolerance: f64 = options.first().copied().unwrap_or(DEFAULT_ROOT_TOLERANCE);
+            let max_iterations: f64 = options.get(1).copied().unwrap_or(DEFAULT_MAX_ITERATIONS);
+
+            if tolerance.is_nan() || tolerance <= 0.0 {
+                return Err(format!(
+                    "Tolerance of {name} function must be positive at position {position}"
+                ));
+            }
+
+            if max_iterations < 1.0 || max_iterations.fract() != 0.0 {
+                return Err(format!(
+                    "Maximal number of iterations of {name} function must be a positive integer at position {position}"
+                ));
+            }
+
+            find_root(f, guess, tolerance, max_iterations as usize)
+        }
+        _ => unreachable!(),
+    };
+
+    // Errors of evaluation of subexpression are reported at position of call
+    return result.map_err(|message| format!("{message} at position {position}"));
+}
+
+/// Get name of calculus function called at position given in argument of expression given in argument,
+/// None if there is no call of calculus function starting at this position
+fn calculus_call_name(expression: &str, start: usize) -> Option<&str> {
+    if !expression[start..].starts_with(char::is_alphabetic)
+        || expression[..start]
+            .chars()
+            .next_back()
+            .is_some_and(is_word_char)
+    {
+        return None;
+    }
+
+    let name: &str = expression[start..]
+        .split(|c: char| !is_word_char(c))
+        .next()
+        .unwrap_or_default();
+
+    if !CALCULUS_FUNCTIONS
+        .iter()
+        .any(|&(function, _, _)| function == name)
+        || !expression[start + name.len()..]
+            .trim_start()
+            .starts_with('(')
+    {
+        return None;
+    }
+
+    // Function sum is a summation only if it has four arguments, the first one being a variable
+    // which its last argument uses, like sum(i, 1, 10, i^2), and aggregates its arguments otherwise
+    if name == "sum" {
+        let args: Vec<(usize, usize)> = match call_arguments(expression, name, start) {
+            Ok((args, _)) if args.len() == 4 => args,
+            _ => return None,
+        };
+        let variable: &str = expression[args[0].0..args[0].1].trim();
+
+        if !variable.starts_with(char::is_alphabetic)
+            || !variable.chars().all(is_word_char)
+            || !expression[args[3].0..args[3].1]
+                .split(|c: char| !is_word_char(c))
+                .any(|word| word == variable)
+        {
+            return None;
+        }
+    }
+
+    return Some(name);
+}
+
+/// Find first call of calculus function of expression given in argument, giving its name and its position,
+/// None if expression has no call of calculus function
+pub(crate) fn find_calculus_call(expression: &str) -> Option<(&str, usize)> {
+    return expression
+        .char_indices()
+        .find_map(|(start, _)| calculus_call_name(expression, start).map(|name| (name, start)));
+}
+
+/// Get positions of arguments of call of calculus function whose name is given in argument,
+/// starting at position given in argument of expression given in argument, with position of its closing parenthesis.
+/// If parenthesis of call is not closed, an error message is stored in string contained in Result output
+fn call_arguments(
+    expression: &str,
+    name: &str,
+    start: usize,
+) -> Result<(Vec<(usize, usize)>, usize), String> {
+    let after_name: &str = &expression[start + name.len()..];
+
+    // Arguments are separated by commas outside parenthesis and brackets
+    let open: usize = expression.len() - after_name.trim_start().len();
+    let mut args: Vec<(usize, usize)> = Vec::new();
+    let mut arg_start: usize = open + 1;
+    let mut depth: usize = 0;
+
+    for (index, c) in expression[open + 1..].char_indices() {
+        let index: usize = open + 1 + index;
+
+        match c {
+            '(' | '[' => depth += 1,
+            ',' | ')' if depth == 0 => {
+                args.push((arg_start, index));
+                arg_start = index + 1;
+
+                if c == ')' {
+                    return Ok((args, index));
+                }
+            }
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+
+    return Err(format!("Unclosed parenthesis at position {open}"));
+}
+
+/// Replace each call of calculus function of expression given in argument, like integrate(x^2, x, 0, 1),
+/// deriv(x^2, x, 1), solve(x^2 - 2, x, 1), series(i, 1, 10, 1 / i^2), prod(i, 1, 5, i), taylor(exp(x), x, 0, 5)
+/// or limit(sin(x) / x, x, 0), by a variable of the same length,
+/// so positions of errors are kept, bound to value of call in a copy of variables given in argument.
+/// Points of calls are evaluated by evaluation function given in argument. None is given if expression has no call of calculus function.
+/// If error occurs during evaluation of a call, an error message is stored in string contained in Result output
+pub(crate) fn replace_calculus_calls<E>(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    mut evaluate: E,
+) -> Result<Option<ReplacedCalls>, String>
+where
+    E: FnMut(&str, &HashMap<String, f64>) -> Result<f64, String>,
+{
+    let mut replaced: String = String::with_capacity(expression.len());
+    let mut scope: Option<HashMap<String, f64>> = None;
+    let mut end: usize = 0;
+
+    for (start, _) in expression.char_indices() {
+        let name: &str = match calculus_call_name(expression, start) {
+            Some(name) if start >= end => name,
+            _ => continue,
+        };
+        let (args, close) = call_arguments(expression, name, start)?;
+
+        let value: f64 =
+            evaluate_calculus_call(name, expression, &args, start, variables, &mut evaluate)?;
+
+        let scope: &mut HashMap<String, f64> = scope.get_or_insert_with(|| variables.clone());
+        let word: String = format!("C{:_<width$}", scope.len(), width = close - start);
+        scope.insert(word.clone(), value);
+
+        replaced.push_str(&expression[end..start]);
+        replaced.push_str(&word);
+        end = close + 1;
+    }
+
+    replaced.push_str(&expression[end..]);
+    return Ok(scope.map(|scope| (replaced, scope)));
+}
+
+/// Check if name given in argument is a variable replacing a call of calculus function,
+/// like C0__________ written by replace_calculus_calls function
+pub(crate) fn is_replaced_call(name: &str) -> bool {
+    let number: Option<&str> = name
+        .strip_prefix('C')
+        .map(|rest| rest.trim_end_matches('_'));
+
+    return match number {
+        Some(number) => {
+            !number.is_empty()
+                && number.len() + 1 < name.len()
+                && number.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    };
+}
+
+/// Replace each call of calculus function of expression given in argument by a variable of the same length,
+/// like replace_calculus_calls function but without evaluating calls: their arguments are checked,
+/// and their subexpressions and points are checked by validation function given in argument.
+/// None is given if expression has no call of calculus function.
+/// If a call is not valid, an error message is stored in string contained in Result output
+pub(crate) fn validate_calculus_calls<V>(
+    expression: &str,
+    mut validate: V,
+) -> Result<Option<String>, String>
+where
+    V: FnMut(&str) -> Result<(), String>,
+{
+    let mut replaced: String = String::with_capacity(expression.len());
+    let mut nb_calls: usize = 0;
+    let mut end: usize = 0;
+
+    for (start, _) in expression.char_indices() {
+        let name: &str = match calculus_call_name(expression, start) {
+            Some(name) if start >= end => name,
+            _ => continue,
+        };
+        let (args, close) = call_arguments(expression, name, start)?;
+
+        let ((sub_start, sub_end), _, _) = check_calculus_call(
+            name,
+            expression,
+            &args,
+            start,
+            &HashMap::new(),
+            &mut |point: &str, _: &HashMap<String, f64>| validate(point).map(|_| 0.0),
+        )?;
+        validate(&(" ".repeat(sub_start) + &expression[sub_start..sub_end]))?;
+
+        let word: String = format!("C{:_<width$}", nb_calls, width = close - start);
+        nb_calls += 1;
+
+        replaced.push_str(&expression[end..start]);
+        replaced.push_str(&word);
+        end = close + 1;
+    }
+
+    if nb_calls == 0 {
+        return Ok(None);
+    }
+
+    replaced.push_str(&expression[end..]);
+    return Ok(Some(replaced));
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integrate() {
+        match integrate(|x: f64| Ok(x.sin()), 0.0, std::f64::consts::PI) {
+            Ok(value) => assert!((value - 2.0).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+
+        match integrate(|x: f64| Ok(1.0 / x), 2.0, 1.0) {
+            Ok(value) => assert!((value + 2f64.ln()).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(integrate(|x: f64| Ok(x), 3.0, 3.0), Ok(0.0));
+        assert_eq!(
+            integrate(|_| Err(String::from("Failure")), 0.0, 1.0),
+            Err(String::from("Failure"))
+        );
+    }
+
+    #[test]
+    fn test_differentiate() {
+        match differentiate(|x: f64| Ok(x.exp()), 2.0) {
+            Ok(value) => assert!((value - 2f64.exp()).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+
+        match differentiate(|x: f64| Ok(x.powi(3)), -1000.0) {
+            Ok(value) => assert!((value / 3e6 - 1.0).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            differentiate(|_| Err(String::from("Failure")), 0.0),
+            Err(String::from("Failure"))
+        );
+    }
+
+    #[test]
+    fn test_series() {
+        assert_eq!(series(|i: f64| Ok(i), 1.0, 100.0, false), Ok(5050.0));
+        assert_eq!(series(|i: f64| Ok(i), 3.0, 2.0, false), Ok(0.0));
+        assert_eq!(series(|i: f64| Ok(i), 1.0, 5.0, true), Ok(120.0));
+        assert_eq!(series(|i: f64| Ok(i), 3.0, 2.0, true), Ok(1.0));
+        assert_eq!(
+            series(|i: f64| Ok(i), 0.5, 2.0, false),
+            Err(String::from(
+                "Bounds 0.5 and 2 of summation must be integers"
+            ))
+        );
+        assert_eq!(
+            series(|i: f64| Ok(i), 1.0, 1e9, false),
+            Err(String::from(
+                "Range of summation from 1 to 1000000000 exceeds 1000000 terms"
+            ))
+        );
+        assert_eq!(series(|_| Ok(2.0), 1e16, 1e16 + 4.0, true), Ok(32.0));
+    }
+
+    #[test]
+    fn test_find_root() {
+        match find_root(|x: f64| Ok(x * x - 2.0), 1.0, 1e-12, 100) {
+            Ok(root) => assert!((root - 2f64.sqrt()).abs() < 1e-12),
+            Err(_) => assert!(false),
+        }
+
+        // Newton method overshoots on arc tangent, so bisection keeps root in its bracket
+        match find_root(|x: f64| Ok(x.atan()), 1.5, 1e-12, 100) {
+            Ok(root) => assert!(root.abs() < 1e-12),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            find_root(|x: f64| Ok(x * x + 1.0), 0.0, 1e-12, 100),
+            Err(String::from(
+                "Derivative vanishes at 0, so no root is found from guess 0"
+            ))
+        );
+        assert_eq!(
+            find_root(|x: f64| Ok(x * x + 1.0), 3.0, 1e-12, 10),
+            Err(String::from(
+                "No root found from guess 3 after 10 iterations"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_limit() {
+        match limit(|x: f64| Ok(x.sin() / x), 0.0) {
+            Ok(value) => assert!((value - 1.0).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+        match limit(|x: f64| Ok((1.0 + x).ln() / x), 0.0) {
+            Ok(value) => assert!((value - 1.0).abs() < 1e-6),
+            Err(_) => assert!(false),
+        }
+        match limit(|x: f64| Ok((x * x - 4.0) / (x - 2.0)), 2.0) {
+            Ok(value) => assert!((value - 4.0).abs() < 1e-6),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            limit(|x: f64| Ok(1.0 / x), 0.0),
+            Err(String::from("Limit at 0 diverges on left side"))
+        );
+        assert_eq!(
+            limit(|x: f64| Ok(x.abs() / x), 0.0),
+            Err(String::from(
+                "Limit at 0 differs by side, being -1 on left side and 1 on right side"
+            ))
+        );
+        assert_eq!(
+            limit(|_| Err(String::from("Failure")), 0.0),
+            Err(String::from("Failure"))
+        );
+    }
+
+    #[test]
+    fn test_minimize() {
+        match minimize(|x: f64| Ok((x - 2.0).powi(2) + 1.0), 0.0, 5.0) {
+            Ok((argmin, min)) => {
+                assert!((argmin - 2.0).abs() < 1e-7);
+                assert!((min - 1.0).abs() < 1e-12);
+            }
+            Err(_) => assert!(false),
+        }
+
+        // Bounds can be given in any order
+        match minimize(|x: f64| Ok(x.abs()), 1.0, -2.0) {
+            Ok((argmin, _)) => assert!(argmin.abs() < 1e-7),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            minimize(|_| Err(String::from("Failure")), 0.0, 1.0),
+            Err(String::from("Failure"))
+        );
+    }
+}
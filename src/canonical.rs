@@ -0,0 +1,98 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::Node;
+use super::functions::Function;
+use super::operators::{BinaryOperator, UnaryOperator};
+
+/// Offset basis of 64-bit FNV-1a hash
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Prime of 64-bit FNV-1a hash
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Check if operation of binary operator given in argument gives exactly the same result
+/// when its operands are swapped. Addition and multiplication are commutative even with rounding,
+/// while boolean operators are not, since their right operand is not evaluated when left one decides
+fn is_commutative(ops: BinaryOperator) -> bool {
+    return matches!(ops, BinaryOperator::Plus | BinaryOperator::Multiply);
+}
+
+/// Check if function given in argument gives exactly the same result whatever the order of its arguments
+fn is_symmetric(fun: Function) -> bool {
+    return matches!(
+        fun,
+        Function::Min | Function::Max | Function::Gcd | Function::Lcm
+    );
+}
+
+/// Get canonical form of node given in argument, which is the same for expressions differing only
+/// by order of operands of commutative operations, unary plus, or constants written by name or by value.
+/// Operands are ordered by their written form, while operations are never regrouped,
+/// since grouping changes rounding: canonical form always evaluates like node
+pub(crate) fn canonicalize(node: &Node) -> Node {
+    match node {
+        Node::Constant(_, value) => Node::Number(*value),
+        Node::Unary(UnaryOperator::Plus, operand) => canonicalize(operand),
+        Node::Unary(ops, operand) => Node::Unary(*ops, Box::new(canonicalize(operand))),
+        Node::Binary(ops, left, right) => {
+            let (mut left, mut right): (Node, Node) = (canonicalize(left), canonicalize(right));
+
+            if is_commutative(*ops) && right.to_string() < left.to_string() {
+                std::mem::swap(&mut left, &mut right);
+            }
+
+            Node::Binary(*ops, Box::new(left), Box::new(right))
+        }
+        Node::Call(fun, args) => {
+            let mut args: Vec<Node> = args.iter().map(canonicalize).collect();
+
+            if is_symmetric(*fun) {
+                args.sort_by_cached_key(|arg| arg.to_string());
+            }
+
+            Node::Call(*fun, args)
+        }
+        Node::Tuple(elements) => Node::Tuple(elements.iter().map(canonicalize).collect()),
+        _ => node.clone(),
+    }
+}
+
+/// Hash text given in argument by 64-bit FNV-1a, whose value does not depend on platform,
+/// version of Rust or run, unlike hashers of standard library
+pub(crate) fn stable_hash(text: &str) -> u64 {
+    return text.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    });
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::super::ast::Ast;
+    use super::*;
+
+    fn canonical(expression: &str) -> String {
+        return canonicalize(&Ast::new(expression).unwrap().root).to_string();
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        assert_eq!(canonical("y * x + 2"), "2 + x * y");
+        assert_eq!(canonical("+b - a"), "b - a");
+        assert_eq!(canonical("max(y, x, 1)"), "max(1, x, y)");
+        assert_eq!(canonical("2 * pi"), "2 * 3.141592653589793");
+        assert_eq!(canonical("sin(b + a)^2"), "sin(a + b)^2");
+        assert_eq!(canonical("y || x"), "y || x");
+
+        // Sums are not regrouped, since grouping changes rounding
+        assert_eq!(canonical("z + y + x"), "x + (y + z)");
+        assert_eq!(canonical("x + y + z"), "x + y + z");
+    }
+
+    #[test]
+    fn test_stable_hash() {
+        assert_eq!(stable_hash(""), FNV_OFFSET_BASIS);
+        assert_eq!(stable_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(stable_hash("x + 1"), stable_hash("x + 2"));
+    }
+}
@@ -0,0 +1,458 @@
+#![allow(clippy::needless_return)]
+
+use super::compiler::{CompiledExpression, Instruction};
+use super::functions::Function;
+
+/// Language of generated code
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Language {
+    Rust,
+    Glsl,
+}
+
+impl Language {
+    /// Name of language, used in error messages
+    fn name(&self) -> &'static str {
+        match self {
+            Language::Rust => "Rust",
+            Language::Glsl => "GLSL",
+        }
+    }
+}
+
+/// Precedence of an operation of generated code, operations of lower precedence being parenthesized
+/// when they are operands of operations of higher precedence
+const CONDITIONAL: u8 = 0;
+const ADDITIVE: u8 = 1;
+const MULTIPLICATIVE: u8 = 2;
+const UNARY: u8 = 3;
+const ATOM: u8 = 4;
+
+/// Keywords of Rust, which are written as raw identifiers when they name variables
+const RUST_KEYWORDS: [&str; 35] = [
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "yield",
+];
+
+/// Keywords and type names of GLSL, which cannot name variables
+const GLSL_KEYWORDS: [&str; 24] = [
+    "break", "const", "continue", "discard", "do", "else", "false", "float", "for", "if", "in",
+    "inout", "int", "bool", "out", "return", "struct", "true", "uniform", "vec2", "vec3", "vec4",
+    "void", "while",
+];
+
+/// Fragment of generated code, with precedence of its outermost operation
+#[derive(Debug, Clone)]
+struct Fragment {
+    code: String,
+    precedence: u8,
+    literal: bool,
+}
+
+impl Fragment {
+    /// Create fragment of code given in argument whose outermost operation has precedence given in argument
+    fn new(code: String, precedence: u8) -> Fragment {
+        return Fragment {
+            code,
+            precedence,
+            literal: false,
+        };
+    }
+
+    /// Code of fragment as operand of an operation of precedence given in argument, parenthesized if needed
+    fn operand(&self, precedence: u8) -> String {
+        if self.precedence < precedence {
+            return format!("({})", self.code);
+        }
+
+        return self.code.clone();
+    }
+
+    /// Code of fragment as receiver of a Rust method, whose literal needs a type suffix
+    fn receiver(&self) -> String {
+        if self.literal {
+            return format!("{}_f64", self.code);
+        }
+
+        return self.operand(ATOM);
+    }
+}
+
+/// Generate code of a literal number in language given in argument
+/// If number is not finite and cannot be written in language,
+/// an error message is stored in string contained in Result output
+fn literal(value: f64, language: Language) -> Result<Fragment, String> {
+    let code: String = match (language, value) {
+        (_, value) if value.is_finite() => {
+            return Ok(Fragment {
+                code: format!("{value:?}"),
+                precedence: ATOM,
+                literal: true,
+            });
+        }
+        (Language::Rust, value) if value.is_nan() => String::from("f64::NAN"),
+        (Language::Rust, _) => String::from("f64::INFINITY"),
+        (Language::Glsl, _) => {
+            return Err(format!("Number {value} cannot be generated in GLSL"));
+        }
+    };
+
+    return Ok(Fragment::new(code, ATOM));
+}
+
+/// Generate code of binary operation given by its symbol and precedence, applied on operands given in argument.
+/// Right operand is parenthesized at same precedence, so operations are generated in order of evaluation
+fn binary(left: &Fragment, symbol: char, right: &Fragment, precedence: u8) -> Fragment {
+    return Fragment::new(
+        format!(
+            "{} {symbol} {}",
+            left.operand(precedence),
+            right.operand(precedence + 1)
+        ),
+        precedence,
+    );
+}
+
+/// Generate code of call of function given in argument on arguments given in argument, in language given in argument.
+/// If function cannot be generated in language, an error message is stored in string contained in Result output
+fn call(fun: Function, args: &[Fragment], language: Language) -> Result<Fragment, String> {
+    let unsupported: String = format!(
+        "Function {} cannot be generated in {}",
+        fun.name(),
+        language.name()
+    );
+
+    if language == Language::Rust {
+        let method: &str = match fun {
+            Function::Clamp => {
+                let code: String = format!(
+                    "{}.clamp({}, {})",
+                    args[0].receiver(),
+                    args[1].code,
+                    args[2].code
+                );
+                return Ok(Fragment::new(code, ATOM));
+            }
+            Function::Select => {
+                let code: String = format!(
+                    "if {} != 0.0 {{ {} }} else {{ {} }}",
+                    args[0].operand(ADDITIVE),
+                    args[1].code,
+                    args[2].code
+                );
+                return Ok(Fragment::new(code, CONDITIONAL));
+            }
+            Function::Round | Function::FloorTo | Function::CeilTo => {
+                let factor: String = format!("10_f64.powi({} as i32)", args[1].operand(UNARY));
+                let rounding: &str = match fun {
+                    Function::Round => "round",
+                    Function::FloorTo => "floor",
+                    _ => "ceil",
+                };
+                let code: String = format!(
+                    "({} * {factor}).{rounding}() / {factor}",
+                    args[0].operand(MULTIPLICATIVE)
+                );
+                return Ok(Fragment::new(code, MULTIPLICATIVE));
+            }
+            Function::Lerp => {
+                // Linear interpolation a + (b - a) * t
+                let delta: Fragment = binary(&args[1], '-', &args[0], ADDITIVE);
+                let step: Fragment = binary(&delta, '*', &args[2], MULTIPLICATIVE);
+                return Ok(binary(&args[0], '+', &step, ADDITIVE));
+            }
+            Function::Deg2Rad => "to_radians",
+            Function::Rad2Deg => "to_degrees",
+            Function::Gcd | Function::Lcm | Function::Ncr | Function::Npr => {
+                return Err(unsupported)
+            }
+            _ if fun.is_lookup() || fun.is_variadic() => return Err(unsupported),
+            _ => fun.name(),
+        };
+
+        return Ok(Fragment::new(
+            format!("{}.{method}()", args[0].receiver()),
+            ATOM,
+        ));
+    }
+
+    let name: &str = match fun {
+        Function::Cbrt => {
+            let code: String = format!("sign({0}) * pow(abs({0}), 1.0 / 3.0)", args[0].code);
+            return Ok(Fragment::new(code, MULTIPLICATIVE));
+        }
+        Function::Log10 => {
+            let code: String = format!("log({}) / log(10.0)", args[0].code);
+            return Ok(Fragment::new(code, MULTIPLICATIVE));
+        }
+        Function::Select => {
+            let code: String = format!(
+                "{} != 0.0 ? {} : {}",
+                args[0].operand(ADDITIVE),
+                args[1].operand(ADDITIVE),
+                args[2].operand(ADDITIVE)
+            );
+            return Ok(Fragment::new(code, CONDITIONAL));
+        }
+        Function::Round | Function::FloorTo | Function::CeilTo => {
+            let factor: String = format!("pow(10.0, {})", args[1].code);
+            let scaled: String = format!("{} * {factor}", args[0].operand(MULTIPLICATIVE));
+            let rounded: String = match fun {
+                // Halfway values are rounded away from zero
+                Function::Round => format!("sign({scaled}) * floor(abs({scaled}) + 0.5)"),
+                Function::FloorTo => format!("floor({scaled})"),
+                _ => format!("ceil({scaled})"),
+            };
+            return Ok(Fragment::new(
+                format!("{rounded} / {factor}"),
+                MULTIPLICATIVE,
+            ));
+        }
+        Function::Ln => "log",
+        Function::Lerp => "mix",
+        Function::Deg2Rad => "radians",
+        Function::Rad2Deg => "degrees",
+        Function::Gcd | Function::Lcm | Function::Ncr | Function::Npr => return Err(unsupported),
+        _ if fun.is_lookup() || fun.is_variadic() => return Err(unsupported),
+        _ => fun.name(),
+    };
+
+    let args: Vec<&str> = args.iter().map(|arg| arg.code.as_str()).collect();
+    return Ok(Fragment::new(format!("{name}({})", args.join(", ")), ATOM));
+}
+
+/// Generate code of name of variable given in argument in language given in argument.
+/// If name cannot be written in language, an error message is stored in string contained in Result output
+fn variable(name: &str, language: Language) -> Result<Fragment, String> {
+    let code: String = match language {
+        Language::Rust if RUST_KEYWORDS.contains(&name) => format!("r#{name}"),
+        Language::Rust => String::from(name),
+        Language::Glsl if !name.is_ascii() || GLSL_KEYWORDS.contains(&name) => {
+            return Err(format!("Variable {name} cannot be named in GLSL"));
+        }
+        Language::Glsl => String::from(name),
+    };
+
+    return Ok(Fragment::new(code, ATOM));
+}
+
+impl CompiledExpression {
+    /// Generate code of each output of expression in language given in argument, from its bytecode.
+    /// If expression cannot be generated in language, an error message is stored
+    /// in string contained in Result output
+    fn generate(&self, language: Language) -> Result<Vec<Fragment>, String> {
+        let mut stack: Vec<Fragment> = Vec::new();
+
+        // Stack discipline is checked at compilation, so operands are always available
+        for &instruction in self.code.iter() {
+            let fragment: Fragment = match instruction {
+                Instruction::LoadConstant(slot) => {
+                    literal(self.constants[slot as usize], language)?
+                }
+                Instruction::LoadVariable(slot) => {
+                    variable(&self.variables()[slot as usize], language)?
+                }
+                Instruction::Negate => {
+                    let operand: Fragment = stack.pop().unwrap();
+                    Fragment::new(format!("-{}", operand.operand(ATOM)), UNARY)
+                }
+                Instruction::Call(fun, arity) => {
+                    let args: Vec<Fragment> = stack.split_off(stack.len() - arity as usize);
+                    call(fun, &args, language)?
+                }
+                Instruction::Logic(_)
+                | Instruction::Not
+                | Instruction::JumpIfFalse(_)
+                | Instruction::Jump(_)
+                | Instruction::ShortCircuit(_, _) => {
+                    return Err(String::from(
+                        "Conditional, boolean and comparison operators cannot be generated",
+                    ));
+                }
+                _ => {
+                    let right: Fragment = stack.pop().unwrap();
+                    let left: Fragment = stack.pop().unwrap();
+
+                    match (instruction, language) {
+                        (Instruction::Add, _) => binary(&left, '+', &right, ADDITIVE),
+                        (Instruction::Subtract, _) => binary(&left, '-', &right, ADDITIVE),
+                        (Instruction::Multiply, _) => binary(&left, '*', &right, MULTIPLICATIVE),
+                        (Instruction::Divide, _) => binary(&left, '/', &right, MULTIPLICATIVE),
+                        (_, Language::Rust) => {
+                            Fragment::new(format!("{}.powf({})", left.receiver(), right.code), ATOM)
+                        }
+                        (_, Language::Glsl) => {
+                            Fragment::new(format!("pow({}, {})", left.code, right.code), ATOM)
+                        }
+                    }
+                }
+            };
+
+            stack.push(fragment);
+        }
+
+        return Ok(stack);
+    }
+
+    /// Generate body of a Rust function computing expression, whose parameters are variables
+    /// of expression of type f64, ordered by slot (see `variables`), and returning f64,
+    /// or a tuple of f64 for tuple expressions. Angles of trigonometric functions are in radians.
+    /// Variables named like Rust keywords are written as raw identifiers, like r#type.
+    ///
+    /// If expression calls a function which cannot be generated, like gcd or lookup functions,
+    /// an error message is stored in string contained in Result output
+    ///
+    /// # Example
+    /// ```
+    /// use taz;
+    ///
+    /// let compiled: taz::CompiledExpression = taz::compile("sqrt(x^2 + y^2) / 2").unwrap();
+    /// let body: String = compiled.to_rust_fn().unwrap();
+    /// assert_eq!(body, "(x.powf(2.0) + y.powf(2.0)).sqrt() / 2.0");
+    ///
+    /// let function: String = format!("fn f({}: f64) -> f64 {{ {body} }}", compiled.variables().join(": f64, "));
+    /// assert_eq!(function, "fn f(x: f64, y: f64) -> f64 { (x.powf(2.0) + y.powf(2.0)).sqrt() / 2.0 }");
+    /// ```
+    pub fn to_rust_fn(&self) -> Result<String, String> {
+        let outputs: Vec<Fragment> = self.generate(Language::Rust)?;
+
+        if let [output] = outputs.as_slice() {
+            return Ok(output.code.clone());
+        }
+
+        let codes: Vec<&str> = outputs.iter().map(|output| output.code.as_str()).collect();
+        return Ok(format!("({})", codes.join(", ")));
+    }
+
+    /// Generate body of a GLSL function computing expression, whose parameters are variables
+    /// of expression of type float, ordered by slot (see `variables`), and returning float,
+    /// or a vector for tuple expressions of 2 to 4 elements. Angles of trigonometric functions are in radians.
+    ///
+    /// If expression calls a function which cannot be generated, like gcd or lookup functions,
+    /// if a variable cannot be named in GLSL or if expression has more than 4 outputs,
+    /// an error message is stored in string contained in Result output
+    ///
+    /// # Example
+    /// ```
+    /// use taz;
+    ///
+    /// let compiled: taz::CompiledExpression = taz::compile("(lerp(a, b, t), 1 - t)").unwrap();
+    /// assert_eq!(compiled.to_glsl(), Ok(String::from("return vec2(mix(a, b, t), 1.0 - t);")));
+    /// ```
+    pub fn to_glsl(&self) -> Result<String, String> {
+        let outputs: Vec<Fragment> = self.generate(Language::Glsl)?;
+
+        if let [output] = outputs.as_slice() {
+            return Ok(format!("return {};", output.code));
+        }
+
+        if outputs.len() > 4 {
+            return Err(String::from("GLSL vectors cannot hold more than 4 outputs"));
+        }
+
+        let codes: Vec<&str> = outputs.iter().map(|output| output.code.as_str()).collect();
+        return Ok(format!(
+            "return vec{}({});",
+            outputs.len(),
+            codes.join(", ")
+        ));
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rust(expression: &str) -> Result<String, String> {
+        return CompiledExpression::new(expression)?.to_rust_fn();
+    }
+
+    fn glsl(expression: &str) -> Result<String, String> {
+        return CompiledExpression::new(expression)?.to_glsl();
+    }
+
+    #[test]
+    fn test_to_rust_fn_precedence() {
+        assert_eq!(
+            rust("x - (y - 1) * 2"),
+            Ok(String::from("x - (y - 1.0) * 2.0"))
+        );
+        assert_eq!(
+            rust("(x - y) - 1 / (2 * x)"),
+            Ok(String::from("x - y - 1.0 / (2.0 * x)"))
+        );
+        assert_eq!(rust("-(x + 1) * (-x)"), Ok(String::from("-(x + 1.0) * -x")));
+        assert_eq!(rust("-x^2"), Ok(String::from("(-x).powf(2.0)")));
+        assert_eq!(
+            rust("2^x^0.5"),
+            Ok(String::from("2.0_f64.powf(x.powf(0.5))"))
+        );
+        assert_eq!(
+            rust("pi * sin(x + 1)"),
+            Ok(String::from("3.141592653589793 * (x + 1.0).sin()"))
+        );
+    }
+
+    #[test]
+    fn test_to_rust_fn_functions() {
+        assert_eq!(
+            rust("select(x - 1, clamp(x, 0, 1), 2) + 1"),
+            Ok(String::from(
+                "(if x - 1.0 != 0.0 { x.clamp(0.0, 1.0) } else { 2.0 }) + 1.0"
+            ))
+        );
+        assert_eq!(rust("lerp(a, b, t)"), Ok(String::from("a + (b - a) * t")));
+        assert_eq!(
+            rust("round(x, 2)"),
+            Ok(String::from(
+                "(x * 10_f64.powi(2.0 as i32)).round() / 10_f64.powi(2.0 as i32)"
+            ))
+        );
+        assert_eq!(rust("type * 2"), Ok(String::from("r#type * 2.0")));
+        assert_eq!(
+            rust("(x, deg2rad(x))"),
+            Ok(String::from("(x, x.to_radians())"))
+        );
+        assert_eq!(
+            rust("gcd(x, 4)"),
+            Err(String::from("Function gcd cannot be generated in Rust"))
+        );
+    }
+
+    #[test]
+    fn test_to_glsl() {
+        assert_eq!(
+            glsl("sqrt(x^2 + y^2) / 2"),
+            Ok(String::from(
+                "return sqrt(pow(x, 2.0) + pow(y, 2.0)) / 2.0;"
+            ))
+        );
+        assert_eq!(
+            glsl("2 * select(x, ln(x), cbrt(x))"),
+            Ok(String::from(
+                "return 2.0 * (x != 0.0 ? log(x) : sign(x) * pow(abs(x), 1.0 / 3.0));"
+            ))
+        );
+        assert_eq!(
+            glsl("floorto(x, 1)"),
+            Ok(String::from(
+                "return floor(x * pow(10.0, 1.0)) / pow(10.0, 1.0);"
+            ))
+        );
+        assert_eq!(
+            glsl("(x, 2 * x, 3 * x, 4 * x, 5 * x)"),
+            Err(String::from("GLSL vectors cannot hold more than 4 outputs"))
+        );
+        assert_eq!(
+            glsl("lookup(x, [0, 1], [0, 2])"),
+            Err(String::from("Function lookup cannot be generated in GLSL"))
+        );
+        assert_eq!(
+            glsl("float + 1"),
+            Err(String::from("Variable float cannot be named in GLSL"))
+        );
+    }
+}
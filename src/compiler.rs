@@ -0,0 +1,1484 @@
+#![allow(clippy::needless_return)]
+
+use super::binding::expand_bindings;
+use super::calculus::find_calculus_call;
+use super::conversion::FromTazValue;
+use super::converter::tuple_to_postfix;
+use super::error::Error;
+use super::functions::Function;
+use super::limits::Budget;
+use super::operators::{is_true, BinaryOperator, UnaryOperator};
+use super::options::{ArithmeticPolicy, EvaluationOptions, RoundingMode, ValueRange};
+use super::token::Token;
+use super::tokenizer::tokenize_with_discovery;
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Instruction of bytecode evaluated by a compiled expression.
+/// Operands are referenced by their slot in constants or values of variables,
+/// and function calls hold the number of values they take from operand stack.
+/// Jumps hold the position of the next instruction evaluated when they are taken,
+/// so values of conditional operator and right operands of boolean operators are skipped.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Instruction {
+    LoadConstant(u32),
+    LoadVariable(u32),
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Negate,
+    Call(Function, u32),
+    /// Boolean or comparison operator, giving 1 if it holds and 0 otherwise
+    Logic(BinaryOperator),
+    Not,
+    /// Jump taken if the condition taken from operand stack is false
+    JumpIfFalse(u32),
+    Jump(u32),
+    /// Jump taken if the value of boolean operator is known from its left operand, which is replaced by this value
+    ShortCircuit(BinaryOperator, u32),
+}
+
+impl Instruction {
+    /// Check if instruction is a jump, whose next instruction depends on values of variables
+    fn is_jump(&self) -> bool {
+        return matches!(
+            self,
+            Instruction::JumpIfFalse(_) | Instruction::Jump(_) | Instruction::ShortCircuit(_, _)
+        );
+    }
+}
+
+/// Expression compiled into bytecode, ready to be evaluated many times
+/// with different values of its variables.
+/// The expression can be a tuple like (a, b, c) giving several outputs in one evaluation.
+///
+/// Numbers and constants are stored once in a table of constants,
+/// variables are given slots by order of appearance in expression
+/// and the size of operand stack is computed at compilation.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CompiledExpression {
+    pub(crate) code: Vec<Instruction>,
+    pub(crate) constants: Vec<f64>,
+    variables: Vec<String>,
+    outputs: usize,
+    stack_size: usize,
+}
+
+/// Number of values evaluated together by slice evaluation
+const LANES: usize = 8;
+
+/// Values of a variable evaluated together by slice evaluation
+type Lanes = [f64; LANES];
+
+/// Number of steps between two checks of deadline of a budget, since reading clock is costly
+const STEPS_BETWEEN_DEADLINE_CHECKS: u64 = 256;
+
+/// Give slot of value in table of constants, the value being added if not present yet
+fn constant_slot(constants: &mut Vec<f64>, value: f64) -> u32 {
+    match constants
+        .iter()
+        .position(|constant| constant.to_bits() == value.to_bits())
+    {
+        Some(slot) => slot as u32,
+        None => {
+            constants.push(value);
+            return (constants.len() - 1) as u32;
+        }
+    }
+}
+
+/// Set target of pending jump at position given in argument to the end of code given in argument.
+/// If there is no pending jump, an error message is stored in string contained in Result output
+fn patch_jump(code: &mut [Instruction], jump: Option<usize>) -> Result<(), String> {
+    let target: u32 = code.len() as u32;
+
+    match jump.and_then(|jump| code.get_mut(jump)) {
+        Some(
+            Instruction::JumpIfFalse(to) | Instruction::Jump(to) | Instruction::ShortCircuit(_, to),
+        ) => *to = target,
+        _ => return Err(String::from("Missing operand to compile expression")),
+    }
+
+    return Ok(());
+}
+
+/// Operand of bytecode during partial evaluation, whose value is known or computed by code
+enum Partial {
+    Value(f64),
+    Code(Vec<Instruction>),
+}
+
+impl Partial {
+    /// Append code computing operand to code given in argument, a known value being loaded from constants
+    fn emit(self, code: &mut Vec<Instruction>, constants: &mut Vec<f64>) {
+        match self {
+            Partial::Value(value) => {
+                code.push(Instruction::LoadConstant(constant_slot(constants, value)))
+            }
+            Partial::Code(operand_code) => code.extend(operand_code),
+        }
+    }
+}
+
+/// Compute instruction given in argument on known values of its operands, like evaluation with options
+/// given in argument. Operations giving an error, a value which is not finite or a division by zero are not computed,
+/// so they are still reported at evaluation
+fn fold(instruction: Instruction, args: &[f64], options: &EvaluationOptions) -> Option<f64> {
+    let (operation, value): (String, f64) = match instruction {
+        Instruction::Negate => (String::from("- operation"), -args[0]),
+        // Boolean and comparison operators give exact values, which are never rounded
+        Instruction::Logic(ops) => return ops.apply(args[0], args[1]).ok(),
+        Instruction::Not => return Some(f64::from(!is_true(args[0]))),
+        Instruction::Call(fun, _) => (
+            format!("{} function", fun.name()),
+            fun.apply_with_angle(args, options.domain, options.angle)
+                .ok()?,
+        ),
+        Instruction::LoadConstant(_) | Instruction::LoadVariable(_) => return None,
+        _ if instruction.is_jump() => return None,
+        _ => {
+            let (symbol, value): (char, f64) = match instruction {
+                Instruction::Add => ('+', args[0] + args[1]),
+                Instruction::Subtract => ('-', args[0] - args[1]),
+                Instruction::Multiply => ('*', args[0] * args[1]),
+                Instruction::Divide if args[1] == 0.0 => return None,
+                Instruction::Divide => ('/', args[0] / args[1]),
+                _ => ('^', args[0].powf(args[1])),
+            };
+
+            let value: f64 = match options.round_operations {
+                true => options.rounding.operate(symbol, args[0], args[1]),
+                false => value,
+            };
+
+            (format!("{symbol} operation"), value)
+        }
+    };
+
+    let value: f64 = options
+        .check_result(|| operation.clone(), args, value)
+        .ok()?;
+
+    return value.is_finite().then_some(value);
+}
+
+impl CompiledExpression {
+    /// Compile expression given in argument as string.
+    /// Each word of expression which is neither a constant nor a function is a variable.
+    /// Conditional operator, if function and boolean operators are compiled with jumps,
+    /// so only the values they take are evaluated.
+    /// Local bindings are expanded, each bound name being replaced by its value,
+    /// while calls of calculus functions, like integrate or series, cannot be compiled.
+    /// If expression is not valid, an error message is stored in string contained in Result output
+    pub fn new(expression: &str) -> Result<CompiledExpression, String> {
+        if let Some((name, position)) = find_calculus_call(expression) {
+            return Err(format!(
+                "Function {name} cannot be compiled at position {position}"
+            ));
+        }
+
+        if let Some(expanded) = expand_bindings(expression)? {
+            return CompiledExpression::new(&expanded);
+        }
+
+        let (infix_tokens, variables) = tokenize_with_discovery(expression)?;
+        let (postfix_tokens, outputs) = tuple_to_postfix(infix_tokens)?;
+
+        let mut code: Vec<Instruction> = Vec::with_capacity(postfix_tokens.len());
+        let mut constants: Vec<f64> = Vec::new();
+        let mut stack_len: usize = 0;
+        let mut stack_size: usize = 0;
+        let mut array_lengths: Vec<usize> = Vec::new();
+        let mut jumps: Vec<usize> = Vec::new();
+
+        for token in postfix_tokens {
+            let (instruction, nb_operands) = match token {
+                Token::Number(value) | Token::Constant(value) => (
+                    Instruction::LoadConstant(constant_slot(&mut constants, value)),
+                    0,
+                ),
+                Token::Variable(slot) => (Instruction::LoadVariable(slot as u32), 0),
+                Token::BinaryOperator(ops) => match ops {
+                    BinaryOperator::Plus => (Instruction::Add, 2),
+                    BinaryOperator::Minus => (Instruction::Subtract, 2),
+                    BinaryOperator::Multiply => (Instruction::Multiply, 2),
+                    BinaryOperator::Divide => (Instruction::Divide, 2),
+                    BinaryOperator::Power => (Instruction::Power, 2),
+                    _ => (Instruction::Logic(ops), 2),
+                },
+                Token::UnaryOperator(UnaryOperator::Minus) => (Instruction::Negate, 1),
+                Token::UnaryOperator(UnaryOperator::Plus) => {
+                    if stack_len < 1 {
+                        return Err(String::from("Missing operand to apply unary operation"));
+                    }
+
+                    continue;
+                }
+                Token::UnaryOperator(UnaryOperator::Not) => (Instruction::Not, 1),
+                // Targets of jumps are known at the end of values they skip
+                Token::ShortCircuit(ops) => {
+                    if stack_len < 1 {
+                        return Err(String::from("Missing operand to compile expression"));
+                    }
+
+                    jumps.push(code.len());
+                    code.push(Instruction::ShortCircuit(ops, 0));
+                    continue;
+                }
+                Token::Question => {
+                    if stack_len < 1 {
+                        return Err(String::from("Missing operand to compile expression"));
+                    }
+
+                    stack_len -= 1;
+                    jumps.push(code.len());
+                    code.push(Instruction::JumpIfFalse(0));
+                    continue;
+                }
+                Token::Colon => {
+                    if stack_len < 1 {
+                        return Err(String::from("Missing operand to compile expression"));
+                    }
+
+                    // Second value starts with the operands preceding first value
+                    let condition: Option<usize> = jumps.pop();
+                    stack_len -= 1;
+                    jumps.push(code.len());
+                    code.push(Instruction::Jump(0));
+                    patch_jump(&mut code, condition)?;
+                    continue;
+                }
+                Token::EndConditional => {
+                    patch_jump(&mut code, jumps.pop())?;
+                    continue;
+                }
+                Token::Array(length) => {
+                    array_lengths.push(length);
+                    (
+                        Instruction::LoadConstant(constant_slot(&mut constants, length as f64)),
+                        0,
+                    )
+                }
+                Token::Function(fun) => {
+                    let mut arity: usize = fun.arity();
+
+                    // Breakpoint arrays are stored in stack as their values followed by their length
+                    if fun.is_lookup() {
+                        match (array_lengths.pop(), array_lengths.pop()) {
+                            (Some(ny), Some(nx)) => arity += nx + ny,
+                            _ => return Err(String::from("Missing operand to compile expression")),
+                        }
+                    }
+
+                    // Arguments of aggregation functions are stored in stack followed by their number
+                    if fun.is_variadic() {
+                        match array_lengths.pop() {
+                            Some(nb_args) => arity += nb_args,
+                            None => {
+                                return Err(String::from("Missing operand to compile expression"))
+                            }
+                        }
+                    }
+
+                    (Instruction::Call(fun, arity as u32), arity)
+                }
+                _ => {
+                    return Err(String::from(
+                        "Token non-accepted for evaluation of postfix expression",
+                    ));
+                }
+            };
+
+            if stack_len < nb_operands {
+                return Err(String::from("Missing operand to compile expression"));
+            }
+
+            stack_len = stack_len - nb_operands + 1;
+            stack_size = stack_size.max(stack_len);
+            code.push(instruction);
+
+            if let Instruction::Logic(BinaryOperator::And | BinaryOperator::Or) = instruction {
+                patch_jump(&mut code, jumps.pop())?;
+            }
+        }
+
+        if stack_len != outputs {
+            return Err(String::from(
+                "Expression does not reduce to a single value per output",
+            ));
+        }
+
+        return Ok(CompiledExpression {
+            code,
+            constants,
+            variables,
+            outputs,
+            stack_size,
+        });
+    }
+
+    /// Names of variables of expression, ordered by slot
+    pub fn variables(&self) -> &[String] {
+        return &self.variables;
+    }
+
+    /// Give slots of variables given in argument to variables of expression, like those of another expression,
+    /// so both expressions are evaluated with the same values.
+    /// If a variable of expression is not in variables given in argument,
+    /// an error message is stored in string contained in Result output
+    pub(crate) fn with_variables(
+        mut self,
+        variables: &[String],
+    ) -> Result<CompiledExpression, String> {
+        for instruction in self.code.iter_mut() {
+            if let Instruction::LoadVariable(slot) = instruction {
+                let name: &String = &self.variables[*slot as usize];
+
+                match variables.iter().position(|variable| variable == name) {
+                    Some(position) => *slot = position as u32,
+                    None => return Err(format!("Missing value of variable {name}")),
+                }
+            }
+        }
+
+        self.variables = variables.to_vec();
+        return Ok(self);
+    }
+
+    /// Number of outputs of expression, which is greater than one for tuples
+    pub fn outputs(&self) -> usize {
+        return self.outputs;
+    }
+
+    /// Create expression where variables whose names are given in argument are bound to their values,
+    /// so it depends only on other variables, which keep their order of slots.
+    /// Operations whose operands depend only on bound variables and numbers are computed once,
+    /// like evaluation with options given in argument, and their values are stored as constants.
+    /// Created expression must be evaluated with the same options.
+    /// Names which are not variables of expression are ignored
+    ///
+    /// ```
+    /// use taz::{AngleUnit, EvaluationOptions};
+    ///
+    /// let options = EvaluationOptions { angle: AngleUnit::Degrees, ..EvaluationOptions::default() };
+    /// let compiled = taz::CompiledExpression::new("a * sin(b) + x").unwrap();
+    /// let bound: taz::CompiledExpression = compiled.bind(&[("a", 2.0), ("b", 30.0)], &options);
+    ///
+    /// assert_eq!(bound.variables(), &[String::from("x")]);
+    /// assert!((bound.evaluate_with_options(&[3.0], &options).unwrap() - 4.0).abs() < 1e-12);
+    /// ```
+    pub fn bind(&self, values: &[(&str, f64)], options: &EvaluationOptions) -> CompiledExpression {
+        let value_of = |name: &String| -> Option<f64> {
+            values
+                .iter()
+                .find(|(bound, _)| bound == name)
+                .map(|&(_, value)| value)
+        };
+        let variables: Vec<String> = self
+            .variables
+            .iter()
+            .filter(|name| value_of(name).is_none())
+            .cloned()
+            .collect();
+
+        // Operands of instructions following a jump depend on the jump taken,
+        // so only bound variables are replaced by constants
+        if self.code.iter().any(Instruction::is_jump) {
+            let mut constants: Vec<f64> = self.constants.clone();
+            let code: Vec<Instruction> = self
+                .code
+                .iter()
+                .map(|&instruction| match instruction {
+                    Instruction::LoadVariable(slot) => {
+                        let name: &String = &self.variables[slot as usize];
+
+                        match value_of(name) {
+                            Some(value) => {
+                                Instruction::LoadConstant(constant_slot(&mut constants, value))
+                            }
+                            None => {
+                                let slot: usize = variables.iter().position(|v| v == name).unwrap();
+                                Instruction::LoadVariable(slot as u32)
+                            }
+                        }
+                    }
+                    instruction => instruction,
+                })
+                .collect();
+
+            return CompiledExpression {
+                code,
+                constants,
+                variables,
+                outputs: self.outputs,
+                stack_size: self.stack_size,
+            };
+        }
+
+        let mut constants: Vec<f64> = Vec::new();
+        let mut stack: Vec<Partial> = Vec::with_capacity(self.stack_size);
+
+        // Rounding mode of options applies to the arithmetic operation giving the result at evaluation,
+        // so this operation is never computed
+        let last_rounded: bool =
+            options.rounding != RoundingMode::NearestEven && !options.round_operations;
+
+        // Stack discipline is checked at compilation, so operands are always available
+        for (index, &instruction) in self.code.iter().enumerate() {
+            let operand: Partial = match instruction {
+                Instruction::LoadConstant(slot) => Partial::Value(self.constants[slot as usize]),
+                Instruction::LoadVariable(slot) => {
+                    let name: &String = &self.variables[slot as usize];
+
+                    match value_of(name) {
+                        Some(value) => Partial::Value(value),
+                        None => {
+                            let slot: usize = variables.iter().position(|v| v == name).unwrap();
+                            Partial::Code(vec![Instruction::LoadVariable(slot as u32)])
+                        }
+                    }
+                }
+                _ => {
+                    let nb_operands: usize = match instruction {
+                        Instruction::Negate | Instruction::Not => 1,
+                        Instruction::Call(_, arity) => arity as usize,
+                        _ => 2,
+                    };
+                    let operands: Vec<Partial> = stack.split_off(stack.len() - nb_operands);
+                    let args: Option<Vec<f64>> = operands
+                        .iter()
+                        .map(|operand| match operand {
+                            Partial::Value(value) => Some(*value),
+                            Partial::Code(_) => None,
+                        })
+                        .collect();
+                    let is_rounded: bool = last_rounded
+                        && index + 1 == self.code.len()
+                        && matches!(
+                            instruction,
+                            Instruction::Add
+                                | Instruction::Subtract
+                                | Instruction::Multiply
+                                | Instruction::Divide
+                                | Instruction::Power
+                        );
+
+                    match args
+                        .filter(|_| !is_rounded)
+                        .and_then(|args| fold(instruction, &args, options))
+                    {
+                        Some(value) => Partial::Value(value),
+                        None => {
+                            let mut code: Vec<Instruction> = Vec::new();
+
+                            for operand in operands {
+                                operand.emit(&mut code, &mut constants);
+                            }
+
+                            code.push(instruction);
+                            Partial::Code(code)
+                        }
+                    }
+                }
+            };
+
+            stack.push(operand);
+        }
+
+        let mut code: Vec<Instruction> = Vec::with_capacity(self.code.len());
+
+        for operand in stack {
+            operand.emit(&mut code, &mut constants);
+        }
+
+        // Folding only removes operands from stack, so size of stack of expression is enough
+        return CompiledExpression {
+            code,
+            constants,
+            variables,
+            outputs: self.outputs,
+            stack_size: self.stack_size,
+        };
+    }
+
+    /// Evaluate expression where each variable takes its value
+    /// in slice given in argument at its slot (see `variables`).
+    /// If expression is a tuple or if error occurs during evaluation,
+    /// an error message is stored in string contained in Result output
+    pub fn evaluate(&self, values: &[f64]) -> Result<f64, String> {
+        if self.outputs != 1 {
+            return Err(String::from(
+                "Expression gives several outputs, use tuple evaluation",
+            ));
+        }
+
+        return Ok(self.evaluate_tuple(values)?[0]);
+    }
+
+    /// Evaluate expression like evaluate method, giving its value in type given as generic parameter.
+    /// If expression is a tuple, if error occurs during evaluation or if value cannot be converted,
+    /// an error message is stored in string contained in Result output
+    pub fn evaluate_as<T>(&self, values: &[f64]) -> Result<T, String>
+    where
+        T: FromTazValue,
+    {
+        return T::from_taz_value(self.evaluate(values)?);
+    }
+
+    /// Evaluate expression like evaluate_as method, applying arithmetic policy given in argument
+    /// to result of each operation and function outside of range of type given as generic parameter.
+    /// If error occurs during evaluation or if value cannot be converted,
+    /// an error message is stored in string contained in Result output
+    pub fn evaluate_as_with_policy<T>(
+        &self,
+        values: &[f64],
+        policy: ArithmeticPolicy,
+    ) -> Result<T, String>
+    where
+        T: FromTazValue,
+    {
+        let options: EvaluationOptions = EvaluationOptions {
+            range: T::range().map(|(min, max)| ValueRange::new(min, max, policy)),
+            ..EvaluationOptions::default()
+        };
+
+        return T::from_taz_value(self.evaluate_with_options(values, &options)?);
+    }
+
+    /// Evaluate expression where each variable takes its value
+    /// in slice given in argument at its slot (see `variables`),
+    /// giving a value for each output of expression.
+    /// If error occurs during evaluation, an error message is stored
+    /// in string contained in Result output
+    pub fn evaluate_tuple(&self, values: &[f64]) -> Result<Vec<f64>, String> {
+        return self
+            .run(values, &Budget::default(), &EvaluationOptions::default())
+            .map_err(String::from);
+    }
+
+    /// Evaluate expression like evaluate method, aborting evaluation with BudgetExceeded error
+    /// as soon as its number of steps or its duration exceeds budget given in argument.
+    /// If expression is a tuple or if error occurs during evaluation, an error is given in Result output
+    pub fn evaluate_with_budget(&self, values: &[f64], budget: &Budget) -> Result<f64, Error> {
+        if self.outputs != 1 {
+            return Err(Error::Message(String::from(
+                "Expression gives several outputs, use tuple evaluation",
+            )));
+        }
+
+        return Ok(self.run(values, budget, &EvaluationOptions::default())?[0]);
+    }
+
+    /// Evaluate expression like evaluate method, according to options of evaluation given in argument.
+    /// If expression is a tuple or if error occurs during evaluation, an error is given in Result output
+    pub fn evaluate_with_options(
+        &self,
+        values: &[f64],
+        options: &EvaluationOptions,
+    ) -> Result<f64, Error> {
+        if self.outputs != 1 {
+            return Err(Error::Message(String::from(
+                "Expression gives several outputs, use tuple evaluation",
+            )));
+        }
+
+        return Ok(self.run(values, &Budget::default(), options)?[0]);
+    }
+
+    /// Evaluate bytecode with values of variables given in argument within budget given in argument,
+    /// according to options of evaluation given in argument, giving a value for each output of expression.
+    /// If error occurs during evaluation, an error is given in Result output
+    fn run(
+        &self,
+        values: &[f64],
+        budget: &Budget,
+        options: &EvaluationOptions,
+    ) -> Result<Vec<f64>, Error> {
+        if values.len() < self.variables.len() {
+            return Err(Error::Message(String::from("Missing value of variable")));
+        }
+
+        let mut stack: Vec<f64> = Vec::with_capacity(self.stack_size);
+        let max_steps: u64 = budget.max_steps.unwrap_or(u64::MAX);
+        let mut steps: u64 = 0;
+
+        let mut last_operation: Option<(char, f64, f64)> = None;
+
+        let mut next: usize = 0;
+
+        // Stack discipline is checked at compilation, so operands are always available
+        while let Some(&instruction) = self.code.get(next) {
+            next += 1;
+            steps += 1;
+            last_operation = None;
+
+            if steps > max_steps {
+                return Err(Error::BudgetExceeded);
+            }
+
+            if let Some(deadline) = budget.deadline {
+                if steps % STEPS_BETWEEN_DEADLINE_CHECKS == 1 && Instant::now() >= deadline {
+                    return Err(Error::BudgetExceeded);
+                }
+            }
+
+            match instruction {
+                Instruction::LoadConstant(slot) => stack.push(self.constants[slot as usize]),
+                Instruction::LoadVariable(slot) => stack.push(values[slot as usize]),
+                Instruction::Negate => {
+                    let operand: &mut f64 = stack.last_mut().unwrap();
+                    let operation = || String::from("- operation");
+                    *operand = options.check_result(operation, &[*operand], -*operand)?;
+                }
+                Instruction::Call(fun, arity) => {
+                    let first_arg: usize = stack.len() - arity as usize;
+                    let args: &[f64] = &stack[first_arg..];
+                    let operation = || format!("{} function", fun.name());
+                    let value: f64 = options.check_result(
+                        operation,
+                        args,
+                        fun.apply_with_angle(args, options.domain, options.angle)?,
+                    )?;
+
+                    stack.truncate(first_arg);
+                    stack.push(value);
+                }
+                Instruction::Logic(ops) => {
+                    let right: f64 = stack.pop().unwrap();
+                    let left: &mut f64 = stack.last_mut().unwrap();
+                    *left = ops.apply(*left, right)?;
+                }
+                Instruction::Not => {
+                    let operand: &mut f64 = stack.last_mut().unwrap();
+                    *operand = f64::from(!is_true(*operand));
+                }
+                Instruction::JumpIfFalse(target) => {
+                    if !is_true(stack.pop().unwrap()) {
+                        next = target as usize;
+                    }
+                }
+                Instruction::Jump(target) => next = target as usize,
+                Instruction::ShortCircuit(ops, target) => {
+                    let left: &mut f64 = stack.last_mut().unwrap();
+
+                    if is_true(*left) == (ops == BinaryOperator::Or) {
+                        *left = f64::from(is_true(*left));
+                        next = target as usize;
+                    }
+                }
+                _ => {
+                    let right: f64 = stack.pop().unwrap();
+                    let left: &mut f64 = stack.last_mut().unwrap();
+
+                    let (symbol, value): (char, f64) = match instruction {
+                        Instruction::Add => ('+', *left + right),
+                        Instruction::Subtract => ('-', *left - right),
+                        Instruction::Multiply => ('*', *left * right),
+                        Instruction::Divide => {
+                            if right == 0.0 {
+                                // Division by zero is never reported as an overflow
+                                *left = options.division_by_zero.divide(*left, right)?;
+
+                                if let Some(range) = &options.range {
+                                    *left = range.apply("/ operation", *left)?;
+                                }
+
+                                continue;
+                            }
+
+                            ('/', *left / right)
+                        }
+                        _ => ('^', left.powf(right)),
+                    };
+
+                    let value: f64 = match options.round_operations {
+                        true => options.rounding.operate(symbol, *left, right),
+                        false => value,
+                    };
+
+                    let operation = || format!("{symbol} operation");
+                    last_operation = Some((symbol, *left, right));
+                    *left = options.check_result(operation, &[*left, right], value)?;
+                }
+            }
+        }
+
+        if let Some(result) = stack.last_mut() {
+            options.round_result(result, last_operation)?;
+        }
+
+        return Ok(stack);
+    }
+
+    /// Check that hash map given in argument binds a value to each variable of expression,
+    /// without evaluating it.
+    /// If a variable is missing, an error is given in Result output
+    pub fn check_vars(&self, variables: &HashMap<String, f64>) -> Result<(), Error> {
+        match self
+            .variables
+            .iter()
+            .find(|&name| !variables.contains_key(name))
+        {
+            Some(name) => Err(Error::Message(format!("Missing value of variable {name}"))),
+            None => Ok(()),
+        }
+    }
+
+    /// Evaluate expression where variables take their values in hash map given in argument.
+    /// If a variable is missing or if error occurs during evaluation,
+    /// an error message is stored in string contained in Result output
+    pub fn evaluate_with_variables(&self, variables: &HashMap<String, f64>) -> Result<f64, String> {
+        let mut values: Vec<f64> = Vec::with_capacity(self.variables.len());
+
+        for name in self.variables.iter() {
+            match variables.get(name) {
+                Some(&value) => values.push(value),
+                None => return Err(format!("Missing value of variable {name}")),
+            }
+        }
+
+        return self.evaluate(&values);
+    }
+
+    /// Evaluate expression on lanes of values of its variable.
+    /// If error occurs on one of lanes, None is returned
+    fn evaluate_lanes(&self, values: &Lanes, stack: &mut Vec<Lanes>) -> Option<Lanes> {
+        stack.clear();
+
+        for &instruction in self.code.iter() {
+            match instruction {
+                Instruction::LoadConstant(slot) => {
+                    stack.push([self.constants[slot as usize]; LANES])
+                }
+                Instruction::LoadVariable(_) => stack.push(*values),
+                Instruction::Negate => {
+                    for operand in stack.last_mut().unwrap().iter_mut() {
+                        *operand = -*operand;
+                    }
+                }
+                Instruction::Call(fun, arity) => {
+                    let first_arg: usize = stack.len() - arity as usize;
+                    let mut result: Lanes = [0.0; LANES];
+                    let mut args: Vec<f64> = vec![0.0; arity as usize];
+
+                    for (lane, value) in result.iter_mut().enumerate() {
+                        for (arg, lanes) in args.iter_mut().zip(stack[first_arg..].iter()) {
+                            *arg = lanes[lane];
+                        }
+
+                        *value = fun.apply(&args).ok()?;
+                    }
+
+                    stack.truncate(first_arg);
+                    stack.push(result);
+                }
+                Instruction::Logic(ops) => {
+                    let right: Lanes = stack.pop().unwrap();
+
+                    for (left, right) in stack.last_mut().unwrap().iter_mut().zip(right) {
+                        *left = ops.apply(*left, right).ok()?;
+                    }
+                }
+                Instruction::Not => {
+                    for operand in stack.last_mut().unwrap().iter_mut() {
+                        *operand = f64::from(!is_true(*operand));
+                    }
+                }
+                // Lanes can take different jumps, so they are evaluated value by value
+                Instruction::JumpIfFalse(_)
+                | Instruction::Jump(_)
+                | Instruction::ShortCircuit(_, _) => return None,
+                _ => {
+                    let right: Lanes = stack.pop().unwrap();
+                    let left: &mut Lanes = stack.last_mut().unwrap();
+
+                    match instruction {
+                        Instruction::Add => left.iter_mut().zip(right).for_each(|(l, r)| *l += r),
+                        Instruction::Subtract => {
+                            left.iter_mut().zip(right).for_each(|(l, r)| *l -= r)
+                        }
+                        Instruction::Multiply => {
+                            left.iter_mut().zip(right).for_each(|(l, r)| *l *= r)
+                        }
+                        Instruction::Divide => {
+                            if right.contains(&0.0) {
+                                return None;
+                            }
+
+                            left.iter_mut().zip(right).for_each(|(l, r)| *l /= r);
+                        }
+                        _ => left.iter_mut().zip(right).for_each(|(l, r)| *l = l.powf(r)),
+                    }
+                }
+            }
+        }
+
+        return Some(stack[0]);
+    }
+
+    /// Evaluate expression of at most one variable for each value of slice of inputs,
+    /// storing results in slice of outputs given in argument.
+    /// Values are evaluated by lanes, so the bytecode is dispatched once for several values
+    /// and arithmetic operations can be vectorized.
+    /// If expression has several variables, if slices have different lengths or if error occurs
+    /// during evaluation of an input, an error message is stored in string contained in Result output
+    pub fn eval_slice(&self, inputs: &[f64], out: &mut [f64]) -> Result<(), String> {
+        if self.outputs != 1 {
+            return Err(String::from(
+                "Expression gives several outputs, use tuple evaluation",
+            ));
+        }
+
+        if self.variables.len() > 1 {
+            return Err(String::from(
+                "Slice evaluation requires an expression with at most one variable",
+            ));
+        }
+
+        if inputs.len() != out.len() {
+            return Err(String::from(
+                "Slices of inputs and outputs have different lengths",
+            ));
+        }
+
+        let mut stack: Vec<Lanes> = Vec::with_capacity(self.stack_size);
+
+        for (index, (input_chunk, out_chunk)) in
+            inputs.chunks(LANES).zip(out.chunks_mut(LANES)).enumerate()
+        {
+            // Unused lanes of last chunk repeat its first input to avoid spurious errors
+            let mut values: Lanes = [input_chunk[0]; LANES];
+            values[..input_chunk.len()].copy_from_slice(input_chunk);
+
+            match self.evaluate_lanes(&values, &mut stack) {
+                Some(results) => out_chunk.copy_from_slice(&results[..out_chunk.len()]),
+                None => {
+                    // Evaluate chunk value by value to find the input in error
+                    for (lane, (&input, output)) in
+                        input_chunk.iter().zip(out_chunk.iter_mut()).enumerate()
+                    {
+                        match self.evaluate(&[input]) {
+                            Ok(value) => *output = value,
+                            Err(message) => {
+                                return Err(format!(
+                                    "Evaluation of input {} failed: {message}",
+                                    index * LANES + lane
+                                ))
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        return Ok(());
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::super::options::{AngleUnit, DivisionByZero, DomainMode};
+    use super::*;
+
+    fn relative_error(value: f64, reference: f64) -> f64 {
+        if reference == 0.0 {
+            return value.abs();
+        } else {
+            return (value - reference).abs() / reference.abs();
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_bytecode() {
+        match CompiledExpression::new("2 * x - 2 / y") {
+            Ok(compiled) => {
+                assert_eq!(
+                    compiled.variables(),
+                    &[String::from("x"), String::from("y")]
+                );
+                assert_eq!(compiled.constants, vec![2.0]);
+                assert_eq!(
+                    compiled.code,
+                    vec![
+                        Instruction::LoadConstant(0),
+                        Instruction::LoadVariable(0),
+                        Instruction::Multiply,
+                        Instruction::LoadConstant(0),
+                        Instruction::LoadVariable(1),
+                        Instruction::Divide,
+                        Instruction::Subtract,
+                    ]
+                );
+                assert_eq!(compiled.stack_size, 3);
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_evaluate() {
+        match CompiledExpression::new("1 - cos(theta)^2 + clamp(sin(phi), 0, 1) * pi") {
+            Ok(compiled) => {
+                for (theta, phi) in [(0.25_f64, 1.54_f64), (1.0, -2.0), (0.0, 0.5)] {
+                    let reference: f64 = 1.0 - theta.cos().powf(2.0)
+                        + phi.sin().clamp(0.0, 1.0) * std::f64::consts::PI;
+
+                    match compiled.evaluate(&[theta, phi]) {
+                        Ok(result) => assert!(relative_error(result, reference) < 1e-12),
+                        Err(_) => assert!(false),
+                    }
+                }
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_evaluate_with_variables() {
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("a"), 3.0), (String::from("b"), 4.0)]);
+
+        match CompiledExpression::new("sqrt(a^2 + b^2)") {
+            Ok(compiled) => {
+                assert_eq!(compiled.evaluate_with_variables(&variables), Ok(5.0));
+                assert_eq!(
+                    compiled.evaluate_with_variables(&HashMap::new()),
+                    Err(String::from("Missing value of variable a"))
+                );
+                assert!(compiled.evaluate(&[3.0]).is_err());
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_evaluation_errors() {
+        match CompiledExpression::new("1 / (x - 1)") {
+            Ok(compiled) => assert_eq!(
+                compiled.evaluate(&[1.0]),
+                Err(String::from("Division by zero"))
+            ),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_invalid() {
+        assert!(CompiledExpression::new("").is_err());
+        assert!(CompiledExpression::new("2 +").is_err());
+        assert!(CompiledExpression::new("2 3").is_err());
+        assert!(CompiledExpression::new("sin()").is_err());
+        assert!(CompiledExpression::new("(2").is_err());
+    }
+
+    #[test]
+    fn test_compiled_expression_eval_slice() {
+        let inputs: Vec<f64> = (0..21).map(|i| 0.1 * i as f64 - 1.0).collect();
+        let mut out: Vec<f64> = vec![0.0; inputs.len()];
+
+        match CompiledExpression::new("sin(x)^2 + clamp(x, -0.5, 0.5) / (2 - x) * pi") {
+            Ok(compiled) => {
+                assert!(compiled.eval_slice(&inputs, &mut out).is_ok());
+
+                for (&input, &output) in inputs.iter().zip(out.iter()) {
+                    match compiled.evaluate(&[input]) {
+                        Ok(reference) => assert!(relative_error(output, reference) < 1e-12),
+                        Err(_) => assert!(false),
+                    }
+                }
+            }
+            Err(_) => assert!(false),
+        }
+
+        match CompiledExpression::new("2 * pi") {
+            Ok(compiled) => {
+                assert!(compiled.eval_slice(&inputs[..3], &mut out[..3]).is_ok());
+                assert_eq!(&out[..3], &[2.0 * std::f64::consts::PI; 3]);
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_eval_slice_errors() {
+        let inputs: Vec<f64> = (0..12).map(|i| i as f64).collect();
+        let mut out: Vec<f64> = vec![0.0; inputs.len()];
+
+        match CompiledExpression::new("1 / (x - 10)") {
+            Ok(compiled) => {
+                assert_eq!(
+                    compiled.eval_slice(&inputs, &mut out),
+                    Err(String::from(
+                        "Evaluation of input 10 failed: Division by zero"
+                    ))
+                );
+                assert!(compiled.eval_slice(&inputs, &mut out[..4]).is_err());
+            }
+            Err(_) => assert!(false),
+        }
+
+        match CompiledExpression::new("x * y") {
+            Ok(compiled) => assert!(compiled.eval_slice(&inputs, &mut out).is_err()),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_tuple() {
+        match CompiledExpression::new("(sin(t), cos(t), t^2)") {
+            Ok(compiled) => {
+                assert_eq!(compiled.outputs(), 3);
+                assert_eq!(
+                    compiled.evaluate_tuple(&[0.5]),
+                    Ok(vec![0.5_f64.sin(), 0.5_f64.cos(), 0.25])
+                );
+                assert!(compiled.evaluate(&[0.5]).is_err());
+                assert!(compiled.eval_slice(&[0.5], &mut [0.0]).is_err());
+            }
+            Err(_) => assert!(false),
+        }
+
+        match CompiledExpression::new("(t + 1) * 2") {
+            Ok(compiled) => {
+                assert_eq!(compiled.outputs(), 1);
+                assert_eq!(compiled.evaluate_tuple(&[0.5]), Ok(vec![3.0]));
+            }
+            Err(_) => assert!(false),
+        }
+
+        assert!(CompiledExpression::new("(t, t) + 1").is_err());
+        assert!(CompiledExpression::new("(t, , t)").is_err());
+    }
+
+    #[test]
+    fn test_compiled_expression_conditional_and_boolean_operators() {
+        let expressions: [&str; 8] = [
+            "x ? 1 / x : -1",
+            "x > 0 && ln(x) < 1 ? 1 : y ? 2 : 3",
+            "!x + (x || y) * 2",
+            "x == 0 || 1 / x > y",
+            "if(x >= 1, sqrt(x - 1), y != 1)",
+            "piecewise(x < 0, -x, x < 2, x^2, 4)",
+            "x && y && !(x <= y)",
+            "2 * (x ? (y ? 3 : 4) : (y || x ? 5 : 6)) + 1",
+        ];
+
+        for expression in expressions {
+            let compiled: CompiledExpression = match CompiledExpression::new(expression) {
+                Ok(compiled) => compiled,
+                Err(_) => return assert!(false),
+            };
+
+            for (x, y) in [(0.0, 0.0), (0.5, 1.0), (2.0, 0.0), (-1.0, f64::NAN)] {
+                let variables: HashMap<String, f64> =
+                    HashMap::from([(String::from("x"), x), (String::from("y"), y)]);
+                let expected: Result<f64, String> = crate::evaluate(expression, &variables);
+
+                match compiled.evaluate_with_variables(&variables) {
+                    Ok(value) => assert!(expected.is_ok_and(|expected| {
+                        value == expected || (value.is_nan() && expected.is_nan())
+                    })),
+                    Err(_) => assert!(expected.is_err()),
+                }
+            }
+        }
+
+        // Values which are not taken are not evaluated, even when their variables are bound
+        match CompiledExpression::new("x ? 1 / y : y || ln(y)") {
+            Ok(compiled) => {
+                let bound: CompiledExpression =
+                    compiled.bind(&[("y", 0.0)], &EvaluationOptions::default());
+                assert_eq!(bound.variables(), &[String::from("x")]);
+                assert_eq!(
+                    bound.evaluate(&[0.0]),
+                    Err(String::from("Argument of ln function is negative or null"))
+                );
+                assert_eq!(compiled.evaluate(&[0.0, 2.0]), Ok(1.0));
+                assert!(compiled.evaluate(&[1.0, 0.0]).is_err());
+
+                let inputs: Vec<f64> = vec![2.0; 10];
+                let mut out: Vec<f64> = vec![0.0; 10];
+                let bound: CompiledExpression =
+                    compiled.bind(&[("x", 1.0)], &EvaluationOptions::default());
+                assert_eq!(bound.eval_slice(&inputs, &mut out), Ok(()));
+                assert_eq!(out, vec![0.5; 10]);
+            }
+            Err(_) => assert!(false),
+        }
+
+        match CompiledExpression::new("x < 1 && !y") {
+            Ok(compiled) => {
+                let bound: CompiledExpression =
+                    compiled.bind(&[("y", 0.0)], &EvaluationOptions::default());
+                assert_eq!(bound.evaluate(&[0.0]), Ok(1.0));
+                assert_eq!(bound.evaluate(&[3.0]), Ok(0.0));
+            }
+            Err(_) => assert!(false),
+        }
+
+        match CompiledExpression::new("x > 1 ? 1 : 0").and_then(|compiled| compiled.to_rust_fn()) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(
+                message,
+                "Conditional, boolean and comparison operators cannot be generated"
+            ),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_lookup() {
+        match CompiledExpression::new(
+            "lookup(x, [0, 10, 20], [1, 2, 4]) + 2 * lookup_extrap(x, [0, 1], [0, -1])",
+        ) {
+            Ok(compiled) => {
+                assert_eq!(compiled.evaluate(&[5.0]), Ok(1.5 - 10.0));
+                assert_eq!(compiled.evaluate(&[30.0]), Ok(4.0 - 60.0));
+
+                let mut out: [f64; 2] = [0.0; 2];
+                assert!(compiled.eval_slice(&[5.0, 30.0], &mut out).is_ok());
+                assert_eq!(out, [1.5 - 10.0, 4.0 - 60.0]);
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_aggregation() {
+        match CompiledExpression::new("mean(x, [2, 4], x^2) + max(x, 1)") {
+            Ok(compiled) => {
+                assert_eq!(compiled.evaluate(&[3.0]), Ok(4.5 + 3.0));
+                assert_eq!(compiled.evaluate(&[0.0]), Ok(1.5 + 1.0));
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_bind() {
+        match CompiledExpression::new("(a + 1) * x - sqrt(a) / y + b") {
+            Ok(compiled) => {
+                let bound: CompiledExpression =
+                    compiled.bind(&[("a", 4.0), ("z", 1.0)], &EvaluationOptions::default());
+
+                assert_eq!(
+                    bound.variables(),
+                    &[String::from("x"), String::from("y"), String::from("b")]
+                );
+                assert_eq!(bound.constants, vec![5.0, 2.0]);
+                assert_eq!(
+                    bound.code,
+                    vec![
+                        Instruction::LoadConstant(0),
+                        Instruction::LoadVariable(0),
+                        Instruction::Multiply,
+                        Instruction::LoadConstant(1),
+                        Instruction::LoadVariable(1),
+                        Instruction::Divide,
+                        Instruction::Subtract,
+                        Instruction::LoadVariable(2),
+                        Instruction::Add,
+                    ]
+                );
+                assert_eq!(bound.evaluate(&[3.0, 4.0, 1.0]), Ok(15.5));
+                assert_eq!(
+                    bound.evaluate(&[3.0, 4.0, 1.0]),
+                    compiled.evaluate(&[4.0, 3.0, 4.0, 1.0])
+                );
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_bind_keeps_errors() {
+        match CompiledExpression::new("(x / a, ln(a) + x, mean(a, [1, 3], x))") {
+            Ok(compiled) => {
+                let bound: CompiledExpression =
+                    compiled.bind(&[("a", 0.0)], &EvaluationOptions::default());
+
+                assert_eq!(bound.variables(), &[String::from("x")]);
+                assert_eq!(bound.outputs(), 3);
+                assert!(bound.evaluate_tuple(&[1.0]).is_err());
+                assert_eq!(
+                    compiled
+                        .bind(&[("a", 2.0)], &EvaluationOptions::default())
+                        .evaluate_tuple(&[2.0]),
+                    Ok(vec![1.0, 2f64.ln() + 2.0, 2.0])
+                );
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_bind_with_options() {
+        let degrees: EvaluationOptions = EvaluationOptions {
+            angle: AngleUnit::Degrees,
+            ..EvaluationOptions::default()
+        };
+
+        match CompiledExpression::new("sin(a) * x") {
+            Ok(compiled) => {
+                let bound: CompiledExpression = compiled.bind(&[("a", 30.0)], &degrees);
+
+                match bound.evaluate_with_options(&[4.0], &degrees) {
+                    Ok(value) => assert!(relative_error(value, 2.0) < 1e-12),
+                    Err(_) => assert!(false),
+                }
+                assert_eq!(
+                    bound.evaluate_with_options(&[4.0], &degrees),
+                    compiled.evaluate_with_options(&[30.0, 4.0], &degrees)
+                );
+            }
+            Err(_) => assert!(false),
+        }
+
+        // Operation giving the result is rounded at evaluation, so it is not computed
+        match CompiledExpression::new("x * 0.1 + 0.2") {
+            Ok(compiled) => {
+                let options: EvaluationOptions = EvaluationOptions {
+                    rounding: RoundingMode::Down,
+                    ..EvaluationOptions::default()
+                };
+                let bound: CompiledExpression = compiled.bind(&[("x", 1.0)], &options);
+
+                assert_eq!(bound.code.len(), 3);
+                assert_eq!(bound.evaluate_with_options(&[], &options), Ok(0.3));
+                assert_eq!(
+                    compiled
+                        .bind(&[("x", 1.0)], &EvaluationOptions::default())
+                        .evaluate(&[]),
+                    Ok(0.1 + 0.2)
+                );
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_evaluate_with_budget() {
+        match CompiledExpression::new("sqrt(x^2 + y^2) / 2") {
+            Ok(compiled) => {
+                // Bytecode is made of 10 instructions
+                assert_eq!(
+                    compiled.evaluate_with_budget(&[3.0, 4.0], &Budget::steps(10)),
+                    Ok(2.5)
+                );
+                assert_eq!(
+                    compiled.evaluate_with_budget(&[3.0, 4.0], &Budget::steps(9)),
+                    Err(Error::BudgetExceeded)
+                );
+                assert_eq!(
+                    compiled.evaluate_with_budget(&[3.0, 4.0], &Budget::deadline(Instant::now())),
+                    Err(Error::BudgetExceeded)
+                );
+                assert_eq!(
+                    compiled.evaluate_with_budget(&[3.0, 4.0], &Budget::default()),
+                    Ok(2.5)
+                );
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_check_vars() {
+        match CompiledExpression::new("x * y + z") {
+            Ok(compiled) => {
+                let mut variables: HashMap<String, f64> =
+                    HashMap::from([(String::from("x"), 1.0), (String::from("z"), 2.0)]);
+
+                assert_eq!(
+                    compiled.check_vars(&variables),
+                    Err(Error::Message(String::from("Missing value of variable y")))
+                );
+
+                variables.insert(String::from("y"), 3.0);
+                assert_eq!(compiled.check_vars(&variables), Ok(()));
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_evaluate_with_options() {
+        let lenient: EvaluationOptions = EvaluationOptions {
+            domain: DomainMode::Lenient,
+            ..EvaluationOptions::default()
+        };
+
+        match CompiledExpression::new("ln(x) + acos(x)") {
+            Ok(compiled) => {
+                match compiled.evaluate_with_options(&[-1.0], &lenient) {
+                    Ok(value) => assert!(value.is_nan()),
+                    Err(_) => assert!(false),
+                }
+
+                assert!(compiled
+                    .evaluate_with_options(&[-1.0], &EvaluationOptions::default())
+                    .is_err());
+                assert_eq!(compiled.evaluate_with_options(&[1.0], &lenient), Ok(0.0));
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_detect_overflow() {
+        let options: EvaluationOptions = EvaluationOptions {
+            detect_overflow: true,
+            ..EvaluationOptions::default()
+        };
+
+        match CompiledExpression::new("exp(x) * 2 + 10^x") {
+            Ok(compiled) => {
+                assert_eq!(
+                    compiled.evaluate_with_options(&[1000.0], &options),
+                    Err(Error::Overflow(String::from(
+                        "Result of exp function overflows"
+                    )))
+                );
+                assert_eq!(
+                    compiled.evaluate_with_options(&[400.0], &options),
+                    Err(Error::Overflow(String::from(
+                        "Result of ^ operation overflows"
+                    )))
+                );
+                assert_eq!(
+                    compiled.evaluate_with_options(&[400.0], &EvaluationOptions::default()),
+                    Ok(f64::INFINITY)
+                );
+                assert!(compiled.evaluate_with_options(&[1.0], &options).is_ok());
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_rounding_mode() {
+        match CompiledExpression::new("x * 0.1 + 0.2") {
+            Ok(compiled) => {
+                let options: EvaluationOptions = EvaluationOptions {
+                    rounding: RoundingMode::Down,
+                    ..EvaluationOptions::default()
+                };
+
+                assert_eq!(compiled.evaluate(&[1.0]), Ok(0.1 + 0.2));
+                assert_eq!(compiled.evaluate_with_options(&[1.0], &options), Ok(0.3));
+            }
+            Err(_) => assert!(false),
+        }
+
+        match CompiledExpression::new("1 / x * 1") {
+            Ok(compiled) => {
+                let options: EvaluationOptions = EvaluationOptions {
+                    rounding: RoundingMode::Up,
+                    ..EvaluationOptions::default()
+                };
+
+                // Division is rounded up only if each operation is rounded
+                let third: f64 = 1.0 / 3.0;
+                assert_eq!(compiled.evaluate_with_options(&[3.0], &options), Ok(third));
+
+                let options: EvaluationOptions = EvaluationOptions {
+                    round_operations: true,
+                    ..options
+                };
+
+                assert_eq!(
+                    compiled.evaluate_with_options(&[3.0], &options),
+                    Ok(third.next_up())
+                );
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_division_by_zero_behavior() {
+        match CompiledExpression::new("2 * (1 / x)") {
+            Ok(compiled) => {
+                let options: EvaluationOptions = EvaluationOptions {
+                    division_by_zero: DivisionByZero::Value(-1.0),
+                    ..EvaluationOptions::default()
+                };
+
+                assert_eq!(compiled.evaluate_with_options(&[0.0], &options), Ok(-2.0));
+                assert_eq!(compiled.evaluate_with_options(&[2.0], &options), Ok(1.0));
+
+                let options: EvaluationOptions = EvaluationOptions {
+                    division_by_zero: DivisionByZero::Infinity,
+                    ..options
+                };
+
+                assert_eq!(
+                    compiled.evaluate_with_options(&[0.0], &options),
+                    Ok(f64::INFINITY)
+                );
+                assert!(compiled.evaluate(&[0.0]).is_err());
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    /// Length in millimeters, keeping an integral number of millimeters
+    #[derive(Debug, PartialEq)]
+    struct Millimeters(i64);
+
+    impl FromTazValue for Millimeters {
+        fn from_taz_value(value: f64) -> Result<Millimeters, String> {
+            return Ok(Millimeters(i64::from_taz_value((value * 1000.0).round())?));
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_evaluate_as() {
+        match CompiledExpression::new("width * 2 + 0.0005") {
+            Ok(compiled) => {
+                assert_eq!(compiled.evaluate_as(&[1.25]), Ok(Millimeters(2501)));
+                assert_eq!(compiled.evaluate_as::<f32>(&[1.0]), Ok(2.0005));
+                assert!(compiled.evaluate_as::<u8>(&[1.0]).is_err());
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_compiled_expression_evaluate_as_with_policy() {
+        match CompiledExpression::new("-x * 2 - 1") {
+            Ok(compiled) => {
+                let saturate: ArithmeticPolicy = ArithmeticPolicy::Saturate;
+                let wrap: ArithmeticPolicy = ArithmeticPolicy::Wrap;
+
+                // Negation of -128 is already out of range of i8
+                assert_eq!(
+                    compiled.evaluate_as_with_policy::<i8>(&[-128.0], saturate),
+                    Ok(126)
+                );
+                assert_eq!(
+                    compiled.evaluate_as_with_policy::<i8>(&[-128.0], wrap),
+                    Ok(-1)
+                );
+                assert_eq!(
+                    compiled.evaluate_as_with_policy::<i8>(&[-128.0], ArithmeticPolicy::Error),
+                    Err(String::from(
+                        "Result of - operation is out of range [-128, 127]"
+                    ))
+                );
+                assert_eq!(
+                    compiled.evaluate_as_with_policy::<i8>(&[10.0], wrap),
+                    Ok(-21)
+                );
+                assert_eq!(
+                    compiled.evaluate_as_with_policy::<f64>(&[-128.0], wrap),
+                    Ok(255.0)
+                );
+            }
+            Err(_) => assert!(false),
+        }
+    }
+}
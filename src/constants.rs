@@ -1,16 +1,16 @@
+#![allow(clippy::needless_return)]
+
 /// Available constants used in library
 pub const PI: f64 = std::f64::consts::PI;
 pub const E: f64 = std::f64::consts::E;
 pub const C: f64 = 299792458.0;
 
+/// Names of available constants
+pub const NAMES: [&str; 3] = ["pi", "e", "c"];
+
 /// Check if a string correspond to available constant
 pub fn is_constant(constant: &str) -> bool {
-    match constant {
-        "pi" => true,
-        "e" => true,
-        "c" => true,
-        _ => false,
-    }
+    return NAMES.contains(&constant);
 }
 
 /// Get constant value from a string
@@ -0,0 +1,239 @@
+#![allow(clippy::needless_return)]
+
+use super::binding;
+use super::calculus;
+use super::converter::Postfix;
+use super::evaluator::{self, OperandStack};
+use super::options::EvaluationOptions;
+use super::preprocess;
+use super::token::Token;
+use super::tokenizer::Infix;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// State of host application consulted by evaluation, so variables and functions can be backed
+/// by data structures of application, like components of entities or trees of configuration,
+/// instead of being copied into a hash map for each evaluation.
+///
+/// Predefined constants and functions are resolved before names of context.
+///
+/// # Example
+/// ```
+/// use taz::Context;
+///
+/// struct Vehicle {
+///     speed: f64,
+///     gears: Vec<f64>,
+/// }
+///
+/// impl Context for Vehicle {
+///     fn resolve_variable(&self, name: &str) -> Option<f64> {
+///         match name {
+///             "speed" => Some(self.speed),
+///             _ => None,
+///         }
+///     }
+///
+///     fn has_function(&self, name: &str) -> bool {
+///         return name == "gear";
+///     }
+///
+///     fn call_function(&self, name: &str, args: &[f64]) -> Result<f64, String> {
+///         match self.gears.get(args[0] as usize) {
+///             Some(&ratio) if args.len() == 1 => Ok(ratio),
+///             _ => Err(format!("Invalid arguments of function {name}")),
+///         }
+///     }
+/// }
+///
+/// let vehicle: Vehicle = Vehicle { speed: 20.0, gears: vec![3.5, 2.1, 1.4] };
+///
+/// assert_eq!(taz::evaluate_with_context("speed * gear(2)", &vehicle), Ok(28.0));
+/// assert!(taz::evaluate_with_context("speed * gear(5)", &vehicle).is_err());
+/// ```
+pub trait Context {
+    /// Get value of variable whose name is given in argument, None if context has no such variable
+    fn resolve_variable(&self, name: &str) -> Option<f64>;
+
+    /// Check if context provides a function whose name is given in argument.
+    /// Context provides no function by default
+    fn has_function(&self, _name: &str) -> bool {
+        return false;
+    }
+
+    /// Call function of context whose name is given in argument with values of its arguments.
+    /// This method is only called for names of functions provided by context.
+    /// If error occurs during call, an error message is stored in string contained in Result output
+    fn call_function(&self, name: &str, _args: &[f64]) -> Result<f64, String> {
+        return Err(format!("Unknown function {name}"));
+    }
+}
+
+impl Context for HashMap<String, f64> {
+    fn resolve_variable(&self, name: &str) -> Option<f64> {
+        return self.get(name).copied();
+    }
+}
+
+/// Evaluate an expression like evaluate function, where values of variables are resolved by context given in argument
+/// and functions provided by context can be called like predefined functions, with any number of arguments.
+/// Each variable is resolved once when it is read, so values are never copied into a hash map.
+///
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+pub fn evaluate_with_context<C>(expression: &str, context: &C) -> Result<f64, String>
+where
+    C: Context + ?Sized,
+{
+    return evaluate_in_scope(expression, &HashMap::new(), context);
+}
+
+/// Evaluate an expression like evaluate_with_context function, values of variables of scope given in argument,
+/// bound by local bindings or by calls of calculus functions, overriding those of context
+fn evaluate_in_scope<C>(
+    expression: &str,
+    scope: &HashMap<String, f64>,
+    context: &C,
+) -> Result<f64, String>
+where
+    C: Context + ?Sized,
+{
+    // Local bindings and calls of calculus functions are evaluated with values of variables named in expression
+    if !matches!(binding::let_binding(expression), Ok(None))
+        || calculus::find_calculus_call(expression).is_some()
+    {
+        let mut variables: HashMap<String, f64> = expression
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|name| !scope.contains_key(*name))
+            .filter_map(|name| Some((String::from(name), context.resolve_variable(name)?)))
+            .collect();
+        variables.extend(scope.iter().map(|(name, &value)| (name.clone(), value)));
+
+        if let Some(result) = preprocess(expression, &variables, |expression, scope| {
+            evaluate_in_scope(expression, scope, context)
+        }) {
+            return result;
+        }
+    }
+
+    // Names of functions are shared by tokenization and evaluation, which happen in a single pass
+    let functions: RefCell<Vec<String>> = RefCell::new(Vec::new());
+
+    let infix_tokens = Infix::new(expression, |name: &str| {
+        if let Some(&value) = scope.get(name) {
+            return Some(Token::new_number(value));
+        }
+
+        if context.has_function(name) {
+            let mut functions = functions.borrow_mut();
+            let slot: usize = match functions.iter().position(|function| function == name) {
+                Some(slot) => slot,
+                None => {
+                    functions.push(String::from(name));
+                    functions.len() - 1
+                }
+            };
+
+            return Some(Token::HostFunction(slot, 0));
+        }
+
+        return context.resolve_variable(name).map(Token::new_number);
+    });
+
+    let mut postfix_tokens = Postfix::new(infix_tokens);
+    let result: Result<OperandStack, String> = evaluator::postfix_evaluation_stream_with_host(
+        postfix_tokens.by_ref(),
+        &[],
+        &EvaluationOptions::default(),
+        |slot, args| context.call_function(&functions.borrow()[slot], args),
+    )
+    .map_err(|error| postfix_tokens.tokens().locate(String::from(error)));
+
+    match result?.first() {
+        Some(&value) => Ok(value),
+        None => Err(String::from("Empty expression")),
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Config {
+        scale: f64,
+    }
+
+    impl Context for Config {
+        fn resolve_variable(&self, name: &str) -> Option<f64> {
+            match name {
+                "scale" => Some(self.scale),
+                _ => None,
+            }
+        }
+
+        fn has_function(&self, name: &str) -> bool {
+            return name == "total" || name == "fail";
+        }
+
+        fn call_function(&self, name: &str, args: &[f64]) -> Result<f64, String> {
+            match name {
+                "total" => Ok(args.iter().sum()),
+                _ => Err(String::from("Function failed")),
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_with_context() {
+        let config: Config = Config { scale: 2.0 };
+
+        assert_eq!(
+            evaluate_with_context("scale * total(1, total(2), 3 + scale) - 1", &config),
+            Ok(15.0)
+        );
+        assert_eq!(
+            evaluate_with_context("sin(0) + total(scale, pi - pi)", &config),
+            Ok(2.0)
+        );
+        assert_eq!(
+            evaluate_with_context("scale + fail(1)", &config),
+            Err(String::from("Function failed at position 14"))
+        );
+        assert_eq!(
+            evaluate_with_context("total 2", &config),
+            Err(String::from(
+                "Missing parenthesis after function at position 6"
+            ))
+        );
+        assert_eq!(
+            evaluate_with_context("scale * qqqj", &config),
+            Err(String::from("Unknown name qqqj at position 8"))
+        );
+
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 3.0)]);
+        assert_eq!(evaluate_with_context("x^2", &variables), Ok(9.0));
+    }
+
+    #[test]
+    fn test_evaluate_with_context_binding_and_calculus() {
+        let config: Config = Config { scale: 2.0 };
+
+        assert_eq!(
+            evaluate_with_context("let t = scale^2 in total(t, 1) * t", &config),
+            Ok(20.0)
+        );
+        assert_eq!(
+            evaluate_with_context("total(integrate(scale * x, x, 0, scale), 1)", &config),
+            Ok(5.0)
+        );
+        assert_eq!(
+            evaluate_with_context("let n = scale + 1 in series(i, 1, n, i) + n", &config),
+            Ok(9.0)
+        );
+        assert_eq!(
+            evaluate_with_context("1 + deriv(x^2, x, qqqj)", &config),
+            Err(String::from("Unknown name qqqj at position 18"))
+        );
+    }
+}
@@ -0,0 +1,122 @@
+#![allow(clippy::needless_return)]
+
+/// Types in which value of an expression can be given, like a fixed-point type
+/// or a newtype of units of measure, so applications keep their numeric types end to end.
+/// Conversion is checked: a value which cannot be represented gives an error instead of being truncated.
+pub trait FromTazValue: Sized {
+    /// Convert value of expression given in argument.
+    /// If value cannot be represented, an error message is stored in string contained in Result output
+    fn from_taz_value(value: f64) -> Result<Self, String>;
+
+    /// Get smallest and largest values of type, used by arithmetic policies to bring
+    /// results of operations in range, or None if type has no range narrower than f64
+    fn range() -> Option<(f64, f64)> {
+        return None;
+    }
+}
+
+impl FromTazValue for f64 {
+    fn from_taz_value(value: f64) -> Result<f64, String> {
+        return Ok(value);
+    }
+}
+
+impl FromTazValue for f32 {
+    fn from_taz_value(value: f64) -> Result<f32, String> {
+        let converted: f32 = value as f32;
+
+        if converted.is_infinite() && value.is_finite() {
+            return Err(format!("Value {value} is out of range of f32"));
+        }
+
+        return Ok(converted);
+    }
+}
+
+/// Implement conversion of values of expressions into integer types given in argument,
+/// values being required to be whole numbers in range of integer type
+macro_rules! impl_from_taz_value_for_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl FromTazValue for $integer {
+                fn from_taz_value(value: f64) -> Result<$integer, String> {
+                    if !value.is_finite() || value.fract() != 0.0 {
+                        return Err(format!(
+                            "Value {value} is not a whole number to convert to {}",
+                            stringify!($integer)
+                        ));
+                    }
+
+                    // Maximal value plus one is a power of two, so it is exactly represented
+                    if value < <$integer>::MIN as f64 || value >= <$integer>::MAX as f64 + 1.0 {
+                        return Err(format!(
+                            "Value {value} is out of range of {}",
+                            stringify!($integer)
+                        ));
+                    }
+
+                    return Ok(value as $integer);
+                }
+
+                fn range() -> Option<(f64, f64)> {
+                    // Largest value of 64-bit types is rounded up to a power of two, out of range
+                    let max: f64 = <$integer>::MAX as f64;
+                    let max: f64 = if max < max + 1.0 { max } else { max.next_down() };
+
+                    return Some((<$integer>::MIN as f64, max));
+                }
+            }
+        )*
+    };
+}
+
+impl_from_taz_value_for_integer!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_taz_value_floats() {
+        assert_eq!(f64::from_taz_value(1.5), Ok(1.5));
+        assert_eq!(f32::from_taz_value(1.5), Ok(1.5));
+        assert!(f32::from_taz_value(1e300).is_err());
+        assert_eq!(f32::from_taz_value(f64::INFINITY), Ok(f32::INFINITY));
+    }
+
+    #[test]
+    fn test_from_taz_value_integers() {
+        assert_eq!(i32::from_taz_value(-12.0), Ok(-12));
+        assert_eq!(u8::from_taz_value(255.0), Ok(255));
+        assert_eq!(
+            u8::from_taz_value(256.0),
+            Err(String::from("Value 256 is out of range of u8"))
+        );
+        assert_eq!(
+            i64::from_taz_value(2.5),
+            Err(String::from(
+                "Value 2.5 is not a whole number to convert to i64"
+            ))
+        );
+        assert!(u32::from_taz_value(-1.0).is_err());
+        assert!(i64::from_taz_value(9_223_372_036_854_775_808.0).is_err());
+        assert!(i32::from_taz_value(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_from_taz_value_ranges() {
+        assert_eq!(f64::range(), None);
+        assert_eq!(u8::range(), Some((0.0, 255.0)));
+        assert_eq!(i16::range(), Some((-32768.0, 32767.0)));
+
+        match (i64::range(), u64::range()) {
+            (Some((min, max)), Some((_, max_unsigned))) => {
+                assert_eq!(i64::from_taz_value(min), Ok(i64::MIN));
+                assert!(i64::from_taz_value(max).is_ok());
+                assert!(u64::from_taz_value(max_unsigned).is_ok());
+            }
+            _ => assert!(false),
+        }
+    }
+}
@@ -1,6 +1,13 @@
+#![allow(clippy::needless_return)]
+
+use super::functions::Function;
+use super::limits::{Limit, Limits};
 use super::operators::BinaryOperator;
+use super::options::Capacities;
 use super::token::Token;
 
+use smallvec::SmallVec;
+
 /// Check if last token, which can represent an operator or left parenthesis, is primary
 /// with binary operator given in argument
 fn last_operator_is_primary(token_ops: Token, current_ops: BinaryOperator) -> bool {
@@ -20,80 +27,425 @@ fn last_operator_is_primary(token_ops: Token, current_ops: BinaryOperator) -> bo
     }
 }
 
-/// Convert infix representation of expression into postfix representation
-/// If error occurs during evaluation, an error message is stored
-/// in string contained in Result output
-pub fn infix_to_postfix(tokens: Vec<Token>) -> Result<Vec<Token>, String> {
-    // Build postfix expression from infix expression
-    let mut tokens_postfix: Vec<Token> = Vec::with_capacity(tokens.len());
-    let mut stack_operator: Vec<Token> = Vec::with_capacity(tokens.len());
+/// Iterator converting infix tokens given by an iterator into postfix tokens.
+/// Only operators waiting for their operands are kept in memory, inline for short expressions,
+/// so conversion happens while infix tokens are extracted without heap allocation.
+/// With limits, at most max_operator_depth operators are stored and the stack is never reallocated past this cap,
+/// so conversion uses at most about 32 bytes per allowed operator whatever the length of expression.
+/// Without limits, the stack holds at most one token per token of expression.
+/// Each postfix token is an infix token given by user, operators being only reordered:
+/// no token is generated, like implicit multiplications or parenthesis closed automatically,
+/// except the token ending each conditional operator and the token following left operand of each boolean operator,
+/// so values which are not needed can be skipped by evaluation, and the array token giving the number of arguments
+/// of each aggregation function, which is stored in stack like the length of a breakpoint array.
+/// Conditional operator has the lowest precedence and is right associative,
+/// so a ? b : c ? d : e is converted like a ? b : (c ? d : e).
+/// Calls of if and piecewise functions are converted like conditional operators, so if(a, b, c) gives
+/// the tokens of a ? b : c and piecewise(a, b, c, d, e) gives the tokens of a ? b : c ? d : e.
+/// Infix tokens which give no postfix token, like parenthesis and commas, are consumed while
+/// looking for the next postfix token, so only tokens of postfix expression are given until end of conversion.
+/// If error occurs during conversion, an error message is given and iteration stops
+pub struct Postfix<I>
+where
+    I: Iterator<Item = Result<Token, String>>,
+{
+    tokens: I,
+    stack_operator: SmallVec<[Token; 16]>,
+    call_commas: SmallVec<[usize; 4]>,
+    pending: SmallVec<[Token; 8]>,
+    next_pending: usize,
+    max_depth: usize,
+    exceeded: Option<Limit>,
+    finished: bool,
+}
+
+impl<I> Postfix<I>
+where
+    I: Iterator<Item = Result<Token, String>>,
+{
+    /// Create iterator converting infix tokens given by iterator in argument
+    pub fn new(tokens: I) -> Postfix<I> {
+        return Postfix {
+            tokens,
+            stack_operator: SmallVec::new(),
+            call_commas: SmallVec::new(),
+            pending: SmallVec::new(),
+            next_pending: 0,
+            max_depth: usize::MAX,
+            exceeded: None,
+            finished: false,
+        };
+    }
+
+    /// Set limits of conversion: the number of operators waiting for their operands is bounded
+    /// by maximal operator depth of limits given in argument, conversion failing before this limit is exceeded
+    pub fn with_limits(mut self, limits: Limits) -> Postfix<I> {
+        self.max_depth = limits.max_operator_depth;
+        return self;
+    }
+
+    /// Set initial capacity of stack of operators waiting for their operands
+    /// to capacity of operators given in argument
+    pub fn with_capacities(mut self, capacities: Capacities) -> Postfix<I> {
+        self.stack_operator = SmallVec::with_capacity(capacities.operators);
+        return self;
+    }
+
+    /// Get iterator of infix tokens converted by this iterator
+    pub fn tokens(&self) -> &I {
+        return &self.tokens;
+    }
+
+    /// Get limit exceeded by expression, if conversion failed for this reason
+    pub fn exceeded(&self) -> Option<Limit> {
+        return self.exceeded;
+    }
+
+    /// Push token given in argument on stack operator, checking that maximal operator depth
+    /// is not exceeded so stack never grows past this limit
+    fn push_operator(&mut self, token: Token) -> Result<(), String> {
+        if self.stack_operator.len() >= self.max_depth {
+            let limit: Limit = Limit::OperatorDepth(self.max_depth);
+            self.exceeded = Some(limit);
+            return Err(limit.to_string());
+        }
+
+        self.stack_operator.push(token);
+        return Ok(());
+    }
+
+    /// Pop stack operator until left parenthesis, popped operators being pending postfix tokens.
+    /// Each conditional operator popped gives its end token.
+    /// If a conditional operator has no colon, an error message is stored in string contained in Result output
+    fn pop_until_left_parenthesis(&mut self) -> Result<(), String> {
+        self.pop_operators();
+
+        while let Some(&stack_last) = self.stack_operator.last() {
+            match stack_last {
+                Token::Colon => self.pending.push(Token::EndConditional),
+                Token::Question => {
+                    return Err(String::from("Missing colon of conditional operator"));
+                }
+                _ => break,
+            }
+
+            self.stack_operator.pop();
+            self.pop_operators();
+        }
+
+        return Ok(());
+    }
+
+    /// Pop stack operator until left parenthesis or conditional operator,
+    /// popped operators being pending postfix tokens
+    fn pop_operators(&mut self) {
+        while let Some(&stack_last) = self.stack_operator.last() {
+            if !matches!(
+                stack_last,
+                Token::LeftParenthesis | Token::Question | Token::Colon
+            ) {
+                self.pending.push(stack_last);
+                self.stack_operator.pop();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Process a comma separating arguments of conditional function given in argument,
+    /// which ends a condition or a value taken if condition is true
+    fn process_conditional_comma(&mut self, fun: Function) {
+        if let Some(nb_commas) = self.call_commas.last_mut() {
+            // Extra arguments of if function are reported when parenthesis of call is closed
+            match *nb_commas % 2 {
+                _ if fun == Function::If && *nb_commas >= 2 => (),
+                0 => self.pending.push(Token::Question),
+                _ => self.pending.push(Token::Colon),
+            }
 
-    for token in tokens {
+            *nb_commas += 1;
+        }
+    }
+
+    /// Process an infix token, postfix tokens which can be given being pushed in pending tokens
+    fn process(&mut self, token: Token) -> Result<(), String> {
         match token {
-            Token::Number(_) => tokens_postfix.push(token),
-            Token::Constant(_) => tokens_postfix.push(token),
+            Token::Number(_) => self.pending.push(token),
+            Token::Constant(_) => self.pending.push(token),
+            Token::Variable(_) => self.pending.push(token),
+            Token::Array(_) => self.pending.push(token),
             Token::BinaryOperator(ops) => {
                 // Pop stack operator according to last operators precedence
-                while let Some(&stack_last) = stack_operator.last() {
+                while let Some(&stack_last) = self.stack_operator.last() {
                     if last_operator_is_primary(stack_last, ops) {
-                        tokens_postfix.push(stack_last);
-                        stack_operator.pop();
+                        self.pending.push(stack_last);
+                        self.stack_operator.pop();
                     } else {
                         break;
                     }
                 }
 
-                stack_operator.push(token);
+                // Left operand of boolean operator is complete, so its right operand can be skipped
+                if ops.is_boolean() {
+                    self.pending.push(Token::ShortCircuit(ops));
+                }
+
+                self.push_operator(token)?;
             }
-            Token::UnaryOperator(_) => stack_operator.push(token),
-            Token::Function(_) => stack_operator.push(token),
-            Token::LeftParenthesis => stack_operator.push(token),
-            Token::RightParenthesis => {
-                // Pop stack operator between left and right parenthesis
-                while let Some(&stack_last) = stack_operator.last() {
-                    if stack_last != Token::LeftParenthesis {
-                        tokens_postfix.push(stack_last);
-                        stack_operator.pop();
-                    } else {
-                        break;
+            Token::UnaryOperator(_) => self.push_operator(token)?,
+            Token::Function(_) | Token::HostFunction(_, _) => self.push_operator(token)?,
+            Token::LeftParenthesis => {
+                // Commas of conditional functions are counted to give the tokens of conditional operators,
+                // and commas of aggregation and host functions to give their number of arguments
+                match self.stack_operator.last() {
+                    Some(Token::Function(fun)) if fun.is_conditional() || fun.is_variadic() => {
+                        self.call_commas.push(0)
+                    }
+                    Some(Token::HostFunction(_, _)) => self.call_commas.push(0),
+                    _ => (),
+                }
+
+                self.push_operator(token)?;
+            }
+            Token::Question => {
+                // Condition is complete, conditional operators being kept for their colon
+                self.pop_operators();
+                self.pending.push(token);
+                self.push_operator(token)?;
+            }
+            Token::Colon => {
+                // Conditional operators of first value end before colon
+                self.pop_operators();
+
+                while self.stack_operator.last() == Some(&Token::Colon) {
+                    self.stack_operator.pop();
+                    self.pending.push(Token::EndConditional);
+                    self.pop_operators();
+                }
+
+                match self.stack_operator.last_mut() {
+                    Some(stack_last) if *stack_last == Token::Question => *stack_last = token,
+                    _ => {
+                        return Err(String::from(
+                            "Missing question mark of conditional operator",
+                        ))
                     }
                 }
 
-                if stack_operator.is_empty() {
+                self.pending.push(token);
+            }
+            Token::EndConditional | Token::ShortCircuit(_) => {
+                return Err(String::from(
+                    "Token non-accepted for conversion of infix expression",
+                ));
+            }
+            Token::Comma => {
+                // Pop stack operator until left parenthesis of function call
+                self.pop_until_left_parenthesis()?;
+
+                // Comma is only allowed in parenthesis of function call
+                let nb_operators: usize = self.stack_operator.len();
+
+                if nb_operators < 2 {
+                    return Err(String::from("Misplaced comma"));
+                }
+
+                match self.stack_operator[nb_operators - 2] {
+                    Token::Function(fun) if fun.is_conditional() => {
+                        self.process_conditional_comma(fun)
+                    }
+                    Token::Function(fun) if !fun.is_variadic() => (),
+                    Token::Function(_) | Token::HostFunction(_, _) => {
+                        if let Some(nb_commas) = self.call_commas.last_mut() {
+                            *nb_commas += 1;
+                        }
+                    }
+                    _ => return Err(String::from("Misplaced comma")),
+                }
+            }
+            Token::RightParenthesis => {
+                // Pop stack operator between left and right parenthesis
+                self.pop_until_left_parenthesis()?;
+
+                if self.stack_operator.is_empty() {
                     return Err(String::from("Mismatched parenthesis"));
                 }
 
                 // Pop left parenthesis and function from stack operator
-                stack_operator.pop();
+                self.stack_operator.pop();
+
+                match self.stack_operator.last() {
+                    Some(&Token::Function(fun)) if fun.is_conditional() => {
+                        let nb_commas: usize = self.call_commas.pop().unwrap_or(0);
+
+                        // Each pair of condition and value gives a conditional operator
+                        if nb_commas < 2
+                            || !nb_commas.is_multiple_of(2)
+                            || (fun == Function::If && nb_commas != 2)
+                        {
+                            return Err(format!(
+                                "Wrong number of arguments of {} function",
+                                fun.name()
+                            ));
+                        }
 
-                if let Some(&stack_last) = stack_operator.last() {
-                    match stack_last {
-                        Token::Function(_) => {
-                            tokens_postfix.push(stack_last);
-                            stack_operator.pop();
+                        for _ in 0..nb_commas / 2 {
+                            self.pending.push(Token::EndConditional);
                         }
-                        _ => (),
+
+                        self.stack_operator.pop();
                     }
+                    // Arguments of aggregation function are followed by their number, like breakpoint arrays
+                    Some(&Token::Function(fun)) if fun.is_variadic() => {
+                        let nb_args: usize = self.call_commas.pop().unwrap_or(0) + 1;
+                        self.pending.push(Token::Array(nb_args));
+                        self.pending.push(Token::Function(fun));
+                        self.stack_operator.pop();
+                    }
+                    Some(&Token::Function(fun)) => {
+                        self.pending.push(Token::Function(fun));
+                        self.stack_operator.pop();
+                    }
+                    Some(&Token::HostFunction(slot, _)) => {
+                        let nb_args: usize = self.call_commas.pop().unwrap_or(0) + 1;
+                        self.pending.push(Token::HostFunction(slot, nb_args));
+                        self.stack_operator.pop();
+                    }
+                    _ => (),
                 }
             }
         }
+
+        return Ok(());
     }
+}
 
-    // Push rest of operator. If stack operator contains left parenthesis, then there is an error
-    if !stack_operator.is_empty() {
-        if stack_operator.contains(&Token::LeftParenthesis) {
-            return Err(String::from("Mismatched parenthesis"));
+impl<I> Iterator for Postfix<I>
+where
+    I: Iterator<Item = Result<Token, String>>,
+{
+    type Item = Result<Token, String>;
+
+    fn next(&mut self) -> Option<Result<Token, String>> {
+        loop {
+            // Pending tokens are given in order, then their storage is reused
+            if let Some(&token) = self.pending.get(self.next_pending) {
+                self.next_pending += 1;
+                return Some(Ok(token));
+            }
+
+            self.pending.clear();
+            self.next_pending = 0;
+
+            if self.finished {
+                return None;
+            }
+
+            match self.tokens.next() {
+                Some(Ok(token)) => {
+                    if let Err(message) = self.process(token) {
+                        self.finished = true;
+                        self.pending.clear();
+                        return Some(Err(message));
+                    }
+                }
+                Some(Err(message)) => {
+                    self.finished = true;
+                    return Some(Err(message));
+                }
+                None => {
+                    // Push rest of operator. If stack operator contains left parenthesis, then there is an error
+                    self.finished = true;
+
+                    if self.stack_operator.contains(&Token::LeftParenthesis) {
+                        return Some(Err(String::from("Mismatched parenthesis")));
+                    }
+
+                    if let Err(message) = self.pop_until_left_parenthesis() {
+                        self.pending.clear();
+                        return Some(Err(message));
+                    }
+                }
+            }
         }
+    }
+}
+
+/// Convert infix representation of expression into postfix representation
+/// If error occurs during evaluation, an error message is stored
+/// in string contained in Result output
+pub fn infix_to_postfix(tokens: Vec<Token>) -> Result<Vec<Token>, String> {
+    return Postfix::new(tokens.into_iter().map(Ok)).collect();
+}
 
-        stack_operator.reverse();
-        tokens_postfix.splice(tokens_postfix.len().., stack_operator);
+/// Split infix expression given as tuple of elements separated by commas
+/// inside parenthesis, like (a, b, c), into infix expressions of its elements.
+/// An expression which is not a tuple gives a single element
+fn split_tuple(tokens: &[Token]) -> Vec<&[Token]> {
+    if tokens.first() != Some(&Token::LeftParenthesis) {
+        return vec![tokens];
     }
 
-    return Ok(tokens_postfix);
+    let mut elements: Vec<&[Token]> = Vec::new();
+    let mut depth: usize = 0;
+    let mut element_start: usize = 1;
+
+    for (index, &token) in tokens.iter().enumerate() {
+        match token {
+            Token::LeftParenthesis => depth += 1,
+            Token::RightParenthesis => {
+                depth -= 1;
+
+                // Parenthesis of tuple must enclose the whole expression
+                if depth == 0 && index != tokens.len() - 1 {
+                    return vec![tokens];
+                }
+            }
+            Token::Comma if depth == 1 => {
+                elements.push(&tokens[element_start..index]);
+                element_start = index + 1;
+            }
+            _ => (),
+        }
+
+        if depth == 0 && index == tokens.len() - 1 && !elements.is_empty() {
+            elements.push(&tokens[element_start..index]);
+            return elements;
+        }
+    }
+
+    return vec![tokens];
+}
+
+/// Convert infix representation of expression, which can be a tuple like (a, b, c),
+/// into postfix representation where postfix expressions of elements follow each other.
+/// The number of elements of tuple is given with postfix representation,
+/// an expression which is not a tuple having a single element.
+/// If error occurs during conversion, an error message is stored
+/// in string contained in Result output
+pub fn tuple_to_postfix(tokens: Vec<Token>) -> Result<(Vec<Token>, usize), String> {
+    let elements: Vec<&[Token]> = split_tuple(&tokens);
+
+    if elements.len() == 1 {
+        return Ok((infix_to_postfix(tokens)?, 1));
+    }
+
+    let mut tokens_postfix: Vec<Token> = Vec::with_capacity(tokens.len());
+
+    for element in elements.iter() {
+        if element.is_empty() {
+            return Err(String::from("Empty element in tuple"));
+        }
+
+        tokens_postfix.extend(infix_to_postfix(element.to_vec())?);
+    }
+
+    return Ok((tokens_postfix, elements.len()));
 }
 
 // Units tests
 #[cfg(test)]
+#[allow(clippy::len_zero)]
 mod tests {
     use super::super::constants;
     use super::super::functions::Function;
@@ -712,4 +1064,310 @@ mod tests {
             Err(message) => assert!(message.len() > 0),
         }
     }
+
+    #[test]
+    fn test_infix_to_postfix_expression_with_function_several_arguments() {
+        let tokens: Vec<Token> = vec![
+            Token::Function(Function::Lerp),
+            Token::LeftParenthesis,
+            Token::Number(1.0),
+            Token::Comma,
+            Token::Number(2.0),
+            Token::BinaryOperator(BinaryOperator::Plus),
+            Token::Number(3.0),
+            Token::Comma,
+            Token::Number(0.5),
+            Token::RightParenthesis,
+        ];
+
+        match infix_to_postfix(tokens) {
+            Ok(tokens_postfix) => {
+                assert_eq!(tokens_postfix.len(), 6);
+
+                match tokens_postfix[0] {
+                    Token::Number(number) => assert_eq!(number, 1.0),
+                    _ => assert!(false),
+                }
+
+                match tokens_postfix[1] {
+                    Token::Number(number) => assert_eq!(number, 2.0),
+                    _ => assert!(false),
+                }
+
+                match tokens_postfix[2] {
+                    Token::Number(number) => assert_eq!(number, 3.0),
+                    _ => assert!(false),
+                }
+
+                match tokens_postfix[3] {
+                    Token::BinaryOperator(ops) => assert_eq!(ops, BinaryOperator::Plus),
+                    _ => assert!(false),
+                }
+
+                match tokens_postfix[4] {
+                    Token::Number(number) => assert_eq!(number, 0.5),
+                    _ => assert!(false),
+                }
+
+                match tokens_postfix[5] {
+                    Token::Function(fun) => assert_eq!(fun, Function::Lerp),
+                    _ => assert!(false),
+                }
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_infix_to_postfix_expression_with_misplaced_comma() {
+        let tokens: Vec<Token> = vec![Token::Number(1.0), Token::Comma, Token::Number(2.0)];
+
+        match infix_to_postfix(tokens) {
+            Ok(_tokens_postfix) => assert!(false),
+            Err(message) => assert_eq!(message, "Misplaced comma"),
+        }
+    }
+
+    #[test]
+    fn test_infix_to_postfix_misplaced_comma() {
+        // (2, 3) + 1
+        let tokens: Vec<Token> = vec![
+            Token::LeftParenthesis,
+            Token::Number(2.0),
+            Token::Comma,
+            Token::Number(3.0),
+            Token::RightParenthesis,
+            Token::BinaryOperator(BinaryOperator::Plus),
+            Token::Number(1.0),
+        ];
+
+        assert_eq!(
+            infix_to_postfix(tokens.clone()),
+            Err(String::from("Misplaced comma"))
+        );
+        assert_eq!(
+            tuple_to_postfix(tokens),
+            Err(String::from("Misplaced comma"))
+        );
+    }
+
+    #[test]
+    fn test_tuple_to_postfix() {
+        // (-x, sin(x) * 2)
+        let tokens: Vec<Token> = vec![
+            Token::LeftParenthesis,
+            Token::UnaryOperator(UnaryOperator::Minus),
+            Token::Variable(0),
+            Token::Comma,
+            Token::Function(Function::Sin),
+            Token::LeftParenthesis,
+            Token::Variable(0),
+            Token::RightParenthesis,
+            Token::BinaryOperator(BinaryOperator::Multiply),
+            Token::Number(2.0),
+            Token::RightParenthesis,
+        ];
+
+        let postfix_ref: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::UnaryOperator(UnaryOperator::Minus),
+            Token::Variable(0),
+            Token::Function(Function::Sin),
+            Token::Number(2.0),
+            Token::BinaryOperator(BinaryOperator::Multiply),
+        ];
+
+        assert_eq!(tuple_to_postfix(tokens), Ok((postfix_ref, 2)));
+    }
+
+    #[test]
+    fn test_tuple_to_postfix_not_a_tuple() {
+        // (1 + 2) * 3
+        let tokens: Vec<Token> = vec![
+            Token::LeftParenthesis,
+            Token::Number(1.0),
+            Token::BinaryOperator(BinaryOperator::Plus),
+            Token::Number(2.0),
+            Token::RightParenthesis,
+            Token::BinaryOperator(BinaryOperator::Multiply),
+            Token::Number(3.0),
+        ];
+
+        match tuple_to_postfix(tokens) {
+            Ok((tokens_postfix, nb_elements)) => {
+                assert_eq!(tokens_postfix.len(), 5);
+                assert_eq!(nb_elements, 1);
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_postfix_iterator_gives_tokens_while_converting() {
+        // 2 * 3 + (
+        let tokens: Vec<Result<Token, String>> = vec![
+            Ok(Token::Number(2.0)),
+            Ok(Token::BinaryOperator(BinaryOperator::Multiply)),
+            Ok(Token::Number(3.0)),
+            Ok(Token::BinaryOperator(BinaryOperator::Plus)),
+            Ok(Token::LeftParenthesis),
+        ];
+
+        let mut postfix = Postfix::new(tokens.into_iter());
+
+        assert_eq!(postfix.next(), Some(Ok(Token::Number(2.0))));
+        assert_eq!(postfix.next(), Some(Ok(Token::Number(3.0))));
+        assert_eq!(
+            postfix.next(),
+            Some(Ok(Token::BinaryOperator(BinaryOperator::Multiply)))
+        );
+        assert_eq!(
+            postfix.next(),
+            Some(Err(String::from("Mismatched parenthesis")))
+        );
+        assert_eq!(postfix.next(), None);
+    }
+
+    #[test]
+    fn test_postfix_gives_only_postfix_tokens() {
+        let tokens: Vec<Token> = vec![
+            Token::LeftParenthesis,
+            Token::LeftParenthesis,
+            Token::Number(2.0),
+            Token::RightParenthesis,
+            Token::RightParenthesis,
+        ];
+
+        let tokens_postfix: Vec<Result<Token, String>> =
+            Postfix::new(tokens.into_iter().map(Ok)).collect();
+
+        assert_eq!(tokens_postfix, vec![Ok(Token::Number(2.0))]);
+    }
+
+    #[test]
+    fn test_postfix_stack_bounded_by_limits() {
+        let limits: Limits = Limits {
+            max_operator_depth: 3,
+            ..Limits::default()
+        };
+        let mut tokens: Vec<Token> = vec![Token::LeftParenthesis; 10];
+        tokens.push(Token::Number(1.0));
+
+        let mut postfix = Postfix::new(tokens.into_iter().map(Ok)).with_limits(limits);
+
+        assert_eq!(
+            postfix.next(),
+            Some(Err(String::from(
+                "Expression exceeds limit of 3 pending operators"
+            )))
+        );
+        assert_eq!(postfix.exceeded(), Some(Limit::OperatorDepth(3)));
+        assert_eq!(postfix.stack_operator.len(), 3);
+        assert!(!postfix.stack_operator.spilled());
+    }
+
+    #[test]
+    fn test_infix_to_postfix_conditional_operator() {
+        // a ? b : c ? d + 1 : e
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::Question,
+            Token::Variable(1),
+            Token::Colon,
+            Token::Variable(2),
+            Token::Question,
+            Token::Variable(3),
+            Token::BinaryOperator(BinaryOperator::Plus),
+            Token::Number(1.0),
+            Token::Colon,
+            Token::Variable(4),
+        ];
+
+        assert_eq!(
+            infix_to_postfix(tokens),
+            Ok(vec![
+                Token::Variable(0),
+                Token::Question,
+                Token::Variable(1),
+                Token::Colon,
+                Token::Variable(2),
+                Token::Question,
+                Token::Variable(3),
+                Token::Number(1.0),
+                Token::BinaryOperator(BinaryOperator::Plus),
+                Token::Colon,
+                Token::Variable(4),
+                Token::EndConditional,
+                Token::EndConditional,
+            ])
+        );
+
+        let tokens: Vec<Token> = vec![Token::Variable(0), Token::Question, Token::Number(1.0)];
+        assert_eq!(
+            infix_to_postfix(tokens),
+            Err(String::from("Missing colon of conditional operator"))
+        );
+    }
+
+    #[test]
+    fn test_infix_to_postfix_boolean_operators() {
+        // a || b && c
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::BinaryOperator(BinaryOperator::Or),
+            Token::Variable(1),
+            Token::BinaryOperator(BinaryOperator::And),
+            Token::Variable(2),
+        ];
+
+        assert_eq!(
+            infix_to_postfix(tokens),
+            Ok(vec![
+                Token::Variable(0),
+                Token::ShortCircuit(BinaryOperator::Or),
+                Token::Variable(1),
+                Token::ShortCircuit(BinaryOperator::And),
+                Token::Variable(2),
+                Token::BinaryOperator(BinaryOperator::And),
+                Token::BinaryOperator(BinaryOperator::Or),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_infix_to_postfix_if_function() {
+        // if(a, b, lerp(c, 0, 1))
+        let tokens: Vec<Token> = vec![
+            Token::Function(Function::If),
+            Token::LeftParenthesis,
+            Token::Variable(0),
+            Token::Comma,
+            Token::Variable(1),
+            Token::Comma,
+            Token::Function(Function::Lerp),
+            Token::LeftParenthesis,
+            Token::Variable(2),
+            Token::Comma,
+            Token::Number(0.0),
+            Token::Comma,
+            Token::Number(1.0),
+            Token::RightParenthesis,
+            Token::RightParenthesis,
+        ];
+
+        assert_eq!(
+            infix_to_postfix(tokens),
+            Ok(vec![
+                Token::Variable(0),
+                Token::Question,
+                Token::Variable(1),
+                Token::Colon,
+                Token::Variable(2),
+                Token::Number(0.0),
+                Token::Number(1.0),
+                Token::Function(Function::Lerp),
+                Token::EndConditional,
+            ])
+        );
+    }
 }
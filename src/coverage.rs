@@ -0,0 +1,349 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::{Ast, Node};
+use super::functions::Function;
+use super::operators::is_true;
+use super::trace::{trace, TraceNode};
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Branch of a conditional or piecewise construct, with number of inputs which went through it
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Branch {
+    /// Description of branch, like condition true or between 0 and 10
+    pub label: String,
+    /// Number of inputs which went through branch
+    pub hits: usize,
+}
+
+/// Conditional or piecewise construct of an expression, like select, clamp, abs or lookup function,
+/// with its branches
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Construct {
+    /// Subexpression of construct, like clamp(x, 0, 1)
+    pub expression: String,
+    /// Branches of construct
+    pub branches: Vec<Branch>,
+}
+
+/// Coverage of an expression by a set of inputs, giving branches of its constructs
+/// and functions exercised by inputs
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Coverage {
+    /// Number of evaluations of each function called by expression, sorted by name
+    pub functions: BTreeMap<String, usize>,
+    /// Conditional and piecewise constructs of expression, in order of writing
+    pub constructs: Vec<Construct>,
+    /// Indices of inputs for which evaluation failed, which are not counted
+    pub failed_inputs: Vec<usize>,
+}
+
+impl Coverage {
+    /// Get ratio of branches of constructs went through by at least one input, 1 if expression has no construct
+    pub fn ratio(&self) -> f64 {
+        let branches = self
+            .constructs
+            .iter()
+            .flat_map(|construct| construct.branches.iter());
+        let (nb_branches, nb_hit): (usize, usize) = branches.fold((0, 0), |(nb, hit), branch| {
+            (nb + 1, hit + usize::from(branch.hits > 0))
+        });
+
+        if nb_branches == 0 {
+            return 1.0;
+        }
+
+        return nb_hit as f64 / nb_branches as f64;
+    }
+
+    /// Get branches went through by no input, as subexpressions of their constructs with their labels
+    pub fn missed(&self) -> Vec<(&str, &str)> {
+        return self
+            .constructs
+            .iter()
+            .flat_map(|construct| {
+                construct
+                    .branches
+                    .iter()
+                    .filter(|branch| branch.hits == 0)
+                    .map(|branch| (construct.expression.as_str(), branch.label.as_str()))
+            })
+            .collect();
+    }
+}
+
+/// Coverage is written as a report listing functions, then constructs with hits of their branches
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, nb_calls) in self.functions.iter() {
+            writeln!(f, "function {name}: {nb_calls} calls")?;
+        }
+
+        for construct in self.constructs.iter() {
+            writeln!(f, "{}", construct.expression)?;
+
+            for branch in construct.branches.iter() {
+                let missed: &str = if branch.hits == 0 { " (missed)" } else { "" };
+                writeln!(f, "  {}: {}{missed}", branch.label, branch.hits)?;
+            }
+        }
+
+        return write!(f, "branch coverage: {:.1}%", 100.0 * self.ratio());
+    }
+}
+
+/// Get labels of branches of function given in argument, whose arguments are given in argument,
+/// or None if function is not a conditional or piecewise construct
+fn branch_labels(fun: Function, args: &[Node]) -> Option<Vec<String>> {
+    let labels: Vec<String> = match fun {
+        Function::Select => vec![
+            String::from("condition true"),
+            String::from("condition false"),
+        ],
+        Function::Clamp => vec![
+            String::from("below lower bound"),
+            String::from("within bounds"),
+            String::from("above upper bound"),
+        ],
+        Function::Abs => vec![String::from("negative"), String::from("not negative")],
+        _ if fun.is_lookup() => {
+            let breakpoints: &[f64] = match args.get(1) {
+                Some(Node::Array(breakpoints)) => breakpoints,
+                _ => return None,
+            };
+
+            let mut labels: Vec<String> = vec![format!("below {}", breakpoints.first()?)];
+            labels.extend(
+                breakpoints
+                    .windows(2)
+                    .map(|pair| format!("between {} and {}", pair[0], pair[1])),
+            );
+            labels.push(format!("above {}", breakpoints.last()?));
+            labels
+        }
+        _ => return None,
+    };
+
+    return Some(labels);
+}
+
+/// Get index of branch of function given in argument went through with values of its arguments,
+/// breakpoint arrays being given by arguments of syntax tree
+fn branch_index(fun: Function, args: &[Node], values: &[f64]) -> usize {
+    match fun {
+        Function::Select => usize::from(!is_true(values[0])),
+        Function::Clamp if values[0] < values[1] => 0,
+        Function::Clamp if values[0] > values[2] => 2,
+        Function::Clamp => 1,
+        Function::Abs => usize::from(values[0] >= 0.0),
+        _ => {
+            let breakpoints: &[f64] = match args.get(1) {
+                Some(Node::Array(breakpoints)) => breakpoints,
+                _ => return 0,
+            };
+
+            let x: f64 = values[0];
+
+            if x < breakpoints[0] {
+                return 0;
+            }
+
+            match breakpoints.windows(2).position(|pair| x <= pair[1]) {
+                Some(segment) => segment + 1,
+                None => breakpoints.len(),
+            }
+        }
+    }
+}
+
+/// Collect constructs and functions of node given in argument and its children, in order of writing
+fn collect(node: &Node, coverage: &mut Coverage) {
+    match node {
+        Node::Unary(_, operand) => collect(operand, coverage),
+        Node::Binary(_, left, right) => {
+            collect(left, coverage);
+            collect(right, coverage);
+        }
+        Node::Call(fun, args) => {
+            coverage.functions.insert(String::from(fun.name()), 0);
+
+            if let Some(labels) = branch_labels(*fun, args) {
+                coverage.constructs.push(Construct {
+                    expression: node.to_string(),
+                    branches: labels
+                        .into_iter()
+                        .map(|label| Branch { label, hits: 0 })
+                        .collect(),
+                });
+            }
+
+            args.iter().for_each(|arg| collect(arg, coverage));
+        }
+        Node::Tuple(elements) => elements
+            .iter()
+            .for_each(|element| collect(element, coverage)),
+        _ => (),
+    }
+}
+
+/// Record branches and functions went through by evaluation of node given in argument, whose trace is given.
+/// Constructs are numbered in order of writing like by collect function, from index given in argument
+fn record(node: &Node, trace: &TraceNode, coverage: &mut Coverage, next: &mut usize) {
+    match node {
+        Node::Unary(_, operand) => record(operand, &trace.children[0], coverage, next),
+        Node::Binary(_, left, right) => {
+            record(left, &trace.children[0], coverage, next);
+            record(right, &trace.children[1], coverage, next);
+        }
+        Node::Call(fun, args) => {
+            *coverage
+                .functions
+                .entry(String::from(fun.name()))
+                .or_default() += 1;
+
+            // Breakpoint arrays have no trace
+            let traced: Vec<&Node> = args
+                .iter()
+                .filter(|arg| !matches!(arg, Node::Array(_)))
+                .collect();
+
+            if branch_labels(*fun, args).is_some() {
+                let values: Vec<f64> = trace.children.iter().map(|child| child.value).collect();
+                let branch: usize = branch_index(*fun, args, &values);
+                coverage.constructs[*next].branches[branch].hits += 1;
+                *next += 1;
+            }
+
+            for (arg, child) in traced.into_iter().zip(trace.children.iter()) {
+                record(arg, child, coverage, next);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Evaluate an expression with each set of values of variables given in argument, to report which branches
+/// of its conditional and piecewise constructs and which functions were exercised, so test suites of
+/// business formulas can measure coverage of their logic. Constructs are select, clamp and abs functions,
+/// whose branches are the cases of their results, and lookup functions, whose branches are segments
+/// between breakpoints. Inputs whose evaluation fails are reported and not counted.
+///
+/// If expression is invalid, an error message is stored in string contained in Result output
+///
+/// # Example
+/// ```
+/// use taz::{coverage, Coverage};
+/// use std::collections::HashMap;
+///
+/// let inputs: Vec<HashMap<String, f64>> = vec![HashMap::from([(String::from("x"), 0.5)])];
+/// let coverage: Coverage = coverage("clamp(x, 0, 1)", &inputs).unwrap();
+///
+/// assert_eq!(coverage.missed(), vec![
+///     ("clamp(x, 0, 1)", "below lower bound"),
+///     ("clamp(x, 0, 1)", "above upper bound"),
+/// ]);
+/// ```
+pub fn coverage(expression: &str, inputs: &[HashMap<String, f64>]) -> Result<Coverage, String> {
+    let ast: Ast = Ast::new(expression)?;
+    let mut coverage: Coverage = Coverage::default();
+    collect(&ast.root, &mut coverage);
+
+    let outputs: Vec<&Node> = match &ast.root {
+        Node::Tuple(elements) => elements.iter().collect(),
+        root => vec![root],
+    };
+
+    for (index, variables) in inputs.iter().enumerate() {
+        let traced: Vec<TraceNode> = match trace(expression, variables) {
+            Ok(traced) => traced.outputs,
+            Err(_) => {
+                coverage.failed_inputs.push(index);
+                continue;
+            }
+        };
+
+        let mut next: usize = 0;
+
+        for (output, trace) in outputs.iter().zip(traced.iter()) {
+            record(output, trace, &mut coverage, &mut next);
+        }
+    }
+
+    return Ok(coverage);
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(values: &[f64]) -> Vec<HashMap<String, f64>> {
+        return values
+            .iter()
+            .map(|&value| HashMap::from([(String::from("x"), value)]))
+            .collect();
+    }
+
+    #[test]
+    fn test_coverage_branches() {
+        let expression: &str = "select(x > 1, sqrt(x), abs(x)) + lookup(x, [0, 1, 2], [0, 1, 4])";
+
+        match coverage(expression, &inputs(&[-1.0, 0.5, 4.0, 2.0])) {
+            Ok(coverage) => {
+                assert_eq!(coverage.constructs.len(), 3);
+                assert_eq!(
+                    coverage.constructs[0].expression,
+                    "select(x > 1, sqrt(x), abs(x))"
+                );
+
+                let hits = |index: usize| -> Vec<usize> {
+                    return coverage.constructs[index]
+                        .branches
+                        .iter()
+                        .map(|branch| branch.hits)
+                        .collect();
+                };
+
+                // sqrt(-1) fails, so the first input is not counted
+                assert_eq!(coverage.failed_inputs, vec![0]);
+                assert_eq!(hits(0), vec![2, 1]);
+                assert_eq!(hits(1), vec![0, 3]);
+                assert_eq!(hits(2), vec![0, 1, 1, 1]);
+                assert_eq!(coverage.functions.get("sqrt"), Some(&3));
+                assert_eq!(coverage.ratio(), 6.0 / 8.0);
+                assert_eq!(
+                    coverage.missed(),
+                    vec![
+                        ("abs(x)", "negative"),
+                        ("lookup(x, [0, 1, 2], [0, 1, 4])", "below 0")
+                    ]
+                );
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_coverage_report() {
+        match coverage("(clamp(x, 0, 1), x)", &inputs(&[2.0])) {
+            Ok(coverage) => assert_eq!(
+                coverage.to_string(),
+                "function clamp: 1 calls\n\
+                 clamp(x, 0, 1)\n  \
+                 below lower bound: 0 (missed)\n  \
+                 within bounds: 0 (missed)\n  \
+                 above upper bound: 1\n\
+                 branch coverage: 33.3%"
+            ),
+            Err(_) => assert!(false),
+        }
+
+        match coverage("2 * x", &inputs(&[])) {
+            Ok(coverage) => assert_eq!(coverage.ratio(), 1.0),
+            Err(_) => assert!(false),
+        }
+
+        assert!(coverage("2 *", &inputs(&[1.0])).is_err());
+    }
+}
@@ -0,0 +1,1006 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::Ast;
+use super::constants;
+use super::functions::Function;
+
+use std::collections::HashMap;
+
+/// Dialect of mathematical expressions, giving names of functions and constants and operators
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Dialect {
+    /// Syntax of Taz, like sqrt(x)^2 * pi
+    Taz,
+    /// Syntax of spreadsheet formulas, like =SQRT(A1)^2 * PI()
+    Excel,
+    /// Syntax of Python with math module, like math.sqrt(x)**2 * math.pi
+    Python,
+}
+
+/// Names of functions and constants in Excel dialect, associated to their names in Taz
+const EXCEL_NAMES: [(&str, &str); 31] = [
+    ("ABS", "abs"),
+    ("SQRT", "sqrt"),
+    ("EXP", "exp"),
+    ("LN", "ln"),
+    ("LOG10", "log10"),
+    ("SIN", "sin"),
+    ("COS", "cos"),
+    ("TAN", "tan"),
+    ("ASIN", "asin"),
+    ("ACOS", "acos"),
+    ("ATAN", "atan"),
+    ("SINH", "sinh"),
+    ("COSH", "cosh"),
+    ("TANH", "tanh"),
+    ("ASINH", "asinh"),
+    ("ACOSH", "acosh"),
+    ("ATANH", "atanh"),
+    ("GCD", "gcd"),
+    ("LCM", "lcm"),
+    ("COMBIN", "ncr"),
+    ("PERMUT", "npr"),
+    ("RADIANS", "deg2rad"),
+    ("DEGREES", "rad2deg"),
+    ("ROUND", "round"),
+    ("SUM", "sum"),
+    ("AVERAGE", "mean"),
+    ("MEDIAN", "median"),
+    ("MIN", "min"),
+    ("MAX", "max"),
+    ("PI()", "pi"),
+    ("EXP(1)", "e"),
+];
+
+/// Names of functions and constants in Python dialect, associated to their names in Taz
+const PYTHON_NAMES: [(&str, &str); 29] = [
+    ("abs", "abs"),
+    ("math.sqrt", "sqrt"),
+    ("math.cbrt", "cbrt"),
+    ("math.exp", "exp"),
+    ("math.log", "ln"),
+    ("math.log10", "log10"),
+    ("math.log2", "log2"),
+    ("math.sin", "sin"),
+    ("math.cos", "cos"),
+    ("math.tan", "tan"),
+    ("math.asin", "asin"),
+    ("math.acos", "acos"),
+    ("math.atan", "atan"),
+    ("math.sinh", "sinh"),
+    ("math.cosh", "cosh"),
+    ("math.tanh", "tanh"),
+    ("math.asinh", "asinh"),
+    ("math.acosh", "acosh"),
+    ("math.atanh", "atanh"),
+    ("math.gcd", "gcd"),
+    ("math.lcm", "lcm"),
+    ("math.comb", "ncr"),
+    ("math.perm", "npr"),
+    ("math.radians", "deg2rad"),
+    ("math.degrees", "rad2deg"),
+    ("min", "min"),
+    ("max", "max"),
+    ("math.pi", "pi"),
+    ("math.e", "e"),
+];
+
+/// Lexical element of an expression, enough to translate names and operators between dialects
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Lexeme<'a> {
+    /// Name of function, constant or variable, which can be dotted like math.sqrt
+    Name(&'a str),
+    /// Number, like 1.5e-3
+    Number(&'a str),
+    /// Any other character, like an operator or a parenthesis
+    Symbol(char),
+}
+
+/// Split expression given in argument in lexical elements, whitespaces being kept as symbols
+fn lex(expression: &str) -> Vec<Lexeme<'_>> {
+    let bytes: &[u8] = expression.as_bytes();
+    let mut lexemes: Vec<Lexeme> = Vec::new();
+    let mut index: usize = 0;
+
+    while index < bytes.len() {
+        let start: usize = index;
+        let byte: u8 = bytes[index];
+
+        if byte.is_ascii_alphabetic() || byte == b'_' {
+            while index < bytes.len()
+                && (bytes[index].is_ascii_alphanumeric() || matches!(bytes[index], b'_' | b'.'))
+            {
+                index += 1;
+            }
+
+            lexemes.push(Lexeme::Name(&expression[start..index]));
+        } else if byte.is_ascii_digit() || byte == b'.' {
+            while index < bytes.len() && (bytes[index].is_ascii_digit() || bytes[index] == b'.') {
+                index += 1;
+            }
+
+            // Exponent is part of number only if digits follow, like 1e5 or 2E-3
+            if index < bytes.len() && matches!(bytes[index], b'e' | b'E') {
+                let mut end: usize = index + 1;
+
+                if end < bytes.len() && matches!(bytes[end], b'+' | b'-') {
+                    end += 1;
+                }
+
+                if end < bytes.len() && bytes[end].is_ascii_digit() {
+                    index = end;
+
+                    while index < bytes.len() && bytes[index].is_ascii_digit() {
+                        index += 1;
+                    }
+                }
+            }
+
+            lexemes.push(Lexeme::Number(&expression[start..index]));
+        } else {
+            let character: char = expression[start..].chars().next().unwrap_or(' ');
+            index += character.len_utf8();
+            lexemes.push(Lexeme::Symbol(character));
+        }
+    }
+
+    return lexemes;
+}
+
+/// Get index of lexeme following operand starting at index given in argument,
+/// an operand being a number, a name possibly called with arguments, or an expression in parenthesis
+fn skip_operand(lexemes: &[Lexeme], index: usize) -> usize {
+    let mut index: usize = index;
+
+    if let Some(Lexeme::Name(_) | Lexeme::Number(_)) = lexemes.get(index) {
+        index += 1;
+
+        if lexemes.get(index) != Some(&Lexeme::Symbol('(')) {
+            return index;
+        }
+    }
+
+    let mut depth: usize = 0;
+
+    while let Some(&lexeme) = lexemes.get(index) {
+        index += 1;
+
+        match lexeme {
+            Lexeme::Symbol('(') => depth += 1,
+            Lexeme::Symbol(')') => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+
+        if depth == 0 {
+            break;
+        }
+    }
+
+    return index;
+}
+
+/// Get index of lexeme following operand starting at index given in argument and the powers applied to it,
+/// like x**2**y in Python
+fn skip_power(lexemes: &[Lexeme], index: usize) -> usize {
+    let mut end: usize = skip_operand(lexemes, index);
+
+    while lexemes.get(end) == Some(&Lexeme::Symbol('*'))
+        && lexemes.get(end + 1) == Some(&Lexeme::Symbol('*'))
+    {
+        let mut exponent: usize = end + 2;
+
+        while matches!(lexemes.get(exponent), Some(Lexeme::Symbol('-' | '+'))) {
+            exponent += 1;
+        }
+
+        end = skip_operand(lexemes, exponent);
+    }
+
+    return end;
+}
+
+/// Translation of a spreadsheet formula to Taz syntax, with offset in formula of each byte of translation,
+/// so errors found while evaluating translation are reported at their position in formula
+#[derive(Default)]
+struct Translation {
+    text: String,
+    offsets: Vec<usize>,
+}
+
+impl Translation {
+    /// Create translation of text given in argument, coming from formula at offset given in argument
+    fn new(text: &str, offset: usize) -> Translation {
+        let mut translation: Translation = Translation::default();
+        translation.push(text, offset);
+
+        return translation;
+    }
+
+    /// Append text given in argument, coming from formula at offset given in argument
+    fn push(&mut self, text: &str, offset: usize) {
+        self.text.push_str(text);
+        self.offsets.resize(self.text.len(), offset);
+    }
+
+    /// Append translation given in argument
+    fn append(&mut self, other: Translation) {
+        self.text.push_str(&other.text);
+        self.offsets.extend(other.offsets);
+    }
+
+    /// Get offset in formula of first byte of translation
+    fn start(&self) -> usize {
+        return self.offsets.first().copied().unwrap_or_default();
+    }
+
+    /// Replace position ending error message given in argument, which is an offset in translation,
+    /// by offset in formula whose length is given in argument
+    fn locate(&self, message: String, length: usize) -> String {
+        let (text, position): (&str, &str) = match message.rsplit_once(" at position ") {
+            Some(split) => split,
+            None => return message,
+        };
+
+        return match position.parse::<usize>() {
+            Ok(position) => {
+                let offset: usize = self.offsets.get(position).copied().unwrap_or(length);
+                format!("{text} at position {offset}")
+            }
+            Err(_) => message,
+        };
+    }
+}
+
+/// Parser of spreadsheet formulas, translating them to Taz syntax with precedence of spreadsheets:
+/// negation binds tighter than power, which is left-associative, so -2^2 gives 4 and 2^3^2 gives 64
+struct FormulaParser<'a> {
+    lexemes: Vec<Lexeme<'a>>,
+    /// Offset in formula of each lexeme
+    offsets: Vec<usize>,
+    /// Length of formula
+    length: usize,
+    index: usize,
+}
+
+impl<'a> FormulaParser<'a> {
+    /// Consume next lexeme if it is symbol given in argument
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.lexemes.get(self.index) == Some(&Lexeme::Symbol(symbol)) {
+            self.index += 1;
+            return true;
+        }
+
+        return false;
+    }
+
+    /// Get offset in formula of next lexeme, or length of formula if all lexemes are consumed
+    fn offset(&self) -> usize {
+        return self.offsets.get(self.index).copied().unwrap_or(self.length);
+    }
+
+    /// Give error about next lexeme, which is not expected
+    fn unexpected(&self) -> String {
+        match self.lexemes.get(self.index) {
+            Some(Lexeme::Name(text) | Lexeme::Number(text)) => {
+                format!("Unexpected {text} in formula")
+            }
+            Some(Lexeme::Symbol(symbol)) => format!("Unexpected {symbol} in formula"),
+            None => String::from("Unexpected end of formula"),
+        }
+    }
+
+    /// Enclose in parenthesis operation applying operator given in argument, read at offset given in argument,
+    /// to operands given in argument. Closing parenthesis is located at next lexeme,
+    /// where evaluation applies operation
+    fn operation(
+        &self,
+        left: Translation,
+        operator: &str,
+        offset: usize,
+        right: Translation,
+    ) -> Translation {
+        let mut translated: Translation = Translation::new("(", left.start());
+        translated.append(left);
+        translated.push(&format!(" {operator} "), offset);
+        translated.append(right);
+        translated.push(")", self.offset());
+
+        return translated;
+    }
+
+    /// Translate comparison, like A1 >= B1, or value without comparison.
+    /// A comparison gives 1 when it holds and 0 otherwise, like in spreadsheets
+    fn comparison(&mut self) -> Result<Translation, String> {
+        let left: Translation = self.additive()?;
+        let offset: usize = self.offset();
+
+        let operator: &str = if self.eat('=') {
+            "=="
+        } else if self.eat('<') {
+            match (self.eat('>'), self.eat('=')) {
+                (true, _) => "!=",
+                (_, true) => "<=",
+                _ => "<",
+            }
+        } else if self.eat('>') {
+            match self.eat('=') {
+                true => ">=",
+                false => ">",
+            }
+        } else {
+            return Ok(left);
+        };
+
+        let right: Translation = self.additive()?;
+        return Ok(self.operation(left, operator, offset, right));
+    }
+
+    /// Translate operands separated by operators given in argument, each operand being translated by method given
+    fn operations<F>(&mut self, operators: &[char], operand: F) -> Result<Translation, String>
+    where
+        F: Fn(&mut FormulaParser<'a>) -> Result<Translation, String>,
+    {
+        let mut translated: Translation = operand(self)?;
+        let mut offset: usize = self.offset();
+
+        while let Some(&operator) = operators.iter().find(|&&operator| self.eat(operator)) {
+            let right: Translation = operand(self)?;
+            translated = self.operation(translated, &operator.to_string(), offset, right);
+            offset = self.offset();
+        }
+
+        return Ok(translated);
+    }
+
+    /// Translate sum or difference of products
+    fn additive(&mut self) -> Result<Translation, String> {
+        return self.operations(&['+', '-'], |parser| parser.multiplicative());
+    }
+
+    /// Translate product or quotient of powers
+    fn multiplicative(&mut self) -> Result<Translation, String> {
+        return self.operations(&['*', '/'], |parser| parser.power());
+    }
+
+    /// Translate powers, from left to right
+    fn power(&mut self) -> Result<Translation, String> {
+        return self.operations(&['^'], |parser| parser.unary());
+    }
+
+    /// Translate negation or percentage of an operand
+    fn unary(&mut self) -> Result<Translation, String> {
+        let offset: usize = self.offset();
+
+        if self.eat('-') {
+            let mut translated: Translation = Translation::new("(-", offset);
+            translated.append(self.unary()?);
+            translated.push(")", self.offset());
+
+            return Ok(translated);
+        }
+
+        if self.eat('+') {
+            return self.unary();
+        }
+
+        let mut translated: Translation = self.primary()?;
+        let mut offset: usize = self.offset();
+
+        while self.eat('%') {
+            let mut percentage: Translation = Translation::new("(", translated.start());
+            percentage.append(translated);
+            percentage.push(" / 100", offset);
+            percentage.push(")", self.offset());
+
+            translated = percentage;
+            offset = self.offset();
+        }
+
+        return Ok(translated);
+    }
+
+    /// Translate number, reference to a cell, function call or formula in parenthesis
+    fn primary(&mut self) -> Result<Translation, String> {
+        let lexeme: Option<Lexeme> = self.lexemes.get(self.index).copied();
+        let offset: usize = self.offset();
+
+        match lexeme {
+            Some(Lexeme::Number(number)) => {
+                self.index += 1;
+
+                // Numbers are written without exponent, which Taz does not read
+                match number.parse::<f64>() {
+                    Ok(value) => Ok(Translation::new(&value.to_string(), offset)),
+                    Err(_) => Err(format!("Invalid number {number} in formula")),
+                }
+            }
+            Some(Lexeme::Symbol('(')) => {
+                self.index += 1;
+                let translated: Translation = self.comparison()?;
+
+                match self.eat(')') {
+                    true => Ok(translated),
+                    false => Err(self.unexpected()),
+                }
+            }
+            Some(Lexeme::Name(name)) => {
+                self.index += 1;
+
+                if self.eat('(') {
+                    return self.call(name, offset);
+                }
+
+                if self.lexemes.get(self.index) == Some(&Lexeme::Symbol(':')) {
+                    return Err(String::from(
+                        "Ranges of cells are not supported in formulas",
+                    ));
+                }
+
+                match name.to_uppercase().as_str() {
+                    "TRUE" => Ok(Translation::new("1", offset)),
+                    "FALSE" => Ok(Translation::new("0", offset)),
+                    _ => Ok(Translation::new(name, offset)),
+                }
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// Translate call of function whose name, read at offset given in argument, is given in argument,
+    /// after its opening parenthesis. Arguments are separated by semicolons or commas
+    fn call(&mut self, name: &str, offset: usize) -> Result<Translation, String> {
+        let mut args: Vec<Translation> = Vec::new();
+
+        if !self.eat(')') {
+            loop {
+                args.push(self.comparison()?);
+
+                if self.eat(')') {
+                    break;
+                }
+
+                if !self.eat(';') && !self.eat(',') {
+                    return Err(self.unexpected());
+                }
+            }
+        }
+
+        // Names of functions are case-insensitive, like in spreadsheets
+        let name: String = name.to_uppercase();
+        let nb_args: usize = args.len();
+
+        // Call is translated to function given applied to its arguments separated by separator given,
+        // a separator being located at the argument following it
+        let translate = |function: &str, separator: &str, args: Vec<Translation>| {
+            let mut translated: Translation = Translation::new(function, offset);
+            translated.push("(", offset);
+
+            for (index, arg) in args.into_iter().enumerate() {
+                if index > 0 {
+                    translated.push(separator, arg.start());
+                }
+
+                translated.append(arg);
+            }
+
+            translated.push(")", offset);
+            return translated;
+        };
+
+        match name.as_str() {
+            // Like in spreadsheets, a condition holds when it is not zero,
+            // and only the value chosen is evaluated
+            "IF" if nb_args == 2 || nb_args == 3 => {
+                if nb_args == 2 {
+                    args.push(Translation::new("0", self.offset()));
+                }
+
+                Ok(translate("if", ", ", args))
+            }
+            "IF" => Err(format!(
+                "Function IF expects 2 or 3 arguments but got {nb_args}"
+            )),
+            "SUM" if nb_args > 0 => Ok(translate("", " + ", args)),
+            "SUM" => Err(String::from("Function SUM expects at least 1 argument")),
+            "POWER" if nb_args == 2 => Ok(translate("", "^", args)),
+            "POWER" => Err(format!(
+                "Function POWER expects 2 arguments but got {nb_args}"
+            )),
+            "TRUE" if nb_args == 0 => Ok(Translation::new("1", offset)),
+            "FALSE" if nb_args == 0 => Ok(Translation::new("0", offset)),
+            _ => {
+                // Constants of spreadsheets are functions without arguments, like PI()
+                let called: String = match nb_args {
+                    0 => format!("{name}()"),
+                    _ => name.clone(),
+                };
+
+                let taz_name: &str = match EXCEL_NAMES.iter().find(|&&(other, _)| other == called) {
+                    Some(&(_, taz_name)) => taz_name,
+                    None => {
+                        return Err(format!("Function {name} has no equivalent in Taz dialect"));
+                    }
+                };
+
+                if nb_args == 0 {
+                    return Ok(Translation::new(taz_name, offset));
+                }
+
+                Ok(translate(taz_name, ", ", args))
+            }
+        }
+    }
+}
+
+/// Translate spreadsheet formula given in argument, like =IF(A1 > 0; SUM(A1; B1); 0), to Taz syntax.
+/// If formula is not valid or uses a function without equivalent in Taz,
+/// an error message is stored in string contained in Result output
+fn read_formula(formula: &str) -> Result<Translation, String> {
+    let start: usize = formula.len() - formula.trim_start().len();
+    let start: usize = start + usize::from(formula[start..].starts_with('='));
+
+    // Absolute references like $A$1 are the same cells as A1,
+    // offset in formula of each byte being kept to locate lexemes
+    let mut stripped: String = String::with_capacity(formula.len());
+    let mut origins: Vec<usize> = Vec::with_capacity(formula.len());
+
+    for (offset, character) in formula[start..].char_indices() {
+        if character != '$' {
+            stripped.push(character);
+            origins.resize(stripped.len(), start + offset);
+        }
+    }
+
+    let mut lexemes: Vec<Lexeme> = Vec::new();
+    let mut offsets: Vec<usize> = Vec::new();
+    let mut position: usize = 0;
+
+    for lexeme in lex(&stripped) {
+        let offset: usize = origins[position];
+
+        position += match lexeme {
+            Lexeme::Name(text) | Lexeme::Number(text) => text.len(),
+            Lexeme::Symbol(symbol) => symbol.len_utf8(),
+        };
+
+        if !matches!(lexeme, Lexeme::Symbol(symbol) if symbol.is_whitespace()) {
+            lexemes.push(lexeme);
+            offsets.push(offset);
+        }
+    }
+
+    let mut parser: FormulaParser = FormulaParser {
+        lexemes,
+        offsets,
+        length: formula.len(),
+        index: 0,
+    };
+    let translated: Translation = parser.comparison()?;
+
+    match parser.index == parser.lexemes.len() {
+        true => Ok(translated),
+        false => Err(parser.unexpected()),
+    }
+}
+
+impl Dialect {
+    /// Get names of functions and constants of dialect, associated to their names in Taz
+    fn names(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Dialect::Taz => &[],
+            Dialect::Excel => &EXCEL_NAMES,
+            Dialect::Python => &PYTHON_NAMES,
+        }
+    }
+
+    /// Get name of dialect, like Excel
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dialect::Taz => "Taz",
+            Dialect::Excel => "Excel",
+            Dialect::Python => "Python",
+        }
+    }
+
+    /// Translate expression given in argument, written in this dialect, to Taz syntax.
+    /// Names which are not functions nor constants of dialect are kept as variables.
+    /// If expression cannot be translated, an error message is stored in string contained in Result output
+    fn read(&self, expression: &str) -> Result<String, String> {
+        if *self == Dialect::Excel {
+            return read_formula(expression).map(|translation| translation.text);
+        }
+
+        let lexemes: Vec<Lexeme> = lex(expression)
+            .into_iter()
+            .filter(|lexeme| !matches!(lexeme, Lexeme::Symbol(symbol) if symbol.is_whitespace()))
+            .collect();
+
+        let mut translated: Vec<String> = Vec::with_capacity(lexemes.len());
+        let mut closings: Vec<(usize, String)> = Vec::new();
+        let mut index: usize = 0;
+
+        loop {
+            // Parenthesis enclosing negations are closed after their operand
+            while closings.last().is_some_and(|&(end, _)| end == index) {
+                if let Some((_, closing)) = closings.pop() {
+                    translated.push(closing);
+                }
+            }
+
+            let lexeme: Lexeme = match lexemes.get(index) {
+                Some(&lexeme) => lexeme,
+                None => break,
+            };
+
+            index += 1;
+
+            match lexeme {
+                Lexeme::Name(name) => {
+                    match self.names().iter().find(|&&(other, _)| other == name) {
+                        Some(&(_, taz_name)) => translated.push(String::from(taz_name)),
+                        // Python names its natural logarithm log
+                        None if *self == Dialect::Python && name == "log" => {
+                            translated.push(String::from("ln"))
+                        }
+                        None => translated.push(String::from(name)),
+                    }
+                }
+                Lexeme::Number(number) => translated.push(String::from(number)),
+                Lexeme::Symbol('*')
+                    if *self == Dialect::Python
+                        && lexemes.get(index) == Some(&Lexeme::Symbol('*')) =>
+                {
+                    index += 1;
+                    translated.push(String::from("^"));
+                }
+                Lexeme::Symbol('-') if *self != Dialect::Taz => {
+                    let last: Option<&str> = translated.last().map(|last| last.as_str());
+                    let after_operator: bool = matches!(last, Some("+" | "-" | "*" | "/" | "^"));
+
+                    if !after_operator && !matches!(last, None | Some("(" | ",")) {
+                        translated.push(String::from("-"));
+                        continue;
+                    }
+
+                    // Negation following an operator is enclosed in parenthesis, like 2 * (-x),
+                    // and power binds tighter than negation in Python, unlike in Taz
+                    let operand_end: usize = skip_operand(&lexemes, index);
+                    let end: usize = skip_power(&lexemes, index);
+
+                    let mut opening: String = String::new();
+                    let mut closing: String = String::new();
+
+                    if after_operator {
+                        opening.push('(');
+                        closing.push(')');
+                    }
+
+                    opening.push('-');
+
+                    if end > operand_end {
+                        opening.push('(');
+                        closing.push(')');
+                    }
+
+                    translated.push(opening);
+
+                    if !closing.is_empty() {
+                        closings.push((end, closing));
+                    }
+                }
+                Lexeme::Symbol(symbol) => translated.push(symbol.to_string()),
+            }
+        }
+
+        return Ok(translated.join(" "));
+    }
+
+    /// Translate expression given in argument, written in Taz syntax, to this dialect.
+    /// If a function or a constant has no equivalent in dialect,
+    /// an error message is stored in string contained in Result output
+    fn write(&self, expression: &str) -> Result<String, String> {
+        if *self == Dialect::Taz {
+            return Ok(String::from(expression));
+        }
+
+        let lexemes: Vec<Lexeme> = lex(expression);
+        let mut translated: String = String::with_capacity(expression.len());
+        let mut closings: Vec<usize> = Vec::new();
+
+        for (index, &lexeme) in lexemes.iter().enumerate() {
+            while closings.last() == Some(&index) {
+                closings.pop();
+                translated.push(')');
+            }
+
+            match lexeme {
+                Lexeme::Name(name) => {
+                    let is_call: bool = lexemes.get(index + 1) == Some(&Lexeme::Symbol('('));
+                    let kind: Option<&str> = if is_call && Function::is_fun(name) {
+                        Some("Function")
+                    } else if !is_call && constants::is_constant(name) {
+                        Some("Constant")
+                    } else {
+                        None
+                    };
+
+                    match kind {
+                        Some(kind) => {
+                            match self.names().iter().find(|&&(_, other)| other == name) {
+                                Some(&(dialect_name, _)) => translated.push_str(dialect_name),
+                                None => {
+                                    return Err(format!(
+                                        "{kind} {name} has no equivalent in {} dialect",
+                                        self.name()
+                                    ));
+                                }
+                            }
+                        }
+                        None => translated.push_str(name),
+                    }
+                }
+                Lexeme::Number(number) => translated.push_str(number),
+                Lexeme::Symbol('^') if *self == Dialect::Python => translated.push_str("**"),
+                Lexeme::Symbol('^') => {
+                    translated.push('^');
+
+                    // Powers are left-associative in spreadsheets, so a power as exponent is enclosed
+                    // in parenthesis, like 2^(3^2)
+                    let mut end: usize = skip_operand(&lexemes, index + 1);
+
+                    if lexemes.get(end) == Some(&Lexeme::Symbol('^')) {
+                        while lexemes.get(end) == Some(&Lexeme::Symbol('^')) {
+                            end = skip_operand(&lexemes, end + 1);
+                        }
+
+                        translated.push('(');
+                        closings.push(end);
+                    }
+                }
+                Lexeme::Symbol('[') => {
+                    return Err(format!(
+                        "Arrays have no equivalent in {} dialect",
+                        self.name()
+                    ));
+                }
+                Lexeme::Symbol(symbol) => translated.push(symbol),
+            }
+        }
+
+        translated.extend(closings.iter().map(|_| ')'));
+
+        match self {
+            Dialect::Excel => Ok(format!("={translated}")),
+            _ => Ok(translated),
+        }
+    }
+}
+
+/// Translate expression given in argument from a dialect to another, like from a spreadsheet formula
+/// to Taz syntax or from Taz syntax to Python. Expression is parsed, so it is checked and written
+/// in the canonical layout of syntax trees, with parenthesis only where they are needed.
+/// A Python expression like -x**2 keeps its meaning, power binding tighter than negation in Python.
+///
+/// If expression is invalid or if a function or a constant has no equivalent in target dialect,
+/// an error message is stored in string contained in Result output.
+///
+/// # Example
+/// ```
+/// use taz::{transpile, Dialect};
+///
+/// assert_eq!(
+///     transpile("=SQRT(A1^2 + B1^2) * PI()", Dialect::Excel, Dialect::Taz),
+///     Ok(String::from("sqrt(A1^2 + B1^2) * pi"))
+/// );
+/// assert_eq!(
+///     transpile("sqrt(x^2 + 1) * e", Dialect::Taz, Dialect::Python),
+///     Ok(String::from("math.sqrt(x**2 + 1) * math.e"))
+/// );
+/// ```
+pub fn transpile(expression: &str, from: Dialect, to: Dialect) -> Result<String, String> {
+    let ast: Ast = Ast::new(&from.read(expression)?)?;
+    return to.write(&ast.root.to_string());
+}
+
+/// Evaluate a spreadsheet formula pasted as is, like =IF(A1 > 0; SUM(A1; B1); POWER(2; -A1)),
+/// values of cells and names being given by hash map in argument.
+/// Formula follows conventions of spreadsheets: names of functions are case-insensitive, arguments are
+/// separated by semicolons or commas, negation binds tighter than power, which is left-associative,
+/// and comparisons give 1 when they hold and 0 otherwise. Only the value chosen by IF is evaluated,
+/// so IF(B1 = 0; 0; A1 / B1) gives 0 when B1 is zero.
+///
+/// If formula is invalid or if error occurs during evaluation,
+/// an error message is stored in string contained in Result output,
+/// with position of error in formula given in argument
+///
+/// # Example
+/// ```
+/// use taz::evaluate_spreadsheet;
+/// use std::collections::HashMap;
+///
+/// let cells: HashMap<String, f64> = HashMap::from([(String::from("A1"), 3.0)]);
+///
+/// assert_eq!(evaluate_spreadsheet("=IF(A1 >= 2; -A1^2; 0)", &cells), Ok(9.0));
+/// ```
+pub fn evaluate_spreadsheet(
+    formula: &str,
+    variables: &HashMap<String, f64>,
+) -> Result<f64, String> {
+    let translation: Translation = read_formula(formula)?;
+
+    return super::evaluate(&translation.text, variables)
+        .map_err(|message| translation.locate(message, formula.len()));
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transpile_from_excel() {
+        assert_eq!(
+            transpile(
+                "=ROUND(COMBIN(n, 2) * exp(1), 2)",
+                Dialect::Excel,
+                Dialect::Taz
+            ),
+            Ok(String::from("round(ncr(n, 2) * exp(1), 2)"))
+        );
+        assert_eq!(
+            transpile("=-A1^2 / PI ()", Dialect::Excel, Dialect::Python),
+            Ok(String::from("(-A1)**2 / math.pi"))
+        );
+        assert_eq!(
+            transpile("=2*-A1", Dialect::Excel, Dialect::Taz),
+            Ok(String::from("2 * (-A1)"))
+        );
+        assert_eq!(
+            transpile("=MIN(A1; MAX(B1; 2))", Dialect::Excel, Dialect::Python),
+            Ok(String::from("min(A1, max(B1, 2))"))
+        );
+        assert_eq!(
+            transpile("=AVERAGE(A1; MAX(B1; 2))", Dialect::Excel, Dialect::Taz),
+            Ok(String::from("mean(A1, max(B1, 2))"))
+        );
+        assert!(transpile("=VLOOKUP(A1, B1, 2)", Dialect::Excel, Dialect::Taz).is_err());
+    }
+
+    #[test]
+    fn test_transpile_excel_powers() {
+        assert_eq!(
+            transpile("=2^3^2 + -x^2", Dialect::Excel, Dialect::Taz),
+            Ok(String::from("(2^3)^2 + (-x)^2"))
+        );
+        assert_eq!(
+            transpile("x^y^z^w", Dialect::Taz, Dialect::Excel),
+            Ok(String::from("=x^(y^(z^w))"))
+        );
+        assert_eq!(
+            transpile("=POWER(2; 1.5E3)", Dialect::Excel, Dialect::Taz),
+            Ok(String::from("2^1500"))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_spreadsheet() {
+        let cells: HashMap<String, f64> = HashMap::from([
+            (String::from("A1"), 3.0),
+            (String::from("B1"), -1.0),
+            (String::from("rate"), 0.5),
+        ]);
+
+        for (formula, value) in [
+            ("=SUM(A1; B1; 2) * rate", 2.0),
+            ("=sum(A1, 1) - 1", 3.0),
+            ("=If(A1 > B1; 10; 20)", 10.0),
+            ("=IF(A1 <= B1; 10; 20)", 20.0),
+            ("=IF($A$1 = 3; 10)", 10.0),
+            ("=IF(A1 <> 3; 10)", 0.0),
+            ("=IF(B1; 1; 2) + IF(A1 - 3; 1; 2)", 3.0),
+            ("=(A1 >= 3) + TRUE", 2.0),
+            ("=-2^2 + 2^3^2", 68.0),
+            ("=50% * PI() / pi()", 0.5),
+            ("=Power(2; 3) + average(1; 2) * True()", 9.5),
+            ("=IF(B1 + 1 = 0; 0; A1 / (B1 + 1))", 0.0),
+            ("=IF(A1; 1; 1 / 0)", 1.0),
+        ] {
+            match evaluate_spreadsheet(formula, &cells) {
+                Ok(result) => assert_eq!(result, value),
+                Err(_) => assert!(false),
+            }
+        }
+    }
+
+    #[test]
+    fn test_evaluate_spreadsheet_errors() {
+        let cells: HashMap<String, f64> = HashMap::from([(String::from("A1"), 3.0)]);
+
+        for (formula, message) in [
+            (
+                "=SUM(A1:A3)",
+                "Ranges of cells are not supported in formulas",
+            ),
+            ("=IF(A1)", "Function IF expects 2 or 3 arguments but got 1"),
+            ("=POWER(A1)", "Function POWER expects 2 arguments but got 1"),
+            (
+                "=VLOOKUP(A1; 2)",
+                "Function VLOOKUP has no equivalent in Taz dialect",
+            ),
+            ("=SUM(A1; 2", "Unexpected end of formula"),
+            ("=A1 2", "Unexpected 2 in formula"),
+            // Errors of evaluation are located in formula
+            ("=1/0", "Division by zero at position 4"),
+            ("=IF(0;1;1/0)", "Division by zero at position 11"),
+            ("= 2 * $A$1 / C2", "Unknown name C2 at position 13"),
+            (
+                "=LN(A1 - 3) + 1",
+                "Argument of ln function is negative or null at position 1",
+            ),
+        ] {
+            assert_eq!(
+                evaluate_spreadsheet(formula, &cells),
+                Err(String::from(message))
+            );
+        }
+    }
+
+    #[test]
+    fn test_transpile_from_python() {
+        assert_eq!(
+            transpile(
+                "math.log(x) ** 2 + math.comb(n, k)",
+                Dialect::Python,
+                Dialect::Taz
+            ),
+            Ok(String::from("ln(x)^2 + ncr(n, k)"))
+        );
+        assert_eq!(
+            transpile("2 ** 3 ** 2 * 0.0015", Dialect::Python, Dialect::Excel),
+            Ok(String::from("=2^(3^2) * 0.0015"))
+        );
+    }
+
+    #[test]
+    fn test_transpile_keeps_precedence_of_negation() {
+        // In Python, -x**2 is -(x**2) while (-x)**2 is x squared
+        for (python, taz) in [
+            ("-x**2", "-(x^2)"),
+            ("(-x)**2", "(-x)^2"),
+            ("1 - x**2", "1 - x^2"),
+            ("y * -math.sin(x)**2 ** 3", "y * (-(sin(x)^2^3))"),
+            ("2 ** -x", "2^(-x)"),
+        ] {
+            assert_eq!(
+                transpile(python, Dialect::Python, Dialect::Taz),
+                Ok(String::from(taz))
+            );
+        }
+    }
+
+    #[test]
+    fn test_transpile_without_equivalent() {
+        assert_eq!(
+            transpile(
+                "lookup(x, [0, 1], [0, 2]) + 1",
+                Dialect::Taz,
+                Dialect::Python
+            ),
+            Err(String::from(
+                "Function lookup has no equivalent in Python dialect"
+            ))
+        );
+        assert_eq!(
+            transpile("c * t", Dialect::Taz, Dialect::Excel),
+            Err(String::from(
+                "Constant c has no equivalent in Excel dialect"
+            ))
+        );
+        assert_eq!(
+            transpile("cbrt(x)", Dialect::Taz, Dialect::Excel),
+            Err(String::from(
+                "Function cbrt has no equivalent in Excel dialect"
+            ))
+        );
+    }
+}
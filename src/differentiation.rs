@@ -0,0 +1,568 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::{substitute, Node};
+use super::compiler::{CompiledExpression, Instruction};
+use super::functions::Function;
+use super::operators::{BinaryOperator, UnaryOperator};
+
+/// Maximal order of Taylor polynomials, since derivatives grow with each differentiation
+pub(crate) const MAX_TAYLOR_ORDER: usize = 20;
+
+/// Get value of node given in argument if it is a number or a constant
+fn number(node: &Node) -> Option<f64> {
+    match node {
+        Node::Number(value) | Node::Constant(_, value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Create node negating node given in argument, a number being negated directly
+fn negate(node: Node) -> Node {
+    match node {
+        Node::Number(value) => Node::Number(-value),
+        Node::Unary(UnaryOperator::Minus, operand) => *operand,
+        _ => Node::Unary(UnaryOperator::Minus, Box::new(node)),
+    }
+}
+
+/// Create node applying binary operation on two nodes given in argument.
+/// Operation on two numbers is computed, and operation with 0 or 1 as neutral or absorbing element is simplified
+fn binary(ops: BinaryOperator, left: Node, right: Node) -> Node {
+    let (left_value, right_value): (Option<f64>, Option<f64>) = (number(&left), number(&right));
+
+    if let (Some(left_value), Some(right_value)) = (left_value, right_value) {
+        match ops.apply(left_value, right_value) {
+            Ok(value) if value.is_finite() => return Node::Number(value),
+            _ => (),
+        }
+    }
+
+    let is = |value: Option<f64>, expected: f64| value == Some(expected);
+
+    match ops {
+        BinaryOperator::Plus if is(left_value, 0.0) => right,
+        BinaryOperator::Plus | BinaryOperator::Minus if is(right_value, 0.0) => left,
+        BinaryOperator::Minus if is(left_value, 0.0) => negate(right),
+        BinaryOperator::Multiply if is(left_value, 0.0) || is(right_value, 0.0) => {
+            Node::Number(0.0)
+        }
+        BinaryOperator::Multiply if is(left_value, 1.0) => right,
+        BinaryOperator::Multiply | BinaryOperator::Divide if is(right_value, 1.0) => left,
+        BinaryOperator::Divide if is(left_value, 0.0) => Node::Number(0.0),
+        BinaryOperator::Power if is(right_value, 0.0) => Node::Number(1.0),
+        BinaryOperator::Power if is(right_value, 1.0) => left,
+        // Subtraction of a negative number is an addition, so x - (-1) is written x + 1
+        BinaryOperator::Minus if right_value.is_some_and(|value| value < 0.0) => {
+            binary(BinaryOperator::Plus, left, negate(right))
+        }
+        _ => Node::Binary(ops, Box::new(left), Box::new(right)),
+    }
+}
+
+/// Check if function given in argument is called on a single argument, like sin(x)
+fn is_unary(fun: &Function, args: &[Node]) -> bool {
+    return args.len() == 1 && fun.arity() == 1 && !fun.is_variadic();
+}
+
+/// Create node calling function given in argument on one node, computed if node is a number
+fn call(fun: Function, arg: Node) -> Node {
+    if let Some(value) = number(&arg) {
+        match fun.apply(&[value]) {
+            Ok(value) if value.is_finite() => return Node::Number(value),
+            _ => (),
+        }
+    }
+
+    return Node::Call(fun, vec![arg]);
+}
+
+/// Simplify node given in argument by computing operations on numbers and removing neutral elements
+fn simplify(node: &Node) -> Node {
+    match node {
+        Node::Unary(UnaryOperator::Minus, operand) => negate(simplify(operand)),
+        Node::Binary(ops, left, right) => binary(*ops, simplify(left), simplify(right)),
+        Node::Call(fun, args) if is_unary(fun, args) => call(*fun, simplify(&args[0])),
+        _ => node.clone(),
+    }
+}
+
+/// Check if node given in argument, once simplified, has a finite value whatever its variables.
+/// Simplification computes operations on numbers only when their value is finite,
+/// so an operation left on numbers, like 1 / 0 or ln(0), or a division by zero has no finite value
+fn is_finite(node: &Node) -> bool {
+    match node {
+        Node::Number(value) | Node::Constant(_, value) => value.is_finite(),
+        Node::Unary(UnaryOperator::Minus, operand) => {
+            number(operand).is_none() && is_finite(operand)
+        }
+        Node::Unary(_, operand) => is_finite(operand),
+        Node::Binary(BinaryOperator::Divide, _, right) if number(right) == Some(0.0) => false,
+        Node::Binary(_, left, right) => {
+            (number(left).is_none() || number(right).is_none())
+                && is_finite(left)
+                && is_finite(right)
+        }
+        Node::Call(fun, args) if is_unary(fun, args) => {
+            number(&args[0]).is_none() && is_finite(&args[0])
+        }
+        Node::Call(_, args) | Node::Tuple(args) => args.iter().all(is_finite),
+        _ => true,
+    }
+}
+
+/// Check if node given in argument depends on variable whose name is given in argument
+fn depends_on(node: &Node, name: &str) -> bool {
+    match node {
+        Node::Variable(variable) => variable == name,
+        Node::Unary(_, operand) => depends_on(operand, name),
+        Node::Binary(_, left, right) => depends_on(left, name) || depends_on(right, name),
+        Node::Call(_, args) | Node::Tuple(args) => args.iter().any(|arg| depends_on(arg, name)),
+        _ => false,
+    }
+}
+
+/// Get derivative of function given in argument at node given in argument, as a function of one variable
+fn function_derivative(fun: Function, u: &Node) -> Result<Node, String> {
+    use BinaryOperator::{Divide, Minus, Multiply, Plus, Power};
+
+    let u = || u.clone();
+    let square = |node: Node| binary(Power, node, Node::Number(2.0));
+    let inverse = |node: Node| binary(Divide, Node::Number(1.0), node);
+    let one = || Node::Number(1.0);
+
+    return Ok(match fun {
+        Function::Abs => binary(Divide, u(), call(Function::Abs, u())),
+        Function::Sqrt => inverse(binary(
+            Multiply,
+            Node::Number(2.0),
+            call(Function::Sqrt, u()),
+        )),
+        Function::Cbrt => inverse(binary(
+            Multiply,
+            Node::Number(3.0),
+            square(call(Function::Cbrt, u())),
+        )),
+        Function::Exp => call(Function::Exp, u()),
+        Function::Ln => inverse(u()),
+        Function::Log10 => inverse(binary(Multiply, u(), Node::Number(10f64.ln()))),
+        Function::Log2 => inverse(binary(Multiply, u(), Node::Number(2f64.ln()))),
+        Function::Sin => call(Function::Cos, u()),
+        Function::Cos => negate(call(Function::Sin, u())),
+        Function::Tan => inverse(square(call(Function::Cos, u()))),
+        Function::Asin => inverse(call(Function::Sqrt, binary(Minus, one(), square(u())))),
+        Function::Acos => negate(inverse(call(
+            Function::Sqrt,
+            binary(Minus, one(), square(u())),
+        ))),
+        Function::Atan => inverse(binary(Plus, one(), square(u()))),
+        Function::Sinh => call(Function::Cosh, u()),
+        Function::Cosh => call(Function::Sinh, u()),
+        Function::Tanh => binary(Minus, one(), square(call(Function::Tanh, u()))),
+        Function::Asinh => inverse(call(Function::Sqrt, binary(Plus, square(u()), one()))),
+        Function::Acosh => inverse(call(Function::Sqrt, binary(Minus, square(u()), one()))),
+        Function::Atanh => inverse(binary(Minus, one(), square(u()))),
+        Function::Deg2Rad => Node::Number(std::f64::consts::PI / 180.0),
+        Function::Rad2Deg => Node::Number(180.0 / std::f64::consts::PI),
+        _ => return Err(format!("Function {} cannot be differentiated", fun.name())),
+    });
+}
+
+/// Get derivative of node given in argument with respect to variable whose name is given in argument,
+/// computed symbolically by rules of differentiation and simplified.
+/// If node holds an operator or a function which cannot be differentiated,
+/// an error message is stored in string contained in Result output
+pub(crate) fn derivative(node: &Node, name: &str) -> Result<Node, String> {
+    use BinaryOperator::{And, Divide, Minus, Multiply, Or, Plus, Power};
+
+    if !depends_on(node, name) {
+        return Ok(Node::Number(0.0));
+    }
+
+    return Ok(match node {
+        Node::Variable(_) => Node::Number(1.0),
+        Node::Unary(UnaryOperator::Minus, u) => negate(derivative(u, name)?),
+        Node::Unary(UnaryOperator::Plus, u) => derivative(u, name)?,
+        Node::Unary(UnaryOperator::Not, _) | Node::Binary(And | Or, _, _) => {
+            return Err(String::from("Boolean operators cannot be differentiated"));
+        }
+        Node::Binary(ops, _, _) if ops.is_comparison() => {
+            return Err(String::from(
+                "Comparison operators cannot be differentiated",
+            ));
+        }
+        Node::Binary(ops @ (Plus | Minus), u, v) => {
+            binary(*ops, derivative(u, name)?, derivative(v, name)?)
+        }
+        Node::Binary(Multiply, u, v) => binary(
+            Plus,
+            binary(Multiply, derivative(u, name)?, simplify(v)),
+            binary(Multiply, simplify(u), derivative(v, name)?),
+        ),
+        Node::Binary(Divide, u, v) => binary(
+            Divide,
+            binary(
+                Minus,
+                binary(Multiply, derivative(u, name)?, simplify(v)),
+                binary(Multiply, simplify(u), derivative(v, name)?),
+            ),
+            binary(Power, simplify(v), Node::Number(2.0)),
+        ),
+        // Power with constant exponent n gives n * u^(n - 1) * u'
+        Node::Binary(Power, u, v) if !depends_on(v, name) => binary(
+            Multiply,
+            binary(
+                Multiply,
+                simplify(v),
+                binary(
+                    Power,
+                    simplify(u),
+                    binary(Minus, simplify(v), Node::Number(1.0)),
+                ),
+            ),
+            derivative(u, name)?,
+        ),
+        // Otherwise u^v = exp(v * ln(u)) gives u^v * (v' * ln(u) + v * u' / u)
+        Node::Binary(Power, u, v) => binary(
+            Multiply,
+            binary(Power, simplify(u), simplify(v)),
+            binary(
+                Plus,
+                binary(
+                    Multiply,
+                    derivative(v, name)?,
+                    call(Function::Ln, simplify(u)),
+                ),
+                binary(
+                    Divide,
+                    binary(Multiply, simplify(v), derivative(u, name)?),
+                    simplify(u),
+                ),
+            ),
+        ),
+        Node::Call(fun, args) if is_unary(fun, args) => {
+            let u: Node = simplify(&args[0]);
+            binary(
+                Multiply,
+                function_derivative(*fun, &u)?,
+                derivative(&u, name)?,
+            )
+        }
+        Node::Call(fun, _) => {
+            return Err(format!("Function {} cannot be differentiated", fun.name()))
+        }
+        _ => return Err(String::from("Tuples cannot be differentiated")),
+    });
+}
+
+/// Get Taylor polynomial of node given in argument with respect to variable whose name is given in argument,
+/// at point x0 up to order given in argument, like 1 - 0.5 * x^2 for cos(x) at 0 up to order 2.
+/// Each coefficient is the derivative of node at x0 divided by factorial of its order.
+/// If node cannot be differentiated, if a coefficient is not finite, like for 1 / x at 0,
+/// or if order is greater than maximal order, an error message is stored in string contained in Result output
+pub(crate) fn taylor(node: &Node, name: &str, x0: f64, order: usize) -> Result<Node, String> {
+    if order > MAX_TAYLOR_ORDER {
+        return Err(format!(
+            "Order {order} of Taylor polynomial is greater than {MAX_TAYLOR_ORDER}"
+        ));
+    }
+
+    let variable: Node = Node::Variable(String::from(name));
+    let shift: Node = binary(BinaryOperator::Minus, variable, Node::Number(x0));
+    let point: Node = Node::Number(x0);
+
+    let mut derived: Node = simplify(node);
+    let mut factorial: f64 = 1.0;
+    let mut polynomial: Option<Node> = None;
+
+    for k in 0..=order {
+        if k > 0 {
+            derived = derivative(&derived, name)?;
+            factorial *= k as f64;
+        }
+
+        let derivative_at_point: Node = simplify(&substitute(&derived, &[(name, &point)]));
+
+        if !is_finite(&derivative_at_point) {
+            return Err(format!(
+                "Coefficient of order {k} of Taylor polynomial at {x0} is not finite"
+            ));
+        }
+
+        let coefficient: Node = binary(
+            BinaryOperator::Divide,
+            derivative_at_point,
+            Node::Number(factorial),
+        );
+
+        // Negative coefficients are subtracted, so polynomial reads like 1 - 0.5 * x^2
+        let (ops, coefficient): (BinaryOperator, Node) = match coefficient {
+            Node::Number(value) if value < 0.0 && polynomial.is_some() => {
+                (BinaryOperator::Minus, Node::Number(-value))
+            }
+            _ => (BinaryOperator::Plus, coefficient),
+        };
+
+        let power: Node = binary(BinaryOperator::Power, shift.clone(), Node::Number(k as f64));
+        let term: Node = binary(BinaryOperator::Multiply, coefficient, power);
+
+        polynomial = Some(match polynomial {
+            Some(polynomial) => binary(ops, polynomial, term),
+            None => term,
+        });
+    }
+
+    return Ok(polynomial.unwrap_or(Node::Number(0.0)));
+}
+
+/// Rebuild syntax tree of compiled expression given in argument from its bytecode, constants being numbers.
+/// Breakpoint arrays of lookup functions, stored as their values followed by their length, are rebuilt as arrays,
+/// and numbers of arguments of aggregation functions are removed.
+/// Conditional operators are rebuilt as select calls at the end of their second value, like in syntax trees
+fn decompile(compiled: &CompiledExpression) -> Node {
+    let mut stack: Vec<Node> = Vec::new();
+    let mut ends: Vec<usize> = Vec::new();
+
+    // Stack discipline is checked at compilation, so operands are always available
+    for index in 0..=compiled.code.len() {
+        while ends.last() == Some(&index) {
+            ends.pop();
+            let values: Vec<Node> = stack.split_off(stack.len() - 3);
+            stack.push(Node::Call(Function::Select, values));
+        }
+
+        let instruction: Instruction = match compiled.code.get(index) {
+            Some(&instruction) => instruction,
+            None => break,
+        };
+
+        let node: Node = match instruction {
+            Instruction::LoadConstant(slot) => Node::Number(compiled.constants[slot as usize]),
+            Instruction::LoadVariable(slot) => {
+                Node::Variable(compiled.variables()[slot as usize].clone())
+            }
+            Instruction::Negate => {
+                Node::Unary(UnaryOperator::Minus, Box::new(stack.pop().unwrap()))
+            }
+            Instruction::Call(fun, arity) => {
+                let mut args: Vec<Node> = stack.split_off(stack.len() - arity as usize);
+
+                if fun.is_variadic() {
+                    args.pop();
+                } else if fun.is_lookup() {
+                    let mut arrays: Vec<Node> = Vec::with_capacity(2);
+
+                    for _ in 0..2 {
+                        let length: usize =
+                            args.pop().as_ref().and_then(number).unwrap_or(0.0) as usize;
+                        let values: Vec<f64> = args
+                            .split_off(args.len() - length)
+                            .iter()
+                            .filter_map(number)
+                            .collect();
+                        arrays.insert(0, Node::Array(values));
+                    }
+
+                    args.extend(arrays);
+                }
+
+                Node::Call(fun, args)
+            }
+            Instruction::Not => Node::Unary(UnaryOperator::Not, Box::new(stack.pop().unwrap())),
+            Instruction::Jump(end) => {
+                ends.push(end as usize);
+                continue;
+            }
+            Instruction::JumpIfFalse(_) | Instruction::ShortCircuit(_, _) => continue,
+            _ => {
+                let right: Node = stack.pop().unwrap();
+                let left: Node = stack.pop().unwrap();
+                let ops: BinaryOperator = match instruction {
+                    Instruction::Logic(ops) => ops,
+                    Instruction::Add => BinaryOperator::Plus,
+                    Instruction::Subtract => BinaryOperator::Minus,
+                    Instruction::Multiply => BinaryOperator::Multiply,
+                    Instruction::Divide => BinaryOperator::Divide,
+                    _ => BinaryOperator::Power,
+                };
+
+                Node::Binary(ops, Box::new(left), Box::new(right))
+            }
+        };
+
+        stack.push(node);
+    }
+
+    return stack.pop().unwrap_or(Node::Number(0.0));
+}
+
+impl CompiledExpression {
+    /// Compute gradient of expression, giving for each variable whose name is given in argument
+    /// the derivative of expression with respect to this variable, computed symbolically and compiled.
+    /// Derivatives take the same variables as expression, ordered by the same slots (see `variables`),
+    /// so values given to expression are given to its derivatives too.
+    ///
+    /// If expression is a tuple, or if it uses functions which cannot be differentiated,
+    /// an error message is stored in string contained in Result output
+    ///
+    /// # Example
+    /// ```
+    /// use taz;
+    ///
+    /// let compiled: taz::CompiledExpression = taz::compile("x^2 * y + sin(y)").unwrap();
+    /// let gradient: Vec<taz::CompiledExpression> = compiled.gradient(&["x", "y"]).unwrap();
+    ///
+    /// assert_eq!(gradient[0].evaluate(&[3.0, 1.0]), Ok(6.0));
+    /// assert_eq!(gradient[1].evaluate(&[3.0, 0.0]), Ok(10.0));
+    /// ```
+    pub fn gradient(&self, names: &[&str]) -> Result<Vec<CompiledExpression>, String> {
+        if self.outputs() != 1 {
+            return Err(String::from(
+                "Expression gives several outputs, so its gradient cannot be computed",
+            ));
+        }
+
+        let node: Node = decompile(self);
+
+        return names
+            .iter()
+            .map(|name| {
+                let derived: Node = derivative(&node, name)?;
+                return CompiledExpression::new(&derived.to_string())?
+                    .with_variables(self.variables());
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::ast::Ast;
+    use super::super::compiler::CompiledExpression;
+
+    fn derivative(expression: &str) -> Result<String, String> {
+        return Ok(Ast::new(expression)?.derivative("x")?.to_string());
+    }
+
+    fn taylor(expression: &str, x0: f64, order: usize) -> Result<String, String> {
+        return Ok(Ast::new(expression)?.taylor("x", x0, order)?.to_string());
+    }
+
+    // Units tests
+    #[test]
+    fn test_derivative() {
+        assert_eq!(
+            derivative("x^2 + sin(x)"),
+            Ok(String::from("2 * x + cos(x)"))
+        );
+        assert_eq!(derivative("-cos(x)"), Ok(String::from("sin(x)")));
+        assert_eq!(derivative("a / x"), Ok(String::from("(-a) / x^2")));
+        assert_eq!(derivative("3 * x - y"), Ok(String::from("3")));
+        assert_eq!(derivative("y^2 + pi"), Ok(String::from("0")));
+        assert_eq!(
+            derivative("sqrt(x^2 + 1)"),
+            Ok(String::from("1 / (2 * sqrt(x^2 + 1)) * (2 * x)"))
+        );
+        assert_eq!(derivative("x^x"), Ok(String::from("x^x * (ln(x) + x / x)")));
+
+        assert_eq!(
+            derivative("x && y"),
+            Err(String::from("Boolean operators cannot be differentiated"))
+        );
+        assert_eq!(
+            derivative("x < 1"),
+            Err(String::from(
+                "Comparison operators cannot be differentiated"
+            ))
+        );
+        assert_eq!(
+            derivative("max(x, 1)"),
+            Err(String::from("Function max cannot be differentiated"))
+        );
+        assert_eq!(derivative("max(y, 1) * x"), Ok(String::from("max(y, 1)")));
+    }
+
+    #[test]
+    fn test_gradient() {
+        let gradient =
+            |expression: &str, names: &[&str]| -> Result<Vec<CompiledExpression>, String> {
+                return CompiledExpression::new(expression)?.gradient(names);
+            };
+
+        match gradient("x^2 * y - 3 * exp(y)", &["x", "y", "z"]) {
+            Ok(gradient) => {
+                assert_eq!(gradient.len(), 3);
+                assert_eq!(
+                    gradient[0].variables(),
+                    &[String::from("x"), String::from("y")]
+                );
+                assert_eq!(gradient[0].evaluate(&[2.0, 5.0]), Ok(20.0));
+                assert_eq!(gradient[1].evaluate(&[2.0, 0.0]), Ok(1.0));
+                assert_eq!(gradient[2].evaluate(&[2.0, 0.0]), Ok(0.0));
+            }
+            Err(_) => assert!(false),
+        }
+
+        // Functions which cannot be differentiated are kept when they do not depend on variable
+        match gradient("x * lookup(y, [0, 1], [0, 10]) + max(y, 1, 2)", &["x"]) {
+            Ok(gradient) => assert_eq!(gradient[0].evaluate(&[1.0, 0.5]), Ok(5.0)),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            gradient("max(x, 1)", &["x"]),
+            Err(String::from("Function max cannot be differentiated"))
+        );
+        assert_eq!(
+            gradient("(x, x^2)", &["x"]),
+            Err(String::from(
+                "Expression gives several outputs, so its gradient cannot be computed"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_taylor() {
+        assert_eq!(
+            taylor("sin(x)", 0.0, 5),
+            Ok(String::from(
+                "x - 0.16666666666666666 * x^3 + 0.008333333333333333 * x^5"
+            ))
+        );
+        assert_eq!(taylor("cos(x)", 0.0, 2), Ok(String::from("1 - 0.5 * x^2")));
+        assert_eq!(
+            taylor("1 / (1 - x)", 0.0, 3),
+            Ok(String::from("1 + x + x^2 + x^3"))
+        );
+        assert_eq!(
+            taylor("sqrt(x)", 4.0, 2),
+            Ok(String::from("2 + 0.25 * (x - 4) - 0.015625 * (x - 4)^2"))
+        );
+        assert_eq!(
+            taylor("x^2", -1.0, 3),
+            Ok(String::from("1 - 2 * (x + 1) + (x + 1)^2"))
+        );
+        assert_eq!(taylor("exp(x)", 0.0, 0), Ok(String::from("1")));
+
+        assert_eq!(
+            taylor("exp(x)", 0.0, 21),
+            Err(String::from(
+                "Order 21 of Taylor polynomial is greater than 20"
+            ))
+        );
+
+        // Coefficients must be finite, whatever the other variables
+        for (expression, order) in [("1 / x", 0), ("y / x", 0), ("ln(x)", 0), ("sqrt(x)", 1)] {
+            assert_eq!(
+                taylor(expression, 0.0, 3),
+                Err(format!(
+                    "Coefficient of order {order} of Taylor polynomial at 0 is not finite"
+                ))
+            );
+        }
+
+        assert_eq!(
+            taylor("1 / x + max(x, 1)", 1.0, 0),
+            Ok(String::from("1 + max(1, 1)"))
+        );
+    }
+}
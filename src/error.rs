@@ -0,0 +1,57 @@
+#![allow(clippy::needless_return)]
+
+use super::limits::Limit;
+
+use std::fmt;
+
+/// Error occurring during parsing or evaluation of an expression
+#[derive(Debug, PartialEq, Clone)]
+pub enum Error {
+    /// Invalid expression or evaluation failure, described by a message
+    Message(String),
+    /// Expression exceeds a limit of parser
+    LimitExceeded(Limit),
+    /// Evaluation exceeds its budget of steps or its deadline
+    BudgetExceeded,
+    /// An operation gives an infinite result from finite operands, described by a message
+    Overflow(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Message(message) => write!(f, "{message}"),
+            Error::LimitExceeded(limit) => write!(f, "{limit}"),
+            Error::BudgetExceeded => write!(f, "Evaluation exceeds its budget"),
+            Error::Overflow(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<String> for Error {
+    fn from(message: String) -> Error {
+        return Error::Message(message);
+    }
+}
+
+impl Error {
+    /// Transform message of error with function given in argument, like adding its position in expression
+    pub(crate) fn map_message<F>(self, transform: F) -> Error
+    where
+        F: FnOnce(String) -> String,
+    {
+        match self {
+            Error::Message(message) => Error::Message(transform(message)),
+            Error::Overflow(message) => Error::Overflow(transform(message)),
+            error => error,
+        }
+    }
+}
+
+impl From<Error> for String {
+    fn from(error: Error) -> String {
+        return error.to_string();
+    }
+}
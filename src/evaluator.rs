@@ -1,58 +1,282 @@
+#![allow(clippy::needless_return)]
+
+use super::error::Error;
+use super::operators::{is_true, BinaryOperator};
+use super::options::EvaluationOptions;
 use super::token::Token;
 
+use smallvec::SmallVec;
+
+/// Operand stack of evaluation, stored inline without heap allocation for short expressions
+pub type OperandStack = SmallVec<[f64; 16]>;
+
 /// Evaluate postfix expression given as vector of token
 /// If error occurs during evaluation, an error message is stored
 /// in string contained in Result output
+#[cfg(test)]
 pub fn postfix_evaluation(tokens: Vec<Token>) -> Result<f64, String> {
-    let mut stack_operand: Vec<f64> = Vec::new();
-    stack_operand.reserve(10);
+    return postfix_evaluation_with_values(&tokens, &[]);
+}
+
+/// Evaluate postfix expression given as slice of token
+/// where each variable token takes its value in slice of values given in argument.
+/// If error occurs during evaluation, an error message is stored
+/// in string contained in Result output
+pub fn postfix_evaluation_with_values(tokens: &[Token], values: &[f64]) -> Result<f64, String> {
+    match postfix_evaluation_stack(tokens, values)?.first() {
+        Some(&value) => Ok(value),
+        None => Err(String::from("Empty expression")),
+    }
+}
+
+/// Evaluate postfix expression given as slice of token
+/// where each variable token takes its value in slice of values given in argument.
+/// All values remaining in operand stack at the end of evaluation are returned,
+/// so postfix expressions of elements of a tuple give a value for each element.
+/// If error occurs during evaluation, an error message is stored
+/// in string contained in Result output
+pub fn postfix_evaluation_stack(tokens: &[Token], values: &[f64]) -> Result<OperandStack, String> {
+    return postfix_evaluation_stream(tokens.iter().copied().map(Ok), values);
+}
+
+/// Evaluate postfix expression given by an iterator of tokens, like a converter of infix tokens,
+/// where each variable token takes its value in slice of values given in argument.
+/// Tokens are evaluated as soon as they are given, so only operands are kept in memory.
+/// All values remaining in operand stack at the end of evaluation are returned.
+/// If an error is given by iterator or occurs during evaluation, an error message is stored
+/// in string contained in Result output
+pub fn postfix_evaluation_stream<I>(tokens: I, values: &[f64]) -> Result<OperandStack, String>
+where
+    I: IntoIterator<Item = Result<Token, String>>,
+{
+    return postfix_evaluation_stream_with_options(tokens, values, &EvaluationOptions::default())
+        .map_err(String::from);
+}
+
+/// Evaluate postfix expression given by an iterator of tokens like postfix_evaluation_stream function,
+/// according to options of evaluation given in argument.
+/// If an error is given by iterator or occurs during evaluation, an error is given in Result output
+pub fn postfix_evaluation_stream_with_options<I>(
+    tokens: I,
+    values: &[f64],
+    options: &EvaluationOptions,
+) -> Result<OperandStack, Error>
+where
+    I: IntoIterator<Item = Result<Token, String>>,
+{
+    return postfix_evaluation_stream_with_host(tokens, values, options, |_, _| {
+        Err(String::from("Missing host of function"))
+    });
+}
+
+/// Evaluate postfix expression given by an iterator of tokens like postfix_evaluation_stream_with_options function,
+/// where each function provided by host is called by function given in argument with its slot and its arguments.
+/// If an error is given by iterator, by a function of host or occurs during evaluation, an error is given in Result output
+pub fn postfix_evaluation_stream_with_host<I, H>(
+    tokens: I,
+    values: &[f64],
+    options: &EvaluationOptions,
+    mut call_host: H,
+) -> Result<OperandStack, Error>
+where
+    I: IntoIterator<Item = Result<Token, String>>,
+    H: FnMut(usize, &[f64]) -> Result<f64, String>,
+{
+    let mut stack_operand: OperandStack = OperandStack::with_capacity(options.capacities.operands);
+    let mut last_operation: Option<(char, f64, f64)> = None;
+    let mut tokens = tokens.into_iter();
+
+    while let Some(token) = tokens.next() {
+        let token: Token = token?;
+        last_operation = None;
 
-    for token in tokens {
         match token {
             Token::Number(number) => stack_operand.push(number),
+            Token::Variable(slot) => match values.get(slot) {
+                Some(&value) => stack_operand.push(value),
+                None => {
+                    return Err(Error::Message(String::from("Missing value of variable")));
+                }
+            },
             Token::BinaryOperator(ops) => {
                 if let Some(right) = stack_operand.pop() {
                     if let Some(left) = stack_operand.pop() {
-                        stack_operand.push(ops.apply(left, right)?);
+                        let operation = || format!("{} operation", ops.notation());
+
+                        if ops == BinaryOperator::Divide && right == 0.0 {
+                            // Division by zero is never reported as an overflow
+                            let mut value: f64 = options.division_by_zero.divide(left, right)?;
+
+                            if let Some(range) = &options.range {
+                                value = range.apply(&operation(), value)?;
+                            }
+
+                            stack_operand.push(value);
+                            continue;
+                        }
+
+                        // Boolean and comparison operators give exact values, which are never rounded
+                        if ops.is_boolean() || ops.is_comparison() {
+                            stack_operand.push(ops.apply(left, right)?);
+                            continue;
+                        }
+
+                        let value: f64 = match options.round_operations {
+                            true => options.rounding.operate(ops.symbol(), left, right),
+                            false => ops.apply(left, right)?,
+                        };
+
+                        last_operation = Some((ops.symbol(), left, right));
+                        stack_operand.push(options.check_result(
+                            operation,
+                            &[left, right],
+                            value,
+                        )?);
                     } else {
-                        return Err(String::from(
+                        return Err(Error::Message(String::from(
                             "Missing left operand to apply binary operation",
-                        ));
+                        )));
                     }
                 } else {
-                    return Err(String::from(
+                    return Err(Error::Message(String::from(
                         "Missing right operand to apply binary operation",
-                    ));
+                    )));
                 }
             }
             Token::UnaryOperator(ops) => {
                 if let Some(number) = stack_operand.pop() {
-                    stack_operand.push(ops.apply(number));
+                    let operation = || format!("{} operation", ops.symbol());
+                    let value: f64 = ops.apply(number);
+                    stack_operand.push(options.check_result(operation, &[number], value)?);
                 } else {
-                    return Err(String::from("Missing operand to apply unary operation"));
+                    return Err(Error::Message(String::from(
+                        "Missing operand to apply unary operation",
+                    )));
                 }
             }
+            Token::Array(length) => stack_operand.push(length as f64),
             Token::Function(fun) => {
-                if let Some(arg) = stack_operand.pop() {
-                    stack_operand.push(fun.apply(arg)?);
-                } else {
-                    return Err(String::from("Missing argument to apply function"));
+                let arity: usize = fun.arity_on_stack(&stack_operand)?;
+                let first_arg: usize = stack_operand.len() - arity;
+                let args: &[f64] = &stack_operand[first_arg..];
+                let operation = || format!("{} function", fun.name());
+                let value: f64 = options.check_result(
+                    operation,
+                    args,
+                    fun.apply_with_angle(args, options.domain, options.angle)?,
+                )?;
+
+                stack_operand.truncate(first_arg);
+                stack_operand.push(value);
+            }
+            Token::HostFunction(slot, nb_args) => {
+                if stack_operand.len() < nb_args {
+                    return Err(Error::Message(String::from(
+                        "Missing argument to apply function",
+                    )));
                 }
+
+                let first_arg: usize = stack_operand.len() - nb_args;
+                let args: &[f64] = &stack_operand[first_arg..];
+                let operation = || String::from("host function");
+                let value: f64 = options.check_result(operation, args, call_host(slot, args)?)?;
+
+                stack_operand.truncate(first_arg);
+                stack_operand.push(value);
             }
             Token::Constant(constant) => stack_operand.push(constant),
+            Token::Question => match stack_operand.pop() {
+                // Like select function, first value is taken if condition is true
+                Some(condition) if is_true(condition) => (),
+                Some(_) => skip_conditional_value(&mut tokens, Token::Colon)?,
+                None => {
+                    return Err(Error::Message(String::from(
+                        "Missing condition of conditional operator",
+                    )));
+                }
+            },
+            // First value has been taken, so the other one is not evaluated
+            Token::Colon => skip_conditional_value(&mut tokens, Token::EndConditional)?,
+            Token::EndConditional => (),
+            Token::ShortCircuit(ops) => match stack_operand.last_mut() {
+                // Value of boolean operator is known from its left operand, so its right operand is skipped
+                Some(left) if is_true(*left) == (ops == BinaryOperator::Or) => {
+                    *left = f64::from(is_true(*left));
+                    skip_right_operand(&mut tokens, ops)?;
+                }
+                Some(_) => (),
+                None => {
+                    return Err(Error::Message(String::from(
+                        "Missing left operand to apply binary operation",
+                    )));
+                }
+            },
             _ => {
-                return Err(String::from(
+                return Err(Error::Message(String::from(
                     "Token non-accepted for evaluation of postfix expression",
-                ));
+                )));
+            }
+        }
+    }
+
+    if let Some(result) = stack_operand.last_mut() {
+        options.round_result(result, last_operation)?;
+    }
+
+    return Ok(stack_operand);
+}
+
+/// Skip tokens of a value of conditional operator given by iterator in argument without evaluating them,
+/// until the token given in argument which ends this value, conditional operators nested in value being skipped too.
+/// If an error is given by iterator or if value does not end, an error is given in Result output
+fn skip_conditional_value<I>(tokens: &mut I, end: Token) -> Result<(), Error>
+where
+    I: Iterator<Item = Result<Token, String>>,
+{
+    let mut depth: usize = 0;
+
+    for token in tokens {
+        match token? {
+            token if token == end && depth == 0 => return Ok(()),
+            Token::Question => depth += 1,
+            Token::EndConditional => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+
+    return Err(Error::Message(String::from(
+        "Missing end of conditional operator",
+    )));
+}
+
+/// Skip tokens of right operand of boolean operator given in argument, given by iterator in argument,
+/// without evaluating them until this operator, boolean operators nested in operand being skipped too.
+/// If an error is given by iterator or if operator is missing, an error is given in Result output
+fn skip_right_operand<I>(tokens: &mut I, ops: BinaryOperator) -> Result<(), Error>
+where
+    I: Iterator<Item = Result<Token, String>>,
+{
+    let mut depth: usize = 0;
+
+    for token in tokens {
+        match token? {
+            Token::BinaryOperator(last_ops) if last_ops == ops && depth == 0 => return Ok(()),
+            Token::ShortCircuit(_) => depth += 1,
+            Token::BinaryOperator(last_ops) if last_ops.is_boolean() => {
+                depth = depth.saturating_sub(1)
             }
+            _ => (),
         }
     }
 
-    return Ok(stack_operand[0]);
+    return Err(Error::Message(String::from(
+        "Missing right operand to apply binary operation",
+    )));
 }
 
 // Units tests
 #[cfg(test)]
+#[allow(clippy::unnecessary_cast)]
 mod tests {
     use super::super::constants::PI;
     use super::super::functions::Function;
@@ -286,4 +510,100 @@ mod tests {
             Err(_) => assert!(false),
         }
     }
+
+    #[test]
+    fn test_postfix_evaluation_with_function_several_arguments() {
+        let tokens: Vec<Token> = vec![
+            Token::Number(1.0),
+            Token::Number(2.0),
+            Token::Number(3.0),
+            Token::BinaryOperator(BinaryOperator::Plus),
+            Token::Number(0.5),
+            Token::Function(Function::Lerp),
+        ];
+
+        match postfix_evaluation(tokens) {
+            Ok(result) => {
+                let result_ref: f64 = 1.0 + (5.0 - 1.0) * 0.5;
+                assert!(relative_error(result, result_ref) < 0.01)
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_postfix_evaluation_with_variables_values() {
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::Variable(1),
+            Token::BinaryOperator(BinaryOperator::Minus),
+        ];
+
+        match postfix_evaluation_with_values(&tokens, &[8.0, 3.0]) {
+            Ok(result) => assert!(relative_error(result, 5.0) < 0.01),
+            Err(_) => assert!(false),
+        }
+
+        match postfix_evaluation_with_values(&tokens, &[8.0]) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(message, "Missing value of variable"),
+        }
+    }
+
+    #[test]
+    fn test_postfix_evaluation_stack() {
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::UnaryOperator(UnaryOperator::Minus),
+            Token::Variable(0),
+            Token::Number(2.0),
+            Token::BinaryOperator(BinaryOperator::Multiply),
+        ];
+
+        assert_eq!(
+            postfix_evaluation_stack(&tokens, &[3.0]).map(|values| values.into_vec()),
+            Ok(vec![-3.0, 6.0])
+        );
+        assert_eq!(
+            postfix_evaluation_with_values(&[], &[]),
+            Err(String::from("Empty expression"))
+        );
+    }
+
+    #[test]
+    fn test_postfix_evaluation_stream_with_error() {
+        let tokens: Vec<Result<Token, String>> = vec![
+            Ok(Token::Number(2.0)),
+            Ok(Token::Number(3.0)),
+            Err(String::from("Mismatched parenthesis")),
+            Ok(Token::BinaryOperator(BinaryOperator::Plus)),
+        ];
+
+        assert_eq!(
+            postfix_evaluation_stream(tokens, &[]),
+            Err(String::from("Mismatched parenthesis"))
+        );
+    }
+
+    #[test]
+    fn test_postfix_evaluation_conditional_skips_other_value() {
+        // x ? 1 / x : 2, where division by zero would fail if first value was evaluated
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::Question,
+            Token::Number(1.0),
+            Token::Variable(0),
+            Token::BinaryOperator(BinaryOperator::Divide),
+            Token::Colon,
+            Token::Number(2.0),
+            Token::EndConditional,
+        ];
+
+        assert_eq!(postfix_evaluation_with_values(&tokens, &[0.0]), Ok(2.0));
+        assert_eq!(postfix_evaluation_with_values(&tokens, &[4.0]), Ok(0.25));
+        assert_eq!(
+            postfix_evaluation_with_values(&tokens, &[f64::NAN]),
+            Ok(2.0)
+        );
+    }
 }
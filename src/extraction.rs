@@ -0,0 +1,121 @@
+#![allow(clippy::needless_return)]
+
+use super::evaluate;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+/// Greatest integer such that all integers of smaller magnitude are exactly represented by f64
+const MAX_EXACT_INTEGER: f64 = 9007199254740992.0;
+
+/// Convert result of evaluation to a JSON value.
+/// Whole numbers are given as integers so they can be deserialized into integer fields
+fn to_json_value(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < MAX_EXACT_INTEGER {
+        return Value::Number(Number::from(value as i64));
+    }
+
+    match Number::from_f64(value) {
+        Some(number) => Value::Number(number),
+        None => Value::Null,
+    }
+}
+
+/// Evaluate each named expression of hash map given in argument with the same variables,
+/// and deserialize results into a value of type T, each result being given to the field
+/// with the name of its expression.
+///
+/// If error occurs during evaluation of an expression or during deserialization,
+/// an error message is stored in string contained in Result output
+pub fn evaluate_into<T>(
+    expressions: &HashMap<String, String>,
+    variables: &HashMap<String, f64>,
+) -> Result<T, String>
+where
+    T: DeserializeOwned,
+{
+    let mut names: Vec<&String> = expressions.keys().collect();
+    names.sort_unstable();
+
+    let mut results: Map<String, Value> = Map::with_capacity(names.len());
+
+    for name in names {
+        let value: f64 = evaluate(&expressions[name], variables)
+            .map_err(|message| format!("Expression {name}: {message}"))?;
+
+        results.insert(name.clone(), to_json_value(value));
+    }
+
+    return serde_json::from_value(Value::Object(results)).map_err(|error| error.to_string());
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Metrics {
+        area: f64,
+        perimeter: f64,
+        corners: u32,
+        #[serde(default)]
+        label: Option<String>,
+    }
+
+    fn expressions(area: &str) -> HashMap<String, String> {
+        return HashMap::from([
+            (String::from("area"), String::from(area)),
+            (
+                String::from("perimeter"),
+                String::from("2 * (width + height)"),
+            ),
+            (String::from("corners"), String::from("2 + 2")),
+        ]);
+    }
+
+    fn variables() -> HashMap<String, f64> {
+        return HashMap::from([(String::from("width"), 1.5), (String::from("height"), 2.0)]);
+    }
+
+    #[test]
+    fn test_evaluate_into_struct() {
+        let metrics: Result<Metrics, String> =
+            evaluate_into(&expressions("width * height"), &variables());
+
+        assert_eq!(
+            metrics,
+            Ok(Metrics {
+                area: 3.0,
+                perimeter: 7.0,
+                corners: 4,
+                label: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_into_with_evaluation_error() {
+        let metrics: Result<Metrics, String> =
+            evaluate_into(&expressions("width / 0"), &variables());
+
+        assert_eq!(
+            metrics,
+            Err(String::from(
+                "Expression area: Division by zero at position 9"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_into_with_missing_field() {
+        let mut expressions: HashMap<String, String> = expressions("width * height");
+        expressions.remove("corners");
+
+        let metrics: Result<Metrics, String> = evaluate_into(&expressions, &variables());
+        assert!(metrics.is_err());
+    }
+}
@@ -0,0 +1,346 @@
+#![allow(clippy::needless_return)]
+
+use super::compiler::CompiledExpression;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+/// Status returned by functions of C interface on success
+const TAZ_OK: c_int = 0;
+
+/// Status returned by functions of C interface on failure, message being given by taz_last_error
+const TAZ_ERROR: c_int = -1;
+
+thread_local! {
+    /// Message of last error occured on current thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Store message given in argument as last error of current thread, and give failure status
+fn fail(message: String) -> c_int {
+    // Interior nul bytes cannot be held by a C string, so they are dropped
+    let message: CString = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+
+    return TAZ_ERROR;
+}
+
+/// Run body of function of C interface given in argument, so a panic does not unwind across the C boundary:
+/// it is caught and stored as last error of current thread, value given in argument being returned instead
+fn guard<T, F>(on_panic: T, body: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    // State left by a panic is never reused, since only the last error is kept
+    return match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(value) => value,
+        Err(payload) => {
+            let cause: &str = match payload.downcast_ref::<&str>() {
+                Some(cause) => cause,
+                None => payload
+                    .downcast_ref::<String>()
+                    .map_or("unknown cause", String::as_str),
+            };
+
+            fail(format!("Unexpected panic: {cause}"));
+            on_panic
+        }
+    };
+}
+
+/// Read C string given in argument as UTF-8 string.
+/// If pointer is null or string is not valid UTF-8, an error message is stored in string contained in Result output
+///
+/// # Safety
+/// Pointer must be null or point to a nul-terminated string
+unsafe fn read_str<'a>(string: *const c_char) -> Result<&'a str, String> {
+    if string.is_null() {
+        return Err(String::from("Null pointer given as string"));
+    }
+
+    return CStr::from_ptr(string)
+        .to_str()
+        .map_err(|_| String::from("String is not valid UTF-8"));
+}
+
+/// Expression compiled through C interface, holding names of its variables as C strings
+pub struct TazExpression {
+    compiled: CompiledExpression,
+    names: Vec<CString>,
+}
+
+/// Get message of last error occured on current thread, or null if no error occured.
+/// Message stays valid until next failing call on current thread
+#[no_mangle]
+pub extern "C" fn taz_last_error() -> *const c_char {
+    return LAST_ERROR.with(|last_error| match last_error.borrow().as_ref() {
+        Some(message) => message.as_ptr(),
+        None => ptr::null(),
+    });
+}
+
+/// Evaluate expression given in argument with nb_variables variables, whose names and values
+/// are given by arrays in argument, storing its value in result.
+/// Give 0 on success, or -1 if error occurs, message being given by taz_last_error
+///
+/// # Safety
+/// Expression must be a nul-terminated string, names and values must point to nb_variables elements
+/// (or be null if nb_variables is 0), each name being a nul-terminated string, and result must be valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn taz_evaluate(
+    expression: *const c_char,
+    names: *const *const c_char,
+    values: *const f64,
+    nb_variables: usize,
+    result: *mut f64,
+) -> c_int {
+    return guard(TAZ_ERROR, || {
+        if result.is_null() || (nb_variables > 0 && (names.is_null() || values.is_null())) {
+            return fail(String::from("Null pointer given as argument"));
+        }
+
+        let expression: &str = match read_str(expression) {
+            Ok(expression) => expression,
+            Err(message) => return fail(message),
+        };
+
+        let mut variables: HashMap<String, f64> = HashMap::with_capacity(nb_variables);
+
+        if nb_variables > 0 {
+            let names: &[*const c_char] = slice::from_raw_parts(names, nb_variables);
+            let values: &[f64] = slice::from_raw_parts(values, nb_variables);
+
+            for (&name, &value) in names.iter().zip(values) {
+                match read_str(name) {
+                    Ok(name) => variables.insert(String::from(name), value),
+                    Err(message) => return fail(message),
+                };
+            }
+        }
+
+        match super::evaluate(expression, &variables) {
+            Ok(value) => {
+                *result = value;
+                return TAZ_OK;
+            }
+            Err(message) => return fail(message),
+        }
+    });
+}
+
+/// Compile expression given in argument, to be evaluated many times with taz_compiled_evaluate.
+/// Give compiled expression, to be released with taz_compiled_free,
+/// or null if error occurs, message being given by taz_last_error
+///
+/// # Safety
+/// Expression must be a nul-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn taz_compile(expression: *const c_char) -> *mut TazExpression {
+    return guard(ptr::null_mut(), || {
+        let compiled: Result<CompiledExpression, String> =
+            read_str(expression).and_then(CompiledExpression::new);
+
+        match compiled {
+            Ok(compiled) => {
+                let names: Vec<CString> = compiled
+                    .variables()
+                    .iter()
+                    .map(|name| CString::new(name.as_str()).unwrap_or_default())
+                    .collect();
+
+                return Box::into_raw(Box::new(TazExpression { compiled, names }));
+            }
+            Err(message) => {
+                fail(message);
+                return ptr::null_mut();
+            }
+        }
+    });
+}
+
+/// Get number of variables of compiled expression given in argument, or 0 if it is null
+///
+/// # Safety
+/// Compiled expression must be null or given by taz_compile and not released
+#[no_mangle]
+pub unsafe extern "C" fn taz_compiled_nb_variables(compiled: *const TazExpression) -> usize {
+    return guard(0, || match compiled.as_ref() {
+        Some(compiled) => compiled.names.len(),
+        None => 0,
+    });
+}
+
+/// Get name of variable of compiled expression at slot given in argument,
+/// or null if slot is out of range. Name stays valid until compiled expression is released
+///
+/// # Safety
+/// Compiled expression must be null or given by taz_compile and not released
+#[no_mangle]
+pub unsafe extern "C" fn taz_compiled_variable(
+    compiled: *const TazExpression,
+    slot: usize,
+) -> *const c_char {
+    return guard(ptr::null(), || {
+        match compiled
+            .as_ref()
+            .and_then(|compiled| compiled.names.get(slot))
+        {
+            Some(name) => name.as_ptr(),
+            None => ptr::null(),
+        }
+    });
+}
+
+/// Evaluate compiled expression with nb_values values of variables given in array, ordered by slot,
+/// storing its value in result.
+/// Give 0 on success, or -1 if error occurs, message being given by taz_last_error
+///
+/// # Safety
+/// Compiled expression must be given by taz_compile and not released, values must point to nb_values elements
+/// (or be null if nb_values is 0) and result must be valid for writes
+#[no_mangle]
+pub unsafe extern "C" fn taz_compiled_evaluate(
+    compiled: *const TazExpression,
+    values: *const f64,
+    nb_values: usize,
+    result: *mut f64,
+) -> c_int {
+    return guard(TAZ_ERROR, || {
+        let compiled: &TazExpression = match compiled.as_ref() {
+            Some(compiled) if !result.is_null() && (nb_values == 0 || !values.is_null()) => {
+                compiled
+            }
+            _ => return fail(String::from("Null pointer given as argument")),
+        };
+
+        let values: &[f64] = match nb_values {
+            0 => &[],
+            _ => slice::from_raw_parts(values, nb_values),
+        };
+
+        match compiled.compiled.evaluate(values) {
+            Ok(value) => {
+                *result = value;
+                return TAZ_OK;
+            }
+            Err(message) => return fail(message),
+        }
+    });
+}
+
+/// Release compiled expression given by taz_compile. Nothing is done if it is null
+///
+/// # Safety
+/// Compiled expression must be null or given by taz_compile and not already released
+#[no_mangle]
+pub unsafe extern "C" fn taz_compiled_free(compiled: *mut TazExpression) {
+    guard((), || {
+        if !compiled.is_null() {
+            drop(Box::from_raw(compiled));
+        }
+    });
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Get message of last error of current thread
+    fn last_error() -> String {
+        return unsafe { CStr::from_ptr(taz_last_error()) }
+            .to_string_lossy()
+            .into_owned();
+    }
+
+    #[test]
+    fn test_taz_evaluate() {
+        let expression: CString = CString::new("2 * x + y").unwrap();
+        let x: CString = CString::new("x").unwrap();
+        let y: CString = CString::new("y").unwrap();
+        let names: [*const c_char; 2] = [x.as_ptr(), y.as_ptr()];
+        let values: [f64; 2] = [3.0, 1.0];
+        let mut result: f64 = 0.0;
+
+        let status: c_int = unsafe {
+            taz_evaluate(
+                expression.as_ptr(),
+                names.as_ptr(),
+                values.as_ptr(),
+                2,
+                &mut result,
+            )
+        };
+
+        assert_eq!(status, TAZ_OK);
+        assert_eq!(result, 7.0);
+
+        let status: c_int = unsafe {
+            taz_evaluate(
+                expression.as_ptr(),
+                ptr::null(),
+                ptr::null(),
+                0,
+                &mut result,
+            )
+        };
+
+        assert_eq!(status, TAZ_ERROR);
+        assert_eq!(last_error(), "Unknown name x at position 4");
+    }
+
+    #[test]
+    fn test_taz_compile() {
+        let expression: CString = CString::new("a / b").unwrap();
+
+        unsafe {
+            let compiled: *mut TazExpression = taz_compile(expression.as_ptr());
+            assert!(!compiled.is_null());
+            assert_eq!(taz_compiled_nb_variables(compiled), 2);
+            assert_eq!(
+                CStr::from_ptr(taz_compiled_variable(compiled, 1)).to_str(),
+                Ok("b")
+            );
+            assert!(taz_compiled_variable(compiled, 2).is_null());
+
+            let mut result: f64 = 0.0;
+            let values: [f64; 2] = [3.0, 2.0];
+            assert_eq!(
+                taz_compiled_evaluate(compiled, values.as_ptr(), 2, &mut result),
+                TAZ_OK
+            );
+            assert_eq!(result, 1.5);
+
+            let values: [f64; 2] = [3.0, 0.0];
+            assert_eq!(
+                taz_compiled_evaluate(compiled, values.as_ptr(), 2, &mut result),
+                TAZ_ERROR
+            );
+            assert_eq!(last_error(), "Division by zero");
+
+            taz_compiled_free(compiled);
+
+            let invalid: CString = CString::new("2 +").unwrap();
+            assert!(taz_compile(invalid.as_ptr()).is_null());
+            assert!(!taz_last_error().is_null());
+        }
+    }
+
+    #[test]
+    fn test_guard() {
+        assert_eq!(guard(TAZ_ERROR, || TAZ_OK), TAZ_OK);
+        assert_eq!(guard(TAZ_ERROR, || panic!("Broken invariant")), TAZ_ERROR);
+        assert_eq!(last_error(), "Unexpected panic: Broken invariant");
+
+        let slot: usize = 3;
+        assert!(guard(ptr::null_mut(), || -> *mut TazExpression {
+            panic!("Slot {slot} is out of range")
+        })
+        .is_null());
+        assert_eq!(last_error(), "Unexpected panic: Slot 3 is out of range");
+    }
+}
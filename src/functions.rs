@@ -1,3 +1,8 @@
+#![allow(clippy::needless_return, clippy::manual_range_contains)]
+
+use super::operators::is_true;
+use super::options::{AngleUnit, DomainMode};
+
 /// Available functions used in library
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Function {
@@ -20,6 +25,290 @@ pub enum Function {
     Asinh,
     Acosh,
     Atanh,
+    Clamp,
+    Lerp,
+    Select,
+    /// Like select function, but only the value taken is evaluated,
+    /// so calls of if function are converted like conditional operator
+    If,
+    /// Value of the first true condition, i.e. neither zero nor NaN, among pairs of condition and value, or default value
+    /// given as last argument, like piecewise(x > 1, 2, x > 0, 1, 0). Calls are converted like chained
+    /// conditional operators, so they take any odd number of arguments from three
+    Piecewise,
+    Gcd,
+    Lcm,
+    Ncr,
+    Npr,
+    Deg2Rad,
+    Rad2Deg,
+    Round,
+    FloorTo,
+    CeilTo,
+    Lookup,
+    LookupExtrap,
+    Spline,
+    Sum,
+    Mean,
+    Median,
+    /// Standard deviation of population made of arguments
+    Std,
+    /// Variance of population made of arguments
+    Var,
+    Min,
+    Max,
+}
+
+/// Check if a value is a whole number
+fn is_whole_number(value: f64) -> bool {
+    return value.is_finite() && value.fract() == 0.0;
+}
+
+/// Greatest common divisor of two whole numbers computed by Euclid algorithm
+fn greatest_common_divisor(left: f64, right: f64) -> f64 {
+    let (mut a, mut b): (f64, f64) = (left.abs(), right.abs());
+
+    while b != 0.0 {
+        (a, b) = (b, a % b);
+    }
+
+    return a;
+}
+
+/// Apply a rounding function on value scaled to keep number of decimal digits given in argument.
+/// A negative number of digits rounds to tens, hundreds, ...
+/// Rounding works on the shortest decimal form of value, like 2.3 for 2.3 whose binary value is slightly below,
+/// so floorto(2.3, 2) gives 2.3, and value is given unchanged if it has no more decimal digits than kept
+fn round_to_digits<R>(value: f64, digits: f64, rounding: R) -> f64
+where
+    R: Fn(f64) -> f64,
+{
+    if value == 0.0 || !value.is_finite() {
+        return value;
+    }
+
+    // Shortest decimal form is written like 2.345e0, whose value is mantissa digits times 10^(exponent - nb_digits + 1)
+    let text: String = format!("{:e}", value.abs());
+    let (mantissa, exponent) = text.split_once('e').unwrap();
+    let mantissa: String = mantissa.replace('.', "");
+    let exponent: i64 = exponent.parse().unwrap();
+    let nb_decimals: i64 = mantissa.len() as i64 - 1 - exponent;
+    let digits: i64 = digits as i64;
+
+    if digits >= nb_decimals {
+        return value;
+    }
+
+    // Dropped digits are preceded by zeros when they are more than digits of mantissa,
+    // and are not all zeros, since shortest decimal form does not end with zero
+    let nb_dropped: usize = (nb_decimals - digits) as usize;
+    let (kept, dropped) = mantissa.split_at(mantissa.len().saturating_sub(nb_dropped));
+    let dropped: String = "0".repeat(nb_dropped - dropped.len()) + dropped;
+    let kept: i128 = kept.parse().unwrap_or(0);
+    let fraction: f64 = match (dropped.as_bytes()[0], dropped.len()) {
+        (b'5', 1) => 0.5,
+        (b'0'..=b'4', _) => 0.25,
+        _ => 0.75,
+    };
+
+    // Rounding functions commute with shifts by integers, so only fraction is rounded
+    let sign: f64 = value.signum();
+    let rounded: i128 = sign as i128 * kept + rounding(sign * fraction) as i128;
+
+    return format!("{rounded}e{}", -digits).parse().unwrap();
+}
+
+/// Split arguments of lookup function, given as value followed by breakpoint arrays
+/// where each array is followed by its length, into value and arrays of abscissas and ordinates.
+/// If arguments are not valid, an error message is stored in string contained in Result output
+fn split_lookup_args(args: &[f64]) -> Result<(f64, &[f64], &[f64]), String> {
+    let error: String = String::from("Wrong number of arguments given to function");
+
+    let (&ny, args) = args.split_last().ok_or(error.clone())?;
+    let ny: usize = ny as usize;
+
+    if args.len() < ny + 1 {
+        return Err(error);
+    }
+
+    let (args, ys) = args.split_at(args.len() - ny);
+    let (&nx, args) = args.split_last().ok_or(error.clone())?;
+    let nx: usize = nx as usize;
+
+    if args.len() != nx + 1 {
+        return Err(error);
+    }
+
+    return Ok((args[0], &args[1..], ys));
+}
+
+/// Split arguments of aggregation function, given as values followed by their number, into values.
+/// If number of values is not valid, an error message is stored in string contained in Result output
+fn split_aggregate_args(args: &[f64]) -> Result<&[f64], String> {
+    match args.split_last() {
+        Some((&nb_values, values)) if nb_values as usize == values.len() && !values.is_empty() => {
+            Ok(values)
+        }
+        _ => Err(String::from("Wrong number of arguments given to function")),
+    }
+}
+
+/// Variance of population made of values given in argument, which must not be empty
+fn variance(values: &[f64]) -> f64 {
+    let mean: f64 = values.iter().sum::<f64>() / values.len() as f64;
+    return values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / values.len() as f64;
+}
+
+/// Median of values given in argument, which must not be empty.
+/// For an even number of values, this is the mean of the two middle values
+fn median(values: &[f64]) -> f64 {
+    let mut sorted: Vec<f64> = values.to_vec();
+    sorted.sort_unstable_by(f64::total_cmp);
+
+    let middle: usize = sorted.len() / 2;
+
+    match sorted.len().is_multiple_of(2) {
+        true => (sorted[middle - 1] + sorted[middle]) / 2.0,
+        false => sorted[middle],
+    }
+}
+
+/// Check that breakpoints of lookup function whose name is given in argument are valid,
+/// i.e. arrays of abscissas and ordinates are not empty, have same length and abscissas are strictly increasing.
+/// If breakpoints are not valid, an error message is stored in string contained in Result output
+fn check_breakpoints(name: &str, xs: &[f64], ys: &[f64]) -> Result<(), String> {
+    if xs.len() != ys.len() || xs.is_empty() {
+        return Err(format!(
+            "Breakpoint arrays of {name} function are empty or have different lengths"
+        ));
+    }
+
+    if xs.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(format!(
+            "Abscissas of {name} function are not strictly increasing"
+        ));
+    }
+
+    return Ok(());
+}
+
+/// Piecewise-linear interpolation of value over valid breakpoints given by arrays of abscissas and ordinates.
+/// Outside of breakpoints, the result is clamped to first or last ordinate,
+/// or linearly extrapolated from first or last segment if extrapolate is true
+fn interpolate(x: f64, xs: &[f64], ys: &[f64], extrapolate: bool) -> f64 {
+    let last: usize = xs.len() - 1;
+
+    if last == 0 {
+        return ys[0];
+    }
+
+    if !extrapolate && x <= xs[0] {
+        return ys[0];
+    }
+
+    if !extrapolate && x >= xs[last] {
+        return ys[last];
+    }
+
+    // Index of segment containing value, first or last segment being used outside of breakpoints
+    let segment: usize = xs[1..last].partition_point(|&breakpoint| breakpoint < x);
+    let slope: f64 = (ys[segment + 1] - ys[segment]) / (xs[segment + 1] - xs[segment]);
+
+    return ys[segment] + slope * (x - xs[segment]);
+}
+
+/// Natural cubic spline interpolation of value over valid breakpoints given by arrays of abscissas and ordinates.
+/// Outside of breakpoints, the result is clamped to first or last ordinate
+fn spline_interpolate(x: f64, xs: &[f64], ys: &[f64]) -> f64 {
+    let last: usize = xs.len() - 1;
+
+    if last < 2 || x <= xs[0] || x >= xs[last] {
+        return interpolate(x, xs, ys, false);
+    }
+
+    // Second derivatives at breakpoints, null at both ends for a natural spline,
+    // are solution of a tridiagonal system solved by Thomas algorithm
+    let mut second_derivatives: Vec<f64> = vec![0.0; last + 1];
+    let mut diagonals: Vec<f64> = vec![0.0; last];
+    let mut rights: Vec<f64> = vec![0.0; last];
+
+    for i in 1..last {
+        let (h_left, h_right): (f64, f64) = (xs[i] - xs[i - 1], xs[i + 1] - xs[i]);
+        let right: f64 = 6.0 * ((ys[i + 1] - ys[i]) / h_right - (ys[i] - ys[i - 1]) / h_left);
+
+        // Elimination of sub-diagonal using previous row
+        let factor: f64 = if i > 1 {
+            h_left / diagonals[i - 1]
+        } else {
+            0.0
+        };
+        diagonals[i] = 2.0 * (h_left + h_right) - factor * h_left;
+        rights[i] = right - factor * rights[i - 1];
+    }
+
+    for i in (1..last).rev() {
+        let h_right: f64 = xs[i + 1] - xs[i];
+        second_derivatives[i] = (rights[i] - h_right * second_derivatives[i + 1]) / diagonals[i];
+    }
+
+    let segment: usize = xs[1..last].partition_point(|&breakpoint| breakpoint < x);
+    let h: f64 = xs[segment + 1] - xs[segment];
+    let (a, b): (f64, f64) = ((xs[segment + 1] - x) / h, (x - xs[segment]) / h);
+
+    return a * ys[segment]
+        + b * ys[segment + 1]
+        + ((a * a * a - a) * second_derivatives[segment]
+            + (b * b * b - b) * second_derivatives[segment + 1])
+            * h
+            * h
+            / 6.0;
+}
+
+/// Check if two values are valid arguments of combinatorics function,
+/// i.e. two whole numbers with 0 <= k <= n
+fn is_valid_combinatorics_args(n: f64, k: f64) -> bool {
+    return is_whole_number(n) && is_whole_number(k) && 0.0 <= k && k <= n;
+}
+
+/// Number of k-combinations from n elements.
+/// The binomial coefficient is computed incrementally, alternating multiplication and division,
+/// to avoid overflow of intermediate factorials. Coefficient is at least 2^k for the smallest of k and n - k,
+/// so it overflows after about a thousand factors, where computation stops
+fn combinations(n: f64, k: f64) -> f64 {
+    let k: f64 = k.min(n - k);
+    let mut result: f64 = 1.0;
+
+    // Counter is an integer since f64 counter stops increasing beyond 2^53
+    for i in 1..=(k as u64) {
+        result = result * (n - k + i as f64) / i as f64;
+
+        if result.is_infinite() {
+            return result;
+        }
+    }
+
+    return result.round();
+}
+
+/// Number of k-permutations from n elements.
+/// Only the k last factors of n! are multiplied to avoid overflow of intermediate factorials.
+/// Product is at least k!, so it overflows after about 170 factors, where computation stops
+fn permutations(n: f64, k: f64) -> f64 {
+    let mut result: f64 = 1.0;
+
+    // Counter is an integer since f64 counter stops increasing beyond 2^53
+    for i in 0..(k as u64) {
+        result *= n - i as f64;
+
+        if result.is_infinite() {
+            return result;
+        }
+    }
+
+    return result;
 }
 
 impl Function {
@@ -47,40 +336,321 @@ impl Function {
             "asinh" => Ok(Function::Asinh),
             "acosh" => Ok(Function::Acosh),
             "atanh" => Ok(Function::Atanh),
+            "clamp" => Ok(Function::Clamp),
+            "lerp" => Ok(Function::Lerp),
+            "select" => Ok(Function::Select),
+            "if" => Ok(Function::If),
+            "piecewise" => Ok(Function::Piecewise),
+            "gcd" => Ok(Function::Gcd),
+            "lcm" => Ok(Function::Lcm),
+            "ncr" => Ok(Function::Ncr),
+            "npr" => Ok(Function::Npr),
+            "deg2rad" => Ok(Function::Deg2Rad),
+            "rad" => Ok(Function::Deg2Rad),
+            "rad2deg" => Ok(Function::Rad2Deg),
+            "deg" => Ok(Function::Rad2Deg),
+            "round" => Ok(Function::Round),
+            "floorto" => Ok(Function::FloorTo),
+            "ceilto" => Ok(Function::CeilTo),
+            "lookup" => Ok(Function::Lookup),
+            "lookup_extrap" => Ok(Function::LookupExtrap),
+            "spline" => Ok(Function::Spline),
+            "sum" => Ok(Function::Sum),
+            "mean" => Ok(Function::Mean),
+            "median" => Ok(Function::Median),
+            "std" => Ok(Function::Std),
+            "var" => Ok(Function::Var),
+            "min" => Ok(Function::Min),
+            "max" => Ok(Function::Max),
             _ => Err(String::from("Unknown function string")),
         }
     }
 
+    /// Name of function used in expressions
+    pub fn name(&self) -> &'static str {
+        match self {
+            Function::Abs => "abs",
+            Function::Sqrt => "sqrt",
+            Function::Cbrt => "cbrt",
+            Function::Exp => "exp",
+            Function::Ln => "ln",
+            Function::Log10 => "log10",
+            Function::Log2 => "log2",
+            Function::Sin => "sin",
+            Function::Cos => "cos",
+            Function::Tan => "tan",
+            Function::Asin => "asin",
+            Function::Acos => "acos",
+            Function::Atan => "atan",
+            Function::Sinh => "sinh",
+            Function::Cosh => "cosh",
+            Function::Tanh => "tanh",
+            Function::Asinh => "asinh",
+            Function::Acosh => "acosh",
+            Function::Atanh => "atanh",
+            Function::Clamp => "clamp",
+            Function::Lerp => "lerp",
+            Function::Select => "select",
+            Function::If => "if",
+            Function::Piecewise => "piecewise",
+            Function::Gcd => "gcd",
+            Function::Lcm => "lcm",
+            Function::Ncr => "ncr",
+            Function::Npr => "npr",
+            Function::Deg2Rad => "deg2rad",
+            Function::Rad2Deg => "rad2deg",
+            Function::Round => "round",
+            Function::FloorTo => "floorto",
+            Function::CeilTo => "ceilto",
+            Function::Lookup => "lookup",
+            Function::LookupExtrap => "lookup_extrap",
+            Function::Spline => "spline",
+            Function::Sum => "sum",
+            Function::Mean => "mean",
+            Function::Median => "median",
+            Function::Std => "std",
+            Function::Var => "var",
+            Function::Min => "min",
+            Function::Max => "max",
+        }
+    }
+
+    /// Names of available functions
+    pub const NAMES: [&str; 45] = [
+        "abs",
+        "sqrt",
+        "cbrt",
+        "exp",
+        "ln",
+        "log10",
+        "log2",
+        "sin",
+        "cos",
+        "tan",
+        "asin",
+        "acos",
+        "atan",
+        "sinh",
+        "cosh",
+        "tanh",
+        "asinh",
+        "acosh",
+        "atanh",
+        "clamp",
+        "lerp",
+        "select",
+        "if",
+        "piecewise",
+        "gcd",
+        "lcm",
+        "ncr",
+        "npr",
+        "deg2rad",
+        "rad",
+        "rad2deg",
+        "deg",
+        "round",
+        "floorto",
+        "ceilto",
+        "lookup",
+        "lookup_extrap",
+        "spline",
+        "sum",
+        "mean",
+        "median",
+        "std",
+        "var",
+        "min",
+        "max",
+    ];
+
     /// Check if a string corresponds to function
     pub fn is_fun(fun: &str) -> bool {
-        match fun {
-            "abs" => true,
-            "sqrt" => true,
-            "cbrt" => true,
-            "exp" => true,
-            "ln" => true,
-            "log10" => true,
-            "log2" => true,
-            "sin" => true,
-            "cos" => true,
-            "tan" => true,
-            "asin" => true,
-            "acos" => true,
-            "atan" => true,
-            "sinh" => true,
-            "cosh" => true,
-            "tanh" => true,
-            "asinh" => true,
-            "acosh" => true,
-            "atanh" => true,
-            _ => false,
+        return Function::NAMES.contains(&fun);
+    }
+
+    /// Check if function is converted like conditional operator, so only the value taken is evaluated
+    pub fn is_conditional(&self) -> bool {
+        matches!(self, Function::If | Function::Piecewise)
+    }
+
+    /// Check if function is a lookup function, taking a value and two breakpoint arrays
+    pub fn is_lookup(&self) -> bool {
+        matches!(
+            self,
+            Function::Lookup | Function::LookupExtrap | Function::Spline
+        )
+    }
+
+    /// Check if function is an aggregation function, taking any number of arguments like mean(1, 4, 7)
+    pub fn is_variadic(&self) -> bool {
+        matches!(
+            self,
+            Function::Sum
+                | Function::Mean
+                | Function::Median
+                | Function::Std
+                | Function::Var
+                | Function::Min
+                | Function::Max
+        )
+    }
+
+    /// Number of arguments expected by the function.
+    /// For lookup functions, each breakpoint array counts as a single argument,
+    /// and for piecewise function, which takes any odd number of arguments, and aggregation functions,
+    /// which take any number of arguments, this is the least number.
+    pub fn arity(&self) -> usize {
+        match self {
+            Function::Clamp => 3,
+            Function::Lookup => 3,
+            Function::LookupExtrap => 3,
+            Function::Spline => 3,
+            Function::Lerp => 3,
+            Function::Select => 3,
+            Function::If => 3,
+            Function::Piecewise => 3,
+            Function::Gcd => 2,
+            Function::Lcm => 2,
+            Function::Ncr => 2,
+            Function::Npr => 2,
+            Function::Round => 2,
+            Function::FloorTo => 2,
+            Function::CeilTo => 2,
+            _ => 1,
         }
     }
 
-    /// Apply the function on value given in argument.
-    /// For limits cases, we check that value is valid.
+    /// Number of values taken by the function at the top of operand stack given in argument.
+    /// This is the arity of function, except for lookup functions whose breakpoint arrays
+    /// are stored in stack as their values followed by their length, and for aggregation functions
+    /// whose arguments are stored in stack followed by their number.
+    /// If stack does not contain enough values, an error message is stored
+    /// in string contained in Result output
+    pub fn arity_on_stack(&self, stack: &[f64]) -> Result<usize, String> {
+        let missing: String = String::from("Missing argument to apply function");
+
+        if self.is_variadic() {
+            let arity: usize = *stack.last().ok_or(missing.clone())? as usize + 1;
+
+            if stack.len() < arity {
+                return Err(missing);
+            }
+
+            return Ok(arity);
+        }
+
+        if !self.is_lookup() {
+            if stack.len() < self.arity() {
+                return Err(missing);
+            }
+
+            return Ok(self.arity());
+        }
+
+        let ny: usize = *stack.last().ok_or(missing.clone())? as usize;
+
+        if stack.len() < ny + 2 {
+            return Err(missing);
+        }
+
+        let nx: usize = stack[stack.len() - ny - 2] as usize;
+        let arity: usize = nx + ny + 3;
+
+        if stack.len() < arity {
+            return Err(missing);
+        }
+
+        return Ok(arity);
+    }
+
+    /// Apply function having a checked domain on argument given in argument without checking it.
+    /// None is given for other functions
+    fn apply_unchecked(&self, arg: f64) -> Option<f64> {
+        match self {
+            Function::Sqrt => Some(arg.sqrt()),
+            Function::Ln => Some(arg.ln()),
+            Function::Log10 => Some(arg.log10()),
+            Function::Log2 => Some(arg.log2()),
+            Function::Tan => Some(arg.tan()),
+            Function::Asin => Some(arg.asin()),
+            Function::Acos => Some(arg.acos()),
+            _ => None,
+        }
+    }
+
+    /// Apply the function on values given in argument.
+    /// The number of values must correspond to arity of function,
+    /// breakpoint arrays of lookup functions being given as their values followed by their length.
+    /// For limits cases, we check that values are valid.
     /// To take into account this error, the function return a Result<f64, String>
-    pub fn apply(&self, arg: f64) -> Result<f64, String> {
+    pub fn apply(&self, args: &[f64]) -> Result<f64, String> {
+        return self.apply_in_mode(args, DomainMode::Strict);
+    }
+
+    /// Apply the function on values given in argument like apply_in_mode method, angles of
+    /// trigonometric functions being in unit given in argument: in degrees, sin(90) gives 1 and asin(1) gives 90
+    pub fn apply_with_angle(
+        &self,
+        args: &[f64],
+        mode: DomainMode,
+        angle: AngleUnit,
+    ) -> Result<f64, String> {
+        if angle == AngleUnit::Degrees && args.len() == 1 {
+            match self {
+                Function::Sin | Function::Cos | Function::Tan => {
+                    return self.apply_in_mode(&[args[0].to_radians()], mode);
+                }
+                Function::Asin | Function::Acos | Function::Atan => {
+                    return Ok(self.apply_in_mode(args, mode)?.to_degrees());
+                }
+                _ => (),
+            }
+        }
+
+        return self.apply_in_mode(args, mode);
+    }
+
+    /// Apply the function on values given in argument like apply method,
+    /// domains of functions being checked according to mode given in argument.
+    /// In lenient mode, an argument outside of domain of function gives the IEEE result, like NaN
+    pub fn apply_in_mode(&self, args: &[f64], mode: DomainMode) -> Result<f64, String> {
+        if self.is_lookup() {
+            let (x, xs, ys) = split_lookup_args(args)?;
+            check_breakpoints(self.name(), xs, ys)?;
+
+            match self {
+                Function::Spline => return Ok(spline_interpolate(x, xs, ys)),
+                _ => return Ok(interpolate(x, xs, ys, *self == Function::LookupExtrap)),
+            }
+        }
+
+        if self.is_variadic() {
+            let values: &[f64] = split_aggregate_args(args)?;
+
+            match self {
+                Function::Sum => return Ok(values.iter().sum()),
+                Function::Mean => return Ok(values.iter().sum::<f64>() / values.len() as f64),
+                Function::Median => return Ok(median(values)),
+                Function::Std => return Ok(variance(values).sqrt()),
+                Function::Var => return Ok(variance(values)),
+                Function::Min => return Ok(values.iter().copied().fold(f64::INFINITY, f64::min)),
+                _ => return Ok(values.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+            }
+        }
+
+        if args.len() != self.arity() {
+            return Err(String::from("Wrong number of arguments given to function"));
+        }
+
+        let arg: f64 = args[0];
+
+        if mode == DomainMode::Lenient {
+            if let Some(value) = self.apply_unchecked(arg) {
+                return Ok(value);
+            }
+        }
+
         match self {
             Function::Abs => Ok(arg.abs()),
             Function::Sqrt => {
@@ -154,6 +724,89 @@ impl Function {
             Function::Asinh => Ok(arg.asinh()),
             Function::Acosh => Ok(arg.acosh()),
             Function::Atanh => Ok(arg.atanh()),
+            Function::Clamp => {
+                let (lower, upper): (f64, f64) = (args[1], args[2]);
+
+                if lower <= upper {
+                    return Ok(arg.clamp(lower, upper));
+                } else {
+                    return Err(String::from(
+                        "Lower bound of clamp function is greater than upper bound",
+                    ));
+                }
+            }
+            Function::Lerp => Ok(arg + (args[1] - arg) * args[2]),
+            Function::Select | Function::If | Function::Piecewise => match is_true(arg) {
+                true => Ok(args[1]),
+                false => Ok(args[2]),
+            },
+            Function::Gcd => {
+                if is_whole_number(arg) && is_whole_number(args[1]) {
+                    return Ok(greatest_common_divisor(arg, args[1]));
+                } else {
+                    return Err(String::from(
+                        "Arguments of gcd function are not whole numbers",
+                    ));
+                }
+            }
+            Function::Lcm => {
+                if is_whole_number(arg) && is_whole_number(args[1]) {
+                    if arg == 0.0 || args[1] == 0.0 {
+                        return Ok(0.0);
+                    }
+
+                    let gcd: f64 = greatest_common_divisor(arg, args[1]);
+                    return Ok((arg / gcd * args[1]).abs());
+                } else {
+                    return Err(String::from(
+                        "Arguments of lcm function are not whole numbers",
+                    ));
+                }
+            }
+            Function::Ncr => {
+                if is_valid_combinatorics_args(arg, args[1]) {
+                    return Ok(combinations(arg, args[1]));
+                } else {
+                    return Err(String::from(
+                        "Arguments of ncr function are not whole numbers with 0 <= k <= n",
+                    ));
+                }
+            }
+            Function::Npr => {
+                if is_valid_combinatorics_args(arg, args[1]) {
+                    return Ok(permutations(arg, args[1]));
+                } else {
+                    return Err(String::from(
+                        "Arguments of npr function are not whole numbers with 0 <= k <= n",
+                    ));
+                }
+            }
+            Function::Deg2Rad => Ok(arg.to_radians()),
+            Function::Rad2Deg => Ok(arg.to_degrees()),
+            Function::Round | Function::FloorTo | Function::CeilTo => {
+                let digits: f64 = args[1];
+
+                if !is_whole_number(digits) || digits.abs() > 300.0 {
+                    return Err(format!(
+                        "Number of digits of {} function is not a whole number in [-300, 300]",
+                        self.name()
+                    ));
+                }
+
+                match self {
+                    Function::Round => Ok(round_to_digits(arg, digits, f64::round)),
+                    Function::FloorTo => Ok(round_to_digits(arg, digits, f64::floor)),
+                    _ => Ok(round_to_digits(arg, digits, f64::ceil)),
+                }
+            }
+            Function::Lookup | Function::LookupExtrap | Function::Spline => unreachable!(),
+            Function::Sum
+            | Function::Mean
+            | Function::Median
+            | Function::Std
+            | Function::Var
+            | Function::Min
+            | Function::Max => unreachable!(),
         }
     }
 }
@@ -296,6 +949,67 @@ mod tests {
         assert_eq!(res.unwrap(), Function::Atanh);
     }
 
+    #[test]
+    fn test_function_from_clamp_string() {
+        let res: Result<Function, String> = Function::from_string("clamp");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), Function::Clamp);
+    }
+
+    #[test]
+    fn test_function_from_lerp_string() {
+        let res: Result<Function, String> = Function::from_string("lerp");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), Function::Lerp);
+    }
+
+    #[test]
+    fn test_function_from_gcd_string() {
+        let res: Result<Function, String> = Function::from_string("gcd");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), Function::Gcd);
+    }
+
+    #[test]
+    fn test_function_from_lcm_string() {
+        let res: Result<Function, String> = Function::from_string("lcm");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), Function::Lcm);
+    }
+
+    #[test]
+    fn test_function_from_ncr_string() {
+        let res: Result<Function, String> = Function::from_string("ncr");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), Function::Ncr);
+    }
+
+    #[test]
+    fn test_function_from_npr_string() {
+        let res: Result<Function, String> = Function::from_string("npr");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), Function::Npr);
+    }
+
+    #[test]
+    fn test_function_from_deg2rad_string() {
+        assert_eq!(Function::from_string("deg2rad"), Ok(Function::Deg2Rad));
+        assert_eq!(Function::from_string("rad"), Ok(Function::Deg2Rad));
+    }
+
+    #[test]
+    fn test_function_from_rad2deg_string() {
+        assert_eq!(Function::from_string("rad2deg"), Ok(Function::Rad2Deg));
+        assert_eq!(Function::from_string("deg"), Ok(Function::Rad2Deg));
+    }
+
+    #[test]
+    fn test_function_from_round_string() {
+        assert_eq!(Function::from_string("round"), Ok(Function::Round));
+        assert_eq!(Function::from_string("floorto"), Ok(Function::FloorTo));
+        assert_eq!(Function::from_string("ceilto"), Ok(Function::CeilTo));
+    }
+
     #[test]
     fn test_function_from_unknown_string() {
         let res: Result<Function, String> = Function::from_string("toto");
@@ -324,6 +1038,19 @@ mod tests {
         assert!(Function::is_fun("asinh"));
         assert!(Function::is_fun("acosh"));
         assert!(Function::is_fun("atanh"));
+        assert!(Function::is_fun("clamp"));
+        assert!(Function::is_fun("lerp"));
+        assert!(Function::is_fun("gcd"));
+        assert!(Function::is_fun("lcm"));
+        assert!(Function::is_fun("ncr"));
+        assert!(Function::is_fun("npr"));
+        assert!(Function::is_fun("deg2rad"));
+        assert!(Function::is_fun("rad"));
+        assert!(Function::is_fun("rad2deg"));
+        assert!(Function::is_fun("deg"));
+        assert!(Function::is_fun("round"));
+        assert!(Function::is_fun("floorto"));
+        assert!(Function::is_fun("ceilto"));
         assert!(!Function::is_fun("bunny"));
     }
 
@@ -331,7 +1058,7 @@ mod tests {
     fn test_function_apply_abs() {
         let fun: Function = Function::Abs;
 
-        let res: Result<f64, String> = fun.apply(-2.0);
+        let res: Result<f64, String> = fun.apply(&[-2.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 2.0);
     }
@@ -340,11 +1067,11 @@ mod tests {
     fn test_function_apply_sqrt() {
         let fun: Function = Function::Sqrt;
 
-        let res: Result<f64, String> = fun.apply(4.0);
+        let res: Result<f64, String> = fun.apply(&[4.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 2.0);
 
-        let res_in_err: Result<f64, String> = fun.apply(-4.0);
+        let res_in_err: Result<f64, String> = fun.apply(&[-4.0]);
         assert!(res_in_err.is_err());
         assert_eq!(
             res_in_err.err(),
@@ -356,7 +1083,7 @@ mod tests {
     fn test_function_apply_cbrt() {
         let fun: Function = Function::Cbrt;
 
-        let res: Result<f64, String> = fun.apply(-8.0);
+        let res: Result<f64, String> = fun.apply(&[-8.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), -2.0);
     }
@@ -365,7 +1092,7 @@ mod tests {
     fn test_function_apply_exp() {
         let fun: Function = Function::Exp;
 
-        let res: Result<f64, String> = fun.apply(0.0);
+        let res: Result<f64, String> = fun.apply(&[0.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 1.0);
     }
@@ -374,11 +1101,11 @@ mod tests {
     fn test_function_apply_ln() {
         let fun: Function = Function::Ln;
 
-        let res: Result<f64, String> = fun.apply(1.0);
+        let res: Result<f64, String> = fun.apply(&[1.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 0.0);
 
-        let res_in_err: Result<f64, String> = fun.apply(-4.0);
+        let res_in_err: Result<f64, String> = fun.apply(&[-4.0]);
         assert!(res_in_err.is_err());
         assert_eq!(
             res_in_err.err(),
@@ -390,11 +1117,11 @@ mod tests {
     fn test_function_apply_log10() {
         let fun: Function = Function::Log10;
 
-        let res: Result<f64, String> = fun.apply(10.0);
+        let res: Result<f64, String> = fun.apply(&[10.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 1.0);
 
-        let res_in_err: Result<f64, String> = fun.apply(-4.0);
+        let res_in_err: Result<f64, String> = fun.apply(&[-4.0]);
         assert!(res_in_err.is_err());
         assert_eq!(
             res_in_err.err(),
@@ -408,11 +1135,11 @@ mod tests {
     fn test_function_apply_log2() {
         let fun: Function = Function::Log2;
 
-        let res: Result<f64, String> = fun.apply(2.0);
+        let res: Result<f64, String> = fun.apply(&[2.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 1.0);
 
-        let res_in_err: Result<f64, String> = fun.apply(-4.0);
+        let res_in_err: Result<f64, String> = fun.apply(&[-4.0]);
         assert!(res_in_err.is_err());
         assert_eq!(
             res_in_err.err(),
@@ -426,7 +1153,7 @@ mod tests {
     fn test_function_apply_sin() {
         let fun: Function = Function::Sin;
 
-        let res: Result<f64, String> = fun.apply(0.0);
+        let res: Result<f64, String> = fun.apply(&[0.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 0.0);
     }
@@ -435,7 +1162,7 @@ mod tests {
     fn test_function_apply_cos() {
         let fun: Function = Function::Cos;
 
-        let res: Result<f64, String> = fun.apply(0.0);
+        let res: Result<f64, String> = fun.apply(&[0.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 1.0);
     }
@@ -444,11 +1171,11 @@ mod tests {
     fn test_function_apply_tan() {
         let fun: Function = Function::Tan;
 
-        let res: Result<f64, String> = fun.apply(0.0);
+        let res: Result<f64, String> = fun.apply(&[0.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 0.0);
 
-        let res_in_err: Result<f64, String> = fun.apply(std::f64::consts::FRAC_PI_2);
+        let res_in_err: Result<f64, String> = fun.apply(&[std::f64::consts::FRAC_PI_2]);
         assert!(res_in_err.is_err());
         assert_eq!(
             res_in_err.err(),
@@ -460,11 +1187,11 @@ mod tests {
     fn test_function_apply_asin() {
         let fun: Function = Function::Asin;
 
-        let res: Result<f64, String> = fun.apply(0.0);
+        let res: Result<f64, String> = fun.apply(&[0.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 0.0);
 
-        let res_in_err: Result<f64, String> = fun.apply(1.8);
+        let res_in_err: Result<f64, String> = fun.apply(&[1.8]);
         assert!(res_in_err.is_err());
         assert_eq!(
             res_in_err.err(),
@@ -478,11 +1205,11 @@ mod tests {
     fn test_function_apply_acos() {
         let fun: Function = Function::Acos;
 
-        let res: Result<f64, String> = fun.apply(1.0);
+        let res: Result<f64, String> = fun.apply(&[1.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 0.0);
 
-        let res_in_err: Result<f64, String> = fun.apply(1.8);
+        let res_in_err: Result<f64, String> = fun.apply(&[1.8]);
         assert!(res_in_err.is_err());
         assert_eq!(
             res_in_err.err(),
@@ -496,7 +1223,7 @@ mod tests {
     fn test_function_apply_atan() {
         let fun: Function = Function::Atan;
 
-        let res: Result<f64, String> = fun.apply(0.0);
+        let res: Result<f64, String> = fun.apply(&[0.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 0.0);
     }
@@ -505,7 +1232,7 @@ mod tests {
     fn test_function_apply_sinh() {
         let fun: Function = Function::Sinh;
 
-        let res: Result<f64, String> = fun.apply(0.0);
+        let res: Result<f64, String> = fun.apply(&[0.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 0.0);
     }
@@ -514,7 +1241,7 @@ mod tests {
     fn test_function_apply_cosh() {
         let fun: Function = Function::Cosh;
 
-        let res: Result<f64, String> = fun.apply(0.0);
+        let res: Result<f64, String> = fun.apply(&[0.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 1.0);
     }
@@ -523,7 +1250,7 @@ mod tests {
     fn test_function_apply_tanh() {
         let fun: Function = Function::Tanh;
 
-        let res: Result<f64, String> = fun.apply(0.0);
+        let res: Result<f64, String> = fun.apply(&[0.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 0.0);
     }
@@ -532,7 +1259,7 @@ mod tests {
     fn test_function_apply_asinh() {
         let fun: Function = Function::Asinh;
 
-        let res: Result<f64, String> = fun.apply(0.0);
+        let res: Result<f64, String> = fun.apply(&[0.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 0.0);
     }
@@ -541,7 +1268,7 @@ mod tests {
     fn test_function_apply_acosh() {
         let fun: Function = Function::Acosh;
 
-        let res: Result<f64, String> = fun.apply(1.0);
+        let res: Result<f64, String> = fun.apply(&[1.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 0.0);
     }
@@ -550,8 +1277,385 @@ mod tests {
     fn test_function_apply_atanh() {
         let fun: Function = Function::Atanh;
 
-        let res: Result<f64, String> = fun.apply(0.0);
+        let res: Result<f64, String> = fun.apply(&[0.0]);
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), 0.0);
     }
+
+    #[test]
+    fn test_function_name() {
+        assert_eq!(Function::Sqrt.name(), "sqrt");
+        assert_eq!(Function::Deg2Rad.name(), "deg2rad");
+
+        let fun: Function = Function::Log10;
+        assert_eq!(Function::from_string(fun.name()), Ok(fun));
+    }
+
+    #[test]
+    fn test_function_arity() {
+        assert_eq!(Function::Sqrt.arity(), 1);
+        assert_eq!(Function::Clamp.arity(), 3);
+        assert_eq!(Function::Lerp.arity(), 3);
+    }
+
+    #[test]
+    fn test_function_apply_wrong_number_of_arguments() {
+        let res_in_err: Result<f64, String> = Function::Sqrt.apply(&[4.0, 9.0]);
+        assert!(res_in_err.is_err());
+        assert_eq!(
+            res_in_err.err(),
+            Some(String::from("Wrong number of arguments given to function"))
+        );
+    }
+
+    #[test]
+    fn test_function_apply_clamp() {
+        let fun: Function = Function::Clamp;
+
+        assert_eq!(fun.apply(&[5.0, 0.0, 1.0]).unwrap(), 1.0);
+        assert_eq!(fun.apply(&[-5.0, 0.0, 1.0]).unwrap(), 0.0);
+        assert_eq!(fun.apply(&[0.5, 0.0, 1.0]).unwrap(), 0.5);
+
+        let res_in_err: Result<f64, String> = fun.apply(&[0.5, 1.0, 0.0]);
+        assert!(res_in_err.is_err());
+        assert_eq!(
+            res_in_err.err(),
+            Some(String::from(
+                "Lower bound of clamp function is greater than upper bound"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_function_apply_lerp() {
+        let fun: Function = Function::Lerp;
+
+        assert_eq!(fun.apply(&[2.0, 4.0, 0.0]).unwrap(), 2.0);
+        assert_eq!(fun.apply(&[2.0, 4.0, 1.0]).unwrap(), 4.0);
+        assert_eq!(fun.apply(&[2.0, 4.0, 0.25]).unwrap(), 2.5);
+    }
+
+    #[test]
+    fn test_function_apply_select() {
+        let fun: Function = Function::Select;
+
+        assert_eq!(Function::from_string("select"), Ok(Function::Select));
+        assert_eq!(fun.apply(&[0.5, 1.0, 2.0]).unwrap(), 1.0);
+        assert_eq!(fun.apply(&[0.0, 1.0, 2.0]).unwrap(), 2.0);
+        assert_eq!(fun.apply(&[-3.0, 1.0, 2.0]).unwrap(), 1.0);
+        assert_eq!(fun.apply(&[f64::NAN, 1.0, 2.0]).unwrap(), 2.0);
+    }
+
+    #[test]
+    fn test_function_apply_gcd() {
+        let fun: Function = Function::Gcd;
+
+        assert_eq!(fun.apply(&[12.0, 18.0]).unwrap(), 6.0);
+        assert_eq!(fun.apply(&[-12.0, 18.0]).unwrap(), 6.0);
+        assert_eq!(fun.apply(&[7.0, 0.0]).unwrap(), 7.0);
+        assert_eq!(fun.apply(&[0.0, 0.0]).unwrap(), 0.0);
+
+        let res_in_err: Result<f64, String> = fun.apply(&[12.5, 18.0]);
+        assert!(res_in_err.is_err());
+        assert_eq!(
+            res_in_err.err(),
+            Some(String::from(
+                "Arguments of gcd function are not whole numbers"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_function_apply_lcm() {
+        let fun: Function = Function::Lcm;
+
+        assert_eq!(fun.apply(&[4.0, 6.0]).unwrap(), 12.0);
+        assert_eq!(fun.apply(&[-4.0, 6.0]).unwrap(), 12.0);
+        assert_eq!(fun.apply(&[0.0, 6.0]).unwrap(), 0.0);
+
+        let res_in_err: Result<f64, String> = fun.apply(&[4.0, f64::INFINITY]);
+        assert!(res_in_err.is_err());
+        assert_eq!(
+            res_in_err.err(),
+            Some(String::from(
+                "Arguments of lcm function are not whole numbers"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_function_apply_ncr() {
+        let fun: Function = Function::Ncr;
+
+        assert_eq!(fun.apply(&[5.0, 2.0]).unwrap(), 10.0);
+        assert_eq!(fun.apply(&[5.0, 0.0]).unwrap(), 1.0);
+        assert_eq!(fun.apply(&[5.0, 5.0]).unwrap(), 1.0);
+        assert_eq!(fun.apply(&[52.0, 5.0]).unwrap(), 2598960.0);
+
+        // 200! overflows f64 but the binomial coefficient does not
+        let res: Result<f64, String> = fun.apply(&[200.0, 3.0]);
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), 1313400.0);
+
+        // Large arguments stop at overflow instead of multiplying every factor
+        assert_eq!(fun.apply(&[2000000000.0, 1000000000.0]), Ok(f64::INFINITY));
+        assert_eq!(fun.apply(&[1e18, 2.0]), Ok(5e35));
+        assert_eq!(fun.apply(&[1e300, 1e300]), Ok(1.0));
+
+        let res_in_err: Result<f64, String> = fun.apply(&[3.0, 5.0]);
+        assert!(res_in_err.is_err());
+        assert_eq!(
+            res_in_err.err(),
+            Some(String::from(
+                "Arguments of ncr function are not whole numbers with 0 <= k <= n"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_function_apply_npr() {
+        let fun: Function = Function::Npr;
+
+        assert_eq!(fun.apply(&[5.0, 2.0]).unwrap(), 20.0);
+        assert_eq!(fun.apply(&[5.0, 0.0]).unwrap(), 1.0);
+        assert_eq!(fun.apply(&[5.0, 5.0]).unwrap(), 120.0);
+
+        // 300! overflows f64 but the number of permutations does not
+        let res: Result<f64, String> = fun.apply(&[300.0, 2.0]);
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), 89700.0);
+
+        // Large arguments stop at overflow instead of multiplying every factor
+        assert_eq!(fun.apply(&[1000000000.0, 1000000000.0]), Ok(f64::INFINITY));
+        assert_eq!(fun.apply(&[1e18, 1e18]), Ok(f64::INFINITY));
+        assert_eq!(fun.apply(&[1e18, 1.0]), Ok(1e18));
+
+        let res_in_err: Result<f64, String> = fun.apply(&[5.0, 1.5]);
+        assert!(res_in_err.is_err());
+        assert_eq!(
+            res_in_err.err(),
+            Some(String::from(
+                "Arguments of npr function are not whole numbers with 0 <= k <= n"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_function_apply_deg2rad() {
+        let fun: Function = Function::Deg2Rad;
+
+        let res: Result<f64, String> = fun.apply(&[180.0]);
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), std::f64::consts::PI);
+    }
+
+    #[test]
+    fn test_function_apply_rad2deg() {
+        let fun: Function = Function::Rad2Deg;
+
+        let res: Result<f64, String> = fun.apply(&[std::f64::consts::FRAC_PI_2]);
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), 90.0);
+    }
+
+    #[test]
+    fn test_function_apply_round() {
+        let fun: Function = Function::Round;
+
+        assert_eq!(fun.apply(&[2.345, 2.0]).unwrap(), 2.35);
+        assert_eq!(fun.apply(&[-2.345, 1.0]).unwrap(), -2.3);
+        assert_eq!(fun.apply(&[2.5, 0.0]).unwrap(), 3.0);
+        assert_eq!(fun.apply(&[1234.5, -2.0]).unwrap(), 1200.0);
+        assert_eq!(fun.apply(&[1.005, 2.0]).unwrap(), 1.01);
+        assert_eq!(fun.apply(&[-0.125, 2.0]).unwrap(), -0.13);
+        assert_eq!(fun.apply(&[0.0004, 3.0]).unwrap(), 0.0);
+        assert_eq!(fun.apply(&[0.0005, 3.0]).unwrap(), 0.001);
+        assert_eq!(fun.apply(&[4.9, -1.0]).unwrap(), 0.0);
+        assert_eq!(fun.apply(&[5.1, -1.0]).unwrap(), 10.0);
+
+        // Value is unchanged when it has no more digits than kept, even for many digits
+        assert_eq!(fun.apply(&[10000000000.0, 300.0]).unwrap(), 10000000000.0);
+        assert_eq!(fun.apply(&[0.1, 300.0]).unwrap(), 0.1);
+        assert_eq!(fun.apply(&[f64::MAX, 2.0]).unwrap(), f64::MAX);
+        assert!(fun.apply(&[f64::NAN, 2.0]).unwrap().is_nan());
+
+        let res_in_err: Result<f64, String> = fun.apply(&[2.345, 1.5]);
+        assert!(res_in_err.is_err());
+        assert_eq!(
+            res_in_err.err(),
+            Some(String::from(
+                "Number of digits of round function is not a whole number in [-300, 300]"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_function_apply_floorto() {
+        let fun: Function = Function::FloorTo;
+
+        assert_eq!(fun.apply(&[2.349, 2.0]).unwrap(), 2.34);
+        assert_eq!(fun.apply(&[-2.341, 2.0]).unwrap(), -2.35);
+        assert_eq!(fun.apply(&[1299.0, -2.0]).unwrap(), 1200.0);
+        assert_eq!(fun.apply(&[2.3, 2.0]).unwrap(), 2.3);
+        assert_eq!(fun.apply(&[4.35, 2.0]).unwrap(), 4.35);
+        assert_eq!(fun.apply(&[-4.35, 1.0]).unwrap(), -4.4);
+        assert_eq!(fun.apply(&[0.07, 1.0]).unwrap(), 0.0);
+        assert_eq!(fun.apply(&[-0.07, 1.0]).unwrap(), -0.1);
+        assert_eq!(fun.apply(&[1e-300, 300.0]).unwrap(), 1e-300);
+        assert!(fun.apply(&[2.345, 1000.0]).is_err());
+    }
+
+    #[test]
+    fn test_function_apply_ceilto() {
+        let fun: Function = Function::CeilTo;
+
+        assert_eq!(fun.apply(&[2.341, 2.0]).unwrap(), 2.35);
+        assert_eq!(fun.apply(&[-2.349, 2.0]).unwrap(), -2.34);
+        assert_eq!(fun.apply(&[1201.0, -2.0]).unwrap(), 1300.0);
+        assert_eq!(fun.apply(&[0.29, 1.0]).unwrap(), 0.3);
+        assert_eq!(fun.apply(&[1.1, 1.0]).unwrap(), 1.1);
+        assert_eq!(fun.apply(&[1e-10, -300.0]).unwrap(), 1e300);
+        assert!(fun.apply(&[2.345, f64::NAN]).is_err());
+    }
+
+    #[test]
+    fn test_function_from_lookup_string() {
+        assert_eq!(Function::from_string("lookup"), Ok(Function::Lookup));
+        assert_eq!(
+            Function::from_string("lookup_extrap"),
+            Ok(Function::LookupExtrap)
+        );
+        assert!(Function::is_fun("lookup"));
+        assert!(Function::is_fun("lookup_extrap"));
+    }
+
+    #[test]
+    fn test_function_arity_on_stack() {
+        // 5, [0, 10], [1, 2, 3]
+        let stack: Vec<f64> = vec![7.0, 5.0, 0.0, 10.0, 2.0, 1.0, 2.0, 3.0, 3.0];
+
+        assert_eq!(Function::Lookup.arity_on_stack(&stack), Ok(8));
+        assert_eq!(Function::Clamp.arity_on_stack(&stack), Ok(3));
+        assert!(Function::Lookup.arity_on_stack(&stack[3..]).is_err());
+        assert!(Function::Clamp.arity_on_stack(&stack[..2]).is_err());
+    }
+
+    #[test]
+    fn test_function_apply_lookup() {
+        let fun: Function = Function::Lookup;
+        let table = |x: f64| vec![x, 0.0, 10.0, 20.0, 3.0, 1.0, 2.0, 4.0, 3.0];
+
+        assert_eq!(fun.apply(&table(5.0)).unwrap(), 1.5);
+        assert_eq!(fun.apply(&table(15.0)).unwrap(), 3.0);
+        assert_eq!(fun.apply(&table(10.0)).unwrap(), 2.0);
+        assert_eq!(fun.apply(&table(-5.0)).unwrap(), 1.0);
+        assert_eq!(fun.apply(&table(25.0)).unwrap(), 4.0);
+
+        let fun: Function = Function::LookupExtrap;
+
+        assert_eq!(fun.apply(&table(-5.0)).unwrap(), 0.5);
+        assert_eq!(fun.apply(&table(25.0)).unwrap(), 5.0);
+    }
+
+    #[test]
+    fn test_function_apply_aggregation() {
+        // Values are followed by their number
+        let values: [f64; 5] = [5.0, 3.0, 1.0, 3.0, 4.0];
+
+        assert_eq!(Function::Sum.apply(&values).unwrap(), 12.0);
+        assert_eq!(Function::Mean.apply(&values).unwrap(), 3.0);
+        assert_eq!(Function::Median.apply(&values).unwrap(), 3.0);
+        assert_eq!(Function::Median.apply(&[9.0, 2.0, 4.0, 3.0]).unwrap(), 4.0);
+        assert_eq!(Function::Var.apply(&values).unwrap(), 2.0);
+        assert_eq!(Function::Std.apply(&values).unwrap(), 2.0_f64.sqrt());
+        assert_eq!(Function::Min.apply(&values).unwrap(), 1.0);
+        assert_eq!(Function::Max.apply(&values).unwrap(), 5.0);
+
+        assert!(Function::Sum.apply(&[1.0, 2.0, 3.0]).is_err());
+        assert!(Function::Max.apply(&[0.0]).is_err());
+    }
+
+    #[test]
+    fn test_function_apply_lookup_invalid_breakpoints() {
+        let fun: Function = Function::Lookup;
+
+        assert_eq!(
+            fun.apply(&[1.0, 2.0, 1.0, 2.0, 1.0, 2.0, 2.0]),
+            Err(String::from(
+                "Abscissas of lookup function are not strictly increasing"
+            ))
+        );
+        assert_eq!(
+            fun.apply(&[1.0, 0.0, 1.0, 2.0, 1.0, 1.0]),
+            Err(String::from(
+                "Breakpoint arrays of lookup function are empty or have different lengths"
+            ))
+        );
+        assert!(fun.apply(&[1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn test_function_apply_spline() {
+        let fun: Function = Function::Spline;
+        let table = |x: f64| vec![x, 0.0, 1.0, 2.0, 3.0, 4.0, 0.0, 1.0, 4.0, 9.0, 4.0];
+
+        assert_eq!(Function::from_string("spline"), Ok(Function::Spline));
+        assert_eq!(fun.apply(&table(1.0)).unwrap(), 1.0);
+        assert_eq!(fun.apply(&table(-1.0)).unwrap(), 0.0);
+        assert_eq!(fun.apply(&table(4.0)).unwrap(), 9.0);
+
+        // Natural spline through 0, 1, 4, 9 with null second derivative at both ends
+        assert!((fun.apply(&table(1.5)).unwrap() - 2.2).abs() < 1e-12);
+
+        // Spline over two breakpoints is a linear interpolation
+        assert_eq!(
+            fun.apply(&[0.5, 0.0, 1.0, 2.0, 1.0, 3.0, 2.0]).unwrap(),
+            2.0
+        );
+
+        assert_eq!(
+            fun.apply(&[1.0, 2.0, 1.0, 2.0, 1.0, 2.0, 2.0]),
+            Err(String::from(
+                "Abscissas of spline function are not strictly increasing"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_function_apply_with_angle() {
+        let degrees = |fun: Function, arg: f64| -> f64 {
+            return fun
+                .apply_with_angle(&[arg], DomainMode::Strict, AngleUnit::Degrees)
+                .unwrap_or(f64::NAN);
+        };
+
+        assert!((degrees(Function::Sin, 30.0) - 0.5).abs() < 1e-12);
+        assert!((degrees(Function::Tan, 45.0) - 1.0).abs() < 1e-12);
+        assert!((degrees(Function::Acos, 0.5) - 60.0).abs() < 1e-12);
+        assert_eq!(degrees(Function::Sinh, 0.0), 0.0);
+        assert_eq!(
+            Function::Sin.apply_with_angle(&[1.0], DomainMode::Strict, AngleUnit::Radians),
+            Ok(1.0_f64.sin())
+        );
+        assert!(Function::Asin
+            .apply_with_angle(&[2.0], DomainMode::Strict, AngleUnit::Degrees)
+            .is_err());
+    }
+
+    #[test]
+    fn test_function_apply_in_lenient_mode() {
+        assert!(Function::Sqrt
+            .apply_in_mode(&[-1.0], DomainMode::Lenient)
+            .is_ok_and(|value| value.is_nan()));
+        assert_eq!(
+            Function::Log2.apply_in_mode(&[0.0], DomainMode::Lenient),
+            Ok(f64::NEG_INFINITY)
+        );
+        assert!(Function::Acos
+            .apply_in_mode(&[2.0], DomainMode::Strict)
+            .is_err());
+        assert!(Function::Clamp
+            .apply_in_mode(&[0.0, 1.0, -1.0], DomainMode::Lenient)
+            .is_err());
+    }
 }
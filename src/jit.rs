@@ -0,0 +1,574 @@
+#![allow(clippy::needless_return)]
+
+use super::compiler::{CompiledExpression, Instruction};
+use super::functions::Function;
+use super::operators::BinaryOperator;
+
+use cranelift_codegen::ir::condcodes::FloatCC;
+use cranelift_codegen::ir::{
+    types, AbiParam, Block, BlockArg, InstBuilder, MemFlagsData, Signature, StackSlotData,
+    StackSlotKind, Value,
+};
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
+
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, FuncId, Linkage, Module};
+use std::collections::HashMap;
+
+/// Signature of native function generated for an expression.
+/// It takes pointers on values of variables and on error flag, and returns value of expression
+type NativeFunction = unsafe extern "C" fn(*const f64, *mut u8) -> f64;
+
+/// Power operation called by native code
+extern "C" fn taz_power(left: f64, right: f64) -> f64 {
+    return left.powf(right);
+}
+
+/// Function application called by native code.
+/// If error occurs, the error flag is set and NaN is returned
+unsafe extern "C" fn taz_apply_function(
+    fun: *const Function,
+    args: *const f64,
+    arity: u64,
+    error: *mut u8,
+) -> f64 {
+    let fun: Function = *fun;
+    let args: &[f64] = std::slice::from_raw_parts(args, arity as usize);
+
+    match fun.apply(args) {
+        Ok(value) => value,
+        Err(_) => {
+            *error = 1;
+            return f64::NAN;
+        }
+    }
+}
+
+/// Convert an error of code generator to an error message
+fn jit_error<E: std::fmt::Display>(error: E) -> String {
+    return format!("JIT compilation failed: {error}");
+}
+
+/// Compiled expression translated into native code of host machine.
+///
+/// Evaluation gives the same results as the compiled expression it comes from.
+/// When an error occurs in native code, the expression is evaluated again
+/// by the compiled expression to give the error message.
+pub struct JitExpression {
+    module: Option<JITModule>,
+    function: NativeFunction,
+    // Functions referenced by address from native code
+    _functions: Box<[Function]>,
+    compiled: CompiledExpression,
+}
+
+impl JitExpression {
+    /// Translate compiled expression given in argument into native code.
+    /// If expression is a tuple, if host machine is not supported or if code generation fails,
+    /// an error message is stored in string contained in Result output
+    pub fn new(compiled: &CompiledExpression) -> Result<JitExpression, String> {
+        if compiled.outputs() != 1 {
+            return Err(String::from(
+                "Expression gives several outputs, use tuple evaluation",
+            ));
+        }
+
+        let mut flag_builder = settings::builder();
+        flag_builder
+            .set("use_colocated_libcalls", "false")
+            .map_err(jit_error)?;
+        flag_builder.set("is_pic", "false").map_err(jit_error)?;
+        flag_builder.set("opt_level", "speed").map_err(jit_error)?;
+
+        let isa = cranelift_native::builder()
+            .map_err(jit_error)?
+            .finish(settings::Flags::new(flag_builder))
+            .map_err(jit_error)?;
+
+        let mut jit_builder: JITBuilder = JITBuilder::with_isa(isa, default_libcall_names());
+        jit_builder.symbol("taz_power", taz_power as *const u8);
+        jit_builder.symbol("taz_apply_function", taz_apply_function as *const u8);
+
+        let mut module: JITModule = JITModule::new(jit_builder);
+
+        let functions: Box<[Function]> = compiled
+            .code
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Call(fun, _) => Some(*fun),
+                _ => None,
+            })
+            .collect();
+
+        let func_id: FuncId = match define_expression(&mut module, compiled, &functions) {
+            Ok(func_id) => func_id,
+            Err(message) => {
+                unsafe { module.free_memory() };
+                return Err(message);
+            }
+        };
+
+        if let Err(error) = module.finalize_definitions() {
+            unsafe { module.free_memory() };
+            return Err(jit_error(error));
+        }
+
+        let function: NativeFunction = unsafe {
+            std::mem::transmute::<*const u8, NativeFunction>(module.get_finalized_function(func_id))
+        };
+
+        return Ok(JitExpression {
+            module: Some(module),
+            function,
+            _functions: functions,
+            compiled: compiled.clone(),
+        });
+    }
+
+    /// Names of variables of expression, ordered by slot
+    pub fn variables(&self) -> &[String] {
+        return self.compiled.variables();
+    }
+
+    /// Evaluate expression where each variable takes its value
+    /// in slice given in argument at its slot (see `variables`).
+    /// If error occurs during evaluation, an error message is stored
+    /// in string contained in Result output
+    pub fn evaluate(&self, values: &[f64]) -> Result<f64, String> {
+        if values.len() < self.compiled.variables().len() {
+            return Err(String::from("Missing value of variable"));
+        }
+
+        let mut error: u8 = 0;
+        let value: f64 = unsafe { (self.function)(values.as_ptr(), &mut error) };
+
+        if error != 0 {
+            return self.compiled.evaluate(values);
+        }
+
+        return Ok(value);
+    }
+}
+
+impl Drop for JitExpression {
+    fn drop(&mut self) {
+        if let Some(module) = self.module.take() {
+            // Native function is not reachable anymore once expression is dropped
+            unsafe { module.free_memory() };
+        }
+    }
+}
+
+/// Declare signature of an imported function taking and returning values of types given in argument
+fn import_function(
+    module: &mut JITModule,
+    name: &str,
+    params: &[types::Type],
+    returns: &[types::Type],
+) -> Result<FuncId, String> {
+    let mut signature: Signature = module.make_signature();
+    signature
+        .params
+        .extend(params.iter().map(|&ty| AbiParam::new(ty)));
+    signature
+        .returns
+        .extend(returns.iter().map(|&ty| AbiParam::new(ty)));
+
+    return module
+        .declare_function(name, Linkage::Import, &signature)
+        .map_err(jit_error);
+}
+
+/// Give value of condition given in argument as a boolean, a value being true if it is neither zero nor NaN
+fn is_true(builder: &mut FunctionBuilder, value: Value) -> Value {
+    let zero: Value = builder.ins().f64const(0.0);
+    return builder.ins().fcmp(FloatCC::OrderedNotEqual, value, zero);
+}
+
+/// Give boolean given in argument as a value, 1 if it is true and 0 otherwise
+fn from_bool(builder: &mut FunctionBuilder, boolean: Value) -> Value {
+    let one: Value = builder.ins().f64const(1.0);
+    let zero: Value = builder.ins().f64const(0.0);
+    return builder.ins().select(boolean, one, zero);
+}
+
+/// Generate native function evaluating bytecode of compiled expression in module given in argument
+fn define_expression(
+    module: &mut JITModule,
+    compiled: &CompiledExpression,
+    functions: &[Function],
+) -> Result<FuncId, String> {
+    let ptr_type: types::Type = module.target_config().pointer_type();
+
+    let power_id: FuncId = import_function(
+        module,
+        "taz_power",
+        &[types::F64, types::F64],
+        &[types::F64],
+    )?;
+    let apply_id: FuncId = import_function(
+        module,
+        "taz_apply_function",
+        &[ptr_type, ptr_type, types::I64, ptr_type],
+        &[types::F64],
+    )?;
+
+    let mut ctx = module.make_context();
+    ctx.func.signature.params.push(AbiParam::new(ptr_type));
+    ctx.func.signature.params.push(AbiParam::new(ptr_type));
+    ctx.func.signature.returns.push(AbiParam::new(types::F64));
+
+    let func_id: FuncId = module
+        .declare_anonymous_function(&ctx.func.signature)
+        .map_err(jit_error)?;
+
+    let mut builder_ctx: FunctionBuilderContext = FunctionBuilderContext::new();
+    let mut builder: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut builder_ctx);
+
+    let power_ref = module.declare_func_in_func(power_id, builder.func);
+    let apply_ref = module.declare_func_in_func(apply_id, builder.func);
+
+    let block = builder.create_block();
+    builder.append_block_params_for_function_params(block);
+    builder.switch_to_block(block);
+    builder.seal_block(block);
+
+    let values_ptr: Value = builder.block_params(block)[0];
+    let error_ptr: Value = builder.block_params(block)[1];
+
+    // Arguments of functions are given through a stack slot large enough for all functions
+    let max_arity: u32 = compiled
+        .code
+        .iter()
+        .filter_map(|instruction| match instruction {
+            Instruction::Call(_, arity) => Some(*arity),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0);
+    let args_slot = builder.create_sized_stack_slot(StackSlotData::new(
+        StackSlotKind::ExplicitSlot,
+        8 * max_arity,
+        3,
+    ));
+
+    let zero: Value = builder.ins().f64const(0.0);
+    let no_division: Value = builder.ins().iconst(types::I8, 0);
+    let division_by_zero: Variable = builder.declare_var(types::I8);
+    builder.def_var(division_by_zero, no_division);
+
+    // Each target of jumps starts a block, taking the value ending there as parameter,
+    // except at the start of second value of conditional operator
+    let mut targets: HashMap<usize, Block> = HashMap::new();
+
+    for &instruction in compiled.code.iter() {
+        let (target, takes_value): (u32, bool) = match instruction {
+            Instruction::JumpIfFalse(target) => (target, false),
+            Instruction::Jump(target) | Instruction::ShortCircuit(_, target) => (target, true),
+            _ => continue,
+        };
+
+        targets.entry(target as usize).or_insert_with(|| {
+            let block: Block = builder.create_block();
+
+            if takes_value {
+                builder.append_block_param(block, types::F64);
+            }
+
+            return block;
+        });
+    }
+
+    let mut stack: Vec<Value> = Vec::with_capacity(compiled.code.len());
+    let mut functions_it = functions.iter();
+
+    for index in 0..=compiled.code.len() {
+        if let Some(&target) = targets.get(&index) {
+            let takes_value: bool = !builder.block_params(target).is_empty();
+
+            // Value on top of stack flows into block, unless previous instruction jumps elsewhere
+            if index == 0 || !matches!(compiled.code[index - 1], Instruction::Jump(_)) {
+                let args: Vec<BlockArg> = match takes_value {
+                    true => vec![BlockArg::from(stack.pop().unwrap())],
+                    false => Vec::new(),
+                };
+                builder.ins().jump(target, &args);
+            }
+
+            builder.switch_to_block(target);
+
+            if takes_value {
+                stack.push(builder.block_params(target)[0]);
+            }
+        }
+
+        let instruction: Instruction = match compiled.code.get(index) {
+            Some(&instruction) => instruction,
+            None => break,
+        };
+
+        let value: Value = match instruction {
+            Instruction::LoadConstant(slot) => {
+                builder.ins().f64const(compiled.constants[slot as usize])
+            }
+            Instruction::LoadVariable(slot) => builder.ins().load(
+                types::F64,
+                MemFlagsData::trusted(),
+                values_ptr,
+                8 * slot as i32,
+            ),
+            Instruction::Negate => {
+                let operand: Value = stack.pop().unwrap();
+                builder.ins().fneg(operand)
+            }
+            Instruction::Call(Function::Abs, _) => {
+                let operand: Value = stack.pop().unwrap();
+                functions_it.next();
+                builder.ins().fabs(operand)
+            }
+            Instruction::Call(_, arity) => {
+                let first_arg: usize = stack.len() - arity as usize;
+
+                for (index, &arg) in stack[first_arg..].iter().enumerate() {
+                    builder
+                        .ins()
+                        .stack_store(ptr_type, arg, args_slot, 8 * index as i32);
+                }
+
+                stack.truncate(first_arg);
+
+                let fun_ptr: Value = builder.ins().iconst(
+                    ptr_type,
+                    functions_it.next().unwrap() as *const Function as i64,
+                );
+                let args_ptr: Value = builder.ins().stack_addr(ptr_type, args_slot, 0);
+                let arity: Value = builder.ins().iconst(types::I64, arity as i64);
+                let call = builder
+                    .ins()
+                    .call(apply_ref, &[fun_ptr, args_ptr, arity, error_ptr]);
+                builder.inst_results(call)[0]
+            }
+            Instruction::Not => {
+                let operand: Value = stack.pop().unwrap();
+                let is_false: Value = builder.ins().fcmp(FloatCC::UnorderedOrEqual, operand, zero);
+                from_bool(&mut builder, is_false)
+            }
+            Instruction::JumpIfFalse(target) => {
+                let condition: Value = stack.pop().unwrap();
+                let condition: Value = is_true(&mut builder, condition);
+                let next: Block = builder.create_block();
+                builder
+                    .ins()
+                    .brif(condition, next, &[], targets[&(target as usize)], &[]);
+                builder.switch_to_block(next);
+                continue;
+            }
+            Instruction::Jump(target) => {
+                let value: BlockArg = BlockArg::from(stack.pop().unwrap());
+                builder.ins().jump(targets[&(target as usize)], &[value]);
+                continue;
+            }
+            Instruction::ShortCircuit(ops, target) => {
+                let left: Value = *stack.last().unwrap();
+                let is_left_true: Value = is_true(&mut builder, left);
+                let value: BlockArg = BlockArg::from(from_bool(&mut builder, is_left_true));
+                let is_known: Value = match ops {
+                    BinaryOperator::Or => is_left_true,
+                    _ => builder.ins().fcmp(FloatCC::UnorderedOrEqual, left, zero),
+                };
+                let next: Block = builder.create_block();
+                builder
+                    .ins()
+                    .brif(is_known, targets[&(target as usize)], &[value], next, &[]);
+                builder.switch_to_block(next);
+                continue;
+            }
+            _ => {
+                let right: Value = stack.pop().unwrap();
+                let left: Value = stack.pop().unwrap();
+
+                match instruction {
+                    Instruction::Add => builder.ins().fadd(left, right),
+                    Instruction::Subtract => builder.ins().fsub(left, right),
+                    Instruction::Multiply => builder.ins().fmul(left, right),
+                    Instruction::Divide => {
+                        let is_zero: Value = builder.ins().fcmp(FloatCC::Equal, right, zero);
+                        let flag: Value = builder.use_var(division_by_zero);
+                        let flag: Value = builder.ins().bor(flag, is_zero);
+                        builder.def_var(division_by_zero, flag);
+                        builder.ins().fdiv(left, right)
+                    }
+                    Instruction::Logic(ops) => {
+                        let condition: Value = match ops {
+                            BinaryOperator::And | BinaryOperator::Or => {
+                                let left: Value = is_true(&mut builder, left);
+                                let right: Value = is_true(&mut builder, right);
+
+                                match ops {
+                                    BinaryOperator::And => builder.ins().band(left, right),
+                                    _ => builder.ins().bor(left, right),
+                                }
+                            }
+                            _ => {
+                                let condition: FloatCC = match ops {
+                                    BinaryOperator::Less => FloatCC::LessThan,
+                                    BinaryOperator::LessEqual => FloatCC::LessThanOrEqual,
+                                    BinaryOperator::Greater => FloatCC::GreaterThan,
+                                    BinaryOperator::GreaterEqual => FloatCC::GreaterThanOrEqual,
+                                    BinaryOperator::Equal => FloatCC::Equal,
+                                    _ => FloatCC::NotEqual,
+                                };
+                                builder.ins().fcmp(condition, left, right)
+                            }
+                        };
+
+                        from_bool(&mut builder, condition)
+                    }
+                    _ => {
+                        let call = builder.ins().call(power_ref, &[left, right]);
+                        builder.inst_results(call)[0]
+                    }
+                }
+            }
+        };
+
+        stack.push(value);
+    }
+
+    // Error flag may have been set by functions, so division error is merged into it
+    let error: Value = builder
+        .ins()
+        .load(types::I8, MemFlagsData::trusted(), error_ptr, 0);
+    let division: Value = builder.use_var(division_by_zero);
+    let error: Value = builder.ins().bor(error, division);
+    builder
+        .ins()
+        .store(MemFlagsData::trusted(), error, error_ptr, 0);
+    builder.ins().return_(&[stack[0]]);
+    builder.seal_all_blocks();
+    builder.finalize(module.target_config());
+
+    module
+        .define_function(func_id, &mut ctx)
+        .map_err(jit_error)?;
+    module.clear_context(&mut ctx);
+
+    return Ok(func_id);
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative_error(value: f64, reference: f64) -> f64 {
+        if reference == 0.0 {
+            return value.abs();
+        } else {
+            return (value - reference).abs() / reference.abs();
+        }
+    }
+
+    #[test]
+    fn test_jit_expression_evaluate() {
+        let expression: &str = "1 - cos(theta)^2 + clamp(sin(phi), 0, 1) * pi / abs(-2) + x";
+
+        match CompiledExpression::new(expression) {
+            Ok(compiled) => match JitExpression::new(&compiled) {
+                Ok(jit) => {
+                    assert_eq!(jit.variables(), compiled.variables());
+
+                    for values in [[0.25, 1.54, 3.0], [1.0, -2.0, -0.5], [0.0, 0.5, 1e10]] {
+                        match (jit.evaluate(&values), compiled.evaluate(&values)) {
+                            (Ok(result), Ok(reference)) => {
+                                assert!(relative_error(result, reference) < 1e-12)
+                            }
+                            _ => assert!(false),
+                        }
+                    }
+                }
+                Err(_) => assert!(false),
+            },
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_jit_expression_errors() {
+        match CompiledExpression::new("sqrt(x) / y + asin(z)") {
+            Ok(compiled) => match JitExpression::new(&compiled) {
+                Ok(jit) => {
+                    assert_eq!(
+                        jit.evaluate(&[4.0, 2.0, 0.5]),
+                        compiled.evaluate(&[4.0, 2.0, 0.5])
+                    );
+                    assert_eq!(
+                        jit.evaluate(&[4.0, 0.0, 0.5]),
+                        Err(String::from("Division by zero"))
+                    );
+                    assert_eq!(
+                        jit.evaluate(&[4.0, 2.0, 2.0]),
+                        Err(String::from(
+                            "Argument of asin function is not containing in [-1, 1]"
+                        ))
+                    );
+                    assert!(jit.evaluate(&[4.0, 2.0]).is_err());
+                }
+                Err(_) => assert!(false),
+            },
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_jit_expression_conditional_and_boolean_operators() {
+        for expression in [
+            "x ? 1 / x : 2 * y",
+            "if(x > y, x - y, y - x)",
+            "x && !y || x == y",
+            "y < x < 1",
+            "piecewise(x < 0, -x, x < 1, x^2, 1)",
+            "x ? 1 / x : y || 1 / y",
+        ] {
+            match CompiledExpression::new(expression) {
+                Ok(compiled) => match JitExpression::new(&compiled) {
+                    Ok(jit) => {
+                        for values in [[0.0, 0.0], [0.5, 0.0], [-2.0, 3.0], [2.0, 2.0]] {
+                            assert_eq!(jit.evaluate(&values), compiled.evaluate(&values));
+                        }
+                    }
+                    Err(_) => assert!(false),
+                },
+                Err(_) => assert!(false),
+            }
+        }
+    }
+
+    #[test]
+    fn test_jit_expression_without_function() {
+        match CompiledExpression::new("x^2 / 2 - (-x)") {
+            Ok(compiled) => match JitExpression::new(&compiled) {
+                Ok(jit) => assert_eq!(jit.evaluate(&[4.0]), Ok(12.0)),
+                Err(_) => assert!(false),
+            },
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_jit_expression_lookup() {
+        match CompiledExpression::new(
+            "lookup(x, [0, 10, 20], [1, 2, 4]) * lookup_extrap(x, [0, 1], [1, 2])",
+        ) {
+            Ok(compiled) => match JitExpression::new(&compiled) {
+                Ok(jit) => {
+                    assert_eq!(jit.evaluate(&[5.0]), Ok(1.5 * 6.0));
+                    assert_eq!(jit.evaluate(&[-5.0]), Ok(-4.0));
+                }
+                Err(_) => assert!(false),
+            },
+            Err(_) => assert!(false),
+        }
+    }
+}
@@ -0,0 +1,240 @@
+#![allow(clippy::needless_return)]
+
+use super::calculus;
+use super::converter::Postfix;
+use super::evaluator::{self, OperandStack};
+use super::options::EvaluationOptions;
+use super::preprocess;
+use super::script;
+use super::token::Token;
+use super::tokenizer::Infix;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+/// Kind of access to a variable recorded in a journal
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Access {
+    /// Value of variable read by an expression
+    Read,
+    /// Value assigned to variable by a statement of script
+    Write,
+}
+
+/// Access to a variable recorded in a journal, giving an audit trail of an evaluation
+#[derive(Debug, PartialEq, Clone)]
+pub struct JournalEntry {
+    /// Kind of access
+    pub access: Access,
+    /// Name of variable
+    pub name: String,
+    /// Value of variable before access, None if variable is created by a write
+    pub old_value: Option<f64>,
+    /// Value of variable after access, the same as old value for a read
+    pub new_value: f64,
+    /// Span of name read or of assignment in evaluated text, as byte offsets
+    pub span: Range<usize>,
+}
+
+/// Entry is written like read x = 2 at 4..5 or write y: 1 -> 3 at 0..9
+impl fmt::Display for JournalEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let span: &Range<usize> = &self.span;
+
+        match (self.access, self.old_value) {
+            (Access::Read, _) => write!(f, "read {} = {} at {span:?}", self.name, self.new_value),
+            (Access::Write, Some(old_value)) => write!(
+                f,
+                "write {}: {old_value} -> {} at {span:?}",
+                self.name, self.new_value
+            ),
+            (Access::Write, None) => write!(
+                f,
+                "write {}: none -> {} at {span:?}",
+                self.name, self.new_value
+            ),
+        }
+    }
+}
+
+/// Evaluate expression given in argument according to options of evaluation given in argument,
+/// recording each read of a variable in journal given in argument.
+/// Spans of reads are shifted by offset of expression in evaluated text.
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+pub(crate) fn evaluate_journaled(
+    expression: &str,
+    offset: usize,
+    variables: &HashMap<String, f64>,
+    options: &EvaluationOptions,
+    journal: &mut Vec<JournalEntry>,
+) -> Result<f64, String> {
+    if let Some(result) = preprocess(expression, variables, |expression, scope| {
+        evaluate_journaled(expression, offset, scope, options, journal)
+    }) {
+        return result;
+    }
+
+    let infix_tokens = Infix::new(expression, |name: &str| {
+        let value: f64 = *variables.get(name)?;
+
+        // Values of calls of calculus functions are not variables of expression
+        if calculus::is_replaced_call(name) {
+            return Some(Token::new_number(value));
+        }
+
+        // Names are borrowed from expression, so their address gives their position
+        let start: usize = offset + (name.as_ptr() as usize - expression.as_ptr() as usize);
+
+        journal.push(JournalEntry {
+            access: Access::Read,
+            name: String::from(name),
+            old_value: Some(value),
+            new_value: value,
+            span: start..start + name.len(),
+        });
+
+        return Some(Token::new_number(value));
+    });
+
+    let mut postfix_tokens = Postfix::new(infix_tokens).with_capacities(options.capacities);
+    let values: OperandStack =
+        evaluator::postfix_evaluation_stream_with_options(postfix_tokens.by_ref(), &[], options)
+            .map_err(|error| postfix_tokens.tokens().locate(String::from(error)))?;
+
+    match values.first() {
+        Some(&value) => Ok(value),
+        None => Err(String::from("Empty expression")),
+    }
+}
+
+/// Evaluate an expression like evaluate function, recording each read of a variable
+/// with its value and the span of its name, returned alongside value of expression as an audit trail.
+///
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+pub fn evaluate_with_journal(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+) -> Result<(f64, Vec<JournalEntry>), String> {
+    let mut journal: Vec<JournalEntry> = Vec::new();
+    let options: EvaluationOptions = EvaluationOptions::default();
+    let value: f64 = evaluate_journaled(expression, 0, variables, &options, &mut journal)?;
+
+    return Ok((value, journal));
+}
+
+/// Run a script like run_script function, recording each read and write of a variable,
+/// with old and new values and span in script, as an audit trail of scripts written by users.
+/// Reads of a statement are recorded before write of its assignment.
+///
+/// If error occurs during evaluation of a statement, an error message giving its position in line
+/// and the number of line is stored in string contained in Result output
+///
+/// # Example
+/// ```
+/// use taz::{run_script_with_journal, Access};
+/// use std::collections::HashMap;
+///
+/// let mut variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 1.0)]);
+/// let (value, journal) = run_script_with_journal("x = x + 2", &mut variables).unwrap();
+///
+/// assert_eq!(value, 3.0);
+/// assert_eq!(journal[0].access, Access::Read);
+/// assert_eq!(journal[1].to_string(), "write x: 1 -> 3 at 0..9");
+/// ```
+pub fn run_script_with_journal(
+    script: &str,
+    variables: &mut HashMap<String, f64>,
+) -> Result<(f64, Vec<JournalEntry>), String> {
+    let mut journal: Vec<JournalEntry> = Vec::new();
+    let options: EvaluationOptions = EvaluationOptions::default();
+    let value: f64 = script::script_value(script::run(
+        script,
+        variables,
+        &mut script::UserFunctions::new(),
+        &options,
+        Some(&mut journal),
+    )?)?;
+
+    return Ok((value, journal));
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_with_journal() {
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("x"), 2.0), (String::from("y"), 3.0)]);
+
+        match evaluate_with_journal("x * sin(0) + x^y", &variables) {
+            Ok((value, journal)) => {
+                assert_eq!(value, 8.0);
+
+                let reads: Vec<(&str, Range<usize>)> = journal
+                    .iter()
+                    .map(|entry| (entry.name.as_str(), entry.span.clone()))
+                    .collect();
+                assert_eq!(reads, vec![("x", 0..1), ("x", 13..14), ("y", 15..16)]);
+                assert_eq!(journal[0].to_string(), "read x = 2 at 0..1");
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_run_script_with_journal() {
+        let mut variables: HashMap<String, f64> = HashMap::from([(String::from("rate"), 0.5)]);
+        let script: &str = "base = 100\n  tax = base * rate\n\nbase = tax + base\nbase / 3";
+
+        match run_script_with_journal(script, &mut variables) {
+            Ok((value, journal)) => {
+                assert_eq!(value, 50.0);
+                assert_eq!(variables.get("base"), Some(&150.0));
+
+                let lines: Vec<String> = journal.iter().map(|entry| entry.to_string()).collect();
+                assert_eq!(
+                    lines,
+                    vec![
+                        "write base: none -> 100 at 0..10",
+                        "read base = 100 at 19..23",
+                        "read rate = 0.5 at 26..30",
+                        "write tax: none -> 50 at 13..30",
+                        "read tax = 50 at 39..42",
+                        "read base = 100 at 45..49",
+                        "write base: 100 -> 150 at 32..49",
+                        "read base = 150 at 50..54",
+                    ]
+                );
+                assert_eq!(&script[journal[3].span.clone()], "tax = base * rate");
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_run_script_with_journal_statements() {
+        let mut variables: HashMap<String, f64> = HashMap::new();
+
+        match run_script_with_journal("a = 3; b = a^2;\na + b", &mut variables) {
+            Ok((value, journal)) => {
+                assert_eq!(value, 12.0);
+
+                let lines: Vec<String> = journal.iter().map(|entry| entry.to_string()).collect();
+                assert_eq!(
+                    lines,
+                    vec![
+                        "write a: none -> 3 at 0..5",
+                        "read a = 3 at 11..12",
+                        "write b: none -> 9 at 7..14",
+                        "read a = 3 at 16..17",
+                        "read b = 9 at 20..21",
+                    ]
+                );
+            }
+            Err(_) => assert!(false),
+        }
+    }
+}
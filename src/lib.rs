@@ -1,16 +1,156 @@
+#![allow(clippy::needless_return)]
+#![cfg_attr(test, allow(clippy::assertions_on_constants, clippy::approx_constant))]
+
 mod constants;
 mod functions;
 mod operators;
 mod token;
+mod units;
+mod variables;
+mod vector;
 
+mod ast;
+mod batch;
+#[cfg(feature = "bench")]
+pub mod bench;
+mod binding;
+#[cfg(feature = "serde")]
+mod bundle;
+mod cache;
+mod calculus;
+mod canonical;
+mod codegen;
+mod compiler;
+mod context;
+mod conversion;
 mod converter;
+mod coverage;
+mod dialect;
+mod differentiation;
+mod error;
 mod evaluator;
+#[cfg(feature = "serde")]
+mod extraction;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "jit")]
+mod jit;
+mod journal;
+mod library;
+mod limits;
+mod locale;
+#[cfg(feature = "serde")]
+mod mathjson;
+mod matrix;
+mod options;
+mod polynomial;
+mod resolution;
+mod script;
+mod session;
+mod simplification;
+mod stochastic;
+mod suggestion;
 mod tokenizer;
+mod trace;
+#[cfg(feature = "serde")]
+mod trace_export;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod watch;
+
+pub use ast::{Ast, Simplification, Uses};
+pub use batch::{
+    evaluate_batch, evaluate_batch_masked, evaluate_batch_with_units, BatchResult,
+    MaskedBatchResult, MeasuredBatchResult, Measurements, ValidityMask,
+};
+#[cfg(feature = "serde")]
+pub use bundle::BUNDLE_FORMAT_VERSION;
+pub use cache::ExpressionCache;
+pub use compiler::CompiledExpression;
+pub use context::{evaluate_with_context, Context};
+pub use conversion::FromTazValue;
+pub use coverage::{coverage, Branch, Construct, Coverage};
+pub use dialect::{evaluate_spreadsheet, transpile, Dialect};
+pub use error::Error;
+#[cfg(feature = "serde")]
+pub use extraction::evaluate_into;
+#[cfg(feature = "jit")]
+pub use jit::JitExpression;
+pub use journal::{evaluate_with_journal, run_script_with_journal, Access, JournalEntry};
+pub use library::{Formula, FormulaLibrary, LibraryOptions};
+pub use limits::{Budget, Limit, Limits};
+pub use locale::{evaluate_with_locale, AmbiguityWarning, NumberLocale};
+pub use matrix::{evaluate_matrix, Matrix};
+pub use options::{
+    AngleUnit, ArithmeticPolicy, Capacities, DivisionByZero, DomainMode, EvaluationOptions,
+    RoundingMode, ValueRange,
+};
+pub use resolution::{
+    evaluate_with_resolution, NameKind, ResolutionOptions, ShadowingPolicy, ShadowingWarning,
+};
+pub use script::{evaluate_script, run_script};
+pub use session::Session;
+pub use simplification::{RewriteRule, SimplificationRules};
+pub use stochastic::{evaluate_stochastic, Spread};
+#[cfg(feature = "derive")]
+pub use taz_derive::TazVariables;
+pub use tokenizer::size_hint;
+pub use trace::{trace, Trace, TraceNode};
+#[cfg(feature = "serde")]
+pub use trace_export::TRACE_SCHEMA_VERSION;
+pub use units::Unit;
+pub use variables::TazVariables;
+pub use vector::{evaluate_vector, Value};
+pub use watch::{watch, Change};
+
+// Allow derive macro to refer to this crate by its name in units tests
+#[cfg(all(test, feature = "derive"))]
+extern crate self as taz;
 
 use std::collections::HashMap;
 
+/// Evaluate local binding or calls of calculus functions of expression given in argument, if any,
+/// so each way of evaluating an expression handles them the same way. Value of binding and points of calls
+/// are evaluated by evaluation function given in argument, which then evaluates the rest of expression
+/// with their values. None is given if expression has neither binding nor call of calculus function
+pub(crate) fn preprocess<E, F>(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    mut evaluate: F,
+) -> Option<Result<f64, E>>
+where
+    E: From<String> + Clone,
+    String: From<E>,
+    F: FnMut(&str, &HashMap<String, f64>) -> Result<f64, E>,
+{
+    match binding::let_binding(expression) {
+        Ok(Some(binding)) => return Some(binding::evaluate_binding(&binding, variables, evaluate)),
+        Ok(None) => (),
+        Err(message) => return Some(Err(E::from(message))),
+    }
+
+    // Error of evaluation of a point is given as is, like an exceeded limit
+    let mut point_error: Option<E> = None;
+    let replaced = calculus::replace_calculus_calls(expression, variables, |point, scope| {
+        evaluate(point, scope).map_err(|error| {
+            point_error = Some(error.clone());
+            String::from(error)
+        })
+    });
+
+    match replaced {
+        Ok(Some((expression, scope))) => Some(evaluate(&expression, &scope)),
+        Ok(None) => None,
+        Err(message) => Some(Err(point_error.unwrap_or(E::from(message)))),
+    }
+}
+
 /// Evaluate an expression that can contain customs variables given in argument.
 /// These custom variables is represented with hash map which associate name of variable and its value.
+/// Integrals like integrate(x^2, x, 0, 1), derivatives like deriv(x^2, x, 1) and roots like solve(x^2 - 2, x, 1)
+/// are computed numerically, and summations like sum(i, 1, 10, 1 / i^2) and products like prod(i, 1, 5, i)
+/// evaluate their body for each integer,
+/// their subexpression being compiled.
 ///
 /// If error occurs during evaluation, an error message is stored in string contained in Result output.
 /// Otherwise, the Result output contains the value of evaluation stored in 64-bits float.
@@ -68,11 +208,415 @@ use std::collections::HashMap;
 ///     Err(message) => println!("Error occured: {message}")
 /// }
 /// ```
-pub fn evaluate(expression: &String, variables: &HashMap<String, f64>) -> Result<f64, String> {
-    let tokens: Vec<token::Token> = tokenizer::tokenize(expression.as_str(), variables)?;
-    let posfix_tokens: Vec<token::Token> = converter::infix_to_postfix(tokens)?;
+pub fn evaluate(expression: &str, variables: &HashMap<String, f64>) -> Result<f64, String> {
+    if let Some(result) = preprocess(expression, variables, evaluate) {
+        return result;
+    }
+
+    // Tokens are extracted, converted and evaluated in a single pass without intermediate vector
+    let infix_tokens = tokenizer::infix_tokens(expression, variables);
+    let mut postfix_tokens = converter::Postfix::new(infix_tokens);
+    let values: evaluator::OperandStack =
+        evaluator::postfix_evaluation_stream(postfix_tokens.by_ref(), &[])
+            .map_err(|message| postfix_tokens.tokens().locate(message))?;
+
+    match values.first() {
+        Some(&value) => Ok(value),
+        None => Err(String::from("Empty expression")),
+    }
+}
+
+/// Evaluate an expression like evaluate function, giving its value in type given as generic parameter,
+/// like an integer, a fixed-point type or a newtype of units of measure implementing FromTazValue trait.
+///
+/// If error occurs during evaluation or if value cannot be converted,
+/// an error message is stored in string contained in Result output
+///
+/// # Example
+/// ```
+/// use taz;
+/// use std::collections::HashMap;
+///
+/// let variables: HashMap<String, f64> = HashMap::from([(String::from("n"), 5.0)]);
+///
+/// assert_eq!(taz::evaluate_as::<u32>("n * (n - 1) / 2", &variables), Ok(10));
+/// assert!(taz::evaluate_as::<u32>("n / 2", &variables).is_err());
+/// ```
+pub fn evaluate_as<T>(expression: &str, variables: &HashMap<String, f64>) -> Result<T, String>
+where
+    T: FromTazValue,
+{
+    return T::from_taz_value(evaluate(expression, variables)?);
+}
+
+/// Evaluate an expression like evaluate_as function, applying arithmetic policy given in argument
+/// to result of each operation and function outside of range of type given as generic parameter,
+/// like fixed-point or integer arithmetic of embedded targets would do.
+/// Policy is applied to intermediate results, so 200 + 100 - 50 gives 250 for u8 with wrapping policy
+/// but 205 with saturating policy.
+///
+/// If error occurs during evaluation or if value cannot be converted,
+/// an error message is stored in string contained in Result output
+///
+/// # Example
+/// ```
+/// use taz;
+/// use std::collections::HashMap;
+///
+/// let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 200.0)]);
+///
+/// let result: Result<u8, String> = taz::evaluate_as_with_policy("x + 100", &variables, taz::ArithmeticPolicy::Saturate);
+/// assert_eq!(result, Ok(255));
+///
+/// let result: Result<u8, String> = taz::evaluate_as_with_policy("x + 100", &variables, taz::ArithmeticPolicy::Wrap);
+/// assert_eq!(result, Ok(44));
+///
+/// let result: Result<u8, String> = taz::evaluate_as_with_policy("x + 100", &variables, taz::ArithmeticPolicy::Error);
+/// assert!(result.is_err());
+/// ```
+pub fn evaluate_as_with_policy<T>(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    policy: ArithmeticPolicy,
+) -> Result<T, String>
+where
+    T: FromTazValue,
+{
+    let options: EvaluationOptions = EvaluationOptions {
+        range: T::range().map(|(min, max)| ValueRange::new(min, max, policy)),
+        ..EvaluationOptions::default()
+    };
+
+    return T::from_taz_value(evaluate_with_options(expression, variables, &options)?);
+}
+
+/// Evaluate an expression like evaluate function, rejecting expressions exceeding limits given in argument
+/// on number of tokens, number of pending operators and nesting of parenthesis.
+/// Expression is rejected as soon as a limit is exceeded, so memory used by evaluation stays bounded
+/// whatever the size of expression.
+///
+/// If a limit is exceeded, a LimitExceeded error is given in Result output,
+/// otherwise an error occuring during evaluation is given as an error message.
+///
+/// # Example
+/// ```
+/// use taz;
+/// use std::collections::HashMap;
+///
+/// let limits: taz::Limits = taz::Limits::default();
+/// let hostile: String = "(".repeat(100_000);
+///
+/// let result: Result<f64, taz::Error> = taz::evaluate_with_limits(&hostile, &HashMap::new(), &limits);
+/// assert_eq!(result, Err(taz::Error::LimitExceeded(taz::Limit::Nesting(limits.max_nesting))));
+///
+/// let result: Result<f64, taz::Error> = taz::evaluate_with_limits("2 * (3 + 1)", &HashMap::new(), &limits);
+/// assert_eq!(result, Ok(8.0));
+/// ```
+pub fn evaluate_with_limits(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    limits: &Limits,
+) -> Result<f64, Error> {
+    if let Some(result) = preprocess(expression, variables, |expression, scope| {
+        evaluate_with_limits(expression, scope, limits)
+    }) {
+        return result;
+    }
+
+    let infix_tokens = tokenizer::infix_tokens(expression, variables).with_limits(*limits);
+    let mut postfix_tokens = converter::Postfix::new(infix_tokens).with_limits(*limits);
+
+    let result: Result<evaluator::OperandStack, String> =
+        evaluator::postfix_evaluation_stream(postfix_tokens.by_ref(), &[])
+            .map_err(|message| postfix_tokens.tokens().locate(message));
+
+    let exceeded: Option<Limit> = postfix_tokens
+        .exceeded()
+        .or(postfix_tokens.tokens().exceeded());
+
+    if let Some(limit) = exceeded {
+        return Err(Error::LimitExceeded(limit));
+    }
+
+    match result?.first() {
+        Some(&value) => Ok(value),
+        None => Err(Error::Message(String::from("Empty expression"))),
+    }
+}
+
+/// Evaluate an expression like evaluate function, according to options of evaluation given in argument.
+/// With lenient domain mode, functions like sqrt, ln, tan, asin or acos give the IEEE result,
+/// like NaN or infinity, for an argument outside of their domain instead of an error.
+///
+/// If error occurs during evaluation, an error is given in Result output.
+///
+/// # Example
+/// ```
+/// use taz;
+/// use std::collections::HashMap;
+///
+/// let options: taz::EvaluationOptions = taz::EvaluationOptions {
+///     domain: taz::DomainMode::Lenient,
+///     ..taz::EvaluationOptions::default()
+/// };
+///
+/// let result: Result<f64, taz::Error> = taz::evaluate_with_options("ln(0)", &HashMap::new(), &options);
+/// assert_eq!(result, Ok(f64::NEG_INFINITY));
+///
+/// let result: Result<f64, taz::Error> =
+///     taz::evaluate_with_options("ln(0)", &HashMap::new(), &taz::EvaluationOptions::default());
+/// assert!(result.is_err());
+/// ```
+pub fn evaluate_with_options(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    options: &EvaluationOptions,
+) -> Result<f64, Error> {
+    if let Some(result) = preprocess(expression, variables, |expression, scope| {
+        evaluate_with_options(expression, scope, options)
+    }) {
+        return result;
+    }
+
+    let infix_tokens = tokenizer::infix_tokens(expression, variables);
+    let mut postfix_tokens =
+        converter::Postfix::new(infix_tokens).with_capacities(options.capacities);
+    let values: evaluator::OperandStack =
+        evaluator::postfix_evaluation_stream_with_options(postfix_tokens.by_ref(), &[], options)
+            .map_err(|error| {
+                error.map_message(|message| postfix_tokens.tokens().locate(message))
+            })?;
+
+    match values.first() {
+        Some(&value) => Ok(value),
+        None => Err(Error::Message(String::from("Empty expression"))),
+    }
+}
+
+/// Evaluate an expression using lookup functions over breakpoint arrays.
+/// Breakpoint arrays are given either as literals like [0, 10, 20] or by name of array variables
+/// stored in hash map given in argument, like in lookup(x, speeds, gains).
+/// The function lookup clamps result outside of breakpoints while lookup_extrap extrapolates it linearly.
+///
+/// If error occurs during evaluation, an error message is stored in string contained in Result output.
+///
+/// # Example
+/// ```
+/// use taz;
+/// use std::collections::HashMap;
+///
+/// let variables: HashMap<String, f64> = HashMap::from([(String::from("speed"), 15.0)]);
+/// let arrays: HashMap<String, Vec<f64>> = HashMap::from([
+///     (String::from("speeds"), vec![0.0, 10.0, 20.0]),
+///     (String::from("gains"), vec![1.0, 2.0, 4.0]),
+/// ]);
+///
+/// let result: Result<f64, String> =
+///     taz::evaluate_with_arrays("lookup(speed, speeds, gains)", &variables, &arrays);
+/// assert_eq!(result, Ok(3.0));
+///
+/// let result: Result<f64, String> =
+///     taz::evaluate_with_arrays("lookup_extrap(30, [0, 10], [0, 1])", &variables, &arrays);
+/// assert_eq!(result, Ok(3.0));
+/// ```
+pub fn evaluate_with_arrays(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    arrays: &HashMap<String, Vec<f64>>,
+) -> Result<f64, String> {
+    if let Some(result) = preprocess(expression, variables, |expression, scope| {
+        evaluate_with_arrays(expression, scope, arrays)
+    }) {
+        return result;
+    }
+
+    let infix_tokens = tokenizer::infix_tokens(expression, variables).with_arrays(arrays);
+    let mut postfix_tokens = converter::Postfix::new(infix_tokens);
+    let values: evaluator::OperandStack =
+        evaluator::postfix_evaluation_stream(postfix_tokens.by_ref(), &[])
+            .map_err(|message| postfix_tokens.tokens().locate(message))?;
+
+    match values.first() {
+        Some(&value) => Ok(value),
+        None => Err(String::from("Empty expression")),
+    }
+}
+
+/// Evaluate an expression which can be a tuple of expressions like (sin(t), cos(t), t^2),
+/// giving a value for each element of tuple in one pass over the same variables.
+/// An expression which is not a tuple gives a single value.
+///
+/// If error occurs during evaluation, an error message is stored in string contained in Result output.
+///
+/// # Example
+/// ```
+/// use taz;
+/// use std::collections::HashMap;
+///
+/// let variables: HashMap<String, f64> = HashMap::from([(String::from("t"), 2.0)]);
+///
+/// let result: Result<Vec<f64>, String> = taz::evaluate_tuple("(t + 1, t^2, -t)", &variables);
+/// assert_eq!(result, Ok(vec![3.0, 4.0, -2.0]));
+/// ```
+pub fn evaluate_tuple(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+) -> Result<Vec<f64>, String> {
+    let tokens: Vec<token::Token> = tokenizer::tokenize(expression, variables)?;
+    let (posfix_tokens, nb_elements) = converter::tuple_to_postfix(tokens)?;
+    let values: evaluator::OperandStack = evaluator::postfix_evaluation_stack(&posfix_tokens, &[])?;
+
+    if values.len() != nb_elements {
+        return Err(String::from(
+            "Expression does not reduce to a single value per element",
+        ));
+    }
+
+    return Ok(values.into_vec());
+}
+
+/// Evaluate a postfix expression, also known as reverse Polish notation, like 3 4 + 2 *.
+/// Words of expression are separated by whitespaces and are numbers, constants, functions,
+/// binary operators and neg, the unary minus. Tokens are given directly to evaluator without conversion.
+///
+/// If error occurs during evaluation or if expression does not reduce to a single value,
+/// an error message is stored in string contained in Result output
+///
+/// # Example
+/// ```
+/// use taz;
+///
+/// assert_eq!(taz::evaluate_postfix("3 4 + 2 *"), Ok(14.0));
+/// assert_eq!(taz::evaluate_postfix("pi 2 / sin neg"), Ok(-1.0));
+/// ```
+pub fn evaluate_postfix(expression: &str) -> Result<f64, String> {
+    let mut rpn_tokens = tokenizer::Rpn::new(expression);
+    let values: evaluator::OperandStack =
+        evaluator::postfix_evaluation_stream(rpn_tokens.by_ref(), &[])
+            .map_err(|message| rpn_tokens.locate(message))?;
+
+    match values.len() {
+        0 => Err(String::from("Empty expression")),
+        1 => Ok(values[0]),
+        _ => Err(String::from(
+            "Expression does not reduce to a single value, an operator is missing",
+        )),
+    }
+}
+
+/// Compile an expression into bytecode to evaluate it many times with different values of its variables.
+/// Each word of expression which is neither a constant nor a function is a variable,
+/// variables being given slots by order of appearance.
+///
+/// If expression is not valid, an error message is stored in string contained in Result output.
+///
+/// # Example
+/// ```
+/// use taz;
+///
+/// let compiled: taz::CompiledExpression = taz::compile("x^2 + y").unwrap();
+/// assert_eq!(compiled.variables(), &[String::from("x"), String::from("y")]);
+///
+/// for x in [1.0, 2.0, 3.0] {
+///     assert_eq!(compiled.evaluate(&[x, 1.0]), Ok(x * x + 1.0));
+/// }
+/// ```
+pub fn compile(expression: &str) -> Result<CompiledExpression, String> {
+    return CompiledExpression::new(expression);
+}
 
-    return evaluator::postfix_evaluation(posfix_tokens);
+/// Simplify an expression with all simplification rules, like x + 2 * x giving 3 * x or sin(x)^2 + cos(x)^2 giving 1.
+/// Each word of expression which is neither a constant nor a function is a variable.
+/// Some rules assume values of subexpressions, like x / x giving 1 which assumes x is not 0:
+/// `Ast::simplify` method gives these assumptions and takes the rules to apply.
+///
+/// If expression is not valid, an error message is stored in string contained in Result output.
+///
+/// # Example
+/// ```
+/// use taz;
+///
+/// assert_eq!(taz::simplify("x + 2 * x - 1 + 3"), Ok(String::from("3 * x + 2")));
+/// assert_eq!(taz::simplify("sin(y)^2 + cos(y)^2 + x / x"), Ok(String::from("2")));
+/// ```
+pub fn simplify(expression: &str) -> Result<String, String> {
+    let ast: Ast = Ast::new(expression)?;
+    return Ok(ast
+        .simplify(&SimplificationRules::default())
+        .ast
+        .to_string());
+}
+
+/// Expand an expression by distributing products over sums and expanding powers of sums to positive integer exponents,
+/// like x^2 + 2 * x + 1 for (x + 1)^2. Numbers are folded and like terms are combined after expansion.
+///
+/// If expression is not valid, an error message is stored in string contained in Result output.
+///
+/// # Example
+/// ```
+/// use taz;
+///
+/// assert_eq!(taz::expand("(x + 1)^2"), Ok(String::from("x^2 + 2 * x + 1")));
+/// assert_eq!(taz::expand("(a + b) * (a - b)"), Ok(String::from("a^2 - b^2")));
+/// ```
+pub fn expand(expression: &str) -> Result<String, String> {
+    return Ok(Ast::new(expression)?.expand().to_string());
+}
+
+/// Check syntax of an expression without evaluating it, like a formula being typed in an editor.
+/// Each word of expression which is neither a constant nor a function is considered as a variable,
+/// so an expression is valid whatever the values of its variables.
+/// Expression is parsed like evaluate function does, so local bindings, conditional and boolean operators
+/// and calls of calculus functions are accepted.
+///
+/// If expression is not valid, an error is given in Result output.
+///
+/// # Example
+/// ```
+/// use taz;
+///
+/// assert_eq!(taz::validate("sqrt(x^2 + y^2)"), Ok(()));
+/// assert_eq!(taz::validate("let t = x / 2 in t > 0 ? t : -t"), Ok(()));
+/// assert!(taz::validate("sqrt(x^2 + ").is_err());
+/// ```
+pub fn validate(expression: &str) -> Result<(), Error> {
+    if let Some(binding) = binding::let_binding(expression)? {
+        validate(&binding.value)?;
+        return validate(&binding.body);
+    }
+
+    if let Some(expression) = calculus::validate_calculus_calls(expression, |expression| {
+        validate(expression).map_err(String::from)
+    })? {
+        return validate(&expression);
+    }
+
+    Ast::new(expression)?;
+    return Ok(());
+}
+
+/// Get names of functions available in expressions, like sqrt or lookup
+///
+/// # Example
+/// ```
+/// use taz;
+///
+/// assert!(taz::function_names().contains(&"sqrt"));
+/// ```
+pub fn function_names() -> &'static [&'static str] {
+    return &functions::Function::NAMES;
+}
+
+/// Get names of constants available in expressions, like pi
+///
+/// # Example
+/// ```
+/// use taz;
+///
+/// assert!(taz::constant_names().contains(&"pi"));
+/// ```
+pub fn constant_names() -> &'static [&'static str] {
+    return &constants::NAMES;
 }
 
 /// Units tests
@@ -192,4 +736,1069 @@ mod tests {
             Err(_) => assert!(false),
         }
     }
+
+    #[test]
+    fn test_evaluation_expression_with_clamp_and_lerp() {
+        let expression: String = String::from("clamp(lerp(0.0, 10.0, t), 2.0, 2.0 * 4.0)");
+
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("t"), 0.9)]);
+
+        match evaluate(&expression, &variables) {
+            Ok(result) => assert!(relative_error(result, 8.0) < 0.01),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_expression_with_angle_conversions() {
+        let expression: String = String::from("sin(rad(30.0)) + deg(pi / 4.0)");
+        let reference: f64 = 0.5 + 45.0;
+
+        match evaluate(&expression, &HashMap::new()) {
+            Ok(result) => assert!(relative_error(result, reference) < 0.01),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_expression_with_rounding() {
+        let expression: String =
+            String::from("round(price * (1 + vat), 2) + floorto(12.349, 1) - ceilto(12.341, 1)");
+        let reference: f64 = 23.99 + 12.3 - 12.4;
+
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("price"), 19.99), (String::from("vat"), 0.2)]);
+
+        match evaluate(&expression, &variables) {
+            Ok(result) => assert!(relative_error(result, reference) < 1e-12),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_tuple_expression() {
+        let expression: &str = "(sin(t), cos(t), t^2)";
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("t"), 0.3)]);
+
+        match evaluate_tuple(expression, &variables) {
+            Ok(values) => {
+                assert_eq!(values.len(), 3);
+                assert!(relative_error(values[0], 0.3_f64.sin()) < 0.01);
+                assert!(relative_error(values[1], 0.3_f64.cos()) < 0.01);
+                assert!(relative_error(values[2], 0.09) < 0.01);
+            }
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(evaluate_tuple("2 * (t + 1)", &variables), Ok(vec![2.6]));
+        assert!(evaluate_tuple("(t, 2) * 3", &variables).is_err());
+        assert!(evaluate("(t, 2)", &variables).is_err());
+    }
+
+    #[test]
+    fn test_evaluation_lookup_with_literal_arrays() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 5.0)]);
+
+        assert_eq!(
+            evaluate("lookup(x, [0, 10, 20], [1, 2, 4])", &variables),
+            Ok(1.5)
+        );
+        assert_eq!(
+            evaluate("lookup(-x, [0, 10, 20], [1, 2, 4])", &variables),
+            Ok(1.0)
+        );
+        assert_eq!(
+            evaluate("lookup_extrap(-x, [0, 10, 20], [1, 2, 4])", &variables),
+            Ok(0.5)
+        );
+        assert_eq!(
+            evaluate("2 * lookup(x * 5, [0, pi, 20], [-1, 1, 3]) + 1", &variables),
+            Ok(7.0)
+        );
+        assert_eq!(
+            evaluate("spline(x / 2 - 1, [0, 1, 2, 3], [0, 1, 4, 9])", &variables),
+            Ok(2.2)
+        );
+    }
+
+    #[test]
+    fn test_evaluation_lookup_with_array_variables() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 25.0)]);
+        let arrays: HashMap<String, Vec<f64>> = HashMap::from([
+            (String::from("xs"), vec![0.0, 10.0, 20.0]),
+            (String::from("ys"), vec![1.0, 2.0, 4.0]),
+        ]);
+
+        assert_eq!(
+            evaluate_with_arrays("lookup(x, xs, ys)", &variables, &arrays),
+            Ok(4.0)
+        );
+        assert_eq!(
+            evaluate_with_arrays("lookup_extrap(x, xs, [0, 1, 2])", &variables, &arrays),
+            Ok(2.5)
+        );
+        assert!(evaluate_with_arrays("lookup(x, xs, zs)", &variables, &arrays).is_err());
+    }
+
+    #[test]
+    fn test_evaluation_lookup_with_misplaced_arrays() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 5.0)]);
+
+        for expression in [
+            "[0, 1] + x",
+            "sqrt([0, 1])",
+            "lookup([0, 1], [0, 1], [0, 1])",
+            "lookup(x, [0, 1] + 1, [0, 1])",
+            "lookup(x, [0, 1])",
+            "lookup(x, [0, 1], [0, 1], [0, 1])",
+            "lookup(x, [0, 1], [0, 1, 2])",
+            "lookup(x, [1, 0], [0, 1])",
+            "lookup(x, [0, 1, [0, 1])",
+        ] {
+            assert!(evaluate(expression, &variables).is_err());
+        }
+    }
+
+    #[test]
+    fn test_evaluation_array_errors_located_at_user_text() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 5.0)]);
+        let arrays: HashMap<String, Vec<f64>> =
+            HashMap::from([(String::from("xs"), vec![0.0, 10.0])]);
+
+        assert_eq!(
+            evaluate_with_arrays("x * xs", &variables, &arrays),
+            Err(String::from(
+                "Arrays are only allowed as breakpoints of lookup function or lists of aggregation function at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate_with_arrays("lookup(x, xs, xs + 1)", &variables, &arrays),
+            Err(String::from(
+                "Breakpoint array must be a whole argument of lookup function at position 17"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_with_limits() {
+        let limits: Limits = Limits {
+            max_tokens: 20,
+            max_operator_depth: 4,
+            max_nesting: 3,
+        };
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 2.0)]);
+
+        assert_eq!(
+            evaluate_with_limits("((x + 1) * 2)^2", &variables, &limits),
+            Ok(36.0)
+        );
+        assert_eq!(
+            evaluate_with_limits(
+                "1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1",
+                &variables,
+                &limits
+            ),
+            Err(Error::LimitExceeded(Limit::Tokens(20)))
+        );
+        assert_eq!(
+            evaluate_with_limits("((((x))))", &variables, &limits),
+            Err(Error::LimitExceeded(Limit::Nesting(3)))
+        );
+        assert_eq!(
+            evaluate_with_limits("x^x^x^x^x^x", &variables, &limits),
+            Err(Error::LimitExceeded(Limit::OperatorDepth(4)))
+        );
+        assert_eq!(
+            evaluate_with_limits("x / 0", &variables, &limits),
+            Err(Error::Message(String::from(
+                "Division by zero at position 5"
+            )))
+        );
+        assert_eq!(
+            evaluate_with_limits("x / 0", &variables, &Limits::unlimited()).map_err(String::from),
+            evaluate("x / 0", &variables)
+        );
+    }
+
+    #[test]
+    fn test_evaluation_malformed_expressions() {
+        let variables: HashMap<String, f64> = HashMap::new();
+
+        assert_eq!(
+            evaluate("2 + * 3", &variables),
+            Err(String::from("Unexpected operator at position 4"))
+        );
+        assert_eq!(
+            evaluate("2 3", &variables),
+            Err(String::from("Unexpected operand at position 2"))
+        );
+        assert_eq!(
+            evaluate("2 * 3 -", &variables),
+            Err(String::from("Unexpected end of expression at position 7"))
+        );
+        assert_eq!(
+            evaluate("", &variables),
+            Err(String::from("Expression is empty"))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_errors_with_position() {
+        let variables: HashMap<String, f64> = HashMap::new();
+
+        assert_eq!(
+            evaluate("2 * (3 + 1", &variables),
+            Err(String::from("Unclosed parenthesis at position 4"))
+        );
+        assert_eq!(
+            evaluate("2) * 3", &variables),
+            Err(String::from("Unmatched closing parenthesis at position 1"))
+        );
+        assert_eq!(
+            evaluate("1, 2", &variables),
+            Err(String::from("Misplaced comma at position 1"))
+        );
+        assert_eq!(
+            evaluate("1 / 0 + 2", &variables),
+            Err(String::from("Division by zero at position 6"))
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate("lookup(x, [0, 1], [2, 4]) * pi"), Ok(()));
+        assert_eq!(validate("(sin(t), cos(t))"), Ok(()));
+        assert_eq!(
+            validate("2 + * 3"),
+            Err(Error::Message(String::from(
+                "Unexpected operator at position 4"
+            )))
+        );
+        assert_eq!(
+            validate(""),
+            Err(Error::Message(String::from("Expression is empty")))
+        );
+        assert_eq!(
+            validate("2 * clamp(x, 1)"),
+            Err(Error::Message(String::from(
+                "Function clamp expects 3 arguments but got 2 at position 14"
+            )))
+        );
+
+        assert_eq!(validate("x > 0 ? 1 : 2"), Ok(()));
+        assert_eq!(validate("x < 1"), Ok(()));
+        assert_eq!(validate("a && b"), Ok(()));
+        assert_eq!(validate("!a"), Ok(()));
+        assert_eq!(validate("if(x >= 1, 1 / x, 0)"), Ok(()));
+        assert_eq!(validate("let t = x / 2 in t * t"), Ok(()));
+        assert_eq!(validate("integrate(1 / t, t, 0, b) + 1"), Ok(()));
+        assert_eq!(
+            validate("integrate(t^, t, 0, 1)"),
+            Err(Error::Message(String::from(
+                "Unexpected end of expression at position 12"
+            )))
+        );
+        assert_eq!(
+            validate("let t = 2 in t +"),
+            Err(Error::Message(String::from(
+                "Unexpected end of expression at position 16"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_unknown_name_suggestion() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 4.0)]);
+
+        assert_eq!(
+            evaluate("2 * sqr(x)", &variables),
+            Err(String::from(
+                "Unknown name sqr (did you mean sqrt?) at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate("x + y", &variables),
+            Err(String::from("Unknown name y at position 4"))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_with_domain_mode() {
+        let lenient: EvaluationOptions = EvaluationOptions {
+            domain: DomainMode::Lenient,
+            ..EvaluationOptions::default()
+        };
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), -4.0)]);
+
+        match evaluate_with_options("sqrt(x) + 1", &variables, &lenient) {
+            Ok(value) => assert!(value.is_nan()),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            evaluate_with_options("asin(x / 4) * 2", &variables, &lenient),
+            Ok(-std::f64::consts::PI)
+        );
+        assert_eq!(
+            evaluate_with_options("sqrt(x)", &variables, &EvaluationOptions::default())
+                .map_err(String::from),
+            evaluate("sqrt(x)", &variables)
+        );
+        assert!(evaluate_with_options("x / 0", &variables, &lenient).is_err());
+    }
+
+    #[test]
+    fn test_evaluation_detect_overflow() {
+        let options: EvaluationOptions = EvaluationOptions {
+            detect_overflow: true,
+            ..EvaluationOptions::default()
+        };
+        let variables: HashMap<String, f64> = HashMap::new();
+
+        assert_eq!(
+            evaluate_with_options("1 + 10^400", &variables, &options),
+            Err(Error::Overflow(String::from(
+                "Result of ^ operation overflows at position 10"
+            )))
+        );
+        assert_eq!(
+            evaluate_with_options("exp(1000) - 1", &variables, &options),
+            Err(Error::Overflow(String::from(
+                "Result of exp function overflows at position 8"
+            )))
+        );
+        assert_eq!(
+            evaluate_with_options("exp(1000)", &variables, &EvaluationOptions::default()),
+            Ok(f64::INFINITY)
+        );
+
+        let options: EvaluationOptions = EvaluationOptions {
+            domain: DomainMode::Lenient,
+            ..options
+        };
+
+        match evaluate_with_options("sqrt(-1)", &variables, &options) {
+            Ok(value) => assert!(value.is_nan()),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_with_capacities() {
+        let expression: String = vec!["(x - 1)"; 200].join(" * ");
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 2.0)]);
+
+        for capacities in [
+            Capacities::default(),
+            Capacities::for_expression(&expression),
+            Capacities {
+                operators: 0,
+                operands: 1,
+            },
+        ] {
+            let options: EvaluationOptions = EvaluationOptions {
+                capacities,
+                ..EvaluationOptions::default()
+            };
+
+            assert_eq!(
+                evaluate_with_options(&expression, &variables, &options),
+                Ok(1.0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluation_as_with_policy() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 200.0)]);
+        let expression: &str = "x + 100 - 50";
+
+        assert_eq!(
+            evaluate_as_with_policy::<u8>(expression, &variables, ArithmeticPolicy::Saturate),
+            Ok(205)
+        );
+        assert_eq!(
+            evaluate_as_with_policy::<u8>(expression, &variables, ArithmeticPolicy::Wrap),
+            Ok(250)
+        );
+        assert_eq!(
+            evaluate_as_with_policy::<u8>(expression, &variables, ArithmeticPolicy::Error),
+            Err(String::from(
+                "Result of + operation is out of range [0, 255] at position 8"
+            ))
+        );
+        assert_eq!(
+            evaluate_as_with_policy::<i16>(expression, &variables, ArithmeticPolicy::Error),
+            Ok(250)
+        );
+
+        // Policy is applied to results of functions too
+        assert_eq!(
+            evaluate_as_with_policy::<u8>("exp(x) - 1", &variables, ArithmeticPolicy::Saturate),
+            Ok(254)
+        );
+        assert!(
+            evaluate_as_with_policy::<u8>("exp(1000)", &variables, ArithmeticPolicy::Wrap).is_err()
+        );
+    }
+
+    #[test]
+    fn test_evaluation_division_by_zero_behavior() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 0.0)]);
+        let options = |division_by_zero: DivisionByZero| -> EvaluationOptions {
+            return EvaluationOptions {
+                division_by_zero,
+                detect_overflow: true,
+                ..EvaluationOptions::default()
+            };
+        };
+
+        assert_eq!(
+            evaluate_with_options("1 / x + 1", &variables, &options(DivisionByZero::Error)),
+            Err(Error::Message(String::from(
+                "Division by zero at position 6"
+            )))
+        );
+        assert_eq!(
+            evaluate_with_options("-1 / x + 1", &variables, &options(DivisionByZero::Infinity)),
+            Ok(f64::NEG_INFINITY)
+        );
+        assert_eq!(
+            evaluate_with_options(
+                "1 / x + 1",
+                &variables,
+                &options(DivisionByZero::Value(0.0))
+            ),
+            Ok(1.0)
+        );
+    }
+
+    #[test]
+    fn test_evaluation_rounding_mode() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 3.0)]);
+        let options = |rounding: RoundingMode, round_operations: bool| -> EvaluationOptions {
+            return EvaluationOptions {
+                rounding,
+                round_operations,
+                ..EvaluationOptions::default()
+            };
+        };
+
+        let third: f64 = 1.0 / 3.0;
+        assert_eq!(
+            evaluate_with_options("1 / x", &variables, &options(RoundingMode::Up, false)),
+            Ok(third.next_up())
+        );
+
+        // Only the operation giving the result is rounded, unless each operation is rounded
+        assert_eq!(
+            evaluate_with_options("1 / x + 0", &variables, &options(RoundingMode::Up, false)),
+            Ok(third)
+        );
+        assert_eq!(
+            evaluate_with_options("1 / x + 0", &variables, &options(RoundingMode::Up, true)),
+            Ok(third.next_up())
+        );
+        assert_eq!(
+            evaluate_with_options("sqrt(1 / x)", &variables, &options(RoundingMode::Up, false)),
+            Ok(third.sqrt())
+        );
+    }
+
+    #[test]
+    fn test_evaluation_postfix_expression() {
+        assert_eq!(evaluate_postfix("3 4 + 2 *"), Ok(14.0));
+        assert_eq!(evaluate_postfix(" 2\t3 neg ^ "), Ok(0.125));
+        assert_eq!(evaluate_postfix("0 2 0.25 lerp e ln +"), Ok(1.5));
+
+        assert_eq!(
+            evaluate_postfix("3 qqqj +"),
+            Err(String::from("Unknown name qqqj at position 2"))
+        );
+        assert_eq!(
+            evaluate_postfix("3 4 + *"),
+            Err(String::from(
+                "Missing left operand to apply binary operation at position 6"
+            ))
+        );
+        assert_eq!(
+            evaluate_postfix("1 2.3.4 +"),
+            Err(String::from("Cannot parse this expression at position 2"))
+        );
+        assert_eq!(
+            evaluate_postfix("3 4"),
+            Err(String::from(
+                "Expression does not reduce to a single value, an operator is missing"
+            ))
+        );
+        assert_eq!(
+            evaluate_postfix("  "),
+            Err(String::from("Empty expression"))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_let_binding() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 6.0)]);
+
+        assert_eq!(evaluate("let t = x / 2 in t^2 + t", &variables), Ok(12.0));
+        assert_eq!(
+            evaluate("let a = x + 1 in let b = a * (a - x) in a * b", &variables),
+            Ok(49.0)
+        );
+        assert_eq!(evaluate("let x = x * 2 in x + 1", &variables), Ok(13.0));
+
+        let options: EvaluationOptions = EvaluationOptions {
+            angle: AngleUnit::Degrees,
+            ..EvaluationOptions::default()
+        };
+        match evaluate_with_options("let t = x * 5 in sin(t)", &variables, &options) {
+            Ok(value) => assert!((value - 0.5).abs() < 1e-12),
+            Err(_) => assert!(false),
+        }
+
+        // Errors are located in whole expression
+        assert_eq!(
+            evaluate("let t = x / 2 in t * y", &variables),
+            Err(String::from("Unknown name y at position 21"))
+        );
+        assert_eq!(
+            evaluate("let t = x + in t", &variables),
+            Err(String::from("Unexpected end of expression at position 12"))
+        );
+        assert!(evaluate("x + (let t = 1 in t)", &variables).is_err());
+
+        // Bindings are evaluated the same way by each evaluation and expanded by compilation
+        let expression: &str = "let t = x / 2 in let u = t + 1 in t * u";
+        let arrays: HashMap<String, Vec<f64>> = HashMap::new();
+
+        assert_eq!(
+            evaluate_with_limits(expression, &variables, &Limits::default()),
+            Ok(12.0)
+        );
+        assert_eq!(
+            evaluate_with_arrays(expression, &variables, &arrays),
+            Ok(12.0)
+        );
+        assert_eq!(
+            evaluate_with_arrays("let t = [0, 10] in t", &variables, &arrays),
+            Err(String::from(
+                "Arrays are only allowed as breakpoints of lookup function or lists of aggregation function at position 8"
+            ))
+        );
+
+        match CompiledExpression::new(expression) {
+            Ok(compiled) => {
+                assert_eq!(compiled.variables(), ["x"]);
+                assert_eq!(compiled.evaluate(&[6.0]), Ok(12.0));
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_conditional_operator() {
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("x"), 0.0), (String::from("y"), 2.0)]);
+
+        // Only the value taken is evaluated
+        assert_eq!(evaluate("x ? ln(x) : -1", &variables), Ok(-1.0));
+        assert_eq!(evaluate("y - 2 ? 1 / x : y ? 3 : 4", &variables), Ok(3.0));
+        assert_eq!(evaluate("y ? x ? 1 : 2 : 3", &variables), Ok(2.0));
+
+        // Any value other than zero is true, even negative
+        assert_eq!(evaluate("x - 1 ? 10 : 20", &variables), Ok(10.0));
+        assert_eq!(evaluate("x - y < 0 ? 10 : 20", &variables), Ok(10.0));
+        assert_eq!(
+            evaluate("2 * (y ? y + 1 : 0) + clamp(x ? 5 : -5, 0, 1)", &variables),
+            Ok(6.0)
+        );
+        assert_eq!(
+            evaluate("y ? 1 / x : 0", &variables),
+            Err(String::from("Division by zero at position 10"))
+        );
+
+        match Ast::new("a ? b : c + 1") {
+            Ok(ast) => assert_eq!(ast.to_string(), "select(a, b, c + 1)"),
+            Err(_) => assert!(false),
+        }
+        match CompiledExpression::new("x ? 1 / x : 2 * y") {
+            Ok(compiled) => assert_eq!(compiled.evaluate_with_variables(&variables), Ok(4.0)),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_boolean_operators() {
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("x"), 0.0), (String::from("y"), -2.0)]);
+
+        // Right operand is only evaluated when needed
+        assert_eq!(evaluate("x && ln(x)", &variables), Ok(0.0));
+        assert_eq!(evaluate("y || 1 / x", &variables), Ok(1.0));
+        assert!(evaluate("y && 1 / x", &variables).is_err());
+
+        assert_eq!(evaluate("x || y && !x", &variables), Ok(1.0));
+        assert_eq!(evaluate("!(x || y) + 2 * !x", &variables), Ok(2.0));
+        assert_eq!(evaluate("x || y ? 3 : 4", &variables), Ok(3.0));
+        assert_eq!(evaluate("!y + (y && 1)", &variables), Ok(1.0));
+        assert_eq!(
+            evaluate("x & y", &variables),
+            Err(String::from("Cannot parse this expression at position 2"))
+        );
+
+        match Ast::new("!a && (b || c)") {
+            Ok(ast) => assert_eq!(ast.to_string(), "!a && (b || c)"),
+            Err(_) => assert!(false),
+        }
+        match CompiledExpression::new("y || 1 / x") {
+            Ok(compiled) => assert_eq!(compiled.evaluate_with_variables(&variables), Ok(1.0)),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_comparison_operators() {
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("x"), 1.0), (String::from("y"), -2.0)]);
+
+        assert_eq!(evaluate("x > y", &variables), Ok(1.0));
+        assert_eq!(evaluate("x <= y", &variables), Ok(0.0));
+        assert_eq!(evaluate("x + y + 1 >= 0", &variables), Ok(1.0));
+        assert_eq!(evaluate("2 * x == x + 1", &variables), Ok(1.0));
+        assert_eq!(evaluate("x != 1", &variables), Ok(0.0));
+
+        // Comparisons bind looser than arithmetic and tighter than boolean operators
+        assert_eq!(evaluate("x < 2 && y < x", &variables), Ok(1.0));
+        assert_eq!(evaluate("3 * (x < 2)", &variables), Ok(3.0));
+        assert_eq!(evaluate("y < x < 1", &variables), Ok(0.0));
+
+        match Ast::new("a >= b + 1 || a != b") {
+            Ok(ast) => assert_eq!(ast.to_string(), "a >= b + 1 || a != b"),
+            Err(_) => assert!(false),
+        }
+        match CompiledExpression::new("y < x < 1") {
+            Ok(compiled) => assert_eq!(compiled.evaluate_with_variables(&variables), Ok(0.0)),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_if_function() {
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("x"), 0.0), (String::from("y"), 2.0)]);
+
+        // Unlike select function, only the value taken is evaluated
+        assert_eq!(evaluate("if(x, 1 / x, 0)", &variables), Ok(0.0));
+        assert_eq!(evaluate("if(x - y, 1, 2)", &variables), Ok(1.0));
+        assert_eq!(evaluate("select(x - y, 1, 2)", &variables), Ok(1.0));
+        assert!(evaluate("select(x, 1 / x, 0)", &variables).is_err());
+        assert_eq!(
+            evaluate("2 * if(y, 1 + if(x, ln(x), 2), 3) - 1", &variables),
+            Ok(5.0)
+        );
+        assert_eq!(
+            evaluate("if(y, 1, 2, 3)", &variables),
+            Err(String::from(
+                "Function if expects 3 arguments but got 4 at position 13"
+            ))
+        );
+
+        match Ast::new("if(a, b, c)") {
+            Ok(ast) => assert_eq!(ast.to_string(), "select(a, b, c)"),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_piecewise_function() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 0.0)]);
+
+        // Value of the first true condition is taken, others values being not evaluated
+        let expression: &str = "piecewise(x < 0, ln(-x), x > 0, ln(x), 1 / (x + 1))";
+        assert_eq!(evaluate(expression, &variables), Ok(1.0));
+        assert_eq!(
+            evaluate(expression, &HashMap::from([(String::from("x"), -1.0)])),
+            Ok(0.0)
+        );
+        assert_eq!(evaluate("2 * piecewise(x + 1, 3, 0)", &variables), Ok(6.0));
+        assert_eq!(evaluate("piecewise(x - 1, 3, 0)", &variables), Ok(3.0));
+        assert_eq!(
+            evaluate("piecewise(x, 1, 2, 3)", &variables),
+            Err(String::from(
+                "Function piecewise expects pairs of condition and value followed by a default value but got 4 arguments at position 20"
+            ))
+        );
+
+        match Ast::new("piecewise(a, b, c, d, e)") {
+            Ok(ast) => assert_eq!(ast.to_string(), "select(a, b, select(c, d, e))"),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_aggregation_functions() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 3.0)]);
+
+        assert_eq!(evaluate("mean(1, 4, 7)", &variables), Ok(4.0));
+        assert_eq!(evaluate("sum([1, 2, 3], x) * 2", &variables), Ok(18.0));
+        assert_eq!(
+            evaluate("max(x^2, min([5, -1]), 2) + median(x)", &variables),
+            Ok(12.0)
+        );
+        assert_eq!(evaluate("var(x, -x) - std([1, 3])", &variables), Ok(8.0));
+        assert_eq!(
+            evaluate("sum([1, 2] * 2)", &variables),
+            Err(String::from(
+                "List must be a whole argument of aggregation function at position 11"
+            ))
+        );
+        assert!(evaluate("mean()", &variables).is_err());
+        assert_eq!(
+            evaluate("mean([])", &variables),
+            Err(String::from("List of mean function is empty at position 5"))
+        );
+        assert_eq!(
+            evaluate("sum(x, [ ]) + min([])", &variables),
+            Err(String::from("List of sum function is empty at position 7"))
+        );
+        assert_eq!(
+            CompiledExpression::new("max([])").map(|_| ()),
+            Err(String::from("List of max function is empty at position 4"))
+        );
+
+        match Ast::new("sum([1, 2], x)") {
+            Ok(ast) => assert_eq!(ast.to_string(), "sum(1, 2, x)"),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_deriv() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("k"), 3.0)]);
+
+        match evaluate("deriv(k * t^2, t, 2) + 1", &variables) {
+            Ok(value) => assert!((value - 13.0).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+        match evaluate("deriv(sin(x), x, pi) * deriv(k * ln(x), x, 1)", &variables) {
+            Ok(value) => assert!((value + 3.0).abs() < 1e-6),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            evaluate("deriv(sqrt(x), x, 0)", &variables),
+            Err(String::from(
+                "Argument of sqrt function is negative at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate("deriv(x, x, 0, 1)", &variables),
+            Err(String::from(
+                "Function deriv expects 3 arguments but got 4 at position 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_solve() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("k"), 2.0)]);
+
+        match evaluate("solve(x^2 - k, x, 1)", &variables) {
+            Ok(value) => assert!((value - 2f64.sqrt()).abs() < 1e-12),
+            Err(_) => assert!(false),
+        }
+        match evaluate("solve(cos(x) - x, x, 0, 10^(-6), 20) * 2", &variables) {
+            Ok(value) => assert!((value - 1.4781702).abs() < 1e-6),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            evaluate("1 + solve(x^2 + 1, x, 3, 10^(-12), 5)", &variables),
+            Err(String::from(
+                "No root found from guess 3 after 5 iterations at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate("solve(x, x, 1, 0)", &variables),
+            Err(String::from(
+                "Tolerance of solve function must be positive at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate("solve(x, x, 1, 10^(-9), 2.5)", &variables),
+            Err(String::from(
+                "Maximal number of iterations of solve function must be a positive integer at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate("solve(x, x)", &variables),
+            Err(String::from(
+                "Function solve expects 3 to 5 arguments but got 2 at position 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_summation() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("n"), 100.0)]);
+
+        assert_eq!(evaluate("series(i, 1, n, i^2)", &variables), Ok(338350.0));
+        assert_eq!(
+            evaluate("series(k, 0, 3, 2^k) + sum(1, 2, 3, n)", &variables),
+            Ok(121.0)
+        );
+        assert_eq!(evaluate("series(i, 5, 1, i)", &variables), Ok(0.0));
+
+        match evaluate("sqrt(6 * series(i, 1, 100000, 1 / i^2))", &variables) {
+            Ok(value) => assert!((value - std::f64::consts::PI).abs() < 1e-4),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            evaluate("1 + series(i, 1, n / 3, i)", &variables),
+            Err(String::from(
+                "Bounds 1 and 33.333333333333336 of summation must be integers at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate("series(i, 0, 2, 1 / i)", &variables),
+            Err(String::from("Division by zero at position 0"))
+        );
+
+        // Meaning of a call does not depend on variables bound by caller
+        let bound: HashMap<String, f64> = HashMap::from([(String::from("x"), 4.0)]);
+        assert_eq!(evaluate("series(x, 1, 2, x)", &bound), Ok(3.0));
+        assert_eq!(evaluate("sum(x, 1, 2, x)", &bound), Ok(3.0));
+
+        // Function sum is a summation when its first argument is a variable used by its last argument
+        assert_eq!(evaluate("sum(i, 1, n, i^2)", &variables), Ok(338350.0));
+        assert_eq!(evaluate("sum(x, 1, 3, x^2) * 2", &bound), Ok(28.0));
+        assert_eq!(evaluate("sum(n, 1, 2, 3)", &variables), Ok(106.0));
+        assert_eq!(evaluate("sum(x, 1, 2, x, 3)", &bound), Ok(14.0));
+        assert_eq!(
+            evaluate("series(k, 1, 10^9, k)", &variables),
+            Err(String::from(
+                "Range of summation from 1 to 1000000000 exceeds 1000000 terms at position 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_product() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("n"), 5.0)]);
+
+        assert_eq!(evaluate("prod(i, 1, n, i)", &variables), Ok(120.0));
+        assert_eq!(
+            evaluate("prod(k, 1, 3, 2) - prod(i, 1, 0, i)", &variables),
+            Ok(7.0)
+        );
+        assert_eq!(evaluate("prod(n, 1, 3, n + 1)", &variables), Ok(24.0));
+
+        match evaluate("prod(k, 2, 1000, 1 - 1 / k^2)", &variables) {
+            Ok(value) => assert!((value - 0.5005).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            evaluate("prod(i, 0.5, n, i)", &variables),
+            Err(String::from(
+                "Bounds 0.5 and 5 of product must be integers at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate("prod(2, 1, n, i)", &variables),
+            Err(String::from(
+                "First argument of prod function must be a variable at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate("prod(i, 1, n)", &variables),
+            Err(String::from(
+                "Function prod expects 4 arguments but got 3 at position 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_taylor() {
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("x"), 1.0), (String::from("a"), 2.0)]);
+
+        match evaluate("taylor(exp(x), x, 0, 12)", &variables) {
+            Ok(value) => assert!((value - std::f64::consts::E).abs() < 1e-8),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(evaluate("taylor(cos(x), x, 0, 2)", &variables), Ok(0.5));
+        assert_eq!(evaluate("taylor(a * x^3, x, 2, 1)", &variables), Ok(-8.0));
+
+        assert_eq!(
+            evaluate("taylor(exp(x), x, 0, 1.5)", &variables),
+            Err(String::from(
+                "Order of taylor function must be a non-negative integer at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate("1 + taylor(max(x, 2), x, 0, 1)", &variables),
+            Err(String::from(
+                "Function max cannot be differentiated at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate("taylor(b * exp(y), y, 0, 1)", &variables),
+            Err(String::from("Missing value of variable b at position 7"))
+        );
+        assert_eq!(
+            evaluate("taylor(exp(y), y, 0, 1)", &variables),
+            Err(String::from("Missing value of variable y at position 0"))
+        );
+        assert_eq!(
+            evaluate("2 * taylor(1 / x, x, 0, 3)", &variables),
+            Err(String::from(
+                "Coefficient of order 0 of Taylor polynomial at 0 is not finite at position 4"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_limit() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("a"), 3.0)]);
+
+        match evaluate("limit(sin(a * x) / x, x, 0)", &variables) {
+            Ok(value) => assert!((value - 3.0).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+        match evaluate("limit((1 + 1 / n)^n, n, 10^8)", &variables) {
+            Ok(value) => assert!((value - std::f64::consts::E).abs() < 1e-6),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            evaluate("1 + limit(1 / (x - a), x, a)", &variables),
+            Err(String::from(
+                "Limit at 3 diverges on left side at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate("limit(round(x, 0), x, 0.5)", &variables),
+            Err(String::from(
+                "Limit at 0.5 differs by side, being 0 on left side and 1 on right side at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate("limit(sin(x) / x, 0)", &variables),
+            Err(String::from(
+                "Function limit expects 3 arguments but got 2 at position 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_minimize() {
+        let variables: HashMap<String, f64> = HashMap::new();
+
+        assert_eq!(
+            evaluate("1 + minimize(x^2, x, 0, 1)", &variables),
+            Err(String::from(
+                "Function minimize gives a vector, so it must be evaluated with vectors at position 4"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_integrate() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("k"), 3.0)]);
+
+        match evaluate("integrate(k * t^2, t, 0, 1) + 1", &variables) {
+            Ok(value) => assert!((value - 2.0).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+        match evaluate(
+            "let a = pi in integrate(sin(x), x, 0, a) * integrate(exp(x), x, 0, ln(k))",
+            &variables,
+        ) {
+            Ok(value) => assert!((value - 4.0).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+        match evaluate("integrate(1, x, k, 1)", &variables) {
+            Ok(value) => assert!((value + 2.0).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            evaluate("1 + integrate(1 / x, x, 0, 1)", &variables),
+            Err(String::from("Division by zero at position 4"))
+        );
+        assert_eq!(
+            evaluate("integrate(x * y, x, 0, 1)", &variables),
+            Err(String::from("Missing value of variable y at position 10"))
+        );
+        assert_eq!(
+            evaluate("integrate(x, 2, 0, 1)", &variables),
+            Err(String::from(
+                "Second argument of integrate function must be a variable at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate("integrate(x, x, 0)", &variables),
+            Err(String::from(
+                "Function integrate expects 4 arguments but got 3 at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate("integrate(x, x, 0, z)", &variables),
+            Err(String::from("Unknown name z at position 19"))
+        );
+    }
+
+    #[test]
+    fn test_evaluation_calculus_in_each_evaluation() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("k"), 3.0)]);
+        let arrays: HashMap<String, Vec<f64>> =
+            HashMap::from([(String::from("ks"), vec![0.0, 3.0])]);
+        let expression: &str = "integrate(2 * t, t, 0, k) + deriv(t^2, t, k)";
+
+        match evaluate_with_options(expression, &variables, &EvaluationOptions::default()) {
+            Ok(value) => assert!((value - 15.0).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+        match evaluate_with_limits(expression, &variables, &Limits::default()) {
+            Ok(value) => assert!((value - 15.0).abs() < 1e-9),
+            Err(_) => assert!(false),
+        }
+        assert_eq!(
+            evaluate_with_arrays(
+                "lookup(series(i, 1, 2, i), ks, [0, 6]) + prod(i, 1, k, i)",
+                &variables,
+                &arrays
+            ),
+            Ok(12.0)
+        );
+
+        // Points of calls are evaluated the same way as the rest of expression
+        let options: EvaluationOptions = EvaluationOptions {
+            angle: AngleUnit::Degrees,
+            ..EvaluationOptions::default()
+        };
+        match evaluate_with_options("integrate(1, t, 0, sin(30)) * 2", &variables, &options) {
+            Ok(value) => assert!((value - 1.0).abs() < 1e-12),
+            Err(_) => assert!(false),
+        }
+        assert_eq!(
+            evaluate_with_limits("1 + series(i, 1, ((k)), i)", &variables, &Limits::default()),
+            Ok(7.0)
+        );
+        assert_eq!(
+            evaluate_with_limits(
+                "1 + series(i, 1, ((k)), i)",
+                &variables,
+                &Limits {
+                    max_nesting: 1,
+                    ..Limits::default()
+                }
+            ),
+            Err(Error::LimitExceeded(Limit::Nesting(1)))
+        );
+    }
 }
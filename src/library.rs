@@ -0,0 +1,592 @@
+#![allow(clippy::needless_return)]
+
+use super::constants;
+use super::converter;
+use super::evaluator;
+use super::functions::Function;
+use super::token::Token;
+use super::tokenizer;
+use super::units::{self, Unit};
+
+use std::collections::{BTreeMap, HashMap};
+
+/// Expression stored in a formula library with its metadata
+#[derive(Debug, PartialEq, Clone)]
+pub struct Formula {
+    pub expression: String,
+    pub description: String,
+    pub variables: Vec<String>,
+    pub units: HashMap<String, Unit>,
+    pub result_unit: Option<Unit>,
+}
+
+impl Formula {
+    /// Create a formula from an expression and names of its variables,
+    /// without description nor units
+    pub fn new(expression: &str, variables: &[&str]) -> Formula {
+        Formula {
+            expression: String::from(expression),
+            description: String::new(),
+            variables: variables.iter().map(|&name| String::from(name)).collect(),
+            units: HashMap::new(),
+            result_unit: None,
+        }
+    }
+}
+
+/// Options applied to all formulas of a library
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct LibraryOptions {
+    /// When set, a formula is accepted only if all its variables and its result have a unit
+    pub require_units: bool,
+}
+
+/// Formula of library with its expression already converted into postfix tokens
+#[derive(Debug, PartialEq, Clone)]
+struct Entry {
+    formula: Formula,
+    constant_names: Vec<String>,
+    postfix_tokens: Vec<Token>,
+}
+
+impl Entry {
+    /// Create an entry from formula given in argument.
+    /// Expression of formula is parsed, its variables and constants of library being resolved, and,
+    /// when all variables have a unit, its units are checked against the result unit.
+    /// If error occurs, an error message is stored in string contained in Result output
+    fn new(
+        formula: Formula,
+        library_constants: &BTreeMap<String, f64>,
+        options: &LibraryOptions,
+    ) -> Result<Entry, String> {
+        let constant_names: Vec<String> = library_constants.keys().cloned().collect();
+
+        let names: Vec<&str> = formula
+            .variables
+            .iter()
+            .chain(constant_names.iter())
+            .map(|name| name.as_str())
+            .collect();
+
+        let tokens: Vec<Token> = tokenizer::tokenize_with_slots(&formula.expression, &names)?;
+        let postfix_tokens: Vec<Token> = converter::infix_to_postfix(tokens)?;
+
+        let variable_units: Option<Vec<Unit>> = formula
+            .variables
+            .iter()
+            .map(|name| formula.units.get(name).copied())
+            .chain(constant_names.iter().map(|_| Some(Unit::dimensionless())))
+            .collect();
+
+        if options.require_units && (variable_units.is_none() || formula.result_unit.is_none()) {
+            return Err(String::from(
+                "Units of all variables and of result are required by library",
+            ));
+        }
+
+        if let Some(variable_units) = variable_units {
+            let unit: Unit = units::check_postfix_units(&postfix_tokens, &variable_units)?;
+
+            if let Some(result_unit) = formula.result_unit {
+                if unit != result_unit {
+                    return Err(format!(
+                        "Unit of formula is {unit} whereas {result_unit} is expected"
+                    ));
+                }
+            }
+        }
+
+        return Ok(Entry {
+            formula,
+            constant_names,
+            postfix_tokens,
+        });
+    }
+
+    /// Evaluate formula with values of variables and constants of library given in argument.
+    /// If error occurs during evaluation, an error message is stored
+    /// in string contained in Result output
+    fn evaluate(
+        &self,
+        variables: &HashMap<String, f64>,
+        library_constants: &BTreeMap<String, f64>,
+    ) -> Result<f64, String> {
+        let mut values: Vec<f64> =
+            Vec::with_capacity(self.formula.variables.len() + self.constant_names.len());
+
+        for name in self.formula.variables.iter() {
+            match variables.get(name) {
+                Some(&value) => values.push(value),
+                None => return Err(format!("Missing value of variable {name}")),
+            }
+        }
+
+        for name in self.constant_names.iter() {
+            values.push(library_constants[name]);
+        }
+
+        return evaluator::postfix_evaluation_with_values(&self.postfix_tokens, &values);
+    }
+
+    /// Names of functions used by formula
+    fn functions(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self
+            .postfix_tokens
+            .iter()
+            .filter_map(|token| match token {
+                Token::Function(fun) => Some(fun.name()),
+                _ => None,
+            })
+            .collect();
+
+        names.sort_unstable();
+        names.dedup();
+        return names;
+    }
+}
+
+/// Registry of named and versioned formulas.
+/// Formulas are validated when they are added, then they can be listed,
+/// looked up and evaluated by name and version.
+/// The library can also hold named constants usable in all its formulas
+/// and aliases giving other names to formulas.
+/// Names and versions are always listed in increasing order.
+///
+/// # Example
+/// ```
+/// use taz::{Formula, FormulaLibrary};
+/// use std::collections::HashMap;
+///
+/// let mut library: FormulaLibrary = FormulaLibrary::new();
+///
+/// let mut margin: Formula = Formula::new("(price - cost) / price", &["price", "cost"]);
+/// margin.description = String::from("Gross margin ratio");
+///
+/// library.add("margin", 1, margin).unwrap();
+/// library.add("margin", 2, Formula::new("clamp((price - cost) / price, 0, 1)", &["price", "cost"])).unwrap();
+///
+/// let variables: HashMap<String, f64> = HashMap::from([
+///     (String::from("price"), 10.0),
+///     (String::from("cost"), 12.0),
+/// ]);
+///
+/// assert_eq!(library.evaluate("margin", 1, &variables), Ok(-0.2));
+/// assert_eq!(library.evaluate_latest("margin", &variables), Ok(0.0));
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct FormulaLibrary {
+    entries: BTreeMap<String, BTreeMap<u32, Entry>>,
+    constants: BTreeMap<String, f64>,
+    aliases: BTreeMap<String, String>,
+    options: LibraryOptions,
+}
+
+impl FormulaLibrary {
+    /// Create an empty library
+    pub fn new() -> FormulaLibrary {
+        FormulaLibrary::with_options(LibraryOptions::default())
+    }
+
+    /// Create an empty library with options given in argument
+    pub fn with_options(options: LibraryOptions) -> FormulaLibrary {
+        FormulaLibrary {
+            entries: BTreeMap::new(),
+            constants: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            options,
+        }
+    }
+
+    /// Options of library
+    pub fn options(&self) -> &LibraryOptions {
+        return &self.options;
+    }
+
+    /// Check if a name can be given to a constant, a formula or an alias of library
+    fn check_new_name(&self, name: &str) -> Result<(), String> {
+        if constants::is_constant(name) || Function::is_fun(name) {
+            return Err(format!(
+                "Name {name} is already a predefined constant or function"
+            ));
+        }
+
+        if self.constants.contains_key(name)
+            || self.entries.contains_key(name)
+            || self.aliases.contains_key(name)
+        {
+            return Err(format!("Name {name} is already used in library"));
+        }
+
+        return Ok(());
+    }
+
+    /// Define a constant usable in formulas added after it.
+    /// The value of an existing constant can be changed,
+    /// it is then taken into account in all formulas using this constant.
+    /// If name of constant is already used, an error message is stored
+    /// in string contained in Result output
+    pub fn set_constant(&mut self, name: &str, value: f64) -> Result<(), String> {
+        if !self.constants.contains_key(name) {
+            self.check_new_name(name)?;
+        }
+
+        self.constants.insert(String::from(name), value);
+        return Ok(());
+    }
+
+    /// Iterate over constants of library with their value
+    pub fn constants(&self) -> impl Iterator<Item = (&str, f64)> + '_ {
+        return self
+            .constants
+            .iter()
+            .map(|(name, &value)| (name.as_str(), value));
+    }
+
+    /// Give another name to a formula of library.
+    /// If formula does not exist or if alias is already used, an error message is stored
+    /// in string contained in Result output
+    pub fn add_alias(&mut self, alias: &str, name: &str) -> Result<(), String> {
+        if !self.entries.contains_key(name) {
+            return Err(format!("Unknown formula {name}"));
+        }
+
+        self.check_new_name(alias)?;
+        self.aliases.insert(String::from(alias), String::from(name));
+
+        return Ok(());
+    }
+
+    /// Iterate over aliases of library with the name of formula they refer to
+    pub fn aliases(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        return self
+            .aliases
+            .iter()
+            .map(|(alias, name)| (alias.as_str(), name.as_str()));
+    }
+
+    /// Get name of formula from a name which can be an alias
+    fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        return self
+            .aliases
+            .get(name)
+            .map_or(name, |target| target.as_str());
+    }
+
+    /// Get entry of a version of formula, the name of formula can be an alias
+    fn entry(&self, name: &str, version: u32) -> Option<&Entry> {
+        return self
+            .entries
+            .get(self.resolve(name))
+            .and_then(|versions| versions.get(&version));
+    }
+
+    /// Get entry of latest version of formula, the name of formula can be an alias
+    fn latest_entry(&self, name: &str) -> Option<(u32, &Entry)> {
+        return self
+            .entries
+            .get(self.resolve(name))
+            .and_then(|versions| versions.last_key_value())
+            .map(|(&version, entry)| (version, entry));
+    }
+
+    /// Add a formula to library with name and version given in argument.
+    /// If formula is not valid or if this version of formula already exists,
+    /// an error message is stored in string contained in Result output
+    pub fn add(&mut self, name: &str, version: u32, formula: Formula) -> Result<(), String> {
+        if self.entries.contains_key(name) {
+            if self.get(name, version).is_some() {
+                return Err(format!(
+                    "Version {version} of formula {name} already exists"
+                ));
+            }
+        } else {
+            self.check_new_name(name)?;
+        }
+
+        let entry: Entry = Entry::new(formula, &self.constants, &self.options)?;
+
+        self.entries
+            .entry(String::from(name))
+            .or_default()
+            .insert(version, entry);
+
+        return Ok(());
+    }
+
+    /// Remove a version of formula from library and return it.
+    /// When the last version of formula is removed, its aliases are also removed.
+    pub fn remove(&mut self, name: &str, version: u32) -> Option<Formula> {
+        let versions: &mut BTreeMap<u32, Entry> = self.entries.get_mut(name)?;
+        let entry: Option<Entry> = versions.remove(&version);
+
+        if versions.is_empty() {
+            self.entries.remove(name);
+            self.aliases.retain(|_, target| target != name);
+        }
+
+        return entry.map(|entry| entry.formula);
+    }
+
+    /// Number of formulas in library, all versions included
+    pub fn len(&self) -> usize {
+        return self.entries.values().map(|versions| versions.len()).sum();
+    }
+
+    /// Check if library does not contain any formula
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    /// Get a version of formula, the name of formula can be an alias
+    pub fn get(&self, name: &str, version: u32) -> Option<&Formula> {
+        return self.entry(name, version).map(|entry| &entry.formula);
+    }
+
+    /// Get latest version of formula with its version number, the name of formula can be an alias
+    pub fn latest(&self, name: &str) -> Option<(u32, &Formula)> {
+        return self
+            .latest_entry(name)
+            .map(|(version, entry)| (version, &entry.formula));
+    }
+
+    /// Iterate over names of formulas
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        return self.entries.keys().map(|name| name.as_str());
+    }
+
+    /// Get all versions of formula, the name of formula can be an alias
+    pub fn versions(&self, name: &str) -> Vec<u32> {
+        return self
+            .entries
+            .get(self.resolve(name))
+            .map_or(Vec::new(), |versions| versions.keys().copied().collect());
+    }
+
+    /// Iterate over all formulas with their name and version
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32, &Formula)> + '_ {
+        return self.entries.iter().flat_map(|(name, versions)| {
+            versions
+                .iter()
+                .map(move |(&version, entry)| (name.as_str(), version, &entry.formula))
+        });
+    }
+
+    /// Names of functions used by a version of formula, the name of formula can be an alias
+    pub fn functions(&self, name: &str, version: u32) -> Vec<&'static str> {
+        return self
+            .entry(name, version)
+            .map_or(Vec::new(), |entry| entry.functions());
+    }
+
+    /// Evaluate a version of formula with values of variables given in argument.
+    /// The name of formula can be an alias.
+    /// If formula does not exist or if error occurs during evaluation,
+    /// an error message is stored in string contained in Result output
+    pub fn evaluate(
+        &self,
+        name: &str,
+        version: u32,
+        variables: &HashMap<String, f64>,
+    ) -> Result<f64, String> {
+        match self.entry(name, version) {
+            Some(entry) => entry.evaluate(variables, &self.constants),
+            None => Err(format!("Unknown version {version} of formula {name}")),
+        }
+    }
+
+    /// Evaluate latest version of formula with values of variables given in argument.
+    /// The name of formula can be an alias.
+    /// If formula does not exist or if error occurs during evaluation,
+    /// an error message is stored in string contained in Result output
+    pub fn evaluate_latest(
+        &self,
+        name: &str,
+        variables: &HashMap<String, f64>,
+    ) -> Result<f64, String> {
+        match self.latest_entry(name) {
+            Some((_, entry)) => entry.evaluate(variables, &self.constants),
+            None => Err(format!("Unknown formula {name}")),
+        }
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speed_formula() -> Formula {
+        let mut formula: Formula = Formula::new("d / t", &["d", "t"]);
+        formula.description = String::from("Average speed");
+        formula.units = HashMap::from([
+            (String::from("d"), Unit::meter()),
+            (String::from("t"), Unit::second()),
+        ]);
+        formula.result_unit = Some(Unit::meter() / Unit::second());
+
+        return formula;
+    }
+
+    #[test]
+    fn test_library_add_and_get() {
+        let mut library: FormulaLibrary = FormulaLibrary::new();
+        assert!(library.is_empty());
+
+        assert!(library.add("speed", 1, speed_formula()).is_ok());
+        assert!(library.add("speed", 3, speed_formula()).is_ok());
+        assert!(library
+            .add("double", 1, Formula::new("2 * x", &["x"]))
+            .is_ok());
+
+        assert_eq!(library.len(), 3);
+        assert_eq!(library.get("speed", 1), Some(&speed_formula()));
+        assert!(library.get("speed", 2).is_none());
+        assert_eq!(library.latest("speed").map(|(version, _)| version), Some(3));
+        assert!(library.latest("unknown").is_none());
+
+        let names: Vec<&str> = library.names().collect();
+        assert_eq!(names, vec!["double", "speed"]);
+        assert_eq!(library.versions("speed"), vec![1, 3]);
+
+        let listing: Vec<(&str, u32)> = library
+            .iter()
+            .map(|(name, version, _)| (name, version))
+            .collect();
+        assert_eq!(listing, vec![("double", 1), ("speed", 1), ("speed", 3)]);
+    }
+
+    #[test]
+    fn test_library_add_existing_version() {
+        let mut library: FormulaLibrary = FormulaLibrary::new();
+
+        assert!(library.add("speed", 1, speed_formula()).is_ok());
+
+        match library.add("speed", 1, speed_formula()) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(message, "Version 1 of formula speed already exists"),
+        }
+    }
+
+    #[test]
+    fn test_library_add_invalid_formula() {
+        let mut library: FormulaLibrary = FormulaLibrary::new();
+
+        // Undeclared variable
+        assert!(library.add("f", 1, Formula::new("x + y", &["x"])).is_err());
+
+        // Mismatched parenthesis
+        assert!(library.add("f", 1, Formula::new("(x + 1", &["x"])).is_err());
+
+        // Wrong result unit
+        let mut formula: Formula = speed_formula();
+        formula.result_unit = Some(Unit::meter());
+
+        match library.add("f", 1, formula) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(message, "Unit of formula is m.s^-1 whereas m is expected"),
+        }
+
+        assert!(library.is_empty());
+    }
+
+    #[test]
+    fn test_library_evaluate() {
+        let mut library: FormulaLibrary = FormulaLibrary::new();
+
+        assert!(library.add("speed", 1, speed_formula()).is_ok());
+        assert!(library
+            .add("speed", 2, Formula::new("2 * d / t", &["d", "t"]))
+            .is_ok());
+
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("d"), 100.0), (String::from("t"), 20.0)]);
+
+        assert_eq!(library.evaluate("speed", 1, &variables), Ok(5.0));
+        assert_eq!(library.evaluate("speed", 2, &variables), Ok(10.0));
+        assert_eq!(library.evaluate_latest("speed", &variables), Ok(10.0));
+
+        assert!(library.evaluate("speed", 3, &variables).is_err());
+        assert!(library.evaluate_latest("unknown", &variables).is_err());
+
+        match library.evaluate("speed", 1, &HashMap::new()) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(message, "Missing value of variable d"),
+        }
+    }
+
+    #[test]
+    fn test_library_remove() {
+        let mut library: FormulaLibrary = FormulaLibrary::new();
+
+        assert!(library.add("speed", 1, speed_formula()).is_ok());
+        assert_eq!(library.remove("speed", 1), Some(speed_formula()));
+        assert_eq!(library.remove("speed", 1), None);
+        assert!(library.is_empty());
+    }
+
+    #[test]
+    fn test_library_constants() {
+        let mut library: FormulaLibrary = FormulaLibrary::new();
+
+        assert!(library.set_constant("vat", 0.2).is_ok());
+        assert!(library.set_constant("pi", 3.0).is_err());
+        assert!(library
+            .add("gross", 1, Formula::new("net * (1 + vat)", &["net"]))
+            .is_ok());
+
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("net"), 100.0)]);
+        assert_eq!(library.evaluate("gross", 1, &variables), Ok(120.0));
+
+        assert!(library.set_constant("vat", 0.1).is_ok());
+        assert!((library.evaluate("gross", 1, &variables).unwrap() - 110.0).abs() < 1e-10);
+
+        let constants: Vec<(&str, f64)> = library.constants().collect();
+        assert_eq!(constants, vec![("vat", 0.1)]);
+        assert_eq!(library.functions("gross", 1), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_library_aliases() {
+        let mut library: FormulaLibrary = FormulaLibrary::new();
+
+        assert!(library.add("speed", 1, speed_formula()).is_ok());
+        assert!(library.add_alias("velocity", "speed").is_ok());
+        assert!(library.add_alias("celerity", "unknown").is_err());
+        assert!(library.add_alias("speed", "speed").is_err());
+
+        let variables: HashMap<String, f64> =
+            HashMap::from([(String::from("d"), 100.0), (String::from("t"), 20.0)]);
+
+        assert_eq!(library.evaluate_latest("velocity", &variables), Ok(5.0));
+        assert_eq!(library.get("velocity", 1), library.get("speed", 1));
+        assert_eq!(library.versions("velocity"), vec![1]);
+
+        let aliases: Vec<(&str, &str)> = library.aliases().collect();
+        assert_eq!(aliases, vec![("velocity", "speed")]);
+
+        assert!(library.remove("speed", 1).is_some());
+        assert_eq!(library.aliases().count(), 0);
+    }
+
+    #[test]
+    fn test_library_options_require_units() {
+        let mut library: FormulaLibrary = FormulaLibrary::with_options(LibraryOptions {
+            require_units: true,
+        });
+
+        assert!(library.options().require_units);
+        assert!(library.add("speed", 1, speed_formula()).is_ok());
+        assert!(library
+            .add("double", 1, Formula::new("2 * x", &["x"]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_library_functions() {
+        let mut library: FormulaLibrary = FormulaLibrary::new();
+
+        let formula: Formula = Formula::new("sqrt(x) + cos(x) * sqrt(2)", &["x"]);
+        assert!(library.add("f", 1, formula).is_ok());
+        assert_eq!(library.functions("f", 1), vec!["cos", "sqrt"]);
+    }
+}
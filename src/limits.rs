@@ -0,0 +1,88 @@
+#![allow(clippy::needless_return)]
+
+use std::fmt;
+use std::time::Instant;
+
+/// Limits on size of expressions accepted by parser, so a hostile expression
+/// cannot exhaust memory of an application evaluating expressions given by its users
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Limits {
+    /// Maximal number of tokens of expression
+    pub max_tokens: usize,
+    /// Maximal number of operators waiting for their operands during conversion to postfix expression
+    pub max_operator_depth: usize,
+    /// Maximal number of nested parenthesis
+    pub max_nesting: usize,
+}
+
+impl Limits {
+    /// Create limits accepting expressions of any size
+    pub fn unlimited() -> Limits {
+        return Limits {
+            max_tokens: usize::MAX,
+            max_operator_depth: usize::MAX,
+            max_nesting: usize::MAX,
+        };
+    }
+}
+
+impl Default for Limits {
+    /// Limits large enough for any formula written by hand
+    fn default() -> Limits {
+        return Limits {
+            max_tokens: 10_000,
+            max_operator_depth: 1_000,
+            max_nesting: 256,
+        };
+    }
+}
+
+/// Limit exceeded by an expression, holding the maximal value allowed
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Limit {
+    Tokens(usize),
+    OperatorDepth(usize),
+    Nesting(usize),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Tokens(max) => write!(f, "Expression exceeds limit of {max} tokens"),
+            Limit::OperatorDepth(max) => {
+                write!(f, "Expression exceeds limit of {max} pending operators")
+            }
+            Limit::Nesting(max) => {
+                write!(f, "Expression exceeds limit of {max} nested parenthesis")
+            }
+        }
+    }
+}
+
+/// Budget of an evaluation, giving a maximal number of evaluation steps and a deadline,
+/// so an evaluation cannot hang the thread of its caller
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Budget {
+    /// Maximal number of steps of evaluation, each instruction of bytecode being a step
+    pub max_steps: Option<u64>,
+    /// Instant after which evaluation is aborted
+    pub deadline: Option<Instant>,
+}
+
+impl Budget {
+    /// Create a budget limiting evaluation to a number of steps
+    pub fn steps(max_steps: u64) -> Budget {
+        return Budget {
+            max_steps: Some(max_steps),
+            deadline: None,
+        };
+    }
+
+    /// Create a budget limiting evaluation to a deadline
+    pub fn deadline(deadline: Instant) -> Budget {
+        return Budget {
+            max_steps: None,
+            deadline: Some(deadline),
+        };
+    }
+}
@@ -0,0 +1,227 @@
+#![allow(clippy::needless_return)]
+
+use super::converter::Postfix;
+use super::evaluator::{self, OperandStack};
+use super::tokenizer;
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Separators used to write numbers in a locale, like 1,234,567.89 or 1 234 567,89
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct NumberLocale {
+    decimal_separator: char,
+    group_separator: Option<char>,
+}
+
+impl NumberLocale {
+    /// Locale using point as decimal separator and comma to group digits, like 1,234,567.89
+    pub const POINT_DECIMAL: NumberLocale = NumberLocale {
+        decimal_separator: '.',
+        group_separator: Some(','),
+    };
+
+    /// Locale using comma as decimal separator and space to group digits, like 1 234 567,89
+    pub const COMMA_DECIMAL: NumberLocale = NumberLocale {
+        decimal_separator: ',',
+        group_separator: Some(' '),
+    };
+
+    /// Create locale from its decimal separator and its group separator, if digits can be grouped.
+    /// If separators are the same or one of them is a digit or a letter,
+    /// an error message is stored in string contained in Result output
+    pub fn new(
+        decimal_separator: char,
+        group_separator: Option<char>,
+    ) -> Result<NumberLocale, String> {
+        let separators = std::iter::once(decimal_separator).chain(group_separator);
+
+        for separator in separators {
+            if separator.is_alphanumeric() {
+                return Err(format!(
+                    "Separator {separator} cannot be a digit or a letter"
+                ));
+            }
+        }
+
+        if group_separator == Some(decimal_separator) {
+            return Err(String::from(
+                "Decimal and group separators must be different",
+            ));
+        }
+
+        return Ok(NumberLocale {
+            decimal_separator,
+            group_separator,
+        });
+    }
+
+    /// Get decimal separator of locale
+    pub fn decimal_separator(&self) -> char {
+        return self.decimal_separator;
+    }
+
+    /// Get group separator of locale, if digits can be grouped
+    pub fn group_separator(&self) -> Option<char> {
+        return self.group_separator;
+    }
+}
+
+impl Default for NumberLocale {
+    /// Locale of Taz expressions: point as decimal separator and no grouping of digits
+    fn default() -> NumberLocale {
+        return NumberLocale {
+            decimal_separator: '.',
+            group_separator: None,
+        };
+    }
+}
+
+/// Warning about a number of expression which can be read in two ways, like 1,234
+/// in a locale using comma as decimal separator, which is read as 1.234
+/// but could be meant as 1234 by someone grouping digits with commas
+#[derive(Debug, PartialEq, Clone)]
+pub struct AmbiguityWarning {
+    /// Number as written in expression
+    pub number: String,
+    /// Position of number in expression, as byte offset
+    pub position: usize,
+    /// Value of number according to locale
+    pub value: f64,
+    /// Value of number if its decimal separator was a group separator
+    pub alternative: f64,
+}
+
+impl fmt::Display for AmbiguityWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Number {} at position {} is read as {} but could be {} with grouped digits",
+            self.number, self.position, self.value, self.alternative
+        )
+    }
+}
+
+/// Evaluate an expression like evaluate function, numbers being written with separators of locale
+/// given in argument, like 1,234,567.89 or 1 234 567,89.
+/// A group separator is only read between groups of three digits, so a comma followed by a space
+/// still separates arguments of functions. Each number which could be read in another way,
+/// like 1,234 in a locale using comma as decimal separator, gives a warning alongside value of expression.
+///
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+pub fn evaluate_with_locale(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    locale: &NumberLocale,
+) -> Result<(f64, Vec<AmbiguityWarning>), String> {
+    let infix_tokens = tokenizer::infix_tokens(expression, variables).with_locale(*locale);
+    let mut postfix_tokens = Postfix::new(infix_tokens);
+    let values: OperandStack = evaluator::postfix_evaluation_stream(postfix_tokens.by_ref(), &[])
+        .map_err(|message| postfix_tokens.tokens().locate(message))?;
+
+    match values.first() {
+        Some(&value) => Ok((value, postfix_tokens.tokens().ambiguities().to_vec())),
+        None => Err(String::from("Empty expression")),
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_locale_new() {
+        match NumberLocale::new(',', Some('.')) {
+            Ok(locale) => {
+                assert_eq!(locale.decimal_separator(), ',');
+                assert_eq!(locale.group_separator(), Some('.'));
+            }
+            Err(_) => assert!(false),
+        }
+
+        match NumberLocale::new(',', Some(',')) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(message, "Decimal and group separators must be different"),
+        }
+
+        assert!(NumberLocale::new('.', Some('0')).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_with_locale_grouped_numbers() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 2.0)]);
+
+        match evaluate_with_locale("1,234,567.89 * x", &variables, &NumberLocale::POINT_DECIMAL) {
+            Ok((value, warnings)) => {
+                assert_eq!(value, 2469135.78);
+                assert!(warnings.is_empty());
+            }
+            Err(_) => assert!(false),
+        }
+
+        match evaluate_with_locale("1 234 567,89 * x", &variables, &NumberLocale::COMMA_DECIMAL) {
+            Ok((value, warnings)) => {
+                assert_eq!(value, 2469135.78);
+                assert!(warnings.is_empty());
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_with_locale_arguments() {
+        // A separator which does not group three digits separates arguments
+        match evaluate_with_locale("gcd(1,5, 2)", &HashMap::new(), &NumberLocale::POINT_DECIMAL) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(
+                message,
+                "Function gcd expects 2 arguments but got 3 at position 10"
+            ),
+        }
+
+        match evaluate_with_locale(
+            "lerp(1,5, 2,5, 0,5)",
+            &HashMap::new(),
+            &NumberLocale::COMMA_DECIMAL,
+        ) {
+            Ok((value, warnings)) => {
+                assert_eq!(value, 2.0);
+                assert!(warnings.is_empty());
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_with_locale_ambiguity() {
+        match evaluate_with_locale("2 * 1,234", &HashMap::new(), &NumberLocale::COMMA_DECIMAL) {
+            Ok((value, warnings)) => {
+                assert_eq!(value, 2.468);
+                assert_eq!(
+                    warnings,
+                    vec![AmbiguityWarning {
+                        number: String::from("1,234"),
+                        position: 4,
+                        value: 1.234,
+                        alternative: 1234.0,
+                    }]
+                );
+                assert_eq!(
+                    warnings[0].to_string(),
+                    "Number 1,234 at position 4 is read as 1.234 but could be 1234 with grouped digits"
+                );
+            }
+            Err(_) => assert!(false),
+        }
+
+        // Grouped digits make the comma a decimal separator without doubt
+        match evaluate_with_locale("1 000,234", &HashMap::new(), &NumberLocale::COMMA_DECIMAL) {
+            Ok((value, warnings)) => {
+                assert_eq!(value, 1000.234);
+                assert!(warnings.is_empty());
+            }
+            Err(_) => assert!(false),
+        }
+    }
+}
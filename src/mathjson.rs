@@ -0,0 +1,376 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::{Ast, Node};
+use super::constants;
+use super::functions::Function;
+use super::operators::{BinaryOperator, UnaryOperator};
+
+use serde_json::{json, Value};
+
+/// Functions having the same meaning in Taz and in MathJSON, associated to their MathJSON names.
+/// Other functions keep their Taz name in MathJSON
+const MATHJSON_FUNCTIONS: [(Function, &str); 22] = [
+    (Function::Abs, "Abs"),
+    (Function::Sqrt, "Sqrt"),
+    (Function::Exp, "Exp"),
+    (Function::Ln, "Ln"),
+    (Function::Log10, "Lg"),
+    (Function::Log2, "Lb"),
+    (Function::Sin, "Sin"),
+    (Function::Cos, "Cos"),
+    (Function::Tan, "Tan"),
+    (Function::Asin, "Arcsin"),
+    (Function::Acos, "Arccos"),
+    (Function::Atan, "Arctan"),
+    (Function::Sinh, "Sinh"),
+    (Function::Cosh, "Cosh"),
+    (Function::Tanh, "Tanh"),
+    (Function::Asinh, "Arsinh"),
+    (Function::Acosh, "Arcosh"),
+    (Function::Atanh, "Artanh"),
+    (Function::Clamp, "Clamp"),
+    (Function::Gcd, "GCD"),
+    (Function::Lcm, "LCM"),
+    (Function::Ncr, "Binomial"),
+];
+
+/// Get MathJSON name of binary operator given in argument
+fn operator_name(ops: BinaryOperator) -> &'static str {
+    match ops {
+        BinaryOperator::Plus => "Add",
+        BinaryOperator::Minus => "Subtract",
+        BinaryOperator::Multiply => "Multiply",
+        BinaryOperator::Divide => "Divide",
+        BinaryOperator::Power => "Power",
+        BinaryOperator::And => "And",
+        BinaryOperator::Or => "Or",
+        BinaryOperator::Less => "Less",
+        BinaryOperator::LessEqual => "LessEqual",
+        BinaryOperator::Greater => "Greater",
+        BinaryOperator::GreaterEqual => "GreaterEqual",
+        BinaryOperator::Equal => "Equal",
+        BinaryOperator::NotEqual => "NotEqual",
+    }
+}
+
+/// Write number given in argument as MathJSON number, integers being written without decimals
+/// and non-finite numbers being written as strings like NaN or +Infinity
+fn write_number(number: f64) -> Value {
+    if number.fract() == 0.0 && number.abs() < 9007199254740992.0 {
+        return json!(number as i64);
+    }
+
+    match serde_json::Number::from_f64(number) {
+        Some(number) => Value::Number(number),
+        None if number.is_nan() => json!({ "num": "NaN" }),
+        None if number > 0.0 => json!({ "num": "+Infinity" }),
+        None => json!({ "num": "-Infinity" }),
+    }
+}
+
+/// Write node given in argument as MathJSON expression
+fn write_node(node: &Node) -> Value {
+    match node {
+        Node::Number(number) => write_number(*number),
+        Node::Constant(name, _) => match name.as_str() {
+            "pi" => json!("Pi"),
+            "e" => json!("ExponentialE"),
+            _ => json!(name),
+        },
+        Node::Variable(name) => json!(name),
+        Node::Array(values) => {
+            let mut list: Vec<Value> = vec![json!("List")];
+            list.extend(values.iter().map(|&value| write_number(value)));
+            Value::Array(list)
+        }
+        Node::Unary(UnaryOperator::Plus, operand) => write_node(operand),
+        Node::Unary(UnaryOperator::Minus, operand) => json!(["Negate", write_node(operand)]),
+        Node::Unary(UnaryOperator::Not, operand) => json!(["Not", write_node(operand)]),
+        Node::Binary(ops, left, right) => {
+            json!([operator_name(*ops), write_node(left), write_node(right)])
+        }
+        Node::Call(Function::Cbrt, args) => json!(["Root", write_node(&args[0]), 3]),
+        Node::Call(fun, args) => {
+            let name: &str = MATHJSON_FUNCTIONS
+                .iter()
+                .find(|(function, _)| function == fun)
+                .map_or(fun.name(), |&(_, name)| name);
+
+            let mut call: Vec<Value> = vec![json!(name)];
+            call.extend(args.iter().map(write_node));
+            Value::Array(call)
+        }
+        Node::Tuple(elements) => {
+            let mut tuple: Vec<Value> = vec![json!("Tuple")];
+            tuple.extend(elements.iter().map(write_node));
+            Value::Array(tuple)
+        }
+    }
+}
+
+/// Read number of MathJSON expression given in argument, negative numbers being read as negations.
+/// If number is not finite, an error message is stored in string contained in Result output
+fn read_number(number: f64) -> Result<Node, String> {
+    if !number.is_finite() {
+        return Err(format!(
+            "MathJSON number {number} cannot be written in expression"
+        ));
+    }
+
+    if number.is_sign_negative() && number != 0.0 {
+        return Ok(Node::Unary(
+            UnaryOperator::Minus,
+            Box::new(Node::Number(-number)),
+        ));
+    }
+
+    return Ok(Node::Number(number));
+}
+
+/// Read symbol of MathJSON expression given in argument, as constant or variable
+fn read_symbol(symbol: &str) -> Result<Node, String> {
+    let name: &str = match symbol {
+        "Pi" => "pi",
+        "ExponentialE" => "e",
+        _ if symbol.starts_with('\'') => {
+            return Err(String::from("MathJSON strings are not supported"));
+        }
+        _ => symbol,
+    };
+
+    match constants::from_string(name) {
+        Ok(value) => Ok(Node::Constant(String::from(name), value)),
+        Err(_) => Ok(Node::Variable(String::from(name))),
+    }
+}
+
+/// Combine operands given in argument with binary operator from left to right, like n-ary Add of MathJSON
+fn fold(ops: BinaryOperator, operands: Vec<Node>) -> Option<Node> {
+    return operands
+        .into_iter()
+        .reduce(|left, right| Node::Binary(ops, Box::new(left), Box::new(right)));
+}
+
+/// Read function call of MathJSON expression, whose name and arguments are given in argument.
+/// If function is unknown or its arguments are not valid, an error message is stored in string
+/// contained in Result output
+fn read_call(name: &str, args: &[Value]) -> Result<Node, String> {
+    let mut operands: Vec<Node> = args.iter().map(read_node).collect::<Result<_, _>>()?;
+    let invalid = || format!("Invalid arguments of MathJSON function {name}");
+
+    let binary = |ops: BinaryOperator, mut operands: Vec<Node>| -> Result<Node, String> {
+        match (operands.pop(), operands.pop(), operands.is_empty()) {
+            (Some(right), Some(left), true) => {
+                Ok(Node::Binary(ops, Box::new(left), Box::new(right)))
+            }
+            _ => Err(invalid()),
+        }
+    };
+
+    match (name, operands.len()) {
+        ("Add", _) => fold(BinaryOperator::Plus, operands).ok_or_else(invalid),
+        ("Multiply", _) => fold(BinaryOperator::Multiply, operands).ok_or_else(invalid),
+        ("And", _) => fold(BinaryOperator::And, operands).ok_or_else(invalid),
+        ("Or", _) => fold(BinaryOperator::Or, operands).ok_or_else(invalid),
+        ("Not", 1) => Ok(Node::Unary(
+            UnaryOperator::Not,
+            Box::new(operands.remove(0)),
+        )),
+        ("Subtract" | "Negate", 1) => Ok(Node::Unary(
+            UnaryOperator::Minus,
+            Box::new(operands.remove(0)),
+        )),
+        ("Subtract", _) => binary(BinaryOperator::Minus, operands),
+        ("Less", _) => binary(BinaryOperator::Less, operands),
+        ("LessEqual", _) => binary(BinaryOperator::LessEqual, operands),
+        ("Greater", _) => binary(BinaryOperator::Greater, operands),
+        ("GreaterEqual", _) => binary(BinaryOperator::GreaterEqual, operands),
+        ("Equal", _) => binary(BinaryOperator::Equal, operands),
+        ("NotEqual", _) => binary(BinaryOperator::NotEqual, operands),
+        ("Divide" | "Rational", _) => binary(BinaryOperator::Divide, operands),
+        ("Power", _) => binary(BinaryOperator::Power, operands),
+        ("Square", 1) => {
+            operands.push(Node::Number(2.0));
+            binary(BinaryOperator::Power, operands)
+        }
+        ("Root", 2) => match operands[1] {
+            Node::Number(2.0) => Ok(Node::Call(Function::Sqrt, vec![operands.remove(0)])),
+            Node::Number(3.0) => Ok(Node::Call(Function::Cbrt, vec![operands.remove(0)])),
+            _ => {
+                let degree: Node = operands.remove(1);
+                let exponent: Node = Node::Binary(
+                    BinaryOperator::Divide,
+                    Box::new(Node::Number(1.0)),
+                    Box::new(degree),
+                );
+                operands.push(exponent);
+                binary(BinaryOperator::Power, operands)
+            }
+        },
+        ("Log", 1) => Ok(Node::Call(Function::Log10, operands)),
+        ("Log", 2) => match operands[1] {
+            Node::Number(10.0) => Ok(Node::Call(Function::Log10, vec![operands.remove(0)])),
+            Node::Number(2.0) => Ok(Node::Call(Function::Log2, vec![operands.remove(0)])),
+            _ => {
+                let base: Node = Node::Call(Function::Ln, vec![operands.remove(1)]);
+                let value: Node = Node::Call(Function::Ln, operands);
+                Ok(Node::Binary(
+                    BinaryOperator::Divide,
+                    Box::new(value),
+                    Box::new(base),
+                ))
+            }
+        },
+        ("List", _) => {
+            let values: Option<Vec<f64>> = operands
+                .iter()
+                .map(|operand| match operand {
+                    Node::Number(value) => Some(*value),
+                    Node::Unary(UnaryOperator::Minus, value) => match **value {
+                        Node::Number(value) => Some(-value),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect();
+
+            values
+                .map(Node::Array)
+                .ok_or_else(|| String::from("MathJSON lists must only hold numbers"))
+        }
+        ("Tuple", _) => Ok(Node::Tuple(operands)),
+        _ => {
+            let fun: Function = match MATHJSON_FUNCTIONS.iter().find(|(_, other)| *other == name) {
+                Some(&(fun, _)) => fun,
+                None => Function::from_string(name)
+                    .map_err(|_| format!("Unknown MathJSON function {name}"))?,
+            };
+
+            Ok(Node::Call(fun, operands))
+        }
+    }
+}
+
+/// Read node from MathJSON expression given in argument, in short or object form.
+/// If expression cannot be written in Taz, an error message is stored in string contained in Result output
+fn read_node(value: &Value) -> Result<Node, String> {
+    match value {
+        Value::Number(number) => read_number(number.as_f64().unwrap_or(f64::NAN)),
+        Value::String(symbol) => read_symbol(symbol),
+        Value::Array(call) => match call.split_first() {
+            Some((Value::String(name), args)) => read_call(name, args),
+            _ => Err(String::from("MathJSON function must start with its name")),
+        },
+        Value::Object(object) => {
+            if let Some(Value::String(number)) = object.get("num") {
+                let number: f64 = match number.as_str() {
+                    "NaN" => f64::NAN,
+                    "+Infinity" => f64::INFINITY,
+                    "-Infinity" => f64::NEG_INFINITY,
+                    _ => number
+                        .parse()
+                        .map_err(|_| format!("Invalid MathJSON number {number}"))?,
+                };
+
+                return read_number(number);
+            }
+
+            match (object.get("sym"), object.get("fn")) {
+                (Some(symbol), _) => read_node(symbol),
+                (None, Some(call)) => read_node(call),
+                _ => Err(String::from("Invalid MathJSON object")),
+            }
+        }
+        _ => Err(String::from("Invalid MathJSON expression")),
+    }
+}
+
+impl Ast {
+    /// Write syntax tree as a MathJSON expression, the format of CortexJS Compute Engine,
+    /// like ["Add", ["Multiply", 2, "x"], 1] for 2 * x + 1.
+    /// Functions without MathJSON equivalent, like lerp, keep their name
+    pub fn to_mathjson(&self) -> String {
+        return write_node(&self.root).to_string();
+    }
+
+    /// Read syntax tree from a MathJSON expression, the format of CortexJS Compute Engine.
+    /// Expression read is checked like any expression, so it can be evaluated.
+    ///
+    /// If MathJSON expression is not valid or cannot be written in Taz,
+    /// an error message is stored in string contained in Result output
+    pub fn from_mathjson(mathjson: &str) -> Result<Ast, String> {
+        let value: Value = serde_json::from_str(mathjson).map_err(|error| error.to_string())?;
+        let root: Node = read_node(&value)?;
+
+        return Ast::new(&root.to_string());
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ast_to_mathjson() {
+        match Ast::new("2 * x + sin(pi / 4)^2 - cbrt(-y) / lerp(0, 1.5, t)") {
+            Ok(ast) => assert_eq!(
+                ast.to_mathjson(),
+                concat!(
+                    r#"["Subtract",["Add",["Multiply",2,"x"],["Power",["Sin",["Divide","Pi",4]],2]],"#,
+                    r#"["Divide",["Root",["Negate","y"],3],["lerp",0,1.5,"t"]]]"#
+                )
+            ),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_ast_from_mathjson() {
+        let mathjson: &str = r#"["Add", ["Multiply", 2, "x", "x"], ["Log", "x", 2], ["Root", "y", 4], -3, {"num": "0.5"}]"#;
+
+        match Ast::from_mathjson(mathjson) {
+            Ok(ast) => assert_eq!(
+                ast.to_string(),
+                "2 * x * x + log2(x) + y^(1 / 4) + (-3) + 0.5"
+            ),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_ast_mathjson_round_trip() {
+        for expression in [
+            "-x^2 + e * abs(x - 1)",
+            "lookup(x, [0, 1, 2], [-1, 0, 1])",
+            "(x + 1, atanh(x / 2))",
+        ] {
+            match Ast::new(expression) {
+                Ok(ast) => assert_eq!(Ast::from_mathjson(&ast.to_mathjson()), Ok(ast)),
+                Err(_) => assert!(false),
+            }
+        }
+    }
+
+    #[test]
+    fn test_ast_from_mathjson_errors() {
+        assert_eq!(
+            Ast::from_mathjson(r#"["Integrate", "x"]"#),
+            Err(String::from("Unknown MathJSON function Integrate"))
+        );
+        assert_eq!(
+            Ast::from_mathjson(r#"["Divide", 1]"#),
+            Err(String::from(
+                "Invalid arguments of MathJSON function Divide"
+            ))
+        );
+        assert_eq!(
+            Ast::from_mathjson(r#"{"num": "+Infinity"}"#),
+            Err(String::from(
+                "MathJSON number inf cannot be written in expression"
+            ))
+        );
+        assert!(Ast::from_mathjson(r#"["Sin", "x", "y"]"#).is_err());
+        assert!(Ast::from_mathjson("[1, 2]").is_err());
+    }
+}
@@ -0,0 +1,646 @@
+#![allow(clippy::needless_return)]
+
+use super::converter::Postfix;
+use super::operators::BinaryOperator;
+use super::token::Token;
+use super::tokenizer::Infix;
+use super::vector::Value;
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Names of functions of matrices, whose slot is their index
+const MATRIX_FUNCTIONS: [&str; 3] = ["transpose", "det", "inv"];
+
+/// Matrix of real values, stored row by row
+#[derive(Debug, PartialEq, Clone)]
+pub struct Matrix {
+    nb_rows: usize,
+    nb_columns: usize,
+    values: Vec<f64>,
+}
+
+impl Matrix {
+    /// Create matrix from its rows given in argument.
+    /// If matrix has no value or if rows have different lengths,
+    /// an error message is stored in string contained in Result output
+    pub fn new(rows: &[Vec<f64>]) -> Result<Matrix, String> {
+        let nb_columns: usize = rows.first().map_or(0, |row| row.len());
+
+        if nb_columns == 0 || rows.iter().any(|row| row.len() != nb_columns) {
+            return Err(String::from(
+                "Rows of matrix are empty or have different lengths",
+            ));
+        }
+
+        return Ok(Matrix {
+            nb_rows: rows.len(),
+            nb_columns,
+            values: rows.concat(),
+        });
+    }
+
+    /// Create identity matrix of size given in argument
+    pub fn identity(size: usize) -> Matrix {
+        let mut values: Vec<f64> = vec![0.0; size * size];
+
+        for index in 0..size {
+            values[index * size + index] = 1.0;
+        }
+
+        return Matrix {
+            nb_rows: size,
+            nb_columns: size,
+            values,
+        };
+    }
+
+    /// Get number of rows of matrix
+    pub fn nb_rows(&self) -> usize {
+        return self.nb_rows;
+    }
+
+    /// Get number of columns of matrix
+    pub fn nb_columns(&self) -> usize {
+        return self.nb_columns;
+    }
+
+    /// Get row of matrix whose index is given in argument
+    pub fn row(&self, index: usize) -> &[f64] {
+        return &self.values[index * self.nb_columns..(index + 1) * self.nb_columns];
+    }
+
+    /// Get value of matrix at row and column given in argument
+    pub fn get(&self, row: usize, column: usize) -> f64 {
+        return self.values[row * self.nb_columns + column];
+    }
+
+    /// Size of matrix written like 2x3, to report it in error messages
+    fn size(&self) -> String {
+        return format!("{}x{}", self.nb_rows, self.nb_columns);
+    }
+
+    /// Apply function given in argument on each value of matrix.
+    /// If error occurs on a value, an error message is stored in string contained in Result output
+    fn map<F>(&self, mut fun: F) -> Result<Matrix, String>
+    where
+        F: FnMut(f64) -> Result<f64, String>,
+    {
+        return Ok(Matrix {
+            nb_rows: self.nb_rows,
+            nb_columns: self.nb_columns,
+            values: self
+                .values
+                .iter()
+                .map(|&value| fun(value))
+                .collect::<Result<Vec<f64>, String>>()?,
+        });
+    }
+
+    /// Get transpose of matrix
+    pub fn transpose(&self) -> Matrix {
+        let mut values: Vec<f64> = Vec::with_capacity(self.values.len());
+
+        for column in 0..self.nb_columns {
+            values.extend((0..self.nb_rows).map(|row| self.get(row, column)));
+        }
+
+        return Matrix {
+            nb_rows: self.nb_columns,
+            nb_columns: self.nb_rows,
+            values,
+        };
+    }
+
+    /// Get sum, or difference for minus operator, of matrix with matrix given in argument.
+    /// If matrices have different sizes, an error message is stored in string contained in Result output
+    fn add(&self, other: &Matrix, ops: BinaryOperator) -> Result<Matrix, String> {
+        if self.nb_rows != other.nb_rows || self.nb_columns != other.nb_columns {
+            return Err(format!(
+                "Matrices of sizes {} and {} cannot be added",
+                self.size(),
+                other.size()
+            ));
+        }
+
+        let mut values = other.values.iter();
+        return self.map(|value| ops.apply(value, *values.next().unwrap_or(&0.0)));
+    }
+
+    /// Get product of matrix by matrix given in argument.
+    /// If number of columns of matrix is not number of rows of other matrix,
+    /// an error message is stored in string contained in Result output
+    pub fn multiply(&self, other: &Matrix) -> Result<Matrix, String> {
+        if self.nb_columns != other.nb_rows {
+            return Err(format!(
+                "Matrices of sizes {} and {} cannot be multiplied",
+                self.size(),
+                other.size()
+            ));
+        }
+
+        let mut values: Vec<f64> = Vec::with_capacity(self.nb_rows * other.nb_columns);
+
+        for row in 0..self.nb_rows {
+            for column in 0..other.nb_columns {
+                values.push(
+                    (0..self.nb_columns)
+                        .map(|index| self.get(row, index) * other.get(index, column))
+                        .sum(),
+                );
+            }
+        }
+
+        return Ok(Matrix {
+            nb_rows: self.nb_rows,
+            nb_columns: other.nb_columns,
+            values,
+        });
+    }
+
+    /// Check that matrix is square, to compute its determinant or its inverse given by name in argument.
+    /// If matrix is not square, an error message is stored in string contained in Result output
+    fn check_square(&self, name: &str) -> Result<(), String> {
+        if self.nb_rows != self.nb_columns {
+            return Err(format!(
+                "Matrix of size {} is not square to compute its {name}",
+                self.size()
+            ));
+        }
+
+        return Ok(());
+    }
+
+    /// Get determinant of matrix, computed by Gaussian elimination with partial pivoting.
+    /// If matrix is not square, an error message is stored in string contained in Result output
+    pub fn determinant(&self) -> Result<f64, String> {
+        self.check_square("determinant")?;
+
+        let size: usize = self.nb_rows;
+        let mut values: Vec<f64> = self.values.clone();
+        let mut determinant: f64 = 1.0;
+
+        for pivot in 0..size {
+            let best: usize = (pivot..size)
+                .max_by(|&a, &b| {
+                    values[a * size + pivot]
+                        .abs()
+                        .total_cmp(&values[b * size + pivot].abs())
+                })
+                .unwrap_or(pivot);
+
+            if values[best * size + pivot] == 0.0 {
+                return Ok(0.0);
+            }
+
+            if best != pivot {
+                for column in 0..size {
+                    values.swap(pivot * size + column, best * size + column);
+                }
+                determinant = -determinant;
+            }
+
+            let pivot_value: f64 = values[pivot * size + pivot];
+            determinant *= pivot_value;
+
+            for row in pivot + 1..size {
+                let factor: f64 = values[row * size + pivot] / pivot_value;
+
+                for column in pivot..size {
+                    values[row * size + column] -= factor * values[pivot * size + column];
+                }
+            }
+        }
+
+        return Ok(determinant);
+    }
+
+    /// Get inverse of matrix, computed by Gauss-Jordan elimination with partial pivoting.
+    /// If matrix is not square or is singular, an error message is stored in string contained in Result output
+    pub fn inverse(&self) -> Result<Matrix, String> {
+        self.check_square("inverse")?;
+
+        let size: usize = self.nb_rows;
+        let mut values: Vec<f64> = self.values.clone();
+        let mut inverse: Matrix = Matrix::identity(size);
+
+        for pivot in 0..size {
+            let best: usize = (pivot..size)
+                .max_by(|&a, &b| {
+                    values[a * size + pivot]
+                        .abs()
+                        .total_cmp(&values[b * size + pivot].abs())
+                })
+                .unwrap_or(pivot);
+
+            if values[best * size + pivot] == 0.0 {
+                return Err(String::from("Matrix is singular"));
+            }
+
+            for column in 0..size {
+                values.swap(pivot * size + column, best * size + column);
+                inverse
+                    .values
+                    .swap(pivot * size + column, best * size + column);
+            }
+
+            let pivot_value: f64 = values[pivot * size + pivot];
+
+            for column in 0..size {
+                values[pivot * size + column] /= pivot_value;
+                inverse.values[pivot * size + column] /= pivot_value;
+            }
+
+            for row in (0..size).filter(|&row| row != pivot) {
+                let factor: f64 = values[row * size + pivot];
+
+                for column in 0..size {
+                    values[row * size + column] -= factor * values[pivot * size + column];
+                    inverse.values[row * size + column] -=
+                        factor * inverse.values[pivot * size + column];
+                }
+            }
+        }
+
+        return Ok(inverse);
+    }
+
+    /// Get matrix raised to whole power given in argument, a negative power raising its inverse.
+    /// If matrix is not square, if it is singular for a negative power or if power is not whole,
+    /// an error message is stored in string contained in Result output
+    fn power(&self, exponent: f64) -> Result<Matrix, String> {
+        if exponent.fract() != 0.0 {
+            return Err(format!("Matrix cannot be raised to power {exponent}"));
+        }
+
+        self.check_square("power")?;
+
+        let base: Matrix = match exponent < 0.0 {
+            true => self.inverse()?,
+            false => self.clone(),
+        };
+        let mut result: Matrix = Matrix::identity(self.nb_rows);
+
+        for _ in 0..exponent.abs() as usize {
+            result = result.multiply(&base)?;
+        }
+
+        return Ok(result);
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows: Vec<String> = (0..self.nb_rows)
+            .map(|index| {
+                let values: Vec<String> = self
+                    .row(index)
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect();
+                format!("[{}]", values.join(", "))
+            })
+            .collect();
+
+        write!(f, "[{}]", rows.join(", "))
+    }
+}
+
+/// Get end of matrix literal like [[1, 2], [3, x]] starting at position given in argument,
+/// with matrix whose values are evaluated with variables given in argument.
+/// If matrix cannot be parsed or if error occurs during evaluation of a value,
+/// an error message is stored in string contained in Result output
+fn parse_matrix(
+    expression: &str,
+    start: usize,
+    variables: &HashMap<String, f64>,
+) -> Result<(Matrix, usize), String> {
+    let error = || format!("Cannot parse matrix at position {start}");
+    let mut rows: Vec<Vec<f64>> = Vec::new();
+    let mut chars = expression[start + 1..]
+        .char_indices()
+        .map(|(index, c)| (start + 1 + index, c));
+
+    loop {
+        // Each row starts with a bracket
+        match chars.find(|(_, c)| !c.is_whitespace()) {
+            Some((_, '[')) => (),
+            _ => return Err(error()),
+        }
+
+        let mut row: Vec<f64> = Vec::new();
+        let mut value_start: Option<usize> = None;
+        let mut depth: usize = 0;
+
+        for (index, c) in chars.by_ref() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                ',' | ']' if depth == 0 => {
+                    let value_start: usize = value_start.take().unwrap_or(index);
+                    let value: String = " ".repeat(value_start) + &expression[value_start..index];
+                    row.push(super::evaluate(&value, variables)?);
+
+                    if c == ']' {
+                        break;
+                    }
+
+                    continue;
+                }
+                '[' | ']' => return Err(error()),
+                _ => (),
+            }
+
+            value_start.get_or_insert(index);
+        }
+
+        rows.push(row);
+
+        // Rows are separated by commas and the last one is followed by a bracket
+        match chars.find(|(_, c)| !c.is_whitespace()) {
+            Some((_, ',')) => (),
+            Some((index, ']')) => {
+                return Ok((Matrix::new(&rows).map_err(|_| error())?, index + 1));
+            }
+            _ => return Err(error()),
+        }
+    }
+}
+
+/// Apply binary operator given in argument on values given in argument.
+/// Matrices are added, subtracted and multiplied like in linear algebra and raised to whole powers,
+/// while operations between a matrix and a scalar apply to each value of matrix.
+/// If operation cannot be applied, an error message is stored in string contained in Result output
+fn apply_binary(ops: BinaryOperator, left: Value, right: Value) -> Result<Value, String> {
+    match (left, right) {
+        (Value::Scalar(left), Value::Scalar(right)) => Ok(Value::Scalar(ops.apply(left, right)?)),
+        (Value::Matrix(left), Value::Scalar(right)) if ops == BinaryOperator::Power => {
+            Ok(Value::Matrix(left.power(right)?))
+        }
+        (Value::Matrix(left), Value::Scalar(right)) => {
+            Ok(Value::Matrix(left.map(|value| ops.apply(value, right))?))
+        }
+        (Value::Scalar(_), Value::Matrix(_)) if ops == BinaryOperator::Power => Err(String::from(
+            "Matrix cannot be an exponent of power operation",
+        )),
+        (Value::Scalar(left), Value::Matrix(right)) => {
+            Ok(Value::Matrix(right.map(|value| ops.apply(left, value))?))
+        }
+        (Value::Matrix(left), Value::Matrix(right)) => match ops {
+            BinaryOperator::Plus | BinaryOperator::Minus => {
+                Ok(Value::Matrix(left.add(&right, ops)?))
+            }
+            BinaryOperator::Multiply => Ok(Value::Matrix(left.multiply(&right)?)),
+            _ => Err(format!(
+                "{} operation cannot be applied on two matrices",
+                ops.notation()
+            )),
+        },
+        _ => Err(String::from(
+            "Vectors are not supported by matrix expressions",
+        )),
+    }
+}
+
+/// Apply function of matrices whose slot is given in argument on value given in argument.
+/// If value is not a matrix or if function fails, an error message is stored in string contained in Result output
+fn apply_matrix_function(slot: usize, value: Value) -> Result<Value, String> {
+    let matrix: Matrix = match value {
+        Value::Matrix(matrix) => matrix,
+        _ => {
+            return Err(format!(
+                "Function {} expects a matrix",
+                MATRIX_FUNCTIONS[slot]
+            ))
+        }
+    };
+
+    match slot {
+        0 => Ok(Value::Matrix(matrix.transpose())),
+        1 => Ok(Value::Scalar(matrix.determinant()?)),
+        _ => Ok(Value::Matrix(matrix.inverse()?)),
+    }
+}
+
+/// Evaluate postfix expression given by iterator in argument, where variable tokens are matrices
+/// whose slot is their index in slice given in argument.
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+fn matrix_evaluation<I>(tokens: I, matrices: &[Matrix]) -> Result<Value, String>
+where
+    I: Iterator<Item = Result<Token, String>>,
+{
+    let mut stack: Vec<Value> = Vec::new();
+    let missing = || String::from("Missing operand to apply operation");
+
+    for token in tokens {
+        match token? {
+            Token::Number(value) | Token::Constant(value) => stack.push(Value::Scalar(value)),
+            Token::Variable(slot) => stack.push(Value::Matrix(matrices[slot].clone())),
+            Token::BinaryOperator(ops) => {
+                let right: Value = stack.pop().ok_or_else(missing)?;
+                let left: Value = stack.pop().ok_or_else(missing)?;
+                stack.push(apply_binary(ops, left, right)?);
+            }
+            Token::UnaryOperator(ops) => match stack.pop().ok_or_else(missing)? {
+                Value::Scalar(value) => stack.push(Value::Scalar(ops.apply(value))),
+                Value::Matrix(matrix) => {
+                    stack.push(Value::Matrix(matrix.map(|value| Ok(ops.apply(value)))?))
+                }
+                Value::Vector(_) => {
+                    return Err(String::from(
+                        "Vectors are not supported by matrix expressions",
+                    ))
+                }
+            },
+            Token::Function(fun) if !fun.is_conditional() && !fun.is_lookup() => {
+                if stack.len() < fun.arity() {
+                    return Err(String::from("Missing argument to apply function"));
+                }
+
+                let args: Vec<Value> = stack.split_off(stack.len() - fun.arity());
+
+                match args.as_slice() {
+                    [Value::Matrix(matrix)] => {
+                        stack.push(Value::Matrix(matrix.map(|value| fun.apply(&[value]))?))
+                    }
+                    _ => {
+                        let values: Vec<f64> = args
+                            .iter()
+                            .map(|arg| match arg {
+                                Value::Scalar(value) => Ok(*value),
+                                _ => Err(format!(
+                                    "Function {} cannot take matrices as arguments",
+                                    fun.name()
+                                )),
+                            })
+                            .collect::<Result<Vec<f64>, String>>()?;
+                        stack.push(Value::Scalar(fun.apply(&values)?));
+                    }
+                }
+            }
+            Token::HostFunction(slot, 1) => {
+                let value: Value = stack.pop().ok_or_else(missing)?;
+                stack.push(apply_matrix_function(slot, value)?);
+            }
+            Token::HostFunction(slot, _) => {
+                return Err(format!(
+                    "Function {} expects a single argument",
+                    MATRIX_FUNCTIONS[slot]
+                ))
+            }
+            _ => {
+                return Err(String::from(
+                    "Token non-accepted for evaluation of matrix expression",
+                ))
+            }
+        }
+    }
+
+    return stack
+        .into_iter()
+        .next()
+        .ok_or_else(|| String::from("Empty expression"));
+}
+
+/// Evaluate an expression of matrices written as literals like [[1, 2], [3, 4]], whose values
+/// can be expressions of variables given in argument. Matrices are added, subtracted and multiplied
+/// like in linear algebra and raised to whole powers, a negative power raising their inverse,
+/// while operations with scalars and functions of one argument apply to each value of matrices.
+/// Functions transpose, det and inv give the transpose, the determinant and the inverse of a matrix.
+///
+/// If error occurs during evaluation, an error message is stored in string contained in Result output.
+///
+/// # Example
+/// ```
+/// use taz::{Matrix, Value};
+/// use std::collections::HashMap;
+///
+/// let variables: HashMap<String, f64> = HashMap::from([(String::from("k"), 2.0)]);
+///
+/// let result: Result<Value, String> = taz::evaluate_matrix("[[1, 2], [3, 4]] * [[0], [k]]", &variables);
+/// assert_eq!(result, Ok(Value::Matrix(Matrix::new(&[vec![4.0], vec![8.0]]).unwrap())));
+///
+/// let result: Result<Value, String> = taz::evaluate_matrix("det(inv([[2, 0], [1, k^2]]))", &variables);
+/// assert_eq!(result, Ok(Value::Scalar(0.125)));
+/// ```
+pub fn evaluate_matrix(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+) -> Result<Value, String> {
+    // Each matrix literal is replaced by a name of the same length, so positions of errors are kept
+    let mut matrices: Vec<Matrix> = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    let mut replaced: String = String::with_capacity(expression.len());
+    let mut end: usize = 0;
+
+    for (start, _) in expression.match_indices('[') {
+        if start < end || !expression[start + 1..].trim_start().starts_with('[') {
+            continue;
+        }
+
+        let (matrix, matrix_end) = parse_matrix(expression, start, variables)?;
+        let name: String = format!(
+            "M{:_<width$}",
+            matrices.len(),
+            width = matrix_end - start - 1
+        );
+
+        replaced.push_str(&expression[end..start]);
+        replaced.push_str(&name);
+        matrices.push(matrix);
+        names.push(name);
+        end = matrix_end;
+    }
+
+    replaced.push_str(&expression[end..]);
+
+    let infix_tokens = Infix::new(&replaced, |name: &str| {
+        if let Some(slot) = names.iter().position(|matrix_name| matrix_name == name) {
+            return Some(Token::Variable(slot));
+        }
+
+        if let Some(slot) = MATRIX_FUNCTIONS
+            .iter()
+            .position(|&function| function == name)
+        {
+            return Some(Token::HostFunction(slot, 0));
+        }
+
+        return variables.get(name).map(|&value| Token::new_number(value));
+    });
+
+    let mut postfix_tokens = Postfix::new(infix_tokens);
+    return matrix_evaluation(postfix_tokens.by_ref(), &matrices)
+        .map_err(|message| postfix_tokens.tokens().locate(message));
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix_operations() {
+        let matrix: Matrix = Matrix::new(&[vec![2.0, 1.0], vec![4.0, 3.0]]).unwrap();
+
+        assert_eq!(matrix.determinant(), Ok(2.0));
+        assert_eq!(
+            matrix.transpose(),
+            Matrix::new(&[vec![2.0, 4.0], vec![1.0, 3.0]]).unwrap()
+        );
+
+        match matrix.inverse() {
+            Ok(inverse) => {
+                assert_eq!(inverse.row(0), &[1.5, -0.5]);
+                assert_eq!(inverse.row(1), &[-2.0, 1.0]);
+                assert_eq!(matrix.multiply(&inverse), Ok(Matrix::identity(2)));
+            }
+            Err(_) => assert!(false),
+        }
+
+        let singular: Matrix = Matrix::new(&[vec![1.0, 2.0], vec![2.0, 4.0]]).unwrap();
+        assert_eq!(singular.determinant(), Ok(0.0));
+        assert_eq!(singular.inverse(), Err(String::from("Matrix is singular")));
+
+        assert!(Matrix::new(&[vec![1.0], vec![1.0, 2.0]]).is_err());
+        assert_eq!(matrix.to_string(), "[[2, 1], [4, 3]]");
+    }
+
+    #[test]
+    fn test_evaluate_matrix() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 3.0)]);
+        let matrix = |rows: &[Vec<f64>]| Ok(Value::Matrix(Matrix::new(rows).unwrap()));
+
+        assert_eq!(
+            evaluate_matrix("transpose([[1, 2, 3]]) * 2 - [[0], [1], [x]]", &variables),
+            matrix(&[vec![2.0], vec![3.0], vec![3.0]])
+        );
+        assert_eq!(
+            evaluate_matrix("[[1, 1], [0, 1]]^x + abs(-[[1, 0], [0, 1]])", &variables),
+            matrix(&[vec![2.0, 3.0], vec![0.0, 2.0]])
+        );
+        assert_eq!(
+            evaluate_matrix("det([[2, 0], [0, x]]) + sqrt(4)", &variables),
+            Ok(Value::Scalar(8.0))
+        );
+
+        assert_eq!(
+            evaluate_matrix("[[1, 2]] * [[1, 2]]", &variables),
+            Err(String::from(
+                "Matrices of sizes 1x2 and 1x2 cannot be multiplied at position 19"
+            ))
+        );
+        assert_eq!(
+            evaluate_matrix("inv([[1, 2], [2, 4]])", &variables),
+            Err(String::from("Matrix is singular at position 20"))
+        );
+        assert_eq!(
+            evaluate_matrix("[[1, y]]", &variables),
+            Err(String::from("Unknown name y at position 5"))
+        );
+        assert_eq!(
+            evaluate_matrix("[[1, 2], [3]]", &variables),
+            Err(String::from("Cannot parse matrix at position 0"))
+        );
+    }
+}
@@ -1,3 +1,12 @@
+#![allow(clippy::needless_return, clippy::match_like_matches_macro)]
+
+/// Check if value given in argument is true as a condition, i.e. is neither zero nor NaN.
+/// Boolean operators, conditional operator and conditional functions share this truthiness,
+/// so a negative value is true while an undefined value is false
+pub fn is_true(value: f64) -> bool {
+    return value != 0.0 && !value.is_nan();
+}
+
 /// Available binary operators used library
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum BinaryOperator {
@@ -6,6 +15,14 @@ pub enum BinaryOperator {
     Multiply,
     Divide,
     Power,
+    And,
+    Or,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
 }
 
 impl BinaryOperator {
@@ -19,11 +36,20 @@ impl BinaryOperator {
             '*' => Ok(BinaryOperator::Multiply),
             '/' => Ok(BinaryOperator::Divide),
             '^' => Ok(BinaryOperator::Power),
+            '&' => Ok(BinaryOperator::And),
+            '|' => Ok(BinaryOperator::Or),
+            '<' => Ok(BinaryOperator::Less),
+            '≤' => Ok(BinaryOperator::LessEqual),
+            '>' => Ok(BinaryOperator::Greater),
+            '≥' => Ok(BinaryOperator::GreaterEqual),
+            '=' => Ok(BinaryOperator::Equal),
+            '≠' => Ok(BinaryOperator::NotEqual),
             _ => Err(String::from("Unknown operator characters")),
         }
     }
 
-    /// Check if a char corresponds to binary operator
+    /// Check if a char corresponds to arithmetic binary operator.
+    /// Boolean operators are written with their char doubled, like && and ||, so they are not included
     pub fn is_ops(ops: char) -> bool {
         match ops {
             '+' => true,
@@ -35,14 +61,78 @@ impl BinaryOperator {
         }
     }
 
+    /// Char representing operator in expressions
+    pub fn symbol(&self) -> char {
+        match self {
+            BinaryOperator::Plus => '+',
+            BinaryOperator::Minus => '-',
+            BinaryOperator::Multiply => '*',
+            BinaryOperator::Divide => '/',
+            BinaryOperator::Power => '^',
+            BinaryOperator::And => '&',
+            BinaryOperator::Or => '|',
+            BinaryOperator::Less => '<',
+            BinaryOperator::LessEqual => '≤',
+            BinaryOperator::Greater => '>',
+            BinaryOperator::GreaterEqual => '≥',
+            BinaryOperator::Equal => '=',
+            BinaryOperator::NotEqual => '≠',
+        }
+    }
+
+    /// Notation of operator in expressions, like + or &&
+    pub fn notation(&self) -> &'static str {
+        match self {
+            BinaryOperator::Plus => "+",
+            BinaryOperator::Minus => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Power => "^",
+            BinaryOperator::And => "&&",
+            BinaryOperator::Or => "||",
+            BinaryOperator::Less => "<",
+            BinaryOperator::LessEqual => "<=",
+            BinaryOperator::Greater => ">",
+            BinaryOperator::GreaterEqual => ">=",
+            BinaryOperator::Equal => "==",
+            BinaryOperator::NotEqual => "!=",
+        }
+    }
+
+    /// Check if operator is a boolean operator, whose right operand is only evaluated when needed
+    pub fn is_boolean(&self) -> bool {
+        matches!(self, BinaryOperator::And | BinaryOperator::Or)
+    }
+
+    /// Check if operator is a comparison operator, giving 1 if comparison holds and 0 otherwise
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinaryOperator::Less
+                | BinaryOperator::LessEqual
+                | BinaryOperator::Greater
+                | BinaryOperator::GreaterEqual
+                | BinaryOperator::Equal
+                | BinaryOperator::NotEqual
+        )
+    }
+
     /// Association between operator and its precedence
     pub fn precedence(&self) -> u8 {
         match self {
-            BinaryOperator::Plus => 2,
-            BinaryOperator::Minus => 2,
-            BinaryOperator::Multiply => 3,
-            BinaryOperator::Divide => 3,
-            BinaryOperator::Power => 4,
+            BinaryOperator::Plus => 3,
+            BinaryOperator::Minus => 3,
+            BinaryOperator::Multiply => 4,
+            BinaryOperator::Divide => 4,
+            BinaryOperator::Power => 5,
+            BinaryOperator::And => 1,
+            BinaryOperator::Or => 0,
+            BinaryOperator::Less
+            | BinaryOperator::LessEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterEqual
+            | BinaryOperator::Equal
+            | BinaryOperator::NotEqual => 2,
         }
     }
 
@@ -54,10 +144,20 @@ impl BinaryOperator {
             BinaryOperator::Multiply => true,
             BinaryOperator::Divide => true,
             BinaryOperator::Power => false,
+            BinaryOperator::And => true,
+            BinaryOperator::Or => true,
+            BinaryOperator::Less
+            | BinaryOperator::LessEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::GreaterEqual
+            | BinaryOperator::Equal
+            | BinaryOperator::NotEqual => true,
         }
     }
 
     /// Apply the operation on two values given in argument.
+    /// Boolean operators take values as true following is_true function and give 1 for true and 0 for false,
+    /// like comparison operators.
     /// For division case, we check that right_operand is non-null.
     /// To take into account this error, the function return a Result<f64, String>
     pub fn apply(&self, left_operand: f64, right_operand: f64) -> Result<f64, String> {
@@ -73,15 +173,24 @@ impl BinaryOperator {
                 }
             }
             BinaryOperator::Power => Ok(left_operand.powf(right_operand)),
+            BinaryOperator::And => Ok(f64::from(is_true(left_operand) && is_true(right_operand))),
+            BinaryOperator::Or => Ok(f64::from(is_true(left_operand) || is_true(right_operand))),
+            BinaryOperator::Less => Ok(f64::from(left_operand < right_operand)),
+            BinaryOperator::LessEqual => Ok(f64::from(left_operand <= right_operand)),
+            BinaryOperator::Greater => Ok(f64::from(left_operand > right_operand)),
+            BinaryOperator::GreaterEqual => Ok(f64::from(left_operand >= right_operand)),
+            BinaryOperator::Equal => Ok(f64::from(left_operand == right_operand)),
+            BinaryOperator::NotEqual => Ok(f64::from(left_operand != right_operand)),
         }
     }
 }
 
-//// Available binary operators used in application
+/// Available unary operators used in library
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum UnaryOperator {
     Plus,
     Minus,
+    Not,
 }
 
 impl UnaryOperator {
@@ -92,6 +201,7 @@ impl UnaryOperator {
         match ops {
             '+' => Ok(UnaryOperator::Plus),
             '-' => Ok(UnaryOperator::Minus),
+            '!' => Ok(UnaryOperator::Not),
             _ => Err(String::from("Unknown operator characters")),
         }
     }
@@ -101,15 +211,27 @@ impl UnaryOperator {
         match ops {
             '+' => true,
             '-' => true,
+            '!' => true,
             _ => false,
         }
     }
 
+    /// Char representing operator in expressions
+    pub fn symbol(&self) -> char {
+        match self {
+            UnaryOperator::Plus => '+',
+            UnaryOperator::Minus => '-',
+            UnaryOperator::Not => '!',
+        }
+    }
+
     /// Apply the operation on value given in argument.
+    /// Boolean negation gives 1 for false and 0 for true values, following is_true function
     pub fn apply(&self, operand: f64) -> f64 {
         match self {
             UnaryOperator::Plus => operand,
             UnaryOperator::Minus => -operand,
+            UnaryOperator::Not => f64::from(!is_true(operand)),
         }
     }
 }
@@ -172,24 +294,25 @@ mod tests {
         assert!(BinaryOperator::is_ops('/'));
         assert!(BinaryOperator::is_ops('^'));
         assert!(!BinaryOperator::is_ops('!'));
+        assert!(!BinaryOperator::is_ops('&'));
     }
 
     #[test]
     fn test_binary_operator_precedence() {
         let plus_ops: BinaryOperator = BinaryOperator::Plus;
-        assert_eq!(plus_ops.precedence(), 2);
+        assert_eq!(plus_ops.precedence(), 3);
 
         let minus_ops: BinaryOperator = BinaryOperator::Minus;
-        assert_eq!(minus_ops.precedence(), 2);
+        assert_eq!(minus_ops.precedence(), 3);
 
         let multiply_ops: BinaryOperator = BinaryOperator::Multiply;
-        assert_eq!(multiply_ops.precedence(), 3);
+        assert_eq!(multiply_ops.precedence(), 4);
 
         let divide_ops: BinaryOperator = BinaryOperator::Divide;
-        assert_eq!(divide_ops.precedence(), 3);
+        assert_eq!(divide_ops.precedence(), 4);
 
         let power_ops: BinaryOperator = BinaryOperator::Power;
-        assert_eq!(power_ops.precedence(), 4);
+        assert_eq!(power_ops.precedence(), 5);
     }
 
     #[test]
@@ -287,6 +410,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_binary_operator_apply_boolean() {
+        let ops_and: BinaryOperator = BinaryOperator::And;
+        assert_eq!(ops_and.apply(2.0, -0.5), Ok(1.0));
+        assert_eq!(ops_and.apply(2.0, 0.0), Ok(0.0));
+
+        let ops_or: BinaryOperator = BinaryOperator::Or;
+        assert_eq!(ops_or.apply(0.0, -3.0), Ok(1.0));
+        assert_eq!(ops_or.apply(0.0, 0.0), Ok(0.0));
+
+        assert!(ops_and.precedence() > ops_or.precedence());
+        assert!(BinaryOperator::Plus.precedence() > ops_and.precedence());
+    }
+
+    #[test]
+    fn test_binary_operator_apply_comparison() {
+        assert_eq!(BinaryOperator::Less.apply(-2.0, 1.0), Ok(1.0));
+        assert_eq!(BinaryOperator::LessEqual.apply(1.0, 1.0), Ok(1.0));
+        assert_eq!(BinaryOperator::Greater.apply(-2.0, 1.0), Ok(0.0));
+        assert_eq!(BinaryOperator::GreaterEqual.apply(1.0, 2.0), Ok(0.0));
+        assert_eq!(BinaryOperator::Equal.apply(0.5, 0.5), Ok(1.0));
+        assert_eq!(BinaryOperator::NotEqual.apply(0.5, 0.5), Ok(0.0));
+        assert_eq!(BinaryOperator::Less.apply(f64::NAN, 1.0), Ok(0.0));
+
+        assert!(BinaryOperator::Less.is_comparison());
+        assert!(!BinaryOperator::And.is_comparison());
+        assert!(BinaryOperator::Plus.precedence() > BinaryOperator::Equal.precedence());
+        assert!(BinaryOperator::Equal.precedence() > BinaryOperator::And.precedence());
+    }
+
+    #[test]
+    fn test_is_true() {
+        assert!(is_true(1.0));
+        assert!(is_true(-0.5));
+        assert!(is_true(f64::INFINITY));
+        assert!(!is_true(f64::NAN));
+        assert!(!is_true(0.0));
+        assert!(!is_true(-0.0));
+    }
+
     #[test]
     fn test_unary_operator_from_plus_char() {
         let res_plus: Result<UnaryOperator, String> = UnaryOperator::from_char('+');
@@ -305,7 +468,8 @@ mod tests {
     fn test_unary_operator_is_ops() {
         assert!(UnaryOperator::is_ops('+'));
         assert!(UnaryOperator::is_ops('-'));
-        assert!(!UnaryOperator::is_ops('!'));
+        assert!(UnaryOperator::is_ops('!'));
+        assert!(!UnaryOperator::is_ops('&'));
     }
 
     #[test]
@@ -323,4 +487,26 @@ mod tests {
 
         assert_eq!(ops_minus.apply(operand), -operand);
     }
+
+    #[test]
+    fn test_unary_operator_apply_not() {
+        let ops_not: UnaryOperator = UnaryOperator::Not;
+
+        assert_eq!(ops_not.apply(5.0), 0.0);
+        assert_eq!(ops_not.apply(-5.0), 0.0);
+        assert_eq!(ops_not.apply(0.0), 1.0);
+    }
+
+    #[test]
+    fn test_operators_symbol() {
+        for ops in [
+            '+', '-', '*', '/', '^', '&', '|', '<', '≤', '>', '≥', '=', '≠',
+        ] {
+            assert_eq!(BinaryOperator::from_char(ops).unwrap().symbol(), ops);
+        }
+
+        for ops in ['+', '-', '!'] {
+            assert_eq!(UnaryOperator::from_char(ops).unwrap().symbol(), ops);
+        }
+    }
 }
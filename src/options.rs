@@ -0,0 +1,477 @@
+#![allow(clippy::needless_return)]
+
+use super::error::Error;
+use super::tokenizer::size_hint;
+
+/// Mode of checking of domains of functions like sqrt, ln, log10, log2, tan, asin and acos
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum DomainMode {
+    /// An argument outside of domain of function gives an error
+    #[default]
+    Strict,
+    /// An argument outside of domain of function gives the IEEE result, like NaN for sqrt(-1)
+    /// or -inf for ln(0), so evaluation goes on as expected by scientific computations
+    Lenient,
+}
+
+/// Unit of angles given to and by trigonometric functions sin, cos, tan, asin, acos and atan
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum AngleUnit {
+    /// Angles are in radians
+    #[default]
+    Radians,
+    /// Angles are in degrees, like on a pocket calculator
+    Degrees,
+}
+
+/// Behavior of division by zero
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum DivisionByZero {
+    /// Division by zero gives an error
+    #[default]
+    Error,
+    /// Division by zero gives the IEEE result, i.e. an infinity of the sign of operands, or NaN for 0/0
+    Infinity,
+    /// Division by zero gives the value held, like 0 in spreadsheets
+    Value(f64),
+}
+
+impl DivisionByZero {
+    /// Give result of division of left operand by right operand, which is zero, according to this behavior.
+    /// If division by zero is an error, an error is given in Result output
+    pub fn divide(&self, left: f64, right: f64) -> Result<f64, Error> {
+        match self {
+            DivisionByZero::Error => Err(Error::Message(String::from("Division by zero"))),
+            DivisionByZero::Infinity => Ok(left / right),
+            DivisionByZero::Value(value) => Ok(*value),
+        }
+    }
+}
+
+/// Arithmetic policy applied to results of operations outside of a range of values
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ArithmeticPolicy {
+    /// A result outside of range gives an Overflow error
+    #[default]
+    Error,
+    /// A result outside of range is clamped to the nearest bound of range, like 300 giving 255 for u8
+    Saturate,
+    /// A result outside of range wraps around like integers in two's complement, like 300 giving 44 for u8.
+    /// An infinite result cannot be wrapped and gives an Overflow error
+    Wrap,
+}
+
+/// Range of values of a fixed-point or integer target, like [0, 255] for u8,
+/// with the policy applied to results of operations falling outside of it
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ValueRange {
+    /// Smallest value of range
+    pub min: f64,
+    /// Largest value of range
+    pub max: f64,
+    /// Policy applied to results outside of range
+    pub policy: ArithmeticPolicy,
+}
+
+impl ValueRange {
+    /// Create range of values from its bounds, included, and policy given in argument
+    pub fn new(min: f64, max: f64, policy: ArithmeticPolicy) -> ValueRange {
+        return ValueRange { min, max, policy };
+    }
+
+    /// Bring result of operation described in argument in range according to policy.
+    /// NaN is left as is, conversion into target type rejecting it.
+    /// If result is rejected, an Overflow error is given in Result output
+    pub fn apply(&self, operation: &str, result: f64) -> Result<f64, Error> {
+        if result.is_nan() || (self.min <= result && result <= self.max) {
+            return Ok(result);
+        }
+
+        match self.policy {
+            ArithmeticPolicy::Error => Err(Error::Overflow(format!(
+                "Result of {operation} is out of range [{}, {}]",
+                self.min, self.max
+            ))),
+            ArithmeticPolicy::Saturate => Ok(result.clamp(self.min, self.max)),
+            ArithmeticPolicy::Wrap => {
+                if result.is_infinite() {
+                    return Err(Error::Overflow(format!(
+                        "Result of {operation} is infinite and cannot be wrapped"
+                    )));
+                }
+
+                // Period of wrapping is the number of whole values of range
+                let period: f64 = self.max - self.min + 1.0;
+                return Ok(self.min + (result - self.min).rem_euclid(period));
+            }
+        }
+    }
+}
+
+/// IEEE rounding mode of results of arithmetic operations, for validation against systems with fixed rounding.
+/// Hardware always rounds to nearest, so other modes are emulated in software: addition, subtraction,
+/// multiplication and division are rounded exactly according to mode, from the error of the result rounded
+/// to nearest which is computed exactly. Powers and functions are always rounded to nearest
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum RoundingMode {
+    /// Results are rounded to nearest value, ties to even
+    #[default]
+    NearestEven,
+    /// Results are rounded toward zero, i.e. truncated
+    TowardZero,
+    /// Results are rounded toward positive infinity
+    Up,
+    /// Results are rounded toward negative infinity
+    Down,
+    /// Results are rounded up or down at random, with a probability of rounding to each neighbor
+    /// proportional to its closeness to the exact result. Choices are drawn from the seed held
+    /// and the operands, so an evaluation is reproducible and different seeds give different roundings
+    Stochastic(u64),
+}
+
+impl RoundingMode {
+    /// Compute arithmetic operation whose symbol is given in argument, like + or /,
+    /// on operands given in argument, the result being rounded with this mode
+    pub fn operate(&self, symbol: char, left: f64, right: f64) -> f64 {
+        let (value, error): (f64, f64) = match symbol {
+            '+' => return self.add(left, right),
+            '-' => return self.add(left, -right),
+            '*' => {
+                let value: f64 = left * right;
+                (value, left.mul_add(right, -value))
+            }
+            '/' => {
+                let value: f64 = left / right;
+                // Error of quotient is the exact remainder divided by right operand
+                (value, (-value).mul_add(right, left) / right)
+            }
+            _ => return left.powf(right),
+        };
+
+        return self.adjust(value, error, left.is_finite() && right.is_finite(), symbol);
+    }
+
+    /// Add operands given in argument, the result being rounded with this mode
+    fn add(&self, left: f64, right: f64) -> f64 {
+        let value: f64 = left + right;
+
+        // An exact zero sum is negative when rounding down, like 1 - 1 giving -0
+        if value == 0.0 && *self == RoundingMode::Down {
+            if left.is_sign_negative() || right.is_sign_negative() {
+                return -0.0;
+            }
+
+            return value;
+        }
+
+        // Error of sum rounded to nearest is computed exactly by the 2Sum algorithm
+        let right_part: f64 = value - left;
+        let error: f64 = (left - (value - right_part)) + (right - right_part);
+
+        return self.adjust(value, error, left.is_finite() && right.is_finite(), '+');
+    }
+
+    /// Round with this mode a value rounded to nearest, from its error given in argument,
+    /// which is the exact result minus value, whether operands are finite and symbol of operation
+    fn adjust(&self, value: f64, error: f64, is_finite: bool, symbol: char) -> f64 {
+        if value.is_infinite() {
+            // Overflow of finite operands gives the largest finite value in directed modes
+            let is_kept: bool = !is_finite
+                || match self {
+                    RoundingMode::NearestEven | RoundingMode::Stochastic(_) => true,
+                    RoundingMode::TowardZero => false,
+                    RoundingMode::Up => value > 0.0,
+                    RoundingMode::Down => value < 0.0,
+                };
+
+            return if is_kept {
+                value
+            } else {
+                f64::MAX.copysign(value)
+            };
+        }
+
+        match self {
+            RoundingMode::Up if error > 0.0 => value.next_up(),
+            RoundingMode::Down if error < 0.0 => value.next_down(),
+            RoundingMode::TowardZero if value > 0.0 && error < 0.0 => value.next_down(),
+            RoundingMode::TowardZero if value < 0.0 && error > 0.0 => value.next_up(),
+            RoundingMode::Stochastic(seed) if error != 0.0 => {
+                let neighbor: f64 = match error > 0.0 {
+                    true => value.next_up(),
+                    false => value.next_down(),
+                };
+
+                let draw: u64 =
+                    mix(seed ^ mix(value.to_bits() ^ mix(error.to_bits() ^ symbol as u64)));
+                let uniform: f64 = (draw >> 11) as f64 / (1u64 << 53) as f64;
+
+                match uniform * (neighbor - value).abs() < error.abs() {
+                    true => neighbor,
+                    false => value,
+                }
+            }
+            _ => value,
+        }
+    }
+}
+
+/// Mix bits of integer given in argument, like the SplitMix64 generator,
+/// so close integers give unrelated results
+fn mix(bits: u64) -> u64 {
+    let mut bits: u64 = bits.wrapping_add(0x9E3779B97F4A7C15);
+    bits = (bits ^ (bits >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    bits = (bits ^ (bits >> 27)).wrapping_mul(0x94D049BB133111EB);
+
+    return bits ^ (bits >> 31);
+}
+
+/// Initial capacities of stacks used by evaluation of an expression.
+/// Stacks hold up to 16 elements without allocation and grow as needed beyond,
+/// so larger capacities only save reallocations on long expressions
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Capacities {
+    /// Initial capacity of stack of operators waiting for their operands
+    pub operators: usize,
+    /// Initial capacity of stack of operands
+    pub operands: usize,
+}
+
+impl Capacities {
+    /// Create capacities suited to expression given in argument from estimate of its number of tokens,
+    /// each stack holding at most about half of tokens of a usual expression
+    pub fn for_expression(expression: &str) -> Capacities {
+        let default: Capacities = Capacities::default();
+        let half: usize = size_hint(expression) / 2 + 1;
+
+        return Capacities {
+            operators: half.max(default.operators),
+            operands: half.max(default.operands),
+        };
+    }
+}
+
+impl Default for Capacities {
+    /// Capacities held without allocation
+    fn default() -> Capacities {
+        return Capacities {
+            operators: 16,
+            operands: 16,
+        };
+    }
+}
+
+/// Options of evaluation of an expression
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct EvaluationOptions {
+    /// Mode of checking of domains of functions
+    pub domain: DomainMode,
+    /// If true, an operation giving an infinite result from finite operands,
+    /// like 10^400 or exp(1000), gives an Overflow error instead of propagating infinity
+    pub detect_overflow: bool,
+    /// Behavior of division by zero, which is never reported as an overflow
+    pub division_by_zero: DivisionByZero,
+    /// Range of values of a fixed-point or integer target, applied to result of each operation
+    /// and function, or None to keep results as f64
+    pub range: Option<ValueRange>,
+    /// Initial capacities of stacks used by evaluation
+    pub capacities: Capacities,
+    /// Unit of angles of trigonometric functions
+    pub angle: AngleUnit,
+    /// Rounding mode of the arithmetic operation giving the result of expression,
+    /// i.e. the operation evaluated last
+    pub rounding: RoundingMode,
+    /// If true, rounding mode applies to each arithmetic operation instead of only the one giving the result
+    pub round_operations: bool,
+}
+
+impl EvaluationOptions {
+    /// Check result of operation described by closure given in argument against these options:
+    /// overflow is detected if required, then result is brought in range of values if any.
+    /// If result is rejected, an error is given in Result output
+    pub(crate) fn check_result<F>(
+        &self,
+        operation: F,
+        operands: &[f64],
+        result: f64,
+    ) -> Result<f64, Error>
+    where
+        F: Fn() -> String,
+    {
+        let mut value: f64 = result;
+
+        if self.detect_overflow {
+            value = check_overflow(&operation(), operands, value)?;
+        }
+
+        if let Some(range) = &self.range {
+            value = range.apply(&operation(), value)?;
+        }
+
+        return Ok(value);
+    }
+
+    /// Compute result of expression, held by operand given in argument, with rounding mode of these options
+    /// when it is given by the arithmetic operation whose symbol and operands are given in argument,
+    /// and rounding mode does not already apply to each operation. Result is checked like any result.
+    /// If result is rejected, an error is given in Result output
+    pub(crate) fn round_result(
+        &self,
+        result: &mut f64,
+        last_operation: Option<(char, f64, f64)>,
+    ) -> Result<(), Error> {
+        if self.rounding == RoundingMode::NearestEven || self.round_operations {
+            return Ok(());
+        }
+
+        if let Some((symbol, left, right)) = last_operation {
+            let operation = || format!("{symbol} operation");
+            let value: f64 = self.rounding.operate(symbol, left, right);
+            *result = self.check_result(operation, &[left, right], value)?;
+        }
+
+        return Ok(());
+    }
+}
+
+/// Check that result of operation described in argument is not infinite if its operands are finite.
+/// If operation overflows, an Overflow error is given in Result output
+pub fn check_overflow(operation: &str, operands: &[f64], result: f64) -> Result<f64, Error> {
+    if result.is_infinite() && operands.iter().all(|operand| operand.is_finite()) {
+        return Err(Error::Overflow(format!("Result of {operation} overflows")));
+    }
+
+    return Ok(result);
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capacities_for_expression() {
+        assert_eq!(Capacities::for_expression("1 + 2"), Capacities::default());
+
+        let long: String = vec!["x"; 100].join(" + ");
+        let capacities: Capacities = Capacities::for_expression(&long);
+        assert_eq!(capacities.operators, 100);
+        assert_eq!(capacities.operands, 100);
+    }
+
+    #[test]
+    fn test_rounding_mode_operate() {
+        // Sum of 0.1 and 0.2 rounded to nearest is above exact sum, like 1 / 3 is below exact quotient
+        let nearest: f64 = 0.1 + 0.2;
+        assert_eq!(RoundingMode::NearestEven.operate('+', 0.1, 0.2), nearest);
+        assert_eq!(RoundingMode::Up.operate('+', 0.1, 0.2), nearest);
+        assert_eq!(RoundingMode::Down.operate('+', 0.1, 0.2), 0.3);
+        assert_eq!(RoundingMode::TowardZero.operate('+', 0.1, 0.2), 0.3);
+        assert_eq!(RoundingMode::TowardZero.operate('-', -0.1, 0.2), -0.3);
+
+        let third: f64 = 1.0 / 3.0;
+        assert_eq!(RoundingMode::Up.operate('/', 1.0, 3.0), third.next_up());
+        assert_eq!(RoundingMode::Down.operate('/', 1.0, 3.0), third);
+        assert_eq!(RoundingMode::Down.operate('/', -1.0, 3.0), -third.next_up());
+        assert_eq!(RoundingMode::Up.operate('*', third, 3.0), 1.0);
+        assert_eq!(
+            RoundingMode::Down.operate('*', third, 3.0),
+            1.0_f64.next_down()
+        );
+
+        // Exact results are never changed
+        for mode in [
+            RoundingMode::TowardZero,
+            RoundingMode::Up,
+            RoundingMode::Down,
+        ] {
+            assert_eq!(mode.operate('*', 1.5, 4.0), 6.0);
+            assert_eq!(mode.operate('/', 1.0, 4.0), 0.25);
+        }
+    }
+
+    #[test]
+    fn test_rounding_mode_stochastic() {
+        let third: f64 = 1.0 / 3.0;
+        let results: Vec<f64> = (0..64)
+            .map(|seed| RoundingMode::Stochastic(seed).operate('/', 1.0, 3.0))
+            .collect();
+
+        assert!(results
+            .iter()
+            .all(|&result| result == third || result == third.next_up()));
+        assert!(results.contains(&third) && results.contains(&third.next_up()));
+        assert_eq!(
+            RoundingMode::Stochastic(7).operate('/', 1.0, 3.0),
+            results[7]
+        );
+
+        for seed in 0..8 {
+            assert_eq!(RoundingMode::Stochastic(seed).operate('+', 0.5, 0.25), 0.75);
+        }
+    }
+
+    #[test]
+    fn test_rounding_mode_special_values() {
+        assert_eq!(RoundingMode::Down.operate('*', f64::MAX, 2.0), f64::MAX);
+        assert_eq!(RoundingMode::Up.operate('*', f64::MAX, 2.0), f64::INFINITY);
+        assert_eq!(
+            RoundingMode::TowardZero.operate('*', f64::MAX, -2.0),
+            -f64::MAX
+        );
+        assert_eq!(
+            RoundingMode::Down.operate('+', f64::INFINITY, 1.0),
+            f64::INFINITY
+        );
+        assert!(RoundingMode::Up
+            .operate('-', f64::INFINITY, f64::INFINITY)
+            .is_nan());
+
+        assert!(RoundingMode::Down.operate('-', 1.0, 1.0).is_sign_negative());
+        assert!(RoundingMode::Up.operate('-', 1.0, 1.0).is_sign_positive());
+        assert!(RoundingMode::Down.operate('+', 0.0, 0.0).is_sign_positive());
+    }
+
+    #[test]
+    fn test_value_range_boundaries() {
+        let range = |policy: ArithmeticPolicy| -> ValueRange {
+            return ValueRange::new(-128.0, 127.0, policy);
+        };
+
+        for policy in [
+            ArithmeticPolicy::Error,
+            ArithmeticPolicy::Saturate,
+            ArithmeticPolicy::Wrap,
+        ] {
+            assert_eq!(range(policy).apply("+ operation", -128.0), Ok(-128.0));
+            assert_eq!(range(policy).apply("+ operation", 127.0), Ok(127.0));
+            assert!(range(policy)
+                .apply("+ operation", f64::NAN)
+                .unwrap()
+                .is_nan());
+        }
+
+        assert_eq!(
+            range(ArithmeticPolicy::Error).apply("+ operation", 128.0),
+            Err(Error::Overflow(String::from(
+                "Result of + operation is out of range [-128, 127]"
+            )))
+        );
+
+        let saturate: ValueRange = range(ArithmeticPolicy::Saturate);
+        assert_eq!(saturate.apply("+ operation", 128.0), Ok(127.0));
+        assert_eq!(saturate.apply("- operation", -129.0), Ok(-128.0));
+        assert_eq!(saturate.apply("exp function", f64::INFINITY), Ok(127.0));
+
+        let wrap: ValueRange = range(ArithmeticPolicy::Wrap);
+        assert_eq!(wrap.apply("+ operation", 128.0), Ok(-128.0));
+        assert_eq!(wrap.apply("- operation", -129.0), Ok(127.0));
+        assert_eq!(wrap.apply("* operation", 300.0), Ok(44.0));
+        assert_eq!(
+            wrap.apply("exp function", f64::INFINITY),
+            Err(Error::Overflow(String::from(
+                "Result of exp function is infinite and cannot be wrapped"
+            )))
+        );
+    }
+}
@@ -0,0 +1,190 @@
+#![allow(clippy::needless_return)]
+
+/// Complex number given by its real part and its imaginary part, for roots of polynomials
+type Complex = (f64, f64);
+
+/// Maximal number of iterations of Durand-Kerner method
+const MAX_ITERATIONS: usize = 500;
+
+/// Tolerance on changes of roots stopping iterations, relative to magnitude of roots
+const TOLERANCE: f64 = 1e-14;
+
+/// Tolerance on imaginary part of a root for it to be real, relative to magnitude of root.
+/// Multiple roots are found with less accuracy, so their imaginary parts are not exactly null
+const IMAGINARY_TOLERANCE: f64 = 1e-6;
+
+/// Number of Newton iterations refining real roots on polynomial
+const POLISHING_STEPS: usize = 3;
+
+/// Multiply complex numbers given in argument
+fn multiply(a: Complex, b: Complex) -> Complex {
+    return (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0);
+}
+
+/// Divide complex numbers given in argument, divisor being not null
+fn divide(a: Complex, b: Complex) -> Complex {
+    let norm: f64 = b.0 * b.0 + b.1 * b.1;
+    return (
+        (a.0 * b.0 + a.1 * b.1) / norm,
+        (a.1 * b.0 - a.0 * b.1) / norm,
+    );
+}
+
+/// Evaluate polynomial whose coefficients are given in argument from constant term, at complex z by Horner scheme
+fn evaluate(coefficients: &[f64], z: Complex) -> Complex {
+    return coefficients
+        .iter()
+        .rev()
+        .fold((0.0, 0.0), |value, &coefficient| {
+            let value: Complex = multiply(value, z);
+            return (value.0 + coefficient, value.1);
+        });
+}
+
+/// Evaluate polynomial whose coefficients are given in argument from constant term and its derivative at real x
+fn evaluate_with_derivative(coefficients: &[f64], x: f64) -> (f64, f64) {
+    return coefficients
+        .iter()
+        .rev()
+        .fold((0.0, 0.0), |(value, derivative), &coefficient| {
+            (value * x + coefficient, derivative * x + value)
+        });
+}
+
+/// Find all complex roots of polynomial whose coefficients are given in argument from constant term,
+/// its leading coefficient being not null, by Durand-Kerner method which refines all roots simultaneously.
+/// Initial roots are spread on a circle whose radius bounds magnitudes of roots
+fn complex_roots(coefficients: &[f64]) -> Vec<Complex> {
+    let degree: usize = coefficients.len() - 1;
+    let leading: f64 = coefficients[degree];
+    let monic: Vec<f64> = coefficients.iter().map(|c| c / leading).collect();
+
+    let radius: f64 = 1.0 + monic[..degree].iter().fold(0.0, |max, c| c.abs().max(max));
+    let mut roots: Vec<Complex> = (0..degree)
+        .map(|k| {
+            let angle: f64 = 2.0 * std::f64::consts::PI * k as f64 / degree as f64 + 0.4;
+            return (radius * angle.cos(), radius * angle.sin());
+        })
+        .collect();
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut change: f64 = 0.0;
+
+        for i in 0..degree {
+            let denominator: Complex = (0..degree)
+                .filter(|&j| j != i)
+                .fold((1.0, 0.0), |product, j| {
+                    multiply(product, (roots[i].0 - roots[j].0, roots[i].1 - roots[j].1))
+                });
+
+            // Roots which coincide are moved apart instead of being divided by zero
+            let step: Complex = match denominator == (0.0, 0.0) {
+                true => (TOLERANCE, TOLERANCE),
+                false => divide(evaluate(&monic, roots[i]), denominator),
+            };
+
+            roots[i] = (roots[i].0 - step.0, roots[i].1 - step.1);
+            change = change.max(step.0.hypot(step.1) / roots[i].0.hypot(roots[i].1).max(1.0));
+        }
+
+        if change <= TOLERANCE {
+            break;
+        }
+    }
+
+    return roots;
+}
+
+/// Find real roots of polynomial whose coefficients are given in argument from constant term,
+/// like [2, 3] for 6 - 5x + x^2, sorted in increasing order, each root being repeated by its multiplicity.
+/// Real roots are found among complex roots, then refined by Newton method on polynomial.
+/// If all coefficients are null, an error message is stored in string contained in Result output
+pub(crate) fn real_roots(coefficients: &[f64]) -> Result<Vec<f64>, String> {
+    let degree: usize = match coefficients.iter().rposition(|&c| c != 0.0) {
+        Some(degree) => degree,
+        None => {
+            return Err(String::from(
+                "Polynomial with null coefficients has infinitely many roots",
+            ))
+        }
+    };
+    let coefficients: &[f64] = &coefficients[..=degree];
+
+    // Null constant terms give exact roots at 0, factored out of polynomial
+    let nb_zeros: usize = coefficients.iter().take_while(|&&c| c == 0.0).count();
+    let mut roots: Vec<f64> = vec![0.0; nb_zeros];
+    let reduced: &[f64] = &coefficients[nb_zeros..];
+
+    if reduced.len() > 1 {
+        for (re, im) in complex_roots(reduced) {
+            if im.abs() > IMAGINARY_TOLERANCE * re.abs().max(1.0) {
+                continue;
+            }
+
+            let mut x: f64 = re;
+
+            for _ in 0..POLISHING_STEPS {
+                let (value, derivative): (f64, f64) = evaluate_with_derivative(reduced, x);
+                let next: f64 = x - value / derivative;
+
+                if !next.is_finite()
+                    || evaluate_with_derivative(reduced, next).0.abs() >= value.abs()
+                {
+                    break;
+                }
+
+                x = next;
+            }
+
+            roots.push(x);
+        }
+    }
+
+    roots.sort_by(f64::total_cmp);
+    return Ok(roots);
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(coefficients: &[f64], expected: &[f64]) {
+        match real_roots(coefficients) {
+            Ok(roots) => {
+                assert_eq!(roots.len(), expected.len());
+
+                for (root, expected) in roots.iter().zip(expected) {
+                    assert!((root - expected).abs() < 1e-7);
+                }
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_real_roots() {
+        assert_roots(&[6.0, -5.0, 1.0], &[2.0, 3.0]);
+        assert_roots(&[-2.0, 0.0, 1.0], &[-(2f64.sqrt()), 2f64.sqrt()]);
+        assert_roots(&[-6.0, 11.0, -6.0, 1.0], &[1.0, 2.0, 3.0]);
+        assert_roots(&[3.0, -6.0, 0.0, 0.0], &[0.5]);
+        assert_roots(&[0.0, 0.0, -1.0, 1.0], &[0.0, 0.0, 1.0]);
+        assert_roots(&[1.0, -2.0, 1.0], &[1.0, 1.0]);
+        assert_roots(&[1.0, 0.0, 1.0], &[]);
+        assert_roots(&[-1.0, 0.0, 0.0, 0.0, 1.0], &[-1.0, 1.0]);
+        assert_roots(&[5.0], &[]);
+
+        assert_eq!(
+            real_roots(&[0.0, 0.0]),
+            Err(String::from(
+                "Polynomial with null coefficients has infinitely many roots"
+            ))
+        );
+        assert_eq!(
+            real_roots(&[]),
+            Err(String::from(
+                "Polynomial with null coefficients has infinitely many roots"
+            ))
+        );
+    }
+}
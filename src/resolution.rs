@@ -0,0 +1,273 @@
+#![allow(clippy::needless_return)]
+
+use super::constants::is_constant;
+use super::converter::Postfix;
+use super::evaluator::{self, OperandStack};
+use super::functions::Function;
+use super::tokenizer;
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Kind of name which can be used in an expression
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum NameKind {
+    Constant,
+    Function,
+    Variable,
+}
+
+/// Default order of resolution of names: predefined constants and functions are resolved before variables
+pub const DEFAULT_ORDER: [NameKind; 3] =
+    [NameKind::Constant, NameKind::Function, NameKind::Variable];
+
+impl fmt::Display for NameKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameKind::Constant => write!(f, "constant"),
+            NameKind::Function => write!(f, "function"),
+            NameKind::Variable => write!(f, "variable"),
+        }
+    }
+}
+
+/// Warning about a name used by expression which matches several kinds of names,
+/// like a variable having the same name as a predefined constant or function.
+/// Only the kind coming first in resolution order is used, the other ones being shadowed
+#[derive(Debug, PartialEq, Clone)]
+pub struct ShadowingWarning {
+    /// Name used by expression
+    pub name: String,
+    /// Kind of name resolved
+    pub resolved: NameKind,
+    /// Kind of name shadowed by resolved one
+    pub shadowed: NameKind,
+}
+
+impl fmt::Display for ShadowingWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} is shadowed by {} with the same name",
+            capitalize(self.shadowed),
+            self.name,
+            self.resolved
+        )
+    }
+}
+
+/// Name of kind of name starting with a capital letter
+fn capitalize(kind: NameKind) -> &'static str {
+    match kind {
+        NameKind::Constant => "Constant",
+        NameKind::Function => "Function",
+        NameKind::Variable => "Variable",
+    }
+}
+
+/// Policy applied when a name used by expression shadows another kind of name
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum ShadowingPolicy {
+    /// Expression is evaluated without warning
+    Allow,
+    /// Expression is evaluated and a warning is given for each shadowed name
+    #[default]
+    Warn,
+    /// Expression is rejected with an error
+    Deny,
+}
+
+/// Options of resolution of names of expression
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ResolutionOptions {
+    /// Order in which kinds of names are tried to resolve a name, each kind being given once
+    pub order: [NameKind; 3],
+    /// Policy applied to shadowed names
+    pub shadowing: ShadowingPolicy,
+}
+
+impl Default for ResolutionOptions {
+    fn default() -> ResolutionOptions {
+        return ResolutionOptions {
+            order: DEFAULT_ORDER,
+            shadowing: ShadowingPolicy::default(),
+        };
+    }
+}
+
+/// Evaluate an expression like evaluate function, names being resolved in order given by options in argument.
+/// Each name used by expression which matches several kinds of names shadows the kinds coming after it
+/// in resolution order. According to shadowing policy of options, such name is allowed, gives a warning
+/// alongside value of expression, or gives an error.
+///
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+pub fn evaluate_with_resolution(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    options: &ResolutionOptions,
+) -> Result<(f64, Vec<ShadowingWarning>), String> {
+    let order: [NameKind; 3] = options.order;
+
+    if DEFAULT_ORDER.iter().any(|kind| !order.contains(kind)) {
+        return Err(String::from(
+            "Resolution order must give each kind of name once",
+        ));
+    }
+
+    let mut warnings: Vec<ShadowingWarning> = Vec::new();
+    let mut infix_tokens = tokenizer::infix_tokens(expression, variables).with_order(order);
+
+    let checked_tokens = std::iter::from_fn(|| {
+        let next = infix_tokens.next();
+        let name: &str = infix_tokens.last_word();
+
+        if name.is_empty()
+            || options.shadowing == ShadowingPolicy::Allow
+            || warnings.iter().any(|warning| warning.name == name)
+        {
+            return next;
+        }
+
+        let mut kinds = order.iter().filter(|&&kind| match kind {
+            NameKind::Constant => is_constant(name),
+            NameKind::Function => Function::is_fun(name),
+            NameKind::Variable => variables.contains_key(name),
+        });
+
+        if let Some(&resolved) = kinds.next() {
+            for &shadowed in kinds {
+                let warning: ShadowingWarning = ShadowingWarning {
+                    name: String::from(name),
+                    resolved,
+                    shadowed,
+                };
+
+                if options.shadowing == ShadowingPolicy::Deny {
+                    return Some(Err(warning.to_string()));
+                }
+
+                warnings.push(warning);
+            }
+        }
+
+        return next;
+    });
+
+    let values: OperandStack =
+        evaluator::postfix_evaluation_stream(Postfix::new(checked_tokens), &[])
+            .map_err(|message| infix_tokens.locate(message))?;
+
+    match values.first() {
+        Some(&value) => Ok((value, warnings)),
+        None => Err(String::from("Empty expression")),
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables() -> HashMap<String, f64> {
+        return HashMap::from([
+            (String::from("e"), 0.5),
+            (String::from("sin"), 2.0),
+            (String::from("x"), 3.0),
+        ]);
+    }
+
+    #[test]
+    fn test_evaluate_with_resolution_warnings() {
+        match evaluate_with_resolution(
+            "x * e + sin(0) + e",
+            &variables(),
+            &ResolutionOptions::default(),
+        ) {
+            Ok((value, warnings)) => {
+                assert_eq!(value, 4.0 * std::f64::consts::E);
+                assert_eq!(
+                    warnings,
+                    vec![
+                        ShadowingWarning {
+                            name: String::from("e"),
+                            resolved: NameKind::Constant,
+                            shadowed: NameKind::Variable,
+                        },
+                        ShadowingWarning {
+                            name: String::from("sin"),
+                            resolved: NameKind::Function,
+                            shadowed: NameKind::Variable,
+                        },
+                    ]
+                );
+                assert_eq!(
+                    warnings[0].to_string(),
+                    "Variable e is shadowed by constant with the same name"
+                );
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_with_resolution_without_shadowing() {
+        match evaluate_with_resolution("x + pi", &variables(), &ResolutionOptions::default()) {
+            Ok((value, warnings)) => {
+                assert_eq!(value, 3.0 + std::f64::consts::PI);
+                assert!(warnings.is_empty());
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_with_resolution_deny() {
+        let options: ResolutionOptions = ResolutionOptions {
+            shadowing: ShadowingPolicy::Deny,
+            ..ResolutionOptions::default()
+        };
+
+        assert_eq!(
+            evaluate_with_resolution("x * sin(1)", &variables(), &options),
+            Err(String::from(
+                "Variable sin is shadowed by function with the same name at position 4"
+            ))
+        );
+        assert!(evaluate_with_resolution("x * 2", &variables(), &options).is_ok());
+    }
+
+    #[test]
+    fn test_evaluate_with_resolution_order() {
+        let options: ResolutionOptions = ResolutionOptions {
+            order: [NameKind::Variable, NameKind::Constant, NameKind::Function],
+            shadowing: ShadowingPolicy::Allow,
+        };
+
+        match evaluate_with_resolution("x * e + pi", &variables(), &options) {
+            Ok((value, warnings)) => {
+                assert_eq!(value, 1.5 + std::f64::consts::PI);
+                assert!(warnings.is_empty());
+            }
+            Err(_) => assert!(false),
+        }
+
+        let options: ResolutionOptions = ResolutionOptions {
+            shadowing: ShadowingPolicy::Deny,
+            ..options
+        };
+
+        assert_eq!(
+            evaluate_with_resolution("x * e", &variables(), &options),
+            Err(String::from(
+                "Constant e is shadowed by variable with the same name at position 4"
+            ))
+        );
+
+        let options: ResolutionOptions = ResolutionOptions {
+            order: [NameKind::Variable, NameKind::Variable, NameKind::Function],
+            ..options
+        };
+
+        assert!(evaluate_with_resolution("x", &variables(), &options).is_err());
+    }
+}
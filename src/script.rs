@@ -0,0 +1,583 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::{substitute, Ast, Node};
+use super::constants::is_constant;
+use super::functions::Function;
+use super::journal::{evaluate_journaled, Access, JournalEntry};
+use super::options::EvaluationOptions;
+
+use std::collections::HashMap;
+
+/// Function defined by user in a script, like f(x, y) = x^2 + y
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct UserFunction {
+    /// Names of parameters
+    params: Vec<String>,
+    /// Syntax tree of body, calls of user functions being already expanded
+    body: Node,
+}
+
+impl UserFunction {
+    /// Write definition of function named as given in argument, like f(x, y) = x^2 + y
+    pub(crate) fn definition(&self, name: &str) -> String {
+        return format!("{name}({}) = {}", self.params.join(", "), self.body);
+    }
+}
+
+/// Functions defined by user, by name
+pub(crate) type UserFunctions = HashMap<String, UserFunction>;
+
+/// Left side of a statement of a script
+#[derive(Debug, PartialEq)]
+enum Target<'a> {
+    /// Statement is an expression
+    Expression,
+    /// Statement assigns a variable, like x = 2 * pi
+    Variable(&'a str),
+    /// Statement defines a function with its parameters, like f(x, y) = x^2 + y
+    Function(&'a str, Vec<&'a str>),
+}
+
+/// Statement of a script, which is an expression, an assignment or a function definition
+struct Statement<'a> {
+    /// Variable assigned or function defined by statement
+    target: Target<'a>,
+    /// Expression of statement, right side of assignment or body of function
+    expression: &'a str,
+    /// Offset of statement in script, as byte offset
+    offset: usize,
+    /// Length of statement, surrounding whitespaces excluded
+    length: usize,
+    /// Offset of line of statement in script, as byte offset
+    line_offset: usize,
+    /// Number of line of statement, starting from 1
+    line: usize,
+}
+
+/// Check if string given in argument can be the name of a variable, like x or speed_2
+fn is_name(string: &str) -> bool {
+    return string.starts_with(|c: char| c.is_alphabetic())
+        && string.chars().all(|c| c.is_alphanumeric() || c == '_');
+}
+
+/// Check if name given in argument is the name of a constant or a predefined function
+fn is_predefined(name: &str) -> bool {
+    return is_constant(name) || Function::is_fun(name);
+}
+
+/// Get target of left side of an assignment given in argument, like x or f(x, y).
+/// None is given if it is neither a name nor a function with its parameters
+fn target(left: &str) -> Option<Target<'_>> {
+    let left: &str = left.trim();
+
+    if is_name(left) {
+        return Some(Target::Variable(left));
+    }
+
+    let (name, params) = left.strip_suffix(')')?.split_once('(')?;
+    let name: &str = name.trim();
+
+    if !is_name(name) {
+        return None;
+    }
+
+    let params: Vec<&str> = match params.trim() {
+        "" => Vec::new(),
+        params => params.split(',').map(str::trim).collect(),
+    };
+
+    if !params.iter().all(|param| is_name(param)) {
+        return None;
+    }
+
+    return Some(Target::Function(name, params));
+}
+
+/// Split script given in argument into statements separated by semicolons or new lines.
+/// Empty statements, like the one after a trailing semicolon, are skipped
+fn statements(script: &str) -> Vec<Statement<'_>> {
+    let mut statements: Vec<Statement<'_>> = Vec::new();
+    let mut line_offset: usize = 0;
+
+    for (index, line) in script.split_inclusive('\n').enumerate() {
+        let mut offset: usize = line_offset;
+
+        for text in line.split_inclusive(';') {
+            let start: usize = offset;
+            offset += text.len();
+
+            let statement: &str = text.trim_end_matches(['\n', ';']).trim();
+
+            if statement.is_empty() {
+                continue;
+            }
+
+            // An equal sign followed by another one is an equality operator, not an assignment
+            let (target, expression): (Target<'_>, &str) = match statement
+                .split_once('=')
+                .filter(|(_, expression)| !expression.starts_with('='))
+            {
+                Some((left, expression)) => match target(left) {
+                    Some(target) => (target, expression),
+                    None => (Target::Expression, statement),
+                },
+                None => (Target::Expression, statement),
+            };
+
+            statements.push(Statement {
+                target,
+                expression,
+                offset: start + (text.len() - text.trim_start().len()),
+                length: statement.len(),
+                line_offset,
+                line: index + 1,
+            });
+        }
+
+        line_offset += line.len();
+    }
+
+    return statements;
+}
+
+/// Split arguments of a call given in argument, which starts after its opening parenthesis,
+/// at commas outside parenthesis and brackets. The length of call, closing parenthesis included, is also given.
+/// None is given if parenthesis of call is not closed
+fn call_arguments(call: &str) -> Option<(Vec<&str>, usize)> {
+    let mut args: Vec<&str> = Vec::new();
+    let mut depth: usize = 0;
+    let mut start: usize = 0;
+
+    for (index, c) in call.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' if depth == 0 => {
+                if !args.is_empty() || !call[start..index].trim().is_empty() {
+                    args.push(&call[start..index]);
+                }
+
+                return Some((args, index + 1));
+            }
+            ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                args.push(&call[start..index]);
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+
+    return None;
+}
+
+/// Expand each call of a user function in expression given in argument, a call being replaced by
+/// the body of function whose parameters are replaced by arguments of call.
+/// None is given if expression does not call any user function.
+/// If a call is not valid, an error message is stored in string contained in Result output
+fn expand(expression: &str, functions: &UserFunctions) -> Result<Option<String>, String> {
+    let mut expanded: String = String::with_capacity(expression.len());
+    let mut has_calls: bool = false;
+    let mut rest: &str = expression;
+
+    while let Some(start) = rest.find(|c: char| c.is_alphanumeric()) {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let length: usize = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let (word, after) = rest.split_at(length);
+        let after_word: &str = after.trim_start();
+
+        let function: &UserFunction = match functions.get(word) {
+            Some(function) if after_word.starts_with('(') => function,
+            _ => {
+                expanded.push_str(word);
+                rest = after;
+                continue;
+            }
+        };
+
+        let (args, call_length) = call_arguments(&after_word[1..])
+            .ok_or_else(|| format!("Unclosed parenthesis in call of {word}"))?;
+
+        if args.len() != function.params.len() {
+            return Err(format!(
+                "Function {word} expects {} arguments but got {}",
+                function.params.len(),
+                args.len()
+            ));
+        }
+
+        let mut arg_nodes: Vec<Node> = Vec::with_capacity(args.len());
+
+        for arg in args {
+            let arg: String = expand(arg, functions)?.unwrap_or_else(|| String::from(arg));
+            arg_nodes.push(Ast::new(&arg)?.root);
+        }
+
+        let replacements: Vec<(&str, &Node)> = function
+            .params
+            .iter()
+            .map(String::as_str)
+            .zip(arg_nodes.iter())
+            .collect();
+
+        expanded.push_str(&format!("({})", substitute(&function.body, &replacements)));
+        has_calls = true;
+        rest = &after_word[1 + call_length..];
+    }
+
+    if !has_calls {
+        return Ok(None);
+    }
+
+    expanded.push_str(rest);
+    return Ok(Some(expanded));
+}
+
+/// Define function of statement given in argument, whose name and parameters are given in argument,
+/// in functions given in argument.
+/// If definition is not valid, an error message is stored in string contained in Result output
+fn define(
+    statement: &Statement<'_>,
+    name: &str,
+    params: &[&str],
+    functions: &mut UserFunctions,
+) -> Result<(), String> {
+    for (index, &param) in params.iter().enumerate() {
+        if is_predefined(param) || params[..index].contains(&param) {
+            return Err(format!(
+                "Parameter {param} of function {name} is not a distinct variable name on line {}",
+                statement.line
+            ));
+        }
+    }
+
+    // Calls of functions defined before are expanded, so redefining them later does not change this function
+    let body: String = expand(statement.expression, functions)
+        .map_err(|message| format!("{message} on line {}", statement.line))?
+        .unwrap_or_else(|| String::from(statement.expression));
+    let body: Node = Ast::new(&body)
+        .map_err(|message| format!("{message} in body of {name} on line {}", statement.line))?
+        .root;
+
+    if let Node::Tuple(_) = body {
+        return Err(format!(
+            "Body of function {name} is a tuple on line {}",
+            statement.line
+        ));
+    }
+
+    functions.insert(
+        String::from(name),
+        UserFunction {
+            params: params.iter().map(|&param| String::from(param)).collect(),
+            body,
+        },
+    );
+
+    return Ok(());
+}
+
+/// Run script given in argument according to options of evaluation given in argument,
+/// with functions defined by user given in argument, which are completed by definitions of script,
+/// recording accesses to variables in journal given in argument if any.
+/// Value of last statement which is not a definition is given, None if script only defines functions
+pub(crate) fn run(
+    script: &str,
+    variables: &mut HashMap<String, f64>,
+    functions: &mut UserFunctions,
+    options: &EvaluationOptions,
+    mut journal: Option<&mut Vec<JournalEntry>>,
+) -> Result<Option<f64>, String> {
+    let statements: Vec<Statement<'_>> = statements(script);
+    let mut last_value: Option<f64> = None;
+
+    if statements.is_empty() {
+        return Err(String::from("Empty script"));
+    }
+
+    for statement in statements {
+        let position: usize = statement.offset - statement.line_offset;
+
+        match &statement.target {
+            Target::Variable(name) | Target::Function(name, _) if is_predefined(name) => {
+                return Err(format!(
+                    "Cannot assign to {name}, which is not a variable, at position {position} on line {}",
+                    statement.line
+                ));
+            }
+            Target::Function(name, params) => {
+                define(&statement, name, params, functions)?;
+                continue;
+            }
+            _ => (),
+        }
+
+        let expression_offset: usize =
+            statement.offset + statement.length - statement.expression.len();
+        let mut reads: Vec<JournalEntry> = Vec::new();
+
+        let value: f64 = match expand(statement.expression, functions)
+            .map_err(|message| format!("{message} on line {}", statement.line))?
+        {
+            Some(expanded) => {
+                let value: f64 = evaluate_journaled(&expanded, 0, variables, options, &mut reads)
+                    .map_err(|message| {
+                    format!("{message} in {expanded} on line {}", statement.line)
+                })?;
+
+                // Reads of a statement calling user functions are located at its expression
+                for read in reads.iter_mut() {
+                    read.span = expression_offset..statement.offset + statement.length;
+                }
+
+                value
+            }
+            None => {
+                // Text of line before expression is blanked, so positions of errors are given in line
+                let blanked: String =
+                    " ".repeat(expression_offset - statement.line_offset) + statement.expression;
+
+                evaluate_journaled(
+                    &blanked,
+                    statement.line_offset,
+                    variables,
+                    options,
+                    &mut reads,
+                )
+                .map_err(|message| format!("{message} on line {}", statement.line))?
+            }
+        };
+
+        if let Some(journal) = journal.as_deref_mut() {
+            journal.append(&mut reads);
+        }
+
+        if let Target::Variable(name) = statement.target {
+            let old_value: Option<f64> = variables.insert(String::from(name), value);
+
+            if let Some(journal) = journal.as_deref_mut() {
+                journal.push(JournalEntry {
+                    access: Access::Write,
+                    name: String::from(name),
+                    old_value,
+                    new_value: value,
+                    span: statement.offset..statement.offset + statement.length,
+                });
+            }
+        }
+
+        last_value = Some(value);
+    }
+
+    return Ok(last_value);
+}
+
+/// Check if script given in argument only defines functions, so running it gives no value
+pub(crate) fn defines_only(script: &str) -> bool {
+    let statements: Vec<Statement<'_>> = statements(script);
+
+    return !statements.is_empty()
+        && statements
+            .iter()
+            .all(|statement| matches!(statement.target, Target::Function(_, _)));
+}
+
+/// Get value of script given by run function.
+/// If script only defines functions, an error message is stored in string contained in Result output
+pub(crate) fn script_value(value: Option<f64>) -> Result<f64, String> {
+    return value.ok_or_else(|| String::from("Script defines functions but gives no value"));
+}
+
+/// Run a script made of statements separated by semicolons or new lines, which are expressions,
+/// assignments like x = 2 * pi or definitions of functions like f(x, y) = x^2 + y.
+/// Each assigned variable is stored in hash map given in argument and usable by following statements,
+/// like each function defined, which is called like predefined functions.
+/// Value of last statement which is not a definition is returned.
+///
+/// Calls of functions defined by user are expanded before evaluation, so positions of errors
+/// in a statement calling them are given in its expanded expression, written in error message.
+/// If error occurs during evaluation of a statement, an error message giving its position in line
+/// and the number of line is stored in string contained in Result output
+pub fn run_script(script: &str, variables: &mut HashMap<String, f64>) -> Result<f64, String> {
+    let options: EvaluationOptions = EvaluationOptions::default();
+    return script_value(run(
+        script,
+        variables,
+        &mut UserFunctions::new(),
+        &options,
+        None,
+    )?);
+}
+
+/// Evaluate a script like run_script function, assignments being kept in an environment of the script
+/// initialized with variables given in argument, which are left unchanged.
+/// Value of last statement which is not a definition is returned.
+///
+/// If error occurs during evaluation of a statement, an error message giving its position in line
+/// and the number of line is stored in string contained in Result output
+///
+/// # Example
+/// ```
+/// use taz;
+/// use std::collections::HashMap;
+///
+/// assert_eq!(taz::evaluate_script("a = 3; b = a^2; a + b", &HashMap::new()), Ok(12.0));
+/// assert_eq!(taz::evaluate_script("f(x, y) = x^2 + y; f(3, 1)", &HashMap::new()), Ok(10.0));
+/// ```
+pub fn evaluate_script(script: &str, variables: &HashMap<String, f64>) -> Result<f64, String> {
+    let mut environment: HashMap<String, f64> = variables.clone();
+    let options: EvaluationOptions = EvaluationOptions::default();
+    return script_value(run(
+        script,
+        &mut environment,
+        &mut UserFunctions::new(),
+        &options,
+        None,
+    )?);
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_script() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 2.0)]);
+
+        assert_eq!(
+            evaluate_script("a = 3; b = a^2; a + b", &variables),
+            Ok(12.0)
+        );
+        assert_eq!(
+            evaluate_script("x = x * 5;\nx = x + 1;", &variables),
+            Ok(11.0)
+        );
+        assert_eq!(variables.get("x"), Some(&2.0));
+
+        // Local bindings and calls of calculus functions are evaluated in statements
+        assert_eq!(
+            evaluate_script(
+                "a = series(i, 1, x, i)\nlet b = a + 1 in prod(i, 1, b, i)",
+                &variables
+            ),
+            Ok(24.0)
+        );
+
+        // Equality operator is not an assignment
+        assert_eq!(evaluate_script("a = 1; a == 1", &variables), Ok(1.0));
+        assert_eq!(evaluate_script("a = 1\nb = a == 2; b", &variables), Ok(0.0));
+        assert_eq!(evaluate_script("  ;\n; x", &variables), Ok(2.0));
+    }
+
+    #[test]
+    fn test_run_script() {
+        let mut variables: HashMap<String, f64> = HashMap::new();
+
+        assert_eq!(
+            run_script("rate = 0.5; base = 100", &mut variables),
+            Ok(100.0)
+        );
+        assert_eq!(run_script("base * rate", &mut variables), Ok(50.0));
+        assert_eq!(variables.get("rate"), Some(&0.5));
+
+        // Local bindings are not kept as variables
+        assert_eq!(
+            run_script("y = let t = rate * 4 in t * t; y + 1", &mut variables),
+            Ok(5.0)
+        );
+        assert_eq!(variables.get("t"), None);
+    }
+
+    #[test]
+    fn test_run_script_user_functions() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("k"), 10.0)]);
+
+        assert_eq!(
+            evaluate_script("f(x) = x^2 + 1; f(3)", &variables),
+            Ok(10.0)
+        );
+
+        // Arguments are substituted at once and keep their priority
+        assert_eq!(
+            evaluate_script("g(x, y) = x / y\ny = 6\ng(y, 1 + 2) * 2", &variables),
+            Ok(4.0)
+        );
+
+        // Free variables take their value at call, calls of other functions are expanded at definition
+        assert_eq!(
+            evaluate_script(
+                "h(x) = x * k; s(x) = h(x) + sqrt(h(x)); s(k - 9)",
+                &variables
+            ),
+            Ok(10.0 + 10f64.sqrt())
+        );
+        assert_eq!(
+            evaluate_script("one() = 1; one() + one ( )", &variables),
+            Ok(2.0)
+        );
+    }
+
+    #[test]
+    fn test_run_script_user_functions_errors() {
+        let variables: HashMap<String, f64> = HashMap::new();
+
+        assert_eq!(
+            evaluate_script("f(x) = x^2\nf(1, 2)", &variables),
+            Err(String::from(
+                "Function f expects 1 arguments but got 2 on line 2"
+            ))
+        );
+        assert_eq!(
+            evaluate_script("f(x) = x / z\n1 + f(2)", &variables),
+            Err(String::from(
+                "Unknown name z at position 9 in 1 + (2 / z) on line 2"
+            ))
+        );
+        assert_eq!(
+            evaluate_script("f(x, x) = x", &variables),
+            Err(String::from(
+                "Parameter x of function f is not a distinct variable name on line 1"
+            ))
+        );
+        assert_eq!(
+            evaluate_script("sin(x) = x", &variables),
+            Err(String::from(
+                "Cannot assign to sin, which is not a variable, at position 0 on line 1"
+            ))
+        );
+        assert_eq!(
+            evaluate_script("f(x) = x + 1", &variables),
+            Err(String::from("Script defines functions but gives no value"))
+        );
+    }
+
+    #[test]
+    fn test_run_script_errors() {
+        let mut variables: HashMap<String, f64> = HashMap::new();
+
+        assert_eq!(
+            run_script("x = 1\ny = x + z", &mut variables),
+            Err(String::from("Unknown name z at position 8 on line 2"))
+        );
+        assert_eq!(
+            run_script("x = 1; y = 2 *; x", &mut variables),
+            Err(String::from(
+                "Unexpected end of expression at position 14 on line 1"
+            ))
+        );
+        assert_eq!(
+            run_script("a = 1;  pi = 3", &mut variables),
+            Err(String::from(
+                "Cannot assign to pi, which is not a variable, at position 8 on line 1"
+            ))
+        );
+        assert_eq!(
+            run_script("\n  ;\n", &mut variables),
+            Err(String::from("Empty script"))
+        );
+    }
+}
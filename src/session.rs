@@ -0,0 +1,191 @@
+#![allow(clippy::needless_return)]
+
+use super::options::EvaluationOptions;
+use super::script::{self, UserFunctions};
+
+use std::collections::HashMap;
+
+/// Name of variable holding the result of last evaluation of a session
+const ANS: &str = "ans";
+
+/// Session of evaluations sharing their variables and functions, like a calculator.
+/// Each input is a script of expressions, assignments like x = 2 * pi and definitions of functions
+/// like f(x) = x^2 + 1, whose assigned variables and defined functions are kept for following inputs,
+/// and ans variable is bound to the result of last input.
+///
+/// # Example
+/// ```
+/// use taz::Session;
+///
+/// let mut session: Session = Session::new();
+///
+/// assert_eq!(session.eval("r = 2"), Ok(2.0));
+/// assert_eq!(session.eval("ans * r"), Ok(4.0));
+/// assert_eq!(session.ans(), Some(4.0));
+///
+/// assert_eq!(session.run("f(x) = x^2 + 1"), Ok(None));
+/// assert_eq!(session.eval("f(3)"), Ok(10.0));
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Session {
+    variables: HashMap<String, f64>,
+    functions: UserFunctions,
+    options: EvaluationOptions,
+}
+
+impl Session {
+    /// Create session without variables, evaluating with default options
+    pub fn new() -> Session {
+        return Session::default();
+    }
+
+    /// Evaluate inputs of session according to options of evaluation given in argument,
+    /// like angles in degrees
+    pub fn with_options(mut self, options: EvaluationOptions) -> Session {
+        self.options = options;
+        return self;
+    }
+
+    /// Run input given in argument, which is an expression or a script of statements separated
+    /// by semicolons or new lines, binding ans variable to its value.
+    /// None is given if input only defines functions, ans being left unchanged.
+    /// Assignments and definitions of statements run before an error are kept, but ans is left unchanged.
+    ///
+    /// If error occurs during evaluation, an error message is stored in string contained in Result output
+    pub fn run(&mut self, input: &str) -> Result<Option<f64>, String> {
+        let value: Option<f64> = script::run(
+            input,
+            &mut self.variables,
+            &mut self.functions,
+            &self.options,
+            None,
+        )?;
+
+        if let Some(value) = value {
+            self.variables.insert(String::from(ANS), value);
+        }
+
+        return Ok(value);
+    }
+
+    /// Evaluate input given in argument like run method, giving its value.
+    /// Input which only defines functions is not run, so its definitions are not kept.
+    ///
+    /// If error occurs during evaluation or if input only defines functions,
+    /// an error message is stored in string contained in Result output
+    pub fn eval(&mut self, input: &str) -> Result<f64, String> {
+        if script::defines_only(input) {
+            return script::script_value(None);
+        }
+
+        return script::script_value(self.run(input)?);
+    }
+
+    /// Get result of last evaluation, None if nothing has been evaluated yet
+    pub fn ans(&self) -> Option<f64> {
+        return self.variables.get(ANS).copied();
+    }
+
+    /// Get variables of session, ans included
+    pub fn variables(&self) -> &HashMap<String, f64> {
+        return &self.variables;
+    }
+
+    /// Set value of variable given in argument, usable by following evaluations
+    pub fn set_variable(&mut self, name: &str, value: f64) {
+        self.variables.insert(String::from(name), value);
+    }
+
+    /// Get definitions of functions of session sorted by name, like f(x) = x^2 + 1
+    pub fn functions(&self) -> Vec<String> {
+        let mut names: Vec<&String> = self.functions.keys().collect();
+        names.sort_unstable();
+
+        return names
+            .into_iter()
+            .map(|name| self.functions[name].definition(name))
+            .collect();
+    }
+
+    /// Remove all variables and functions of session, ans included
+    pub fn clear(&mut self) {
+        self.variables.clear();
+        self.functions.clear();
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AngleUnit;
+
+    #[test]
+    fn test_session_eval() {
+        let mut session: Session = Session::new();
+        assert_eq!(session.ans(), None);
+
+        session.set_variable("rate", 0.5);
+        assert_eq!(session.eval("base = 100; base * rate"), Ok(50.0));
+        assert_eq!(session.eval("ans + base"), Ok(150.0));
+        assert_eq!(session.ans(), Some(150.0));
+
+        // A failing input keeps previous result
+        assert_eq!(
+            session.eval("ans / y"),
+            Err(String::from("Unknown name y at position 6 on line 1"))
+        );
+        assert_eq!(session.ans(), Some(150.0));
+        assert_eq!(session.variables().len(), 3);
+
+        session.clear();
+        assert!(session.eval("ans").is_err());
+    }
+
+    #[test]
+    fn test_session_user_functions() {
+        let mut session: Session = Session::new();
+
+        assert_eq!(
+            session.run("area(w, h) = w * h; sq(x) = area(x, x)"),
+            Ok(None)
+        );
+        assert_eq!(session.ans(), None);
+        assert_eq!(session.eval("sq(3) + area(2, 0.5)"), Ok(10.0));
+        assert_eq!(
+            session.functions(),
+            vec![
+                String::from("area(w, h) = w * h"),
+                String::from("sq(x) = x * x")
+            ]
+        );
+
+        // Redefining a function does not change functions defined with it
+        assert_eq!(session.run("area(w, h) = w * h / 2"), Ok(None));
+        assert_eq!(session.eval("sq(3) + area(2, 0.5)"), Ok(9.5));
+        assert!(session.eval("area(1, 1) = 2").is_err());
+
+        // Evaluating a definition fails without defining function
+        assert_eq!(
+            session.eval("cube(x) = x^3"),
+            Err(String::from("Script defines functions but gives no value"))
+        );
+        assert_eq!(session.functions().len(), 2);
+        assert_eq!(session.eval("cube(x) = x^3; cube(2)"), Ok(8.0));
+        assert_eq!(session.functions().len(), 3);
+    }
+
+    #[test]
+    fn test_session_with_options() {
+        let options: EvaluationOptions = EvaluationOptions {
+            angle: AngleUnit::Degrees,
+            ..EvaluationOptions::default()
+        };
+        let mut session: Session = Session::new().with_options(options);
+
+        match session.eval("sin(30)") {
+            Ok(value) => assert!((value - 0.5).abs() < 1e-12),
+            Err(_) => assert!(false),
+        }
+    }
+}
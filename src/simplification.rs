@@ -0,0 +1,1117 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::{self, Ast, Node};
+use super::functions::Function;
+use super::operators::{BinaryOperator, UnaryOperator};
+
+/// Maximal number of passes of simplification, each pass simplifying the result of the previous one
+const MAX_PASSES: usize = 8;
+
+/// Maximal number of terms given by expansion of a product, beyond which the product is kept
+const MAX_EXPANDED_TERMS: usize = 4096;
+
+/// Rule defined by user replacing subexpressions which match its pattern by its replacement,
+/// like cosh(u)^2 - sinh(u)^2 giving 1. Variables of pattern match any subexpression,
+/// a variable appearing several times matching the same subexpression each time
+#[derive(Debug, PartialEq, Clone)]
+pub struct RewriteRule {
+    pattern: Node,
+    replacement: Node,
+}
+
+impl RewriteRule {
+    /// Create rule replacing subexpressions matching pattern given in argument by replacement given in argument,
+    /// whose variables are bound to the subexpressions matched by variables of pattern.
+    /// Pattern is matched against simplified subexpressions, operands of additions and multiplications
+    /// matching in any order.
+    ///
+    /// If pattern or replacement cannot be parsed, pattern is a single variable matching everything
+    /// or replacement uses a variable not in pattern, an error message is stored in string contained in Result output
+    pub fn new(pattern: &str, replacement: &str) -> Result<RewriteRule, String> {
+        let (pattern, replacement): (Ast, Ast) = (Ast::new(pattern)?, Ast::new(replacement)?);
+
+        if let Node::Variable(name) = &pattern.root {
+            return Err(format!(
+                "Pattern cannot be the single variable {name}, since it matches every expression"
+            ));
+        }
+
+        let variables = pattern.uses().variables;
+
+        if let Some(name) = replacement
+            .uses()
+            .variables
+            .into_iter()
+            .find(|name| !variables.contains(name))
+        {
+            return Err(format!(
+                "Variable {name} of replacement does not appear in pattern"
+            ));
+        }
+
+        return Ok(RewriteRule {
+            pattern: pattern.root,
+            replacement: replacement.root,
+        });
+    }
+}
+
+/// Rules applied by simplification of expressions, all enabled by default
+#[derive(Debug, PartialEq, Clone)]
+pub struct SimplificationRules {
+    /// Compute operations and functions on numbers, like 2 * 3 giving 6,
+    /// and remove neutral elements, like x + 0 or x * 1
+    pub fold_constants: bool,
+    /// Combine like terms of sums, like x + 2 * x giving 3 * x,
+    /// and like factors of products, like x * x giving x^2
+    pub combine_like_terms: bool,
+    /// Cancel factors common to numerator and denominator of quotients, like x / x giving 1,
+    /// assuming these factors are not 0
+    pub cancel_quotients: bool,
+    /// Replace sin(u)^2 + cos(u)^2 by 1
+    pub pythagorean_identity: bool,
+    /// Remove a function composed with its inverse, like ln(exp(u)) giving u,
+    /// exp(ln(u)) giving u assuming u is positive, or sqrt(u^2) giving abs(u)
+    pub inverse_functions: bool,
+    /// Rules defined by user, applied to each subexpression after predefined rules,
+    /// the first rule whose pattern matches being applied. None by default
+    pub rewrite_rules: Vec<RewriteRule>,
+}
+
+impl Default for SimplificationRules {
+    fn default() -> SimplificationRules {
+        return SimplificationRules {
+            fold_constants: true,
+            combine_like_terms: true,
+            cancel_quotients: true,
+            pythagorean_identity: true,
+            inverse_functions: true,
+            rewrite_rules: Vec::new(),
+        };
+    }
+}
+
+/// Simplifier of syntax trees applying rules, which records the assumptions made by rules
+/// on values of subexpressions, like x is not 0 when x / x is simplified to 1
+struct Simplifier<'a> {
+    rules: &'a SimplificationRules,
+    assumptions: Vec<String>,
+}
+
+/// Check if node given in argument matches pattern given in argument, binding variables of pattern
+/// to the subexpressions they match. A variable already bound matches only its subexpression
+fn match_pattern(pattern: &Node, node: &Node, bindings: &mut Vec<(String, Node)>) -> bool {
+    match (pattern, node) {
+        (Node::Variable(name), _) => match bindings.iter().find(|(bound, _)| bound == name) {
+            Some((_, bound)) => return bound == node,
+            None => {
+                bindings.push((name.clone(), node.clone()));
+                return true;
+            }
+        },
+        (Node::Unary(pattern_ops, pattern), Node::Unary(ops, operand)) => {
+            return pattern_ops == ops && match_pattern(pattern, operand, bindings);
+        }
+        (
+            Node::Binary(pattern_ops, pattern_left, pattern_right),
+            Node::Binary(ops, left, right),
+        ) if pattern_ops == ops => {
+            let mut candidates: Vec<(&Node, &Node)> = vec![(left, right)];
+
+            if matches!(ops, BinaryOperator::Plus | BinaryOperator::Multiply) {
+                candidates.push((right, left));
+            }
+
+            for (left, right) in candidates {
+                let mut candidate: Vec<(String, Node)> = bindings.clone();
+
+                if match_pattern(pattern_left, left, &mut candidate)
+                    && match_pattern(pattern_right, right, &mut candidate)
+                {
+                    *bindings = candidate;
+                    return true;
+                }
+            }
+
+            return false;
+        }
+        (Node::Call(pattern_fun, patterns), Node::Call(fun, args)) => {
+            return pattern_fun == fun && match_all(patterns, args, bindings);
+        }
+        (Node::Tuple(patterns), Node::Tuple(elements)) => {
+            return match_all(patterns, elements, bindings);
+        }
+        _ => return pattern == node,
+    }
+}
+
+/// Get kind of chain of operations built by binary operator given in argument:
+/// true for sums, false for products and None for other operators
+fn chain_kind(ops: BinaryOperator) -> Option<bool> {
+    match ops {
+        BinaryOperator::Plus | BinaryOperator::Minus => Some(true),
+        BinaryOperator::Multiply | BinaryOperator::Divide => Some(false),
+        _ => None,
+    }
+}
+
+/// Check if each node given in argument matches the pattern at the same position, binding variables of patterns
+fn match_all(patterns: &[Node], nodes: &[Node], bindings: &mut Vec<(String, Node)>) -> bool {
+    return patterns.len() == nodes.len()
+        && patterns
+            .iter()
+            .zip(nodes)
+            .all(|(pattern, node)| match_pattern(pattern, node, bindings));
+}
+
+/// Get value of node given in argument if it is a number. Constants like pi are kept by simplification
+fn number(node: &Node) -> Option<f64> {
+    match node {
+        Node::Number(value) => Some(*value),
+        _ => None,
+    }
+}
+
+/// Get assumptions under which node given in argument is defined, like x is not 0 for 1 / x,
+/// None if node can be undefined for a reason which is not expressed by an assumption,
+/// like a division by 0 or a function whose domain is not described
+fn definition_assumptions(node: &Node) -> Option<Vec<String>> {
+    let mut assumptions: Vec<String> = Vec::new();
+
+    match node {
+        Node::Number(value) if !value.is_finite() => return None,
+        Node::Number(_) | Node::Constant(_, _) | Node::Variable(_) | Node::Array(_) => (),
+        Node::Unary(_, operand) => assumptions = definition_assumptions(operand)?,
+        Node::Binary(ops, left, right) => {
+            assumptions = definition_assumptions(left)?;
+            assumptions.extend(definition_assumptions(right)?);
+
+            match (ops, number(right)) {
+                (BinaryOperator::Divide, Some(0.0)) => return None,
+                (BinaryOperator::Power, Some(exponent))
+                    if number(left).is_some_and(|base| !base.powf(exponent).is_finite()) =>
+                {
+                    return None
+                }
+                (BinaryOperator::Power, Some(_)) if number(left).is_some() => (),
+                (BinaryOperator::Divide, None) => assumptions.push(format!("{right} is not 0")),
+                (BinaryOperator::Power, Some(exponent)) if exponent < 0.0 => {
+                    assumptions.push(format!("{left} is not 0"))
+                }
+                (BinaryOperator::Power, Some(exponent)) if exponent.fract() != 0.0 => {
+                    assumptions.push(format!("{left} is not negative"))
+                }
+                (BinaryOperator::Power, None) => return None,
+                _ => (),
+            }
+        }
+        Node::Call(fun, args) => {
+            for arg in args {
+                assumptions.extend(definition_assumptions(arg)?);
+            }
+
+            match fun {
+                Function::Ln | Function::Log10 | Function::Log2 => {
+                    assumptions.push(format!("{} is positive", args[0]))
+                }
+                Function::Sqrt => assumptions.push(format!("{} is not negative", args[0])),
+                Function::Abs
+                | Function::Cbrt
+                | Function::Exp
+                | Function::Sin
+                | Function::Cos
+                | Function::Atan
+                | Function::Sinh
+                | Function::Cosh
+                | Function::Tanh
+                | Function::Asinh
+                | Function::Lerp
+                | Function::Select
+                | Function::If
+                | Function::Piecewise
+                | Function::Deg2Rad
+                | Function::Rad2Deg
+                | Function::Sum
+                | Function::Mean
+                | Function::Median
+                | Function::Std
+                | Function::Var
+                | Function::Min
+                | Function::Max => (),
+                _ => return None,
+            }
+        }
+        Node::Tuple(elements) => {
+            for element in elements {
+                assumptions.extend(definition_assumptions(element)?);
+            }
+        }
+    }
+
+    return Some(assumptions);
+}
+
+/// Get key of term given in argument, which is the same for terms differing only by order of their factors
+fn term_key(node: &Node) -> String {
+    fn collect(node: &Node, factors: &mut Vec<String>) {
+        match node {
+            Node::Binary(BinaryOperator::Multiply, left, right) => {
+                collect(left, factors);
+                collect(right, factors);
+            }
+            _ => factors.push(node.to_string()),
+        }
+    }
+
+    let mut factors: Vec<String> = Vec::new();
+    collect(node, &mut factors);
+    factors.sort();
+    return factors.join(" * ");
+}
+
+/// Split term given in argument between its numeric coefficient, which is the first factor of its product,
+/// and the rest of the term, like 2 and x * y for 2 * x * y. Coefficient is 1 if term has no numeric factor
+fn split_coefficient(node: Node) -> (f64, Node) {
+    match node {
+        Node::Binary(BinaryOperator::Multiply, left, right) => match *left {
+            Node::Number(coefficient) => (coefficient, *right),
+            left => match split_coefficient(left) {
+                (coefficient, left) if coefficient != 1.0 => (
+                    coefficient,
+                    Node::Binary(BinaryOperator::Multiply, Box::new(left), right),
+                ),
+                (_, left) => (
+                    1.0,
+                    Node::Binary(BinaryOperator::Multiply, Box::new(left), right),
+                ),
+            },
+        },
+        Node::Binary(BinaryOperator::Divide, left, right)
+            if matches!(*left, Node::Binary(BinaryOperator::Multiply, _, _)) =>
+        {
+            let (coefficient, left): (f64, Node) = split_coefficient(*left);
+            (
+                coefficient,
+                Node::Binary(BinaryOperator::Divide, Box::new(left), right),
+            )
+        }
+        _ => (1.0, node),
+    }
+}
+
+/// Multiply term given in argument by factor given in argument, which is put first in its product,
+/// like 2 * x * y for 2 and x * y
+fn prepend_factor(factor: Node, term: Node) -> Node {
+    match term {
+        Node::Binary(ops @ (BinaryOperator::Multiply | BinaryOperator::Divide), left, right) => {
+            Node::Binary(ops, Box::new(prepend_factor(factor, *left)), right)
+        }
+        _ => Node::Binary(BinaryOperator::Multiply, Box::new(factor), Box::new(term)),
+    }
+}
+
+/// Split node given in argument between its operands added or subtracted, with their signs
+fn flatten_sum(node: Node, sign: f64, operands: &mut Vec<(f64, Node)>) {
+    match node {
+        Node::Binary(BinaryOperator::Plus, left, right) => {
+            flatten_sum(*left, sign, operands);
+            flatten_sum(*right, sign, operands);
+        }
+        Node::Binary(BinaryOperator::Minus, left, right) => {
+            flatten_sum(*left, sign, operands);
+            flatten_sum(*right, -sign, operands);
+        }
+        Node::Unary(UnaryOperator::Minus, operand) => flatten_sum(*operand, -sign, operands),
+        _ => operands.push((sign, node)),
+    }
+}
+
+/// Split node given in argument between its operands multiplied or divided,
+/// with true for operands which are divisors
+fn flatten_product(node: Node, is_divisor: bool, operands: &mut Vec<(bool, Node)>) {
+    match node {
+        Node::Binary(BinaryOperator::Multiply, left, right) => {
+            flatten_product(*left, is_divisor, operands);
+            flatten_product(*right, is_divisor, operands);
+        }
+        Node::Binary(BinaryOperator::Divide, left, right) => {
+            flatten_product(*left, is_divisor, operands);
+            flatten_product(*right, !is_divisor, operands);
+        }
+        _ => operands.push((is_divisor, node)),
+    }
+}
+
+/// Create node raising base given in argument to exponent given in argument, which is not written if it is 1
+fn power(base: Node, exponent: f64) -> Node {
+    match exponent == 1.0 {
+        true => base,
+        false => Node::Binary(
+            BinaryOperator::Power,
+            Box::new(base),
+            Box::new(Node::Number(exponent)),
+        ),
+    }
+}
+
+/// Create node multiplying nodes given in argument, None if there is no node
+fn multiply_all(nodes: Vec<Node>) -> Option<Node> {
+    return nodes.into_iter().reduce(|product, node| {
+        Node::Binary(BinaryOperator::Multiply, Box::new(product), Box::new(node))
+    });
+}
+
+/// Check if node given in argument is the square of a call of function given in argument, giving its argument
+fn squared_argument(node: &Node, fun: Function) -> Option<&Node> {
+    match node {
+        Node::Binary(BinaryOperator::Power, base, exponent) if number(exponent) == Some(2.0) => {
+            match &**base {
+                Node::Call(called, args) if *called == fun && args.len() == 1 => Some(&args[0]),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+impl Simplifier<'_> {
+    /// Record assumption given in argument, made by a rule, once
+    fn assume(&mut self, assumption: String) {
+        if !self.assumptions.contains(&assumption) {
+            self.assumptions.push(assumption);
+        }
+    }
+
+    /// Simplify node given in argument and its children, then apply rules defined by user to simplified node
+    fn simplify(&mut self, node: &Node) -> Node {
+        let simplified: Node = self.simplify_node(node);
+        return self.rewrite(simplified);
+    }
+
+    /// Replace node given in argument by replacement of the first rule defined by user whose pattern matches it
+    fn rewrite(&self, node: Node) -> Node {
+        // Sums and products are rebuilt as chains of binary operations whose inner nodes are not simplified again,
+        // so rules are applied to inner nodes of chains from the first operands
+        let node: Node = match node {
+            Node::Binary(ops, left, right)
+                if matches!(&*left, Node::Binary(inner, _, _) if chain_kind(*inner).is_some()
+                    && chain_kind(*inner) == chain_kind(ops)) =>
+            {
+                Node::Binary(ops, Box::new(self.rewrite(*left)), right)
+            }
+            _ => node,
+        };
+
+        for rule in &self.rules.rewrite_rules {
+            let mut bindings: Vec<(String, Node)> = Vec::new();
+
+            if match_pattern(&rule.pattern, &node, &mut bindings) {
+                let replacements: Vec<(&str, &Node)> = bindings
+                    .iter()
+                    .map(|(name, node)| (name.as_str(), node))
+                    .collect();
+                return ast::substitute(&rule.replacement, &replacements);
+            }
+        }
+
+        return node;
+    }
+
+    /// Simplify node given in argument and its children by predefined rules
+    fn simplify_node(&mut self, node: &Node) -> Node {
+        match node {
+            Node::Unary(UnaryOperator::Plus, operand) => self.simplify(operand),
+            Node::Unary(UnaryOperator::Minus, _)
+            | Node::Binary(BinaryOperator::Plus | BinaryOperator::Minus, _, _) => {
+                self.simplify_sum(node)
+            }
+            Node::Unary(ops, operand) => {
+                let operand: Node = self.simplify(operand);
+
+                match number(&operand) {
+                    Some(value) if self.rules.fold_constants => Node::Number(ops.apply(value)),
+                    _ => Node::Unary(*ops, Box::new(operand)),
+                }
+            }
+            Node::Binary(BinaryOperator::Multiply | BinaryOperator::Divide, _, _) => {
+                self.simplify_product(node)
+            }
+            Node::Binary(BinaryOperator::Power, base, exponent) => {
+                let (base, exponent): (Node, Node) = (self.simplify(base), self.simplify(exponent));
+                self.simplify_power(base, exponent)
+            }
+            Node::Binary(ops, left, right) => {
+                let (left, right): (Node, Node) = (self.simplify(left), self.simplify(right));
+                self.fold_binary(*ops, left, right)
+            }
+            Node::Call(fun, args) => {
+                let args: Vec<Node> = args.iter().map(|arg| self.simplify(arg)).collect();
+                self.simplify_call(*fun, args)
+            }
+            Node::Tuple(elements) => {
+                Node::Tuple(elements.iter().map(|node| self.simplify(node)).collect())
+            }
+            _ => node.clone(),
+        }
+    }
+
+    /// Compute binary operation on nodes given in argument if they are numbers and its result is finite
+    fn fold_binary(&self, ops: BinaryOperator, left: Node, right: Node) -> Node {
+        if let (true, Some(left), Some(right)) =
+            (self.rules.fold_constants, number(&left), number(&right))
+        {
+            match ops.apply(left, right) {
+                Ok(value) if value.is_finite() => return Node::Number(value),
+                _ => (),
+            }
+        }
+
+        return Node::Binary(ops, Box::new(left), Box::new(right));
+    }
+
+    /// Simplify sum of terms given by node in argument: numbers are added, like terms are combined
+    /// and squares of sine and cosine of the same argument are replaced by 1
+    fn simplify_sum(&mut self, node: &Node) -> Node {
+        let mut operands: Vec<(f64, Node)> = Vec::new();
+        flatten_sum(node.clone(), 1.0, &mut operands);
+
+        // Simplified operands can be sums themselves, like (x + 1) * 1
+        let mut simplified: Vec<(f64, Node)> = Vec::new();
+
+        for (sign, operand) in operands {
+            let operand: Node = self.simplify(&operand);
+            flatten_sum(operand, sign, &mut simplified);
+        }
+
+        let mut constant: f64 = 0.0;
+        let mut terms: Vec<(f64, Node)> = Vec::new();
+
+        for (sign, operand) in simplified {
+            let (coefficient, term): (f64, Node) = match operand {
+                Node::Number(value) if self.rules.fold_constants => {
+                    constant += sign * value;
+                    continue;
+                }
+                _ if self.rules.combine_like_terms => {
+                    let (coefficient, term): (f64, Node) = split_coefficient(operand);
+                    (sign * coefficient, term)
+                }
+                _ => (sign, operand),
+            };
+
+            let like_term: Option<&mut (f64, Node)> = match self.rules.combine_like_terms {
+                true if number(&term).is_none() => terms
+                    .iter_mut()
+                    .find(|(_, other)| term_key(other) == term_key(&term)),
+                _ => None,
+            };
+
+            match like_term {
+                Some((total, _)) => *total += coefficient,
+                None => terms.push((coefficient, term)),
+            }
+        }
+
+        if self.rules.pythagorean_identity {
+            let mut index: usize = 0;
+
+            while index < terms.len() {
+                let found: Option<usize> = squared_argument(&terms[index].1, Function::Sin)
+                    .and_then(|argument| {
+                        terms.iter().position(|(coefficient, term)| {
+                            *coefficient == terms[index].0
+                                && squared_argument(term, Function::Cos) == Some(argument)
+                        })
+                    });
+
+                match found {
+                    Some(cosine) => {
+                        let (coefficient, _) = terms.remove(index.max(cosine));
+                        terms.remove(index.min(cosine));
+
+                        match self.rules.fold_constants {
+                            true => constant += coefficient,
+                            false => terms.push((coefficient, Node::Number(1.0))),
+                        }
+                    }
+                    None => index += 1,
+                }
+            }
+        }
+
+        if self.rules.fold_constants && constant != 0.0 {
+            terms.push((1.0, Node::Number(constant)));
+        }
+
+        let mut signed_terms: Vec<(f64, Node)> = Vec::with_capacity(terms.len());
+
+        for (coefficient, term) in terms {
+            if coefficient == 0.0 && (self.rules.fold_constants || self.rules.combine_like_terms) {
+                continue;
+            }
+
+            // Negative numbers are subtracted, like x - 1
+            let (coefficient, term): (f64, Node) = match term {
+                Node::Number(value) if value < 0.0 => (-coefficient, Node::Number(-value)),
+                _ => (coefficient, term),
+            };
+
+            let magnitude: Node = match coefficient.abs() == 1.0 {
+                true => term,
+                false => prepend_factor(Node::Number(coefficient.abs()), term),
+            };
+
+            signed_terms.push((coefficient.signum(), magnitude));
+        }
+
+        // A positive term comes first, so sum reads like 1 - x^2 rather than -(x^2) + 1
+        if let Some(first_positive) = signed_terms.iter().position(|(sign, _)| *sign > 0.0) {
+            let term: (f64, Node) = signed_terms.remove(first_positive);
+            signed_terms.insert(0, term);
+        }
+
+        return sum_of_terms(signed_terms);
+    }
+
+    /// Simplify product of factors given by node in argument: numbers are multiplied, like factors are combined
+    /// into powers and factors common to numerator and denominator are cancelled
+    fn simplify_product(&mut self, node: &Node) -> Node {
+        let mut operands: Vec<(bool, Node)> = Vec::new();
+        flatten_product(node.clone(), false, &mut operands);
+
+        let mut simplified: Vec<(bool, Node)> = Vec::new();
+
+        for (is_divisor, operand) in operands {
+            let operand: Node = self.simplify(&operand);
+            flatten_product(operand, is_divisor, &mut simplified);
+        }
+
+        let mut coefficient: f64 = 1.0;
+
+        // Factors are bases raised to an exponent, negative for divisors
+        let mut factors: Vec<(Node, f64)> = Vec::new();
+
+        for (is_divisor, operand) in simplified {
+            let sign: f64 = if is_divisor { -1.0 } else { 1.0 };
+            let (base, exponent): (Node, f64) = match operand {
+                // A division by 0 is kept, so it gives an error when expression is evaluated
+                Node::Number(value)
+                    if self.rules.fold_constants && !(is_divisor && value == 0.0) =>
+                {
+                    coefficient *= value.powf(sign);
+                    continue;
+                }
+                Node::Unary(UnaryOperator::Minus, operand) if self.rules.fold_constants => {
+                    coefficient = -coefficient;
+                    (*operand, sign)
+                }
+                Node::Binary(BinaryOperator::Power, base, exponent)
+                    if self.rules.combine_like_terms && number(&exponent).is_some() =>
+                {
+                    (*base, sign * number(&exponent).unwrap_or(1.0))
+                }
+                _ => (operand, sign),
+            };
+
+            let like_factor: Option<&mut (Node, f64)> =
+                factors.iter_mut().find(|(other, other_exponent)| {
+                    let is_same_side: bool = (*other_exponent > 0.0) == (exponent > 0.0);
+                    *other == base
+                        && number(&base).is_none()
+                        && match is_same_side {
+                            true => self.rules.combine_like_terms,
+                            false => self.rules.cancel_quotients,
+                        }
+                });
+
+            match like_factor {
+                Some((other, total)) => {
+                    // Cancelled factors cannot be 0, and powers with fractional exponents need a base which is not negative
+                    let assumption: Option<String> = if (*total > 0.0) != (exponent > 0.0) {
+                        Some(format!("{other} is not 0"))
+                    } else if total.fract() != 0.0 || exponent.fract() != 0.0 {
+                        Some(format!("{other} is not negative"))
+                    } else {
+                        None
+                    };
+
+                    *total += exponent;
+
+                    if let Some(assumption) = assumption {
+                        self.assume(assumption);
+                    }
+                }
+                None => factors.push((base, exponent)),
+            }
+        }
+
+        // A product is 0 only where all its factors are defined, so their domains are assumed
+        if self.rules.fold_constants && coefficient == 0.0 {
+            let assumptions: Option<Vec<String>> =
+                factors
+                    .iter()
+                    .try_fold(Vec::new(), |mut assumptions, (base, exponent)| {
+                        assumptions
+                            .extend(definition_assumptions(&power(base.clone(), *exponent))?);
+                        return Some(assumptions);
+                    });
+
+            if let Some(assumptions) = assumptions {
+                for assumption in assumptions {
+                    self.assume(assumption);
+                }
+
+                return Node::Number(0.0);
+            }
+        }
+
+        let mut numerator: Vec<Node> = Vec::new();
+        let mut denominator: Vec<Node> = Vec::new();
+
+        for (base, exponent) in factors {
+            match exponent {
+                _ if exponent == 0.0 => (),
+                _ if exponent > 0.0 => numerator.push(power(base, exponent)),
+                _ => denominator.push(power(base, -exponent)),
+            }
+        }
+
+        if coefficient.abs() != 1.0 || numerator.is_empty() {
+            numerator.insert(0, Node::Number(coefficient.abs()));
+        }
+
+        let mut product: Node = multiply_all(numerator).unwrap_or(Node::Number(1.0));
+
+        if let Some(divisor) = multiply_all(denominator) {
+            product = Node::Binary(BinaryOperator::Divide, Box::new(product), Box::new(divisor));
+        }
+
+        return match coefficient < 0.0 {
+            true => Node::Unary(UnaryOperator::Minus, Box::new(product)),
+            false => product,
+        };
+    }
+
+    /// Simplify power of simplified base and exponent given in argument
+    fn simplify_power(&mut self, base: Node, exponent: Node) -> Node {
+        if self.rules.fold_constants {
+            match number(&exponent) {
+                Some(1.0) => return base,
+                Some(0.0) => return Node::Number(1.0),
+                _ => (),
+            }
+        }
+
+        // Square of square root is its argument, which cannot be negative
+        if let (true, Some(2.0), Node::Call(Function::Sqrt, args)) =
+            (self.rules.inverse_functions, number(&exponent), &base)
+        {
+            if let [argument] = args.as_slice() {
+                self.assume(format!("{argument} is not negative"));
+                return argument.clone();
+            }
+        }
+
+        return self.fold_binary(BinaryOperator::Power, base, exponent);
+    }
+
+    /// Simplify call of function given in argument on simplified arguments given in argument
+    fn simplify_call(&mut self, fun: Function, args: Vec<Node>) -> Node {
+        let values: Option<Vec<f64>> = args.iter().map(number).collect();
+
+        if let (true, Some(values)) = (self.rules.fold_constants, values) {
+            if !fun.is_variadic()
+                && !fun.is_lookup()
+                && !fun.is_conditional()
+                && values.len() == fun.arity()
+            {
+                match fun.apply(&values) {
+                    Ok(value) if value.is_finite() => return Node::Number(value),
+                    _ => (),
+                }
+            }
+        }
+
+        if self.rules.inverse_functions {
+            match (fun, args.as_slice()) {
+                (Function::Ln, [Node::Call(Function::Exp, inner)]) if inner.len() == 1 => {
+                    return inner[0].clone();
+                }
+                (Function::Exp, [Node::Call(Function::Ln, inner)]) if inner.len() == 1 => {
+                    self.assume(format!("{} is positive", inner[0]));
+                    return inner[0].clone();
+                }
+                (Function::Sqrt, [Node::Binary(BinaryOperator::Power, base, exponent)])
+                    if number(exponent) == Some(2.0) =>
+                {
+                    return Node::Call(Function::Abs, vec![*base.clone()]);
+                }
+                _ => (),
+            }
+        }
+
+        return Node::Call(fun, args);
+    }
+}
+
+/// Simplify node given in argument by rules given in argument, until it cannot be simplified anymore.
+/// Simplified node is given with the assumptions made by rules on values of subexpressions,
+/// like x is not 0 when x / x is simplified to 1
+pub(crate) fn simplify(node: &Node, rules: &SimplificationRules) -> (Node, Vec<String>) {
+    let mut simplifier: Simplifier = Simplifier {
+        rules,
+        assumptions: Vec::new(),
+    };
+    let mut simplified: Node = node.clone();
+
+    for _ in 0..MAX_PASSES {
+        let next: Node = simplifier.simplify(&simplified);
+
+        if next == simplified {
+            break;
+        }
+
+        simplified = next;
+    }
+
+    return (simplified, simplifier.assumptions);
+}
+
+/// Build sum of terms given in argument with their signs, negative terms being subtracted
+fn sum_of_terms(terms: Vec<(f64, Node)>) -> Node {
+    let mut sum: Option<Node> = None;
+
+    for (sign, term) in terms {
+        sum = Some(match (sum, sign < 0.0) {
+            (None, false) => term,
+            (None, true) => Node::Unary(UnaryOperator::Minus, Box::new(term)),
+            (Some(sum), false) => Node::Binary(BinaryOperator::Plus, Box::new(sum), Box::new(term)),
+            (Some(sum), true) => Node::Binary(BinaryOperator::Minus, Box::new(sum), Box::new(term)),
+        });
+    }
+
+    return sum.unwrap_or(Node::Number(0.0));
+}
+
+/// Multiply sums given by their terms in argument, distributing product over terms.
+/// If product would have too many terms, it is kept as a product of sums
+fn distribute(left: Vec<(f64, Node)>, right: Vec<(f64, Node)>) -> Vec<(f64, Node)> {
+    if left.len() * right.len() > MAX_EXPANDED_TERMS {
+        let product: Node = Node::Binary(
+            BinaryOperator::Multiply,
+            Box::new(sum_of_terms(left)),
+            Box::new(sum_of_terms(right)),
+        );
+        return vec![(1.0, product)];
+    }
+
+    let mut terms: Vec<(f64, Node)> = Vec::with_capacity(left.len() * right.len());
+
+    for (left_sign, left_term) in left.iter() {
+        for (right_sign, right_term) in right.iter() {
+            let product: Node = Node::Binary(
+                BinaryOperator::Multiply,
+                Box::new(left_term.clone()),
+                Box::new(right_term.clone()),
+            );
+            terms.push((left_sign * right_sign, product));
+        }
+    }
+
+    return terms;
+}
+
+/// Expand node given in argument into terms with their signs, distributing products and quotients over sums
+/// and expanding powers of sums to positive integer exponents
+fn expanded_terms(node: &Node) -> Vec<(f64, Node)> {
+    let negated = |terms: Vec<(f64, Node)>| -> Vec<(f64, Node)> {
+        terms
+            .into_iter()
+            .map(|(sign, term)| (-sign, term))
+            .collect()
+    };
+
+    match node {
+        Node::Binary(BinaryOperator::Plus, left, right) => {
+            let mut terms: Vec<(f64, Node)> = expanded_terms(left);
+            terms.extend(expanded_terms(right));
+            return terms;
+        }
+        Node::Binary(BinaryOperator::Minus, left, right) => {
+            let mut terms: Vec<(f64, Node)> = expanded_terms(left);
+            terms.extend(negated(expanded_terms(right)));
+            return terms;
+        }
+        Node::Unary(UnaryOperator::Minus, operand) => return negated(expanded_terms(operand)),
+        Node::Unary(UnaryOperator::Plus, operand) => return expanded_terms(operand),
+        Node::Binary(BinaryOperator::Multiply, left, right) => {
+            return distribute(expanded_terms(left), expanded_terms(right));
+        }
+        // Divisor is expanded as a whole, since quotients are only distributed over terms of dividend
+        Node::Binary(BinaryOperator::Divide, left, right) => {
+            let divisor: Node = expand_terms_of(right);
+
+            return expanded_terms(left)
+                .into_iter()
+                .map(|(sign, term)| {
+                    let quotient: Node = Node::Binary(
+                        BinaryOperator::Divide,
+                        Box::new(term),
+                        Box::new(divisor.clone()),
+                    );
+                    return (sign, quotient);
+                })
+                .collect();
+        }
+        Node::Binary(BinaryOperator::Power, base, exponent) => {
+            let base_terms: Vec<(f64, Node)> = expanded_terms(base);
+
+            match number(exponent) {
+                Some(n) if base_terms.len() > 1 && n >= 1.0 && n.fract() == 0.0 => {
+                    let mut terms: Vec<(f64, Node)> = base_terms.clone();
+
+                    for _ in 1..n as usize {
+                        // Power is kept whole rather than partially expanded when it has too many terms
+                        if terms.len() * base_terms.len() > MAX_EXPANDED_TERMS {
+                            let power: Node = Node::Binary(
+                                BinaryOperator::Power,
+                                Box::new(sum_of_terms(base_terms)),
+                                Box::new(Node::Number(n)),
+                            );
+                            return vec![(1.0, power)];
+                        }
+
+                        terms = distribute(terms, base_terms.clone());
+                    }
+
+                    return terms;
+                }
+                _ => {
+                    let power: Node = Node::Binary(
+                        BinaryOperator::Power,
+                        Box::new(sum_of_terms(base_terms)),
+                        Box::new(expand_terms_of(exponent)),
+                    );
+                    return vec![(1.0, power)];
+                }
+            }
+        }
+        Node::Call(fun, args) => {
+            return vec![(
+                1.0,
+                Node::Call(*fun, args.iter().map(expand_terms_of).collect()),
+            )];
+        }
+        Node::Tuple(elements) => {
+            return vec![(
+                1.0,
+                Node::Tuple(elements.iter().map(expand_terms_of).collect()),
+            )];
+        }
+        _ => return vec![(1.0, node.clone())],
+    }
+}
+
+/// Expand node given in argument into a sum of its expanded terms
+fn expand_terms_of(node: &Node) -> Node {
+    return sum_of_terms(expanded_terms(node));
+}
+
+/// Expand node given in argument by distributing products over sums and expanding powers of sums
+/// to positive integer exponents, like x^2 + 2 * x + 1 for (x + 1)^2.
+/// Numbers are folded and like terms are combined after expansion
+pub(crate) fn expand(node: &Node) -> Node {
+    let rules: SimplificationRules = SimplificationRules {
+        fold_constants: true,
+        combine_like_terms: true,
+        cancel_quotients: false,
+        pythagorean_identity: false,
+        inverse_functions: false,
+        rewrite_rules: Vec::new(),
+    };
+
+    return simplify(&expand_terms_of(node), &rules).0;
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::super::ast::{Ast, Simplification};
+    use super::*;
+
+    fn simplify_with(expression: &str, rules: &SimplificationRules) -> (String, Vec<String>) {
+        let simplification: Simplification = Ast::new(expression).unwrap().simplify(rules);
+        return (simplification.ast.to_string(), simplification.assumptions);
+    }
+
+    fn simplified(expression: &str) -> String {
+        return simplify_with(expression, &SimplificationRules::default()).0;
+    }
+
+    #[test]
+    fn test_simplify() {
+        assert_eq!(simplified("x + 2 * x - 1 + 3"), "3 * x + 2");
+        assert_eq!(simplified("2 * x * y + y * x * 3"), "5 * x * y");
+        assert_eq!(simplified("x - (y - x)"), "2 * x - y");
+        assert_eq!(simplified("-(2 * x) - x"), "-(3 * x)");
+        assert_eq!(simplified("x - x + pi * 2 - pi"), "pi");
+        assert_eq!(simplified("(x + 1) * (x + 1) * 2"), "2 * (x + 1)^2");
+        assert_eq!(simplified("1 / x + 1 / x"), "2 / x");
+        assert_eq!(simplified("3 * sin(t)^2 + 3 * cos(t)^2"), "3");
+        assert_eq!(simplified("ln(exp(y)) + sqrt(y^2)"), "y + abs(y)");
+        assert_eq!(simplified("(x, x + x)"), "(x, 2 * x)");
+        assert_eq!(simplified("x / 0"), "x / 0");
+        assert_eq!(
+            simplified("lookup(x, [0, 1], [1, 2]) * 2"),
+            "2 * lookup(x, [0, 1], [1, 2])"
+        );
+    }
+
+    #[test]
+    fn test_simplify_assumptions() {
+        let rules: SimplificationRules = SimplificationRules::default();
+
+        assert_eq!(
+            simplify_with("x * x * y / x + a / a", &rules),
+            (
+                String::from("x * y + 1"),
+                vec![String::from("x is not 0"), String::from("a is not 0")]
+            )
+        );
+        assert_eq!(
+            simplify_with("exp(ln(x + 1)) + sqrt(z)^2", &rules),
+            (
+                String::from("x + z + 1"),
+                vec![
+                    String::from("x + 1 is positive"),
+                    String::from("z is not negative")
+                ]
+            )
+        );
+        assert_eq!(
+            simplify_with("x^0.5 * x^0.5", &rules),
+            (String::from("x"), vec![String::from("x is not negative")])
+        );
+        assert_eq!(
+            simplify_with("x * x", &rules),
+            (String::from("x^2"), Vec::new())
+        );
+
+        // A product is only folded to 0 where its factors are defined
+        assert_eq!(
+            simplify_with("0 / x + 0 * ln(y)", &rules),
+            (
+                String::from("0"),
+                vec![String::from("x is not 0"), String::from("y is positive")]
+            )
+        );
+        assert_eq!(simplify_with("0 / 0", &rules).0, "0 / 0");
+        assert_eq!(simplify_with("0 * (1 / 0)", &rules).0, "0 / 0");
+        assert_eq!(
+            simplify_with("0 * sqrt(x) * 2", &rules),
+            (String::from("0"), vec![String::from("x is not negative")])
+        );
+    }
+
+    #[test]
+    fn test_simplify_rules() {
+        let rules = |update: fn(&mut SimplificationRules)| -> SimplificationRules {
+            let mut rules: SimplificationRules = SimplificationRules::default();
+            update(&mut rules);
+            return rules;
+        };
+
+        let no_folding: SimplificationRules = rules(|rules| rules.fold_constants = false);
+        assert_eq!(
+            simplify_with("2 * 3 + x + x", &no_folding).0,
+            "2 * 3 + 2 * x"
+        );
+
+        let no_combination: SimplificationRules = rules(|rules| rules.combine_like_terms = false);
+        assert_eq!(
+            simplify_with("x + x + x / x", &no_combination).0,
+            "x + x + 1"
+        );
+
+        let no_cancellation: SimplificationRules = rules(|rules| rules.cancel_quotients = false);
+        assert_eq!(simplify_with("x * x / x", &no_cancellation).0, "x^2 / x");
+
+        let no_identity: SimplificationRules = rules(|rules| rules.pythagorean_identity = false);
+        assert_eq!(
+            simplify_with("sin(x)^2 + cos(x)^2", &no_identity).0,
+            "sin(x)^2 + cos(x)^2"
+        );
+
+        let no_inverse: SimplificationRules = rules(|rules| rules.inverse_functions = false);
+        assert_eq!(simplify_with("ln(exp(x))", &no_inverse).0, "ln(exp(x))");
+    }
+
+    #[test]
+    fn test_simplify_rewrite_rules() {
+        let rules: SimplificationRules = SimplificationRules {
+            rewrite_rules: vec![
+                RewriteRule::new("cosh(u)^2 - sinh(u)^2", "1").unwrap(),
+                RewriteRule::new("ln(a) + ln(b)", "ln(a * b)").unwrap(),
+                RewriteRule::new("max(u, u)", "u").unwrap(),
+            ],
+            ..SimplificationRules::default()
+        };
+
+        assert_eq!(
+            simplify_with("2 * (cosh(x + 1)^2 - sinh(x + 1)^2) + x", &rules).0,
+            "x + 2"
+        );
+        assert_eq!(
+            simplify_with("ln(y) + ln(x) + ln(z)", &rules).0,
+            "ln(y * x * z)"
+        );
+        assert_eq!(
+            simplify_with("max(x, x + 0) + max(x, y)", &rules).0,
+            "x + max(x, y)"
+        );
+        assert_eq!(
+            simplify_with("cosh(x)^2 - sinh(y)^2", &rules).0,
+            "cosh(x)^2 - sinh(y)^2"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_rule_invalid() {
+        assert_eq!(
+            RewriteRule::new("x", "x + 0"),
+            Err(String::from(
+                "Pattern cannot be the single variable x, since it matches every expression"
+            ))
+        );
+        assert_eq!(
+            RewriteRule::new("ln(a) + ln(b)", "ln(a * c1)"),
+            Err(String::from(
+                "Variable c1 of replacement does not appear in pattern"
+            ))
+        );
+        assert!(RewriteRule::new("ln(a", "a").is_err());
+    }
+
+    #[test]
+    fn test_expand() {
+        let expanded = |expression: &str| -> String {
+            return Ast::new(expression).unwrap().expand().to_string();
+        };
+
+        assert_eq!(expanded("(x + 1)^2"), "x^2 + 2 * x + 1");
+        assert_eq!(expanded("(x - y)^2"), "x^2 - 2 * x * y + y^2");
+        assert_eq!(expanded("(x + 1)^3"), "x^3 + 3 * x^2 + 3 * x + 1");
+        assert_eq!(expanded("(a + b) * (a - b)"), "a^2 - b^2");
+        assert_eq!(expanded("-(x + 1) * (x - 1)"), "1 - x^2");
+        assert_eq!(expanded("2 * (x + 3) - x"), "x + 6");
+        assert_eq!(expanded("(x + 1)^2 - x^2"), "2 * x + 1");
+        assert_eq!(expanded("(x + 1) / (y + 2)"), "x / (y + 2) + 1 / (y + 2)");
+        assert_eq!(expanded("sin((x + 1)^2)"), "sin(x^2 + 2 * x + 1)");
+        assert_eq!(expanded("(x + 1)^0.5"), "(x + 1)^0.5");
+        assert_eq!(expanded("(x + 1)^n"), "(x + 1)^n");
+
+        // Powers with too many terms are not expanded, rather than expanded to a lower exponent
+        assert_eq!(expanded("(x + 1)^100"), "(x + 1)^100");
+        assert_eq!(expanded("2 * (x + y)^20"), "2 * (x + y)^20");
+    }
+}
@@ -0,0 +1,138 @@
+#![allow(clippy::needless_return)]
+
+use super::options::{EvaluationOptions, RoundingMode};
+
+use std::collections::HashMap;
+
+/// Spread of results of an expression evaluated several times with stochastic rounding.
+/// A large spread reveals a formula which is numerically fragile, like one subtracting close values
+#[derive(Debug, PartialEq, Clone)]
+pub struct Spread {
+    /// Results of each run
+    pub values: Vec<f64>,
+    /// Smallest result
+    pub min: f64,
+    /// Largest result
+    pub max: f64,
+    /// Mean of results
+    pub mean: f64,
+    /// Standard deviation of results
+    pub std_dev: f64,
+}
+
+impl Spread {
+    /// Create spread of results given in argument
+    fn new(values: Vec<f64>) -> Spread {
+        let nb_values: f64 = values.len() as f64;
+        let mean: f64 = values.iter().sum::<f64>() / nb_values;
+        let variance: f64 = values
+            .iter()
+            .map(|value| (value - mean) * (value - mean))
+            .sum::<f64>()
+            / (nb_values - 1.0);
+
+        return Spread {
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean,
+            std_dev: variance.sqrt(),
+            values,
+        };
+    }
+
+    /// Estimate number of significant decimal digits of result, from relative standard deviation of results.
+    /// Results all equal give the precision of f64, about 15.95 digits
+    pub fn significant_digits(&self) -> f64 {
+        let precision: f64 = f64::MANTISSA_DIGITS as f64 * 2f64.log10();
+
+        if self.std_dev == 0.0 {
+            return precision;
+        }
+
+        return (-(self.std_dev / self.mean.abs()).log10()).clamp(0.0, precision);
+    }
+}
+
+/// Evaluate an expression like evaluate function as many times as number of runs given in argument,
+/// each arithmetic operation being rounded at random up or down, to get the spread of results.
+/// A formula whose results spread widely is numerically fragile, which is detected without interval arithmetic.
+/// Runs are reproducible, the rounding of each run being drawn from its index.
+///
+/// If number of runs is less than 2 or error occurs during evaluation,
+/// an error message is stored in string contained in Result output
+pub fn evaluate_stochastic(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    runs: usize,
+) -> Result<Spread, String> {
+    if runs < 2 {
+        return Err(String::from("Stochastic evaluation needs at least 2 runs"));
+    }
+
+    let mut values: Vec<f64> = Vec::with_capacity(runs);
+
+    for run in 0..runs {
+        let options: EvaluationOptions = EvaluationOptions {
+            rounding: RoundingMode::Stochastic(run as u64),
+            round_operations: true,
+            ..EvaluationOptions::default()
+        };
+
+        values.push(super::evaluate_with_options(
+            expression, variables, &options,
+        )?);
+    }
+
+    return Ok(Spread::new(values));
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_stochastic_fragile_formula() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 1e-15)]);
+
+        // Subtraction of close values loses most digits
+        match evaluate_stochastic("(1 + x) - 1", &variables, 16) {
+            Ok(spread) => {
+                assert_eq!(spread.values.len(), 16);
+                assert!(spread.min < spread.max);
+                assert!(spread.significant_digits() < 2.0);
+            }
+            Err(_) => assert!(false),
+        }
+
+        match evaluate_stochastic("x * 3 + 1", &variables, 16) {
+            Ok(spread) => assert!(spread.significant_digits() > 15.0),
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_stochastic_exact_formula() {
+        match evaluate_stochastic("1.5 * 4 - 2", &HashMap::new(), 8) {
+            Ok(spread) => {
+                assert_eq!(spread.min, 4.0);
+                assert_eq!(spread.max, 4.0);
+                assert_eq!(spread.std_dev, 0.0);
+                assert_eq!(spread.significant_digits(), 53.0 * 2f64.log10());
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_stochastic_errors() {
+        assert_eq!(
+            evaluate_stochastic("1 + 2", &HashMap::new(), 1),
+            Err(String::from("Stochastic evaluation needs at least 2 runs"))
+        );
+        assert_eq!(
+            evaluate_stochastic("2 * y", &HashMap::new(), 2),
+            Err(String::from("Unknown name y at position 4"))
+        );
+    }
+}
@@ -0,0 +1,92 @@
+#![allow(clippy::needless_return)]
+
+use super::constants;
+use super::functions::Function;
+
+/// Maximal number of edits between an unknown word and a known name suggested for it
+const MAX_DISTANCE: usize = 2;
+
+/// Compute edit distance between two words given in argument,
+/// i.e. the minimal number of insertions, deletions and substitutions of characters
+/// transforming first word into second one
+fn edit_distance(first: &str, second: &str) -> usize {
+    let second: Vec<char> = second.chars().collect();
+    let mut previous: Vec<usize> = (0..=second.len()).collect();
+    let mut current: Vec<usize> = vec![0; second.len() + 1];
+
+    for (i, first_char) in first.chars().enumerate() {
+        current[0] = i + 1;
+
+        for (j, &second_char) in second.iter().enumerate() {
+            let substitution: usize = previous[j] + usize::from(first_char != second_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    return previous[second.len()];
+}
+
+/// Give name of constant or function closest to unknown word given in argument,
+/// like sqrt for sqr, if it is close enough to be a misspelling of this name.
+/// Names at the same distance are ordered alphabetically, so the suggestion never depends
+/// on the order in which names are declared
+pub fn closest_name(word: &str) -> Option<&'static str> {
+    return constants::NAMES
+        .iter()
+        .chain(Function::NAMES.iter())
+        .map(|&name| (name, edit_distance(word, name)))
+        .filter(|&(_, distance)| distance <= MAX_DISTANCE && distance < word.chars().count())
+        .min_by_key(|&(name, distance)| (distance, name))
+        .map(|(name, _)| name);
+}
+
+/// Error message for unknown word given in argument, suggesting closest known name if any
+pub fn unknown_name_message(word: &str) -> String {
+    match closest_name(word) {
+        Some(name) => format!("Unknown name {word} (did you mean {name}?)"),
+        None => format!("Unknown name {word}"),
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("sqr", "sqrt"), 1);
+        assert_eq!(edit_distance("sine", "sin"), 1);
+        assert_eq!(edit_distance("cso", "cos"), 2);
+        assert_eq!(edit_distance("", "exp"), 3);
+        assert_eq!(edit_distance("lookup", "lookup"), 0);
+    }
+
+    #[test]
+    fn test_closest_name() {
+        assert_eq!(closest_name("sqr"), Some("sqrt"));
+        assert_eq!(closest_name("lookpu"), Some("lookup"));
+        assert_eq!(closest_name("pii"), Some("pi"));
+        assert_eq!(closest_name("velocity"), None);
+        assert_eq!(closest_name("x"), None);
+    }
+
+    #[test]
+    fn test_closest_name_ties() {
+        // cos and cosh, or sin and sinh, are both at distance 1
+        assert_eq!(closest_name("cosa"), Some("cos"));
+        assert_eq!(closest_name("sinx"), Some("sin"));
+        assert_eq!(closest_name("tamh"), Some("tanh"));
+    }
+
+    #[test]
+    fn test_unknown_name_message() {
+        assert_eq!(
+            unknown_name_message("sqr"),
+            "Unknown name sqr (did you mean sqrt?)"
+        );
+        assert_eq!(unknown_name_message("speed"), "Unknown name speed");
+    }
+}
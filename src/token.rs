@@ -1,8 +1,12 @@
+#![allow(clippy::redundant_closure)]
+
 use super::constants;
 use super::functions::Function;
 use super::operators::{BinaryOperator, UnaryOperator};
 
-/// Token used in library
+/// Token used in library.
+/// This is the single token model shared by tokenizer, converter, evaluator, compiler and syntax tree:
+/// iterators over tokens end with None instead of a marker token, so no placeholder token exists.
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Token {
     Number(f64),
@@ -10,8 +14,24 @@ pub enum Token {
     UnaryOperator(UnaryOperator),
     LeftParenthesis,
     RightParenthesis,
+    Comma,
+    /// Question mark of conditional operator, which separates condition from value taken if it is true.
+    /// In postfix expressions, it follows condition
+    Question,
+    /// Colon of conditional operator, which separates value taken if condition is true from the other value.
+    /// In postfix expressions, it follows value taken if condition is true
+    Colon,
+    /// End of conditional operator, only found in postfix expressions after value taken if condition is false
+    EndConditional,
+    /// Boolean operator whose right operand can be skipped, only found in postfix expressions after its left operand
+    ShortCircuit(BinaryOperator),
     Constant(f64),
     Function(Function),
+    /// Function provided by host application, identified by its slot, with its number of arguments.
+    /// The number of arguments is only known in postfix expressions, being zero in infix expressions
+    HostFunction(usize, usize),
+    Variable(usize),
+    Array(usize),
 }
 
 impl Token {
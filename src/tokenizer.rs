@@ -1,49 +1,953 @@
+#![allow(clippy::needless_return, clippy::is_digit_ascii_radix)]
+
 use super::constants::*;
 use super::functions::Function;
+use super::limits::{Limit, Limits};
+use super::locale::{AmbiguityWarning, NumberLocale};
 use super::operators::{BinaryOperator, UnaryOperator};
+use super::resolution::{NameKind, DEFAULT_ORDER};
+use super::suggestion::unknown_name_message;
 use super::token::Token;
 
-use std::collections::HashMap;
-use std::iter::Peekable;
-use std::ops::Fn;
-use std::str::Chars;
+use smallvec::SmallVec;
+use std::collections::{HashMap, VecDeque};
+use std::ops::{Fn, FnMut};
+
+/// Cursor over string given by user, reading its bytes.
+/// ASCII characters are read directly from bytes, other characters being decoded only when they are met,
+/// and substrings are borrowed from string given by user by slice indices
+#[derive(Debug, Clone)]
+struct Cursor<'a> {
+    expression: &'a str,
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// Create cursor at the beginning of string given in argument
+    fn new(expression: &'a str) -> Cursor<'a> {
+        return Cursor {
+            expression,
+            position: 0,
+        };
+    }
+
+    /// Give next character without consuming it
+    fn peek(&self) -> Option<char> {
+        match self.expression.as_bytes().get(self.position) {
+            Some(&byte) if byte.is_ascii() => Some(byte as char),
+            Some(_) => self.expression[self.position..].chars().next(),
+            None => None,
+        }
+    }
+
+    /// Get rest of string not consumed yet
+    fn as_str(&self) -> &'a str {
+        return &self.expression[self.position..];
+    }
+}
+
+impl Iterator for Cursor<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c: char = self.peek()?;
+        self.position += c.len_utf8();
+        return Some(c);
+    }
+}
 
 /// Extract a substring from string given by user
-/// where each characters check a predicat
-fn extract_if<P>(char_it: &mut Peekable<Chars<'_>>, predicate: P) -> String
+/// where each characters check a predicat.
+/// The substring is borrowed from string given by user, so no memory is allocated
+fn extract_if<'a, P>(cursor: &mut Cursor<'a>, predicate: P) -> &'a str
 where
     P: Fn(char) -> bool,
 {
-    let mut substr: String = String::new();
+    let bytes: &[u8] = cursor.expression.as_bytes();
+    let start: usize = cursor.position;
 
-    // Search maximal size that can reach the substr to reserve the memory space
-    if let (_lb_size, Some(ub_size)) = char_it.size_hint() {
-        substr.reserve(ub_size);
-    }
+    while let Some(&byte) = bytes.get(cursor.position) {
+        if byte.is_ascii() {
+            if !predicate(byte as char) {
+                break;
+            }
 
-    while let Some(&c) = char_it.peek() {
-        if predicate(c) {
-            substr.push(c);
-            char_it.next();
+            cursor.position += 1;
         } else {
-            break;
+            match cursor.peek() {
+                Some(c) if predicate(c) => cursor.position += c.len_utf8(),
+                _ => break,
+            }
         }
     }
 
-    substr.shrink_to_fit();
-    return substr;
+    return &cursor.expression[start..cursor.position];
 }
 
-/// Extract a number from string given by user via its char iterator
+/// Extract a number from string given by user via its cursor
 /// We return an Option<f64>, if we don't find a number the option is none.
-fn extract_number(char_it: &mut Peekable<Chars<'_>>) -> Option<f64> {
-    let str_number: String = extract_if(char_it, |c: char| c.is_digit(10) || c == '.');
+fn extract_number(cursor: &mut Cursor<'_>) -> Option<f64> {
+    let str_number: &str = extract_if(cursor, |c: char| c.is_digit(10) || c == '.');
     return str_number.parse().ok();
 }
 
-/// Extract a word from string given by user via its char iterator
-fn extract_word(char_it: &mut Peekable<Chars<'_>>) -> String {
-    return extract_if(char_it, |c: char| c.is_alphanumeric() || c == '_');
+/// Extract a number written with separators of locale given in argument, like 1,234,567.89 or 1 234 567,89,
+/// from string given by user via its cursor.
+/// A group separator is only read when followed by a group of three digits, so a separator
+/// which does not group digits, like a comma separating arguments, ends the number.
+/// We return an Option holding the number and its value if its decimal separator was a group separator,
+/// when it can be read this way, like 1234 for 1,234 in a locale using comma as decimal separator.
+/// If we don't find a number the option is none.
+fn extract_localized_number(
+    cursor: &mut Cursor<'_>,
+    locale: &NumberLocale,
+) -> Option<(f64, Option<f64>)> {
+    let is_digit = |c: char| c.is_ascii_digit();
+    let mut str_number: String = String::from(extract_if(cursor, is_digit));
+    let mut is_grouped: bool = false;
+
+    if let Some(group) = locale.group_separator() {
+        while str_number.len() <= 3 || is_grouped {
+            let mut ahead: Cursor<'_> = cursor.clone();
+
+            if ahead.next() != Some(group) {
+                break;
+            }
+
+            match extract_if(&mut ahead, is_digit) {
+                digits if digits.len() == 3 => {
+                    str_number.push_str(digits);
+                    is_grouped = true;
+                    *cursor = ahead;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    let mut alternative: Option<f64> = None;
+    let mut ahead: Cursor<'_> = cursor.clone();
+
+    if ahead.next() == Some(locale.decimal_separator()) {
+        let decimals: &str = extract_if(&mut ahead, is_digit);
+
+        if !decimals.is_empty() {
+            // In locales using comma as decimal separator, 1,234 is also read as 1234
+            // by people grouping digits with commas
+            if locale.decimal_separator() == ',' && !is_grouped && decimals.len() == 3 {
+                alternative = format!("{str_number}{decimals}").parse().ok();
+            }
+
+            str_number.push('.');
+            str_number.push_str(decimals);
+            *cursor = ahead;
+        }
+    }
+
+    return str_number.parse().ok().map(|number| (number, alternative));
+}
+
+/// Skip whitespaces of string given by user via its cursor
+fn skip_whitespaces(cursor: &mut Cursor<'_>) {
+    extract_if(cursor, |c: char| c.is_whitespace());
+}
+
+/// Get comparison operator written at start of text given in argument, like <= or !=,
+/// operators of two chars being tried first. A single = is not an operator
+fn comparison_operator(text: &str) -> Option<BinaryOperator> {
+    return [
+        BinaryOperator::LessEqual,
+        BinaryOperator::GreaterEqual,
+        BinaryOperator::Equal,
+        BinaryOperator::NotEqual,
+        BinaryOperator::Less,
+        BinaryOperator::Greater,
+    ]
+    .into_iter()
+    .find(|ops| text.starts_with(ops.notation()));
+}
+
+/// Extract a word from string given by user via its cursor
+fn extract_word<'a>(cursor: &mut Cursor<'a>) -> &'a str {
+    return extract_if(cursor, |c: char| c.is_alphanumeric() || c == '_');
+}
+
+/// Kind of token expected by grammar of expressions after tokens extracted so far
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Expected {
+    /// Operand or anything starting an operand, like unary operator, function or parenthesis
+    Operand,
+    /// Binary operator, comma or closing parenthesis following an operand
+    Operator,
+    /// Opening parenthesis of function call
+    Parenthesis,
+}
+
+/// Describe kind of token given in argument in error messages
+fn describe(token: Token) -> &'static str {
+    match token {
+        Token::BinaryOperator(_) | Token::UnaryOperator(_) | Token::Question | Token::Colon => {
+            "operator"
+        }
+        Token::LeftParenthesis | Token::RightParenthesis => "parenthesis",
+        Token::Comma => "comma",
+        _ => "operand",
+    }
+}
+
+/// Describe number of arguments given in argument, like "2 arguments"
+fn count_arguments(nb_args: usize) -> String {
+    match nb_args {
+        1 => String::from("1 argument"),
+        _ => format!("{nb_args} arguments"),
+    }
+}
+
+/// Parenthesis opened in expression, with its position to report it if it is not closed
+/// and information needed to check arguments of function called with this parenthesis
+struct OpenParenthesis {
+    position: usize,
+    function: Option<Function>,
+    is_lookup: bool,
+    nb_commas: usize,
+    array_args: [bool; 3],
+}
+
+/// Question mark of conditional operator waiting for its colon, with its position to report it
+/// and the number of parenthesis opened before it, since its colon must be in the same parenthesis
+struct OpenQuestion {
+    position: usize,
+    depth: usize,
+}
+
+/// Iterator over tokens of infix expression given as string.
+/// The resolve function gives the token associated to a word which is neither a constant nor a function.
+/// Tokens are extracted one by one, so the expression is never collected in memory,
+/// and words are borrowed from expression without allocation.
+///
+/// Breakpoint arrays of lookup functions, given as literals like [0, 1.5, 3] or as array variables,
+/// are given as number tokens of their values followed by an array token holding their length.
+/// These tokens are located at the bracket or the name of array written by user,
+/// so errors never point at text which is not in expression.
+/// Lists given as whole arguments of aggregation functions, like sum([1, 2, 3]), are given as their values
+/// separated by comma tokens, so they count as their values among arguments of function.
+/// When a lane of vectors is given, arrays which are not breakpoints are vectors
+/// given as a number token of their element in this lane, vectors of a single element being broadcast.
+/// If error occurs during tokenization, an error message is given and iteration stops
+pub struct Infix<'a, R>
+where
+    R: FnMut(&str) -> Option<Token>,
+{
+    cursor: Cursor<'a>,
+    resolve: R,
+    arrays: Option<&'a HashMap<String, Vec<f64>>>,
+    pending: VecDeque<Token>,
+    parenthesis: SmallVec<[OpenParenthesis; 8]>,
+    questions: SmallVec<[OpenQuestion; 4]>,
+    last_token: Option<Token>,
+    last_word: &'a str,
+    after_array: bool,
+    in_list: bool,
+    after_list: bool,
+    order: [NameKind; 3],
+    locale: Option<NumberLocale>,
+    ambiguities: Vec<AmbiguityWarning>,
+    expected: Expected,
+    in_array: bool,
+    lane: Option<usize>,
+    vector_length: Option<usize>,
+    token_start: usize,
+    limits: Limits,
+    nb_tokens: usize,
+    exceeded: Option<Limit>,
+    failed: bool,
+    located: bool,
+}
+
+impl<'a, R> Infix<'a, R>
+where
+    R: FnMut(&str) -> Option<Token>,
+{
+    /// Create iterator over tokens of expression given in argument
+    pub fn new(expression: &'a str, resolve: R) -> Infix<'a, R> {
+        return Infix {
+            cursor: Cursor::new(expression),
+            resolve,
+            arrays: None,
+            pending: VecDeque::new(),
+            parenthesis: SmallVec::new(),
+            questions: SmallVec::new(),
+            last_token: None,
+            last_word: "",
+            after_array: false,
+            in_list: false,
+            after_list: false,
+            order: DEFAULT_ORDER,
+            locale: None,
+            ambiguities: Vec::new(),
+            expected: Expected::Operand,
+            in_array: false,
+            lane: None,
+            vector_length: None,
+            token_start: 0,
+            limits: Limits::unlimited(),
+            nb_tokens: 0,
+            exceeded: None,
+            failed: false,
+            located: false,
+        };
+    }
+
+    /// Give array variables, stored in hash map given in argument,
+    /// which can be used as breakpoint arrays of lookup functions
+    pub fn with_arrays(mut self, arrays: &'a HashMap<String, Vec<f64>>) -> Infix<'a, R> {
+        self.arrays = Some(arrays);
+        return self;
+    }
+
+    /// Give arrays which are not breakpoints of lookup functions as vectors,
+    /// each vector being replaced by its element in lane given in argument
+    pub fn with_vector_lane(mut self, lane: usize) -> Infix<'a, R> {
+        self.lane = Some(lane);
+        return self;
+    }
+
+    /// Get length of vectors of expression tokenized so far, None if expression has no vector
+    pub fn vector_length(&self) -> Option<usize> {
+        return self.vector_length;
+    }
+
+    /// Set limits on number of tokens and nesting of parenthesis, tokenization failing
+    /// as soon as a limit is exceeded
+    pub fn with_limits(mut self, limits: Limits) -> Infix<'a, R> {
+        self.limits = limits;
+        return self;
+    }
+
+    /// Get limit exceeded by expression, if tokenization failed for this reason
+    pub fn exceeded(&self) -> Option<Limit> {
+        return self.exceeded;
+    }
+
+    /// Check that limits are not exceeded by tokens extracted so far
+    fn check_limits(&mut self) -> Result<(), String> {
+        if self.nb_tokens > self.limits.max_tokens {
+            self.exceeded = Some(Limit::Tokens(self.limits.max_tokens));
+        } else if self.parenthesis.len() > self.limits.max_nesting {
+            self.exceeded = Some(Limit::Nesting(self.limits.max_nesting));
+        }
+
+        match self.exceeded {
+            Some(limit) => Err(limit.to_string()),
+            None => Ok(()),
+        }
+    }
+
+    /// Set order in which kinds of names are tried to resolve a word of expression
+    pub fn with_order(mut self, order: [NameKind; 3]) -> Infix<'a, R> {
+        self.order = order;
+        return self;
+    }
+
+    /// Read numbers with separators of locale given in argument, like 1,234,567.89 or 1 234 567,89,
+    /// instead of separators of Taz expressions
+    pub fn with_locale(mut self, locale: NumberLocale) -> Infix<'a, R> {
+        self.locale = Some(locale);
+        return self;
+    }
+
+    /// Get warnings about numbers extracted so far which can be read in two ways according to locale
+    pub fn ambiguities(&self) -> &[AmbiguityWarning] {
+        return &self.ambiguities;
+    }
+
+    /// Extract a number from expression, with separators of locale if one is given,
+    /// keeping a warning if number can be read in two ways
+    fn read_number(&mut self) -> Option<f64> {
+        let locale: NumberLocale = match self.locale {
+            Some(locale) => locale,
+            None => return extract_number(&mut self.cursor),
+        };
+
+        let start: usize = self.cursor.position;
+        let (number, alternative) = extract_localized_number(&mut self.cursor, &locale)?;
+
+        if let Some(alternative) = alternative {
+            self.ambiguities.push(AmbiguityWarning {
+                number: String::from(&self.cursor.expression[start..self.cursor.position]),
+                position: start,
+                value: number,
+                alternative,
+            });
+        }
+
+        return Some(number);
+    }
+
+    /// Add position in expression, as byte offset, of last token extracted to error message given in argument,
+    /// like an error of conversion or evaluation occuring while tokens are extracted.
+    /// Message of error given by this iterator already contains its position, so it is kept as is
+    pub fn locate(&self, message: String) -> String {
+        if self.located {
+            return message;
+        }
+
+        return format!("{message} at position {}", self.token_start);
+    }
+
+    /// Stop iteration with error message given in argument, completed by position of current token
+    fn fail(&mut self, message: String) -> String {
+        let message: String = self.locate(message);
+
+        self.failed = true;
+        self.located = true;
+
+        return message;
+    }
+
+    /// Get word from which last token has been extracted, like the name of a function as written in expression.
+    /// An empty string is given if last token has not been extracted from a word
+    pub fn last_word(&self) -> &'a str {
+        return self.last_word;
+    }
+
+    /// Extract an array literal like [0, -1.5, pi] from expression
+    fn extract_array(&mut self) -> Result<Vec<f64>, String> {
+        let error: String = String::from("Cannot parse breakpoint array");
+        let mut values: Vec<f64> = Vec::new();
+
+        // Skip opening bracket
+        self.cursor.next();
+        skip_whitespaces(&mut self.cursor);
+
+        if let Some(']') = self.cursor.peek() {
+            self.cursor.next();
+            return Ok(values);
+        }
+
+        loop {
+            skip_whitespaces(&mut self.cursor);
+
+            let sign: f64 = match self.cursor.peek() {
+                Some('-') => -1.0,
+                _ => 1.0,
+            };
+
+            if let Some('+' | '-') = self.cursor.peek() {
+                self.cursor.next();
+            }
+
+            match self.cursor.peek() {
+                Some(c) if c.is_ascii_digit() => match self.read_number() {
+                    Some(number) => values.push(sign * number),
+                    None => return Err(error),
+                },
+                Some(c) if c.is_alphabetic() => {
+                    let name: &str = extract_word(&mut self.cursor);
+                    values.push(sign * from_string(name).map_err(|_| error.clone())?);
+                }
+                _ => return Err(error),
+            }
+
+            skip_whitespaces(&mut self.cursor);
+
+            match self.cursor.next() {
+                Some(',') => (),
+                Some(']') => return Ok(values),
+                _ => return Err(error),
+            }
+        }
+    }
+
+    /// Give number token of element of vector given in argument in lane given in argument,
+    /// checking that vector can be broadcast with vectors tokenized before
+    fn vector_element(&mut self, values: &[f64], lane: usize) -> Result<Token, String> {
+        if values.is_empty() {
+            return Err(String::from(
+                "Empty vector cannot be evaluated element by element",
+            ));
+        }
+
+        self.vector_length = match self.vector_length {
+            Some(length) if length != values.len() && length != 1 && values.len() != 1 => {
+                return Err(format!(
+                    "Vectors of lengths {length} and {} cannot be broadcast",
+                    values.len()
+                ));
+            }
+            Some(length) => Some(length.max(values.len())),
+            None => Some(values.len()),
+        };
+
+        return Ok(Token::new_number(values[lane.min(values.len() - 1)]));
+    }
+
+    /// Give tokens of breakpoint array given in argument, checking that array
+    /// is given as second or third argument of a lookup function.
+    /// Arrays given as arguments of aggregation functions are lists of their arguments
+    fn array_tokens(&mut self, values: &[f64]) -> Option<Result<Token, String>> {
+        let is_argument: bool = self.last_token == Some(Token::Comma);
+        let starts_argument: bool = is_argument || self.last_token == Some(Token::LeftParenthesis);
+
+        match self.parenthesis.last_mut() {
+            Some(open) if open.is_lookup && is_argument && open.nb_commas <= 2 => {
+                open.array_args[open.nb_commas] = true;
+            }
+            Some(open) if starts_argument && open.function.is_some_and(|fun| fun.is_variadic()) => {
+                if values.is_empty() {
+                    let name: &str = open.function.map_or("", |fun| fun.name());
+                    return Some(Err(format!("List of {name} function is empty")));
+                }
+
+                self.in_list = true;
+
+                for (index, &value) in values.iter().enumerate() {
+                    if index > 0 {
+                        self.pending.push_back(Token::Comma);
+                    }
+
+                    self.pending.push_back(Token::new_number(value));
+                }
+
+                return self.pending.pop_front().map(Ok);
+            }
+            _ => match self.lane {
+                Some(lane) => return Some(self.vector_element(values, lane)),
+                None => {
+                    return Some(Err(String::from(
+                        "Arrays are only allowed as breakpoints of lookup function or lists of aggregation function",
+                    )))
+                }
+            },
+        }
+
+        self.in_array = true;
+        self.pending
+            .extend(values.iter().map(|&value| Token::new_number(value)));
+        self.pending.push_back(Token::Array(values.len()));
+
+        return self.pending.pop_front().map(Ok);
+    }
+
+    /// Check that token given in argument follows grammar of expressions,
+    /// i.e. operands and operators alternate and each function is called with parenthesis.
+    /// If token is not expected, an error message is stored in string contained in Result output
+    fn check_grammar(&mut self, token: Token) -> Result<(), String> {
+        // Values of breakpoint array form a single operand ending with array token
+        if self.in_array {
+            if let Token::Array(_) = token {
+                self.in_array = false;
+                self.expected = Expected::Operator;
+            }
+
+            return Ok(());
+        }
+
+        let expected: Option<Expected> = match (self.expected, token) {
+            (Expected::Parenthesis, Token::LeftParenthesis) => Some(Expected::Operand),
+            (Expected::Parenthesis, _) => {
+                return Err(String::from("Missing parenthesis after function"));
+            }
+            (Expected::Operand, Token::Number(_) | Token::Constant(_) | Token::Variable(_)) => {
+                Some(Expected::Operator)
+            }
+            (Expected::Operand, Token::UnaryOperator(_) | Token::LeftParenthesis) => {
+                Some(Expected::Operand)
+            }
+            (Expected::Operand, Token::Function(_) | Token::HostFunction(_, _)) => {
+                Some(Expected::Parenthesis)
+            }
+            (
+                Expected::Operator,
+                Token::BinaryOperator(_) | Token::Comma | Token::Question | Token::Colon,
+            ) => Some(Expected::Operand),
+            (Expected::Operator, Token::RightParenthesis) => Some(Expected::Operator),
+            _ => None,
+        };
+
+        match expected {
+            Some(expected) => {
+                self.expected = expected;
+                return Ok(());
+            }
+            None => Err(format!("Unexpected {}", describe(token))),
+        }
+    }
+
+    /// Check that expression ends where grammar of expressions allows it.
+    /// If expression is incomplete, an error message is stored in string contained in Result output
+    fn check_end(&mut self) -> Result<(), String> {
+        match self.expected {
+            Expected::Operator if !self.questions.is_empty() => {
+                self.token_start = self.questions.last().map_or(0, |open| open.position);
+                Err(String::from("Missing colon of conditional operator"))
+            }
+            Expected::Operator if !self.parenthesis.is_empty() => {
+                // Error points to the last parenthesis opened which is not closed
+                self.token_start = self.parenthesis.last().map_or(0, |open| open.position);
+                Err(String::from("Unclosed parenthesis"))
+            }
+            Expected::Operator => Ok(()),
+            Expected::Operand => Err(String::from("Unexpected end of expression")),
+            Expected::Parenthesis => Err(String::from("Missing parenthesis after function")),
+        }
+    }
+
+    /// Check that token given in argument is well placed according to previous tokens,
+    /// keeping track of opened parenthesis to check arguments of lookup functions
+    fn check_placement(&mut self, token: Token) -> Result<(), String> {
+        if self.after_array && token != Token::Comma && token != Token::RightParenthesis {
+            return Err(String::from(
+                "Breakpoint array must be a whole argument of lookup function",
+            ));
+        }
+
+        if self.after_list && token != Token::Comma && token != Token::RightParenthesis {
+            return Err(String::from(
+                "List must be a whole argument of aggregation function",
+            ));
+        }
+
+        self.after_array = false;
+        self.after_list = false;
+
+        // Conditional operator must be complete before the end of its parenthesis or argument
+        if matches!(token, Token::Comma | Token::RightParenthesis) {
+            self.check_questions()?;
+        }
+
+        match token {
+            Token::Array(_) => self.after_array = true,
+            // Last value of list ends its argument
+            Token::Number(_) if self.in_list && self.pending.is_empty() => {
+                self.in_list = false;
+                self.after_list = true;
+            }
+            Token::LeftParenthesis => self.parenthesis.push(OpenParenthesis {
+                position: self.token_start,
+                function: match self.last_token {
+                    Some(Token::Function(fun)) => Some(fun),
+                    _ => None,
+                },
+                is_lookup: matches!(self.last_token, Some(Token::Function(fun)) if fun.is_lookup()),
+                nb_commas: 0,
+                array_args: [false; 3],
+            }),
+            Token::Question => self.questions.push(OpenQuestion {
+                position: self.token_start,
+                depth: self.parenthesis.len(),
+            }),
+            Token::Colon => match self.questions.last() {
+                Some(open) if open.depth == self.parenthesis.len() => {
+                    self.questions.pop();
+                }
+                _ => {
+                    return Err(String::from(
+                        "Missing question mark of conditional operator",
+                    ))
+                }
+            },
+            Token::Comma => {
+                if let Some(open) = self.parenthesis.last_mut() {
+                    open.nb_commas += 1;
+                }
+            }
+            Token::RightParenthesis => match self.parenthesis.pop() {
+                Some(open)
+                    if open.is_lookup
+                        && (open.nb_commas != 2 || open.array_args != [false, true, true]) =>
+                {
+                    return Err(String::from(
+                        "Lookup function requires a value and two breakpoint arrays as arguments",
+                    ));
+                }
+                Some(OpenParenthesis {
+                    function: Some(Function::Piecewise),
+                    nb_commas,
+                    ..
+                }) if nb_commas < 2 || !nb_commas.is_multiple_of(2) => {
+                    return Err(format!(
+                        "Function piecewise expects pairs of condition and value followed by a default value but got {}",
+                        count_arguments(nb_commas + 1)
+                    ));
+                }
+                Some(OpenParenthesis {
+                    function: Some(Function::Piecewise),
+                    ..
+                }) => (),
+                Some(OpenParenthesis {
+                    function: Some(fun),
+                    ..
+                }) if fun.is_variadic() => (),
+                Some(OpenParenthesis {
+                    function: Some(fun),
+                    nb_commas,
+                    ..
+                }) if nb_commas + 1 != fun.arity() => {
+                    return Err(format!(
+                        "Function {} expects {} but got {}",
+                        fun.name(),
+                        count_arguments(fun.arity()),
+                        nb_commas + 1
+                    ));
+                }
+                Some(_) => (),
+                None => return Err(String::from("Unmatched closing parenthesis")),
+            },
+            _ => (),
+        }
+
+        return Ok(());
+    }
+
+    /// Check that each question mark opened in current parenthesis has its colon.
+    /// If a colon is missing, an error message located at its question mark is stored in string contained in Result output
+    fn check_questions(&mut self) -> Result<(), String> {
+        match self.questions.last() {
+            Some(open) if open.depth == self.parenthesis.len() => {
+                self.token_start = open.position;
+                Err(String::from("Missing colon of conditional operator"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Extract next token from expression, None being given at the end of expression
+    fn next_token(&mut self) -> Option<Result<Token, String>> {
+        while let Some(c) = self.cursor.peek() {
+            self.token_start = self.cursor.position;
+
+            if c.is_whitespace() {
+                self.cursor.next();
+            } else if c.is_digit(10) {
+                match self.read_number() {
+                    Some(number) => return Some(Ok(Token::new_number(number))),
+                    None => return Some(Err(String::from("Cannot parse this expression"))),
+                }
+            } else if let Some(ops) = comparison_operator(self.cursor.as_str()) {
+                for _ in 0..ops.notation().len() {
+                    self.cursor.next();
+                }
+
+                return Some(Ok(Token::BinaryOperator(ops)));
+            } else if c == '&' || c == '|' {
+                self.cursor.next();
+
+                // Boolean operators are written with their char doubled
+                match self.cursor.next() {
+                    Some(next) if next == c => return Some(Token::new_binary_ops(c)),
+                    _ => return Some(Err(String::from("Cannot parse this expression"))),
+                }
+            } else if BinaryOperator::is_ops(c) || UnaryOperator::is_ops(c) {
+                self.cursor.next();
+
+                match self.last_token {
+                    // Boolean negation is only a unary operator, so it can follow a binary operator
+                    _ if c == '!' => return Some(Token::new_unary_ops(c)),
+                    None => return Some(Token::new_unary_ops(c)),
+                    Some(Token::LeftParenthesis) => return Some(Token::new_unary_ops(c)),
+                    Some(Token::Comma) => return Some(Token::new_unary_ops(c)),
+                    Some(Token::Question | Token::Colon) => return Some(Token::new_unary_ops(c)),
+                    _ => return Some(Token::new_binary_ops(c)),
+                }
+            } else if c == '(' {
+                self.cursor.next();
+                return Some(Ok(Token::LeftParenthesis));
+            } else if c == ')' {
+                self.cursor.next();
+                return Some(Ok(Token::RightParenthesis));
+            } else if c == ',' {
+                self.cursor.next();
+                return Some(Ok(Token::Comma));
+            } else if c == '?' {
+                self.cursor.next();
+                return Some(Ok(Token::Question));
+            } else if c == ':' {
+                self.cursor.next();
+                return Some(Ok(Token::Colon));
+            } else if c == '[' {
+                match self.extract_array() {
+                    Ok(values) => return self.array_tokens(&values),
+                    Err(message) => return Some(Err(message)),
+                }
+            } else if c.is_alphanumeric() {
+                let name: &'a str = extract_word(&mut self.cursor);
+                self.last_word = name;
+
+                // Name is resolved as the first kind of name matching it in resolution order
+                for kind in self.order {
+                    match kind {
+                        NameKind::Constant if is_constant(name) => {
+                            return Some(Token::new_constant(name))
+                        }
+                        NameKind::Function if Function::is_fun(name) => {
+                            return Some(Token::new_function(name))
+                        }
+                        NameKind::Variable => {
+                            if let Some(values) = self.arrays.and_then(|arrays| arrays.get(name)) {
+                                return self.array_tokens(values);
+                            } else if let Some(token) = (self.resolve)(name) {
+                                return Some(Ok(token));
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+
+                return Some(Err(unknown_name_message(name)));
+            } else {
+                return Some(Err(String::from("Cannot parse this expression")));
+            }
+        }
+
+        return None;
+    }
+}
+
+impl<R> Iterator for Infix<'_, R>
+where
+    R: FnMut(&str) -> Option<Token>,
+{
+    type Item = Result<Token, String>;
+
+    fn next(&mut self) -> Option<Result<Token, String>> {
+        if self.failed {
+            return None;
+        }
+
+        self.last_word = "";
+
+        let next: Option<Result<Token, String>> = match self.pending.pop_front() {
+            Some(token) => Some(Ok(token)),
+            None => self.next_token(),
+        };
+
+        match next {
+            Some(Ok(token)) => {
+                self.nb_tokens += 1;
+
+                if let Err(message) = self
+                    .check_grammar(token)
+                    .and_then(|_| self.check_placement(token))
+                    .and_then(|_| self.check_limits())
+                {
+                    return Some(Err(self.fail(message)));
+                }
+
+                self.last_token = Some(token);
+            }
+            Some(Err(message)) => return Some(Err(self.fail(message))),
+            None => {
+                self.failed = true;
+                self.token_start = self.cursor.position;
+
+                if self.nb_tokens == 0 {
+                    self.located = true;
+                    return Some(Err(String::from("Expression is empty")));
+                }
+
+                if let Err(message) = self.check_end() {
+                    return Some(Err(self.fail(message)));
+                }
+            }
+        }
+
+        return next;
+    }
+}
+
+/// Iterator over tokens of postfix expression given as string, like 3 4 + 2 *, whose words are separated
+/// by whitespaces. Words are numbers, constants, functions, binary operators and neg, the unary minus,
+/// so tokens are given directly to evaluator without conversion.
+/// If a word is unknown, an error message is given and iteration stops
+pub struct Rpn<'a> {
+    expression: &'a str,
+    words: std::str::SplitWhitespace<'a>,
+    token_start: usize,
+    failed: bool,
+}
+
+impl<'a> Rpn<'a> {
+    /// Create iterator over tokens of postfix expression given in argument
+    pub fn new(expression: &'a str) -> Rpn<'a> {
+        return Rpn {
+            expression,
+            words: expression.split_whitespace(),
+            token_start: 0,
+            failed: false,
+        };
+    }
+
+    /// Add position in expression, as byte offset, of last word extracted to error message given in argument
+    pub fn locate(&self, message: String) -> String {
+        return format!("{message} at position {}", self.token_start);
+    }
+
+    /// Get token of word given in argument
+    fn word_token(word: &str) -> Result<Token, String> {
+        let mut cursor: Cursor<'_> = Cursor::new(word);
+
+        match word.chars().next() {
+            Some(c) if c.is_ascii_digit() || c == '.' => match extract_number(&mut cursor) {
+                Some(number) if cursor.position == word.len() => Ok(Token::new_number(number)),
+                _ => Err(String::from("Cannot parse this expression")),
+            },
+            Some(c) if word.len() == 1 && BinaryOperator::is_ops(c) => Token::new_binary_ops(c),
+            _ if word == "neg" => Token::new_unary_ops('-'),
+            _ if is_constant(word) => Token::new_constant(word),
+            _ if Function::is_fun(word) => Token::new_function(word),
+            _ => Err(unknown_name_message(word)),
+        }
+    }
+}
+
+impl Iterator for Rpn<'_> {
+    type Item = Result<Token, String>;
+
+    fn next(&mut self) -> Option<Result<Token, String>> {
+        if self.failed {
+            return None;
+        }
+
+        let word: &str = self.words.next()?;
+        // Words are borrowed from expression, so their address gives their position
+        self.token_start = word.as_ptr() as usize - self.expression.as_ptr() as usize;
+
+        let token: Result<Token, String> = Rpn::word_token(word);
+
+        if token.is_err() {
+            self.failed = true;
+        }
+
+        return Some(token);
+    }
+}
+
+/// Tokenization of expression given in argument as string.
+/// The resolve function gives the token associated to a word which is neither a constant nor a function.
+/// If error occurs during evaluation, an error message is stored
+/// in string contained in Result output
+fn tokenize_with<R>(expression: &str, resolve: R) -> Result<Vec<Token>, String>
+where
+    R: FnMut(&str) -> Option<Token>,
+{
+    let mut tokens: Vec<Token> = Vec::with_capacity(size_hint(expression));
+
+    for token in Infix::new(expression, resolve) {
+        tokens.push(token?);
+    }
+
+    return Ok(tokens);
+}
+
+/// Estimate number of tokens of expression given in argument from its byte length, whitespaces excluded,
+/// without tokenizing it. Names and numbers of several characters giving a single token,
+/// estimate is usually greater than number of tokens, so it can be used to size buffers holding tokens
+pub fn size_hint(expression: &str) -> usize {
+    return expression
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .count();
 }
 
 /// Tokenization of expression given in argument as string.
@@ -51,52 +955,57 @@ fn extract_word(char_it: &mut Peekable<Chars<'_>>) -> String {
 /// If error occurs during evaluation, an error message is stored
 /// in string contained in Result output
 pub fn tokenize(expression: &str, variables: &HashMap<String, f64>) -> Result<Vec<Token>, String> {
-    let mut tokens: Vec<Token> = Vec::with_capacity(expression.len());
-    let mut char_it = expression.chars().peekable();
-
-    while let Some(&c) = char_it.peek() {
-        if c.is_whitespace() {
-            char_it.next();
-        } else if c.is_digit(10) {
-            match extract_number(char_it.by_ref()) {
-                Some(number) => tokens.push(Token::new_number(number)),
-                None => return Err(String::from("Cannot parse this expression")),
-            }
-        } else if BinaryOperator::is_ops(c) || UnaryOperator::is_ops(c) {
-            if tokens.is_empty() {
-                tokens.push(Token::new_unary_ops(c)?);
-            } else {
-                match tokens.last().unwrap() {
-                    &Token::LeftParenthesis => tokens.push(Token::new_unary_ops(c)?),
-                    _ => tokens.push(Token::new_binary_ops(c)?),
-                }
-            }
-
-            char_it.next();
-        } else if c == '(' {
-            tokens.push(Token::LeftParenthesis);
-            char_it.next();
-        } else if c == ')' {
-            tokens.push(Token::RightParenthesis);
-            char_it.next();
-        } else if c.is_alphanumeric() {
-            let name: String = extract_word(char_it.by_ref());
-
-            if is_constant(name.as_str()) {
-                tokens.push(Token::new_constant(name.as_str())?);
-            } else if Function::is_fun(name.as_str()) {
-                tokens.push(Token::new_function(name.as_str())?);
-            } else if variables.contains_key(&name) {
-                tokens.push(Token::new_number(variables[&name]));
-            } else {
-                return Err(String::from("Cannot parse this expression"));
+    return tokenize_with(expression, |name: &str| {
+        variables.get(name).map(|&value| Token::new_number(value))
+    });
+}
+
+/// Iterator over tokens of expression given in argument as string.
+/// This expression can contains predefined variables stored in HashMap given in argument.
+pub fn infix_tokens<'a>(
+    expression: &'a str,
+    variables: &'a HashMap<String, f64>,
+) -> Infix<'a, impl FnMut(&str) -> Option<Token> + 'a> {
+    return Infix::new(expression, |name: &str| {
+        variables.get(name).map(|&value| Token::new_number(value))
+    });
+}
+
+/// Tokenization of expression given in argument as string.
+/// Each variable of expression is replaced by a variable token holding
+/// the index of its name in slice given in argument,
+/// so the same tokens can be evaluated with different values of variables.
+/// If error occurs during evaluation, an error message is stored
+/// in string contained in Result output
+pub fn tokenize_with_slots(expression: &str, names: &[&str]) -> Result<Vec<Token>, String> {
+    return tokenize_with(expression, |name: &str| {
+        names
+            .iter()
+            .position(|&slot_name| slot_name == name)
+            .map(Token::Variable)
+    });
+}
+
+/// Tokenization of expression given in argument as string.
+/// Each word which is neither a constant nor a function is considered as a variable
+/// and replaced by a variable token holding its slot, slots being given by order of appearance.
+/// The names of variables are returned alongside tokens, ordered by slot.
+/// If error occurs during evaluation, an error message is stored
+/// in string contained in Result output
+pub fn tokenize_with_discovery(expression: &str) -> Result<(Vec<Token>, Vec<String>), String> {
+    let mut names: Vec<String> = Vec::new();
+
+    let tokens: Vec<Token> = tokenize_with(expression, |name: &str| {
+        match names.iter().position(|slot_name| slot_name == name) {
+            Some(slot) => Some(Token::Variable(slot)),
+            None => {
+                names.push(String::from(name));
+                Some(Token::Variable(names.len() - 1))
             }
-        } else {
-            return Err(String::from("Cannot parse this expression"));
         }
-    }
+    })?;
 
-    return Ok(tokens);
+    return Ok((tokens, names));
 }
 
 // Units tests
@@ -109,7 +1018,7 @@ mod tests {
         let number: i64 = 4354;
         let str_number: String = number.to_string();
 
-        let value: Option<f64> = extract_number(str_number.chars().peekable().by_ref());
+        let value: Option<f64> = extract_number(&mut Cursor::new(&str_number));
         assert!(value.is_some());
         assert_eq!(value.unwrap(), number as f64);
     }
@@ -119,7 +1028,7 @@ mod tests {
         let number: f64 = 4354.75;
         let str_number: String = number.to_string();
 
-        let value: Option<f64> = extract_number(str_number.chars().peekable().by_ref());
+        let value: Option<f64> = extract_number(&mut Cursor::new(&str_number));
         assert!(value.is_some());
         assert_eq!(value.unwrap(), number);
     }
@@ -131,7 +1040,7 @@ mod tests {
 
         str_number.push_str("Hello World");
 
-        let value: Option<f64> = extract_number(str_number.chars().peekable().by_ref());
+        let value: Option<f64> = extract_number(&mut Cursor::new(&str_number));
         assert!(value.is_some());
         assert_eq!(value.unwrap(), number as f64);
     }
@@ -143,7 +1052,7 @@ mod tests {
 
         str_number.push_str("Hello World");
 
-        let value: Option<f64> = extract_number(str_number.chars().peekable().by_ref());
+        let value: Option<f64> = extract_number(&mut Cursor::new(&str_number));
         assert!(value.is_some());
         assert_eq!(value.unwrap(), number);
     }
@@ -156,14 +1065,14 @@ mod tests {
         expression.push_str(number.to_string().as_str());
         expression.push(')');
 
-        let mut char_it = expression.chars();
+        let mut char_it = Cursor::new(&expression);
         assert_eq!(char_it.next(), Some('s'));
         assert_eq!(char_it.next(), Some('q'));
         assert_eq!(char_it.next(), Some('r'));
         assert_eq!(char_it.next(), Some('t'));
         assert_eq!(char_it.next(), Some('('));
 
-        let value: Option<f64> = extract_number(char_it.peekable().by_ref());
+        let value: Option<f64> = extract_number(&mut char_it);
         assert!(value.is_some());
         assert_eq!(value.unwrap(), number as f64);
     }
@@ -176,14 +1085,14 @@ mod tests {
         expression.push_str(number.to_string().as_str());
         expression.push(')');
 
-        let mut char_it = expression.chars();
+        let mut char_it = Cursor::new(&expression);
         assert_eq!(char_it.next(), Some('s'));
         assert_eq!(char_it.next(), Some('q'));
         assert_eq!(char_it.next(), Some('r'));
         assert_eq!(char_it.next(), Some('t'));
         assert_eq!(char_it.next(), Some('('));
 
-        let value: Option<f64> = extract_number(char_it.peekable().by_ref());
+        let value: Option<f64> = extract_number(&mut char_it);
         assert!(value.is_some());
         assert_eq!(value.unwrap(), number);
     }
@@ -191,7 +1100,7 @@ mod tests {
     #[test]
     fn test_extract_word_solo() {
         let expression: String = String::from("abs");
-        let word: String = extract_word(expression.chars().peekable().by_ref());
+        let word: &str = extract_word(&mut Cursor::new(&expression));
 
         assert_eq!(expression, word);
     }
@@ -199,7 +1108,7 @@ mod tests {
     #[test]
     fn test_extract_word_with_seperator_solo() {
         let expression: String = String::from("abs_f");
-        let word: String = extract_word(expression.chars().peekable().by_ref());
+        let word: &str = extract_word(&mut Cursor::new(&expression));
 
         assert_eq!(expression, word);
     }
@@ -207,7 +1116,7 @@ mod tests {
     #[test]
     fn test_extract_word_with_number_solo() {
         let expression: String = String::from("log10");
-        let word: String = extract_word(expression.chars().peekable().by_ref());
+        let word: &str = extract_word(&mut Cursor::new(&expression));
 
         assert_eq!(expression, word);
     }
@@ -215,16 +1124,25 @@ mod tests {
     #[test]
     fn test_extract_word_with_parenthesis() {
         let expression: String = String::from("abs(");
-        let word: String = extract_word(expression.chars().peekable().by_ref());
+        let word: &str = extract_word(&mut Cursor::new(&expression));
 
         let word_ref: String = String::from("abs");
         assert_eq!(word_ref, word);
     }
 
+    #[test]
+    fn test_extract_word_non_ascii() {
+        let expression: String = String::from("θ_1 + 2");
+        let mut char_it = Cursor::new(&expression);
+
+        assert_eq!(extract_word(&mut char_it), "θ_1");
+        assert_eq!(char_it.as_str(), " + 2");
+    }
+
     #[test]
     fn test_extract_word_in_expression() {
         let expression: String = String::from("Hello Ariane 5");
-        let mut char_it = expression.chars();
+        let mut char_it = Cursor::new(&expression);
 
         assert_eq!(char_it.next(), Some('H'));
         assert_eq!(char_it.next(), Some('e'));
@@ -233,7 +1151,7 @@ mod tests {
         assert_eq!(char_it.next(), Some('o'));
         assert_eq!(char_it.next(), Some(' '));
 
-        let word: String = extract_word(char_it.peekable().by_ref());
+        let word: &str = extract_word(&mut char_it);
         let word_ref: String = String::from("Ariane");
         assert_eq!(word_ref, word);
     }
@@ -655,4 +1573,184 @@ mod tests {
             Err(_) => assert!(false),
         }
     }
+
+    #[test]
+    fn test_tokenization_expression_with_function_several_arguments() {
+        let expression: &str = "clamp(x, 0, -1)";
+        let x_ref: f64 = 0.5;
+
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), x_ref)]);
+
+        match tokenize(expression, &variables) {
+            Ok(tokens) => {
+                assert_eq!(tokens.len(), 9);
+
+                match tokens[0] {
+                    Token::Function(fun) => assert_eq!(fun, Function::Clamp),
+                    _ => assert!(false),
+                }
+
+                match tokens[2] {
+                    Token::Number(number) => assert_eq!(number, x_ref),
+                    _ => assert!(false),
+                }
+
+                match tokens[3] {
+                    Token::Comma => assert!(true),
+                    _ => assert!(false),
+                }
+
+                match tokens[5] {
+                    Token::Comma => assert!(true),
+                    _ => assert!(false),
+                }
+
+                match tokens[6] {
+                    Token::UnaryOperator(ops) => assert_eq!(ops, UnaryOperator::Minus),
+                    _ => assert!(false),
+                }
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_tokenization_expression_with_variable_slots() {
+        let expression: &str = "x * y + x";
+
+        match tokenize_with_slots(expression, &["y", "x"]) {
+            Ok(tokens) => {
+                assert_eq!(tokens.len(), 5);
+
+                match tokens[0] {
+                    Token::Variable(slot) => assert_eq!(slot, 1),
+                    _ => assert!(false),
+                }
+
+                match tokens[2] {
+                    Token::Variable(slot) => assert_eq!(slot, 0),
+                    _ => assert!(false),
+                }
+
+                match tokens[4] {
+                    Token::Variable(slot) => assert_eq!(slot, 1),
+                    _ => assert!(false),
+                }
+            }
+            Err(_) => assert!(false),
+        }
+
+        assert!(tokenize_with_slots(expression, &["x"]).is_err());
+    }
+
+    #[test]
+    fn test_tokenize_with_discovery() {
+        match tokenize_with_discovery("x * y + sin(x) - pi") {
+            Ok((tokens, names)) => {
+                assert_eq!(names, vec![String::from("x"), String::from("y")]);
+                assert_eq!(tokens[0], Token::Variable(0));
+                assert_eq!(tokens[2], Token::Variable(1));
+                assert_eq!(tokens[6], Token::Variable(0));
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_infix_iterator_stops_after_error() {
+        let mut infix = Infix::new("2 + $ 3", |_: &str| None);
+
+        assert_eq!(infix.next(), Some(Ok(Token::Number(2.0))));
+        assert_eq!(
+            infix.next(),
+            Some(Ok(Token::BinaryOperator(BinaryOperator::Plus)))
+        );
+        assert_eq!(
+            infix.next(),
+            Some(Err(String::from(
+                "Cannot parse this expression at position 4"
+            )))
+        );
+        assert_eq!(infix.next(), None);
+    }
+
+    #[test]
+    fn test_size_hint() {
+        let variables: HashMap<String, f64> = HashMap::new();
+
+        assert_eq!(size_hint(""), 0);
+        assert_eq!(size_hint("2 * (3 + 1)"), 7);
+
+        for expression in ["2 * (3 + 1)", "sqrt(pi) - 12.5 / 3", "-(1, 2^3)"] {
+            match tokenize(expression, &variables) {
+                Ok(tokens) => assert!(tokens.len() <= size_hint(expression)),
+                Err(_) => assert!(false),
+            }
+        }
+    }
+
+    #[test]
+    fn test_tokenize_surfaces_errors() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 1.0)]);
+
+        assert_eq!(
+            tokenize("2 $ 3", &variables),
+            Err(String::from("Cannot parse this expression at position 2"))
+        );
+        assert_eq!(
+            tokenize("x + speed", &variables),
+            Err(String::from("Unknown name speed at position 4"))
+        );
+        assert_eq!(
+            tokenize_with_slots("x + speed", &["x"]),
+            Err(String::from("Unknown name speed at position 4"))
+        );
+    }
+
+    #[test]
+    fn test_infix_tokens_malformed_expressions() {
+        let cases: [(&str, &str); 16] = [
+            ("2 + * 3", "Unexpected operator at position 4"),
+            ("2 3", "Unexpected operand at position 2"),
+            ("2 +", "Unexpected end of expression at position 3"),
+            ("(2 + 3)(4)", "Unexpected parenthesis at position 7"),
+            ("(, 2)", "Unexpected comma at position 1"),
+            ("sqrt 4", "Missing parenthesis after function at position 5"),
+            ("sin((1 + 1) * (2", "Unclosed parenthesis at position 14"),
+            (
+                "(1 + 2)) * 3",
+                "Unmatched closing parenthesis at position 7",
+            ),
+            (
+                "clamp(1, 2)",
+                "Function clamp expects 3 arguments but got 2 at position 10",
+            ),
+            (
+                "sqrt(1, 2)",
+                "Function sqrt expects 1 argument but got 2 at position 9",
+            ),
+            ("", "Expression is empty"),
+            ("   ", "Expression is empty"),
+            (
+                "1 ? 2",
+                "Missing colon of conditional operator at position 2",
+            ),
+            (
+                "(1 ? 2) : 3",
+                "Missing colon of conditional operator at position 3",
+            ),
+            (
+                "1 ? 2 : 3 : 4",
+                "Missing question mark of conditional operator at position 10",
+            ),
+            ("1 ? : 3", "Unexpected operator at position 4"),
+        ];
+
+        for (expression, message) in cases {
+            let tokens: Result<Vec<Token>, String> =
+                Infix::new(expression, |_: &str| None).collect();
+
+            assert_eq!(tokens, Err(String::from(message)));
+        }
+    }
 }
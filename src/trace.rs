@@ -0,0 +1,174 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::{Ast, Node};
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Node of evaluation trace, giving value of a subexpression and time spent to evaluate it
+#[derive(Debug, PartialEq, Clone)]
+pub struct TraceNode {
+    /// Kind of subexpression: number, constant, variable, unary, binary or call
+    pub kind: &'static str,
+    /// Subexpression as written by syntax tree, like sqrt(x^2 + y^2)
+    pub expression: String,
+    /// Value of subexpression
+    pub value: f64,
+    /// Time spent to evaluate subexpression, including its children
+    pub duration: Duration,
+    /// Traces of operands or arguments of subexpression, breakpoint arrays being omitted
+    pub children: Vec<TraceNode>,
+}
+
+/// Trace of evaluation of an expression, giving a tree of evaluated subexpressions for each output
+#[derive(Debug, PartialEq, Clone)]
+pub struct Trace {
+    /// Expression evaluated
+    pub expression: String,
+    /// Trace of each output of expression, a tuple like (a, b) having several outputs
+    pub outputs: Vec<TraceNode>,
+}
+
+/// Evaluate node given in argument with values of variables given in argument, tracing its evaluation
+fn trace_node(node: &Node, variables: &HashMap<String, f64>) -> Result<TraceNode, String> {
+    let start: Instant = Instant::now();
+    let mut children: Vec<TraceNode> = Vec::new();
+
+    let (kind, value): (&'static str, f64) = match node {
+        Node::Number(number) => ("number", *number),
+        Node::Constant(_, value) => ("constant", *value),
+        Node::Variable(name) => match variables.get(name) {
+            Some(&value) => ("variable", value),
+            None => return Err(format!("Missing value of variable {name}")),
+        },
+        Node::Unary(ops, operand) => {
+            children.push(trace_node(operand, variables)?);
+            ("unary", ops.apply(children[0].value))
+        }
+        Node::Binary(ops, left, right) => {
+            children.push(trace_node(left, variables)?);
+            children.push(trace_node(right, variables)?);
+            ("binary", ops.apply(children[0].value, children[1].value)?)
+        }
+        Node::Call(fun, args) => {
+            let mut values: Vec<f64> = Vec::new();
+
+            for arg in args {
+                match arg {
+                    // Breakpoint arrays are given as their values followed by their length
+                    Node::Array(array) => {
+                        values.extend_from_slice(array);
+                        values.push(array.len() as f64);
+                    }
+                    _ => {
+                        let child: TraceNode = trace_node(arg, variables)?;
+                        values.push(child.value);
+                        children.push(child);
+                    }
+                }
+            }
+
+            // Arguments of aggregation functions are given followed by their number
+            if fun.is_variadic() {
+                values.push(args.len() as f64);
+            }
+
+            ("call", fun.apply(&values)?)
+        }
+        Node::Array(_) | Node::Tuple(_) => {
+            return Err(String::from("Node cannot be evaluated as a single value"));
+        }
+    };
+
+    return Ok(TraceNode {
+        kind,
+        expression: node.to_string(),
+        value,
+        duration: start.elapsed(),
+        children,
+    });
+}
+
+/// Evaluate an expression with values of variables stored in hash map given in argument,
+/// tracing value of each subexpression and time spent to evaluate it,
+/// so the evaluation tree can be inspected or rendered by external tools.
+///
+/// If error occurs during evaluation, an error message is stored in string contained in Result output.
+///
+/// # Example
+/// ```
+/// use taz;
+/// use std::collections::HashMap;
+///
+/// let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 3.0)]);
+///
+/// let trace: taz::Trace = taz::trace("2 * x + 1", &variables).unwrap();
+/// let root: &taz::TraceNode = &trace.outputs[0];
+///
+/// assert_eq!(root.value, 7.0);
+/// assert_eq!(root.children[0].expression, "2 * x");
+/// assert_eq!(root.children[0].value, 6.0);
+/// ```
+pub fn trace(expression: &str, variables: &HashMap<String, f64>) -> Result<Trace, String> {
+    let ast: Ast = Ast::new(expression)?;
+
+    let outputs: Vec<TraceNode> = match &ast.root {
+        Node::Tuple(elements) => elements
+            .iter()
+            .map(|element| trace_node(element, variables))
+            .collect::<Result<Vec<TraceNode>, String>>()?,
+        root => vec![trace_node(root, variables)?],
+    };
+
+    return Ok(Trace {
+        expression: String::from(expression),
+        outputs,
+    });
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_tree() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 4.0)]);
+
+        match trace("-sqrt(x) + lookup(x, [0, 8], [0, 1])", &variables) {
+            Ok(trace) => {
+                assert_eq!(trace.outputs.len(), 1);
+
+                let root: &TraceNode = &trace.outputs[0];
+                assert_eq!(root.kind, "binary");
+                assert_eq!(root.value, -1.5);
+
+                let kinds: Vec<&str> = root.children.iter().map(|child| child.kind).collect();
+                assert_eq!(kinds, vec!["unary", "call"]);
+                assert_eq!(root.children[0].children[0].expression, "sqrt(x)");
+                assert_eq!(root.children[1].children.len(), 1);
+                assert!(root.duration >= root.children[1].duration);
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_trace_tuple_and_errors() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("t"), 2.0)]);
+
+        match trace("(t, t^2, pi)", &variables) {
+            Ok(trace) => {
+                let values: Vec<f64> = trace.outputs.iter().map(|output| output.value).collect();
+                assert_eq!(values, vec![2.0, 4.0, std::f64::consts::PI]);
+            }
+            Err(_) => assert!(false),
+        }
+
+        assert_eq!(
+            trace("t + u", &variables),
+            Err(String::from("Missing value of variable u"))
+        );
+        assert!(trace("1 / (t - 2)", &variables).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+#![allow(clippy::needless_return)]
+
+use super::trace::{Trace, TraceNode};
+
+use serde::Serialize;
+
+/// Version of schema of evaluation traces exported as JSON by this version of library
+pub const TRACE_SCHEMA_VERSION: u32 = 1;
+
+/// Node of evaluation trace as exported, duration being given in nanoseconds
+/// and non-finite values being written as null
+#[derive(Debug, Serialize)]
+struct TraceEntry<'a> {
+    kind: &'a str,
+    expression: &'a str,
+    value: f64,
+    duration_ns: u64,
+    children: Vec<TraceEntry<'a>>,
+}
+
+/// Evaluation trace as exported
+#[derive(Debug, Serialize)]
+struct TraceDocument<'a> {
+    schema_version: u32,
+    expression: &'a str,
+    outputs: Vec<TraceEntry<'a>>,
+}
+
+impl<'a> TraceEntry<'a> {
+    /// Create exported node from node of evaluation trace and its children
+    fn from_node(node: &'a TraceNode) -> TraceEntry<'a> {
+        return TraceEntry {
+            kind: node.kind,
+            expression: &node.expression,
+            value: node.value,
+            duration_ns: u64::try_from(node.duration.as_nanos()).unwrap_or(u64::MAX),
+            children: node.children.iter().map(TraceEntry::from_node).collect(),
+        };
+    }
+}
+
+impl Trace {
+    /// Export evaluation trace to JSON document, following schema described in README.
+    /// If error occurs during export, an error message is stored in string contained in Result output
+    pub fn to_json(&self) -> Result<String, String> {
+        let document: TraceDocument = TraceDocument {
+            schema_version: TRACE_SCHEMA_VERSION,
+            expression: &self.expression,
+            outputs: self.outputs.iter().map(TraceEntry::from_node).collect(),
+        };
+
+        return serde_json::to_string_pretty(&document).map_err(|error| error.to_string());
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::super::trace::trace;
+
+    use serde_json::Value;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_trace_to_json() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), -1.0)]);
+
+        match trace("x * 2 + ln(1)", &variables).and_then(|trace| trace.to_json()) {
+            Ok(json) => match serde_json::from_str::<Value>(&json) {
+                Ok(document) => {
+                    assert_eq!(document["schema_version"], 1);
+                    assert_eq!(document["expression"], "x * 2 + ln(1)");
+
+                    let root: &Value = &document["outputs"][0];
+                    assert_eq!(root["kind"], "binary");
+                    assert_eq!(root["value"], -2.0);
+                    assert!(root["duration_ns"].is_u64());
+                    assert_eq!(root["children"][0]["expression"], "x * 2");
+                    assert_eq!(root["children"][1]["children"][0]["kind"], "number");
+                }
+                Err(_) => assert!(false),
+            },
+            Err(_) => assert!(false),
+        }
+    }
+}
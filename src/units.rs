@@ -0,0 +1,567 @@
+#![allow(clippy::needless_return)]
+
+use super::functions::Function;
+use super::operators::{is_true, BinaryOperator, UnaryOperator};
+use super::token::Token;
+
+use std::fmt;
+use std::ops::{Div, Mul};
+use std::str::FromStr;
+
+/// Symbols of SI base units, in the order of unit exponents
+const BASE_SYMBOLS: [&str; 7] = ["m", "kg", "s", "A", "K", "mol", "cd"];
+
+/// Physical unit represented by the exponents of the seven SI base units
+/// (meter, kilogram, second, ampere, kelvin, mole, candela)
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Unit {
+    exponents: [i8; 7],
+}
+
+impl Unit {
+    /// Create a unit from exponents of SI base units
+    pub fn from_exponents(exponents: [i8; 7]) -> Unit {
+        Unit { exponents }
+    }
+
+    /// Unit of a pure number
+    pub fn dimensionless() -> Unit {
+        Unit::from_exponents([0; 7])
+    }
+
+    /// Create a SI base unit from index of its exponent
+    fn base(index: usize) -> Unit {
+        let mut exponents: [i8; 7] = [0; 7];
+        exponents[index] = 1;
+        return Unit::from_exponents(exponents);
+    }
+
+    /// Meter, SI base unit of length
+    pub fn meter() -> Unit {
+        Unit::base(0)
+    }
+
+    /// Kilogram, SI base unit of mass
+    pub fn kilogram() -> Unit {
+        Unit::base(1)
+    }
+
+    /// Second, SI base unit of time
+    pub fn second() -> Unit {
+        Unit::base(2)
+    }
+
+    /// Ampere, SI base unit of electric current
+    pub fn ampere() -> Unit {
+        Unit::base(3)
+    }
+
+    /// Kelvin, SI base unit of temperature
+    pub fn kelvin() -> Unit {
+        Unit::base(4)
+    }
+
+    /// Mole, SI base unit of amount of substance
+    pub fn mole() -> Unit {
+        Unit::base(5)
+    }
+
+    /// Candela, SI base unit of luminous intensity
+    pub fn candela() -> Unit {
+        Unit::base(6)
+    }
+
+    /// Exponents of SI base units
+    pub fn exponents(&self) -> [i8; 7] {
+        return self.exponents;
+    }
+
+    /// Check if unit is the unit of a pure number
+    pub fn is_dimensionless(&self) -> bool {
+        return self.exponents.iter().all(|&exponent| exponent == 0);
+    }
+
+    /// Raise unit to an integer power.
+    /// If an exponent overflows, an error message is stored in string contained in Result output
+    pub fn powi(&self, power: i32) -> Result<Unit, String> {
+        let mut exponents: [i8; 7] = [0; 7];
+
+        for (exponent, &base_exponent) in exponents.iter_mut().zip(self.exponents.iter()) {
+            *exponent = i8::try_from(base_exponent as i32 * power)
+                .map_err(|_| String::from("Exponent of unit is too large"))?;
+        }
+
+        return Ok(Unit::from_exponents(exponents));
+    }
+
+    /// Take the n-th root of unit.
+    /// If an exponent is not divisible by n, an error message is stored
+    /// in string contained in Result output
+    pub fn root(&self, n: i8) -> Result<Unit, String> {
+        if self.exponents.iter().any(|&exponent| exponent % n != 0) {
+            return Err(format!("Cannot take root of order {n} of unit {self}"));
+        }
+
+        return Ok(Unit::from_exponents(
+            self.exponents.map(|exponent| exponent / n),
+        ));
+    }
+
+    /// Combine exponents of two units, the exponents of other unit being multiplied by sign
+    fn combine(&self, other: &Unit, sign: i8) -> Unit {
+        let mut exponents: [i8; 7] = self.exponents;
+
+        for (exponent, &other_exponent) in exponents.iter_mut().zip(other.exponents.iter()) {
+            *exponent = exponent.saturating_add(sign.saturating_mul(other_exponent));
+        }
+
+        return Unit::from_exponents(exponents);
+    }
+}
+
+impl Mul for Unit {
+    type Output = Unit;
+
+    fn mul(self, other: Unit) -> Unit {
+        self.combine(&other, 1)
+    }
+}
+
+impl Div for Unit {
+    type Output = Unit;
+
+    fn div(self, other: Unit) -> Unit {
+        self.combine(&other, -1)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_dimensionless() {
+            return write!(f, "1");
+        }
+
+        let mut factors: Vec<String> = Vec::with_capacity(BASE_SYMBOLS.len());
+
+        for (symbol, &exponent) in BASE_SYMBOLS.iter().zip(self.exponents.iter()) {
+            match exponent {
+                0 => (),
+                1 => factors.push(symbol.to_string()),
+                _ => factors.push(format!("{symbol}^{exponent}")),
+            }
+        }
+
+        return write!(f, "{}", factors.join("."));
+    }
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    /// Parse a unit written like its display, for instance "m.kg^2.s^-1" or "1" for dimensionless unit.
+    /// If string given in argument does not correspond to unit,
+    /// an error message is stored in string contained in Result output
+    fn from_str(unit: &str) -> Result<Unit, String> {
+        let unit: &str = unit.trim();
+        let mut exponents: [i8; 7] = [0; 7];
+
+        if unit == "1" {
+            return Ok(Unit::from_exponents(exponents));
+        }
+
+        for factor in unit.split('.') {
+            let (symbol, exponent): (&str, i8) = match factor.split_once('^') {
+                Some((symbol, exponent)) => match exponent.parse() {
+                    Ok(exponent) => (symbol, exponent),
+                    Err(_) => return Err(format!("Invalid exponent in unit factor {factor}")),
+                },
+                None => (factor, 1),
+            };
+
+            match BASE_SYMBOLS
+                .iter()
+                .position(|&base_symbol| base_symbol == symbol)
+            {
+                Some(index) => exponents[index] = exponents[index].saturating_add(exponent),
+                None => return Err(format!("Unknown unit symbol {symbol}")),
+            }
+        }
+
+        return Ok(Unit::from_exponents(exponents));
+    }
+}
+
+/// Unit of an operand during unit checking,
+/// with its value when it is known without variables
+type CheckedOperand = (Unit, Option<f64>);
+
+/// Check that all arguments of function have the same unit and return this unit
+fn same_unit(args: &[CheckedOperand], fun_name: &str) -> Result<Unit, String> {
+    let unit: Unit = args[0].0;
+
+    if args.iter().any(|&(arg_unit, _)| arg_unit != unit) {
+        return Err(format!(
+            "Arguments of {fun_name} function must have the same unit"
+        ));
+    }
+
+    return Ok(unit);
+}
+
+/// Compute unit of result of function applied on arguments given in argument
+fn function_unit(fun: Function, args: &[CheckedOperand]) -> Result<Unit, String> {
+    match fun {
+        Function::Abs => Ok(args[0].0),
+        Function::Sqrt => args[0].0.root(2),
+        Function::Cbrt => args[0].0.root(3),
+        Function::Clamp => same_unit(args, "clamp"),
+        Function::Lerp => {
+            if !args[2].0.is_dimensionless() {
+                return Err(String::from(
+                    "Interpolation parameter of lerp function must be dimensionless",
+                ));
+            }
+
+            return same_unit(&args[..2], "lerp");
+        }
+        Function::Select => same_unit(&args[1..], "select"),
+        // Arguments of aggregation functions are followed by their number
+        _ if fun.is_variadic() => {
+            let unit: Unit = same_unit(&args[..args.len() - 1], fun.name())?;
+
+            match fun {
+                Function::Var => Ok(unit * unit),
+                _ => Ok(unit),
+            }
+        }
+        Function::Round | Function::FloorTo | Function::CeilTo => {
+            if !args[1].0.is_dimensionless() {
+                return Err(format!(
+                    "Number of digits of {} function must be dimensionless",
+                    fun.name()
+                ));
+            }
+
+            return Ok(args[0].0);
+        }
+        _ => {
+            if args.iter().all(|&(unit, _)| unit.is_dimensionless()) {
+                return Ok(Unit::dimensionless());
+            } else {
+                return Err(String::from("Arguments of function must be dimensionless"));
+            }
+        }
+    }
+}
+
+/// Compute unit of result of binary operation applied on two operands given in argument
+fn binary_operator_unit(
+    ops: BinaryOperator,
+    left: CheckedOperand,
+    right: CheckedOperand,
+) -> Result<Unit, String> {
+    let (left_unit, right_unit): (Unit, Unit) = (left.0, right.0);
+
+    match ops {
+        BinaryOperator::Plus | BinaryOperator::Minus => {
+            if left_unit == right_unit {
+                return Ok(left_unit);
+            } else {
+                return Err(format!(
+                    "Incompatible units {left_unit} and {right_unit} in addition or subtraction"
+                ));
+            }
+        }
+        // Truth of a quantity does not depend on its unit
+        BinaryOperator::And | BinaryOperator::Or => Ok(Unit::dimensionless()),
+        BinaryOperator::Less
+        | BinaryOperator::LessEqual
+        | BinaryOperator::Greater
+        | BinaryOperator::GreaterEqual
+        | BinaryOperator::Equal
+        | BinaryOperator::NotEqual => {
+            if left_unit == right_unit {
+                return Ok(Unit::dimensionless());
+            } else {
+                return Err(format!(
+                    "Incompatible units {left_unit} and {right_unit} in comparison"
+                ));
+            }
+        }
+        BinaryOperator::Multiply => Ok(left_unit * right_unit),
+        BinaryOperator::Divide => Ok(left_unit / right_unit),
+        BinaryOperator::Power => {
+            if !right_unit.is_dimensionless() {
+                return Err(String::from("Exponent must be dimensionless"));
+            }
+
+            if left_unit.is_dimensionless() {
+                return Ok(left_unit);
+            }
+
+            match right.1 {
+                Some(power) if power.fract() == 0.0 && power.abs() <= i8::MAX as f64 => {
+                    return left_unit.powi(power as i32);
+                }
+                _ => {
+                    return Err(String::from(
+                        "Exponent of a quantity with unit must be a constant whole number",
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Check units of postfix expression given as slice of token
+/// where each variable token has the unit given at same index in slice of units.
+/// The unit of result of expression is returned.
+/// If units are not consistent, an error message is stored
+/// in string contained in Result output
+pub fn check_postfix_units(tokens: &[Token], units: &[Unit]) -> Result<Unit, String> {
+    let mut stack_operand: Vec<CheckedOperand> = Vec::with_capacity(10);
+
+    for &token in tokens {
+        match token {
+            Token::Number(number) => stack_operand.push((Unit::dimensionless(), Some(number))),
+            Token::Constant(constant) => {
+                stack_operand.push((Unit::dimensionless(), Some(constant)))
+            }
+            Token::Variable(slot) => match units.get(slot) {
+                Some(&unit) => stack_operand.push((unit, None)),
+                None => return Err(String::from("Missing unit of variable")),
+            },
+            Token::Array(length) => {
+                stack_operand.push((Unit::dimensionless(), Some(length as f64)))
+            }
+            Token::UnaryOperator(ops) => match stack_operand.pop() {
+                Some((_, value)) if ops == UnaryOperator::Not => stack_operand
+                    .push((Unit::dimensionless(), value.map(|number| ops.apply(number)))),
+                Some((unit, value)) => {
+                    stack_operand.push((unit, value.map(|number| ops.apply(number))))
+                }
+                None => return Err(String::from("Missing operand to apply unary operation")),
+            },
+            Token::BinaryOperator(ops) => {
+                if stack_operand.len() < 2 {
+                    return Err(String::from("Missing operand to apply binary operation"));
+                }
+
+                let right: CheckedOperand = stack_operand.pop().unwrap();
+                let left: CheckedOperand = stack_operand.pop().unwrap();
+
+                let unit: Unit = binary_operator_unit(ops, left, right)?;
+                let value: Option<f64> = match (left.1, right.1) {
+                    (Some(left_value), Some(right_value)) => {
+                        ops.apply(left_value, right_value).ok()
+                    }
+                    _ => None,
+                };
+
+                stack_operand.push((unit, value));
+            }
+            Token::Function(fun) => {
+                // Lengths of breakpoint arrays are always known values
+                let values: Vec<f64> = stack_operand
+                    .iter()
+                    .map(|&(_, value)| value.unwrap_or(f64::NAN))
+                    .collect();
+                let arity: usize = fun.arity_on_stack(&values)?;
+
+                let first_arg: usize = stack_operand.len() - arity;
+                let args: &[CheckedOperand] = &stack_operand[first_arg..];
+
+                let unit: Unit = function_unit(fun, args)?;
+                let value: Option<f64> = args
+                    .iter()
+                    .map(|&(_, value)| value)
+                    .collect::<Option<Vec<f64>>>()
+                    .and_then(|values| fun.apply(&values).ok());
+
+                stack_operand.truncate(first_arg);
+                stack_operand.push((unit, value));
+            }
+            // Values of conditional operator are checked like arguments of select function
+            Token::Question | Token::Colon | Token::ShortCircuit(_) => (),
+            Token::EndConditional => {
+                if stack_operand.len() < 3 {
+                    return Err(String::from(
+                        "Missing operand to apply conditional operator",
+                    ));
+                }
+
+                let first_arg: usize = stack_operand.len() - 3;
+                let args: &[CheckedOperand] = &stack_operand[first_arg..];
+
+                let unit: Unit = function_unit(Function::Select, args)?;
+                let value: Option<f64> = match args[0].1 {
+                    Some(condition) if is_true(condition) => args[1].1,
+                    Some(_) => args[2].1,
+                    None => None,
+                };
+
+                stack_operand.truncate(first_arg);
+                stack_operand.push((unit, value));
+            }
+            _ => {
+                return Err(String::from(
+                    "Token non-accepted for unit checking of postfix expression",
+                ));
+            }
+        }
+    }
+
+    match stack_operand.first() {
+        Some(&(unit, _)) => Ok(unit),
+        None => Err(String::from("Expression is empty")),
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::super::operators::UnaryOperator;
+    use super::*;
+
+    fn meter_per_second() -> Unit {
+        Unit::meter() / Unit::second()
+    }
+
+    #[test]
+    fn test_unit_operations() {
+        let speed: Unit = meter_per_second();
+        assert_eq!(speed.exponents(), [1, 0, -1, 0, 0, 0, 0]);
+        assert_eq!((speed * Unit::second()), Unit::meter());
+        assert_eq!(speed.powi(2).unwrap().exponents(), [2, 0, -2, 0, 0, 0, 0]);
+        assert_eq!(speed.powi(2).unwrap().root(2).unwrap(), speed);
+        assert!(speed.root(2).is_err());
+        assert!(Unit::meter().powi(200).is_err());
+        assert!((Unit::meter() / Unit::meter()).is_dimensionless());
+    }
+
+    #[test]
+    fn test_unit_display() {
+        assert_eq!(Unit::dimensionless().to_string(), "1");
+        assert_eq!(meter_per_second().to_string(), "m.s^-1");
+        assert_eq!((Unit::kilogram() * Unit::meter()).to_string(), "m.kg");
+    }
+
+    #[test]
+    fn test_unit_from_string() {
+        assert_eq!("1".parse::<Unit>(), Ok(Unit::dimensionless()));
+        assert_eq!("m.s^-1".parse::<Unit>(), Ok(meter_per_second()));
+        assert_eq!(
+            "kg.m^2.s^-2".parse::<Unit>(),
+            Ok(Unit::kilogram() * meter_per_second().powi(2).unwrap())
+        );
+        assert_eq!(
+            meter_per_second().to_string().parse::<Unit>(),
+            Ok(meter_per_second())
+        );
+        assert!("furlong".parse::<Unit>().is_err());
+        assert!("m^x".parse::<Unit>().is_err());
+    }
+
+    #[test]
+    fn test_check_postfix_units_consistent() {
+        // d / t * 2
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::Variable(1),
+            Token::BinaryOperator(BinaryOperator::Divide),
+            Token::Number(2.0),
+            Token::BinaryOperator(BinaryOperator::Multiply),
+        ];
+
+        let units: Vec<Unit> = vec![Unit::meter(), Unit::second()];
+        assert_eq!(check_postfix_units(&tokens, &units), Ok(meter_per_second()));
+    }
+
+    #[test]
+    fn test_check_postfix_units_addition_mismatch() {
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::Variable(1),
+            Token::BinaryOperator(BinaryOperator::Plus),
+        ];
+
+        let units: Vec<Unit> = vec![Unit::meter(), Unit::second()];
+
+        match check_postfix_units(&tokens, &units) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(
+                message,
+                "Incompatible units m and s in addition or subtraction"
+            ),
+        }
+    }
+
+    #[test]
+    fn test_check_postfix_units_power_and_root() {
+        // sqrt(x^2 + y^2)
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::Number(2.0),
+            Token::BinaryOperator(BinaryOperator::Power),
+            Token::Variable(1),
+            Token::Number(2.0),
+            Token::BinaryOperator(BinaryOperator::Power),
+            Token::BinaryOperator(BinaryOperator::Plus),
+            Token::Function(Function::Sqrt),
+        ];
+
+        let units: Vec<Unit> = vec![Unit::meter(), Unit::meter()];
+        assert_eq!(check_postfix_units(&tokens, &units), Ok(Unit::meter()));
+    }
+
+    #[test]
+    fn test_check_postfix_units_power_with_variable_exponent() {
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::Variable(1),
+            Token::BinaryOperator(BinaryOperator::Power),
+        ];
+
+        let units: Vec<Unit> = vec![Unit::meter(), Unit::dimensionless()];
+        assert!(check_postfix_units(&tokens, &units).is_err());
+    }
+
+    #[test]
+    fn test_check_postfix_units_function_of_quantity() {
+        // sin(x) with x in meter
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::UnaryOperator(UnaryOperator::Minus),
+            Token::Function(Function::Sin),
+        ];
+
+        match check_postfix_units(&tokens, &[Unit::meter()]) {
+            Ok(_) => assert!(false),
+            Err(message) => assert_eq!(message, "Arguments of function must be dimensionless"),
+        }
+    }
+
+    #[test]
+    fn test_check_postfix_units_rounding_keeps_unit() {
+        // round(x, 2)
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::Number(2.0),
+            Token::Function(Function::Round),
+        ];
+
+        assert_eq!(
+            check_postfix_units(&tokens, &[Unit::meter()]),
+            Ok(Unit::meter())
+        );
+
+        // floorto(x, x)
+        let tokens: Vec<Token> = vec![
+            Token::Variable(0),
+            Token::Variable(0),
+            Token::Function(Function::FloorTo),
+        ];
+
+        assert!(check_postfix_units(&tokens, &[Unit::meter()]).is_err());
+    }
+}
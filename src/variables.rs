@@ -0,0 +1,62 @@
+#![allow(clippy::needless_return)]
+
+use std::collections::HashMap;
+
+/// Types whose values can be bound as variables of expressions.
+///
+/// With *derive* feature, this trait can be derived on structs with named fields
+/// to bind each numeric field to a variable with the name of the field.
+pub trait TazVariables {
+    /// Insert values of variables in hash map given in argument,
+    /// replacing values of variables already present
+    fn bind_variables(&self, variables: &mut HashMap<String, f64>);
+
+    /// Create hash map associating name of each variable and its value
+    fn to_variables(&self) -> HashMap<String, f64> {
+        let mut variables: HashMap<String, f64> = HashMap::new();
+        self.bind_variables(&mut variables);
+        return variables;
+    }
+}
+
+// Units tests
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use super::*;
+    use crate::TazVariables;
+
+    #[allow(dead_code)]
+    #[derive(TazVariables)]
+    struct State {
+        x: f64,
+        count: u32,
+        #[taz(rename = "theta")]
+        angle: f32,
+        #[taz(skip)]
+        ignored: f64,
+        label: String,
+    }
+
+    #[test]
+    fn test_derive_taz_variables() {
+        let state: State = State {
+            x: 2.0,
+            count: 3,
+            angle: 0.5,
+            ignored: 7.0,
+            label: String::from("state"),
+        };
+
+        let variables: HashMap<String, f64> = state.to_variables();
+
+        assert_eq!(variables.len(), 3);
+        assert_eq!(variables.get("x"), Some(&2.0));
+        assert_eq!(variables.get("count"), Some(&3.0));
+        assert_eq!(variables.get("theta"), Some(&0.5));
+
+        assert_eq!(
+            crate::evaluate("x * count + 2 * theta", &variables),
+            Ok(7.0)
+        );
+    }
+}
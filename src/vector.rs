@@ -0,0 +1,1440 @@
+#![allow(clippy::needless_return)]
+
+use super::calculus;
+use super::converter::Postfix;
+use super::evaluator::{self, OperandStack};
+use super::functions::Function;
+use super::limits::Limits;
+use super::matrix::Matrix;
+use super::operators::is_true;
+use super::polynomial;
+use super::token::Token;
+use super::tokenizer::Infix;
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Names of functions of whole vectors, whose values are not computed element by element,
+/// aggregation functions taking whole vectors too
+const VECTOR_FUNCTIONS: [&str; 13] = [
+    "dot", "cross", "range", "index", "sort", "cumsum", "diff", "linspace", "logspace", "norm",
+    "norm1", "norminf", "roots",
+];
+
+/// Names of higher-order functions, whose second argument is a lambda like x -> x^2 applied to each element of a vector
+const HIGHER_ORDER_FUNCTIONS: [&str; 2] = ["map", "filter"];
+
+/// Value of an expression which can hold vectors like [1, 2, 3] or matrices like [[1, 2], [3, 4]].
+/// An expression without vector gives a scalar, while an expression with vectors gives a vector
+/// holding its value for each element of its vectors
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Scalar(f64),
+    Vector(Vec<f64>),
+    Matrix(Matrix),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Scalar(value) => write!(f, "{value}"),
+            Value::Vector(values) => {
+                let elements: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Value::Matrix(matrix) => write!(f, "{matrix}"),
+        }
+    }
+}
+
+/// Values of calls of vector functions and ranges, stored by name of the word replacing each of them in expression
+#[derive(Default)]
+struct VectorCalls {
+    scalars: HashMap<String, f64>,
+    vectors: HashMap<String, Vec<f64>>,
+}
+
+impl VectorCalls {
+    /// Store value given in argument, giving the word of length given in argument which replaces it in expression
+    fn store(&mut self, value: Value, length: usize) -> String {
+        let slot: usize = self.scalars.len() + self.vectors.len();
+        let word: String = format!("V{slot:_<width$}", width = length.saturating_sub(1));
+
+        match value {
+            Value::Vector(values) => {
+                self.vectors.insert(word.clone(), values);
+            }
+            Value::Scalar(value) => {
+                self.scalars.insert(word.clone(), value);
+            }
+            Value::Matrix(_) => (),
+        }
+
+        return word;
+    }
+}
+
+/// Check if a name is the name of a function taking whole vectors, like vector functions and aggregation functions
+fn is_vector_function(name: &str) -> bool {
+    return VECTOR_FUNCTIONS.contains(&name)
+        || HIGHER_ORDER_FUNCTIONS.contains(&name)
+        || name == "minimize"
+        || Function::from_string(name).is_ok_and(|fun| fun.is_variadic());
+}
+
+/// Get values of range from start to stop, excluded, by step given in argument.
+/// If step is null or if range is empty, an error message is stored in string contained in Result output
+fn range(start: f64, stop: f64, step: f64) -> Result<Vec<f64>, String> {
+    if step == 0.0 {
+        return Err(String::from("Step of range function is null"));
+    }
+
+    let length: f64 = ((stop - start) / step).ceil();
+
+    if length.is_nan() || length < 1.0 {
+        return Err(format!(
+            "Range from {start} to {stop} by step {step} is empty"
+        ));
+    }
+
+    let max_length: usize = Limits::default().max_tokens;
+
+    if length > max_length as f64 {
+        return Err(format!(
+            "Range from {start} to {stop} by step {step} exceeds limit of {max_length} elements"
+        ));
+    }
+
+    return Ok((0..length as usize)
+        .map(|index| start + index as f64 * step)
+        .collect());
+}
+
+/// Get values of given number of points evenly spaced from start to stop, both included
+fn linspace(start: f64, stop: f64, nb_points: usize) -> Vec<f64> {
+    if nb_points == 1 {
+        return vec![start];
+    }
+
+    let step: f64 = (stop - start) / (nb_points - 1) as f64;
+
+    return (0..nb_points)
+        .map(|index| start + index as f64 * step)
+        .collect();
+}
+
+/// Get element of vector given in argument at index given in argument, counted from 0.
+/// If index is not an integer or is out of range of vector, an error message is stored in string contained in Result output
+fn vector_index(values: &[f64], index: f64) -> Result<f64, String> {
+    if index.fract() != 0.0 {
+        return Err(format!("Index {index} of vector is not an integer"));
+    }
+
+    match values.get(index as usize) {
+        Some(&value) if index >= 0.0 => Ok(value),
+        _ => Err(format!(
+            "Index {index} is out of range of vector of length {}",
+            values.len()
+        )),
+    }
+}
+
+/// Apply vector function whose name is given in argument on values given in argument.
+/// Aggregation functions take the values of vectors as their arguments and give a scalar.
+/// If arguments are not valid for function, an error message is stored in string contained in Result output
+fn apply_vector_function(name: &str, args: &[Value]) -> Result<Value, String> {
+    if let Ok(fun) = Function::from_string(name) {
+        let mut values: Vec<f64> = Vec::new();
+
+        for arg in args {
+            match arg {
+                Value::Scalar(value) => values.push(*value),
+                Value::Vector(elements) => values.extend_from_slice(elements),
+                Value::Matrix(_) => {
+                    return Err(format!("Function {name} cannot take matrices as arguments"))
+                }
+            }
+        }
+
+        // Values are followed by their number, like in operand stack
+        values.push(values.len() as f64);
+        return Ok(Value::Scalar(fun.apply(&values)?));
+    }
+
+    // Coefficients of polynomial are given from constant term, as scalars or vectors like aggregation functions
+    if name == "roots" {
+        let mut coefficients: Vec<f64> = Vec::new();
+
+        for arg in args {
+            match arg {
+                Value::Scalar(value) => coefficients.push(*value),
+                Value::Vector(elements) => coefficients.extend_from_slice(elements),
+                Value::Matrix(_) => {
+                    return Err(format!("Function {name} cannot take matrices as arguments"))
+                }
+            }
+        }
+
+        return Ok(Value::Vector(polynomial::real_roots(&coefficients)?));
+    }
+
+    if let "range" | "linspace" | "logspace" = name {
+        let bounds: Vec<f64> = args
+            .iter()
+            .map(|arg| match arg {
+                Value::Scalar(value) => Ok(*value),
+                _ => Err(format!("Arguments of {name} function must be scalars")),
+            })
+            .collect::<Result<Vec<f64>, String>>()?;
+
+        match (name, bounds.as_slice()) {
+            ("range", [start, stop]) => return Ok(Value::Vector(range(*start, *stop, 1.0)?)),
+            ("range", [start, stop, step]) => {
+                return Ok(Value::Vector(range(*start, *stop, *step)?))
+            }
+            ("range", _) => {
+                return Err(format!(
+                    "Function range expects 2 or 3 arguments but got {}",
+                    bounds.len()
+                ))
+            }
+            (_, [start, stop, nb_points]) => {
+                if *nb_points < 1.0 || nb_points.fract() != 0.0 {
+                    return Err(format!(
+                        "Number of points of {name} function must be a positive integer but got {nb_points}"
+                    ));
+                }
+
+                let max_length: usize = Limits::default().max_tokens;
+
+                if *nb_points > max_length as f64 {
+                    return Err(format!(
+                        "Number of points of {name} function exceeds limit of {max_length} elements"
+                    ));
+                }
+
+                let values: Vec<f64> = linspace(*start, *stop, *nb_points as usize);
+
+                return Ok(Value::Vector(match name {
+                    "linspace" => values,
+                    _ => values.into_iter().map(|value| 10f64.powf(value)).collect(),
+                }));
+            }
+            _ => {
+                return Err(format!(
+                    "Function {name} expects 3 arguments but got {}",
+                    bounds.len()
+                ))
+            }
+        }
+    }
+
+    if name == "index" {
+        match args {
+            [Value::Vector(values), Value::Scalar(index)] => {
+                return Ok(Value::Scalar(vector_index(values, *index)?))
+            }
+            [_, _] => {
+                return Err(String::from(
+                    "Function index expects a vector and a scalar as arguments",
+                ))
+            }
+            _ => {
+                return Err(format!(
+                    "Function index expects 2 arguments but got {}",
+                    args.len()
+                ))
+            }
+        }
+    }
+
+    if let "sort" | "cumsum" | "diff" | "norm" | "norm1" | "norminf" = name {
+        let values: &[f64] = match args {
+            [Value::Vector(values)] => values,
+            [_] => return Err(format!("Argument of {name} function must be a vector")),
+            _ => {
+                return Err(format!(
+                    "Function {name} expects 1 argument but got {}",
+                    args.len()
+                ))
+            }
+        };
+
+        if name.starts_with("norm") {
+            if values.is_empty() {
+                return Err(format!("Function {name} needs a non-empty vector"));
+            }
+
+            return Ok(Value::Scalar(match name {
+                "norm" => values.iter().map(|value| value * value).sum::<f64>().sqrt(),
+                "norm1" => values.iter().map(|value| value.abs()).sum(),
+                _ => values.iter().fold(0.0, |max, value| value.abs().max(max)),
+            }));
+        }
+
+        return Ok(Value::Vector(match name {
+            "sort" => {
+                let mut sorted: Vec<f64> = values.to_vec();
+                sorted.sort_by(f64::total_cmp);
+                sorted
+            }
+            "cumsum" => values
+                .iter()
+                .scan(0.0, |total, value| {
+                    *total += value;
+                    return Some(*total);
+                })
+                .collect(),
+            _ => values.windows(2).map(|pair| pair[1] - pair[0]).collect(),
+        }));
+    }
+
+    let vectors: Vec<&[f64]> = args
+        .iter()
+        .map(|arg| match arg {
+            Value::Vector(values) => Ok(values.as_slice()),
+            _ => Err(format!("Arguments of {name} function must be vectors")),
+        })
+        .collect::<Result<Vec<&[f64]>, String>>()?;
+
+    let (a, b): (&[f64], &[f64]) = match vectors.as_slice() {
+        &[a, b] => (a, b),
+        _ => {
+            return Err(format!(
+                "Function {name} expects 2 arguments but got {}",
+                vectors.len()
+            ))
+        }
+    };
+
+    if name == "dot" {
+        if a.len() != b.len() {
+            return Err(format!(
+                "Dot product needs vectors of the same length but got lengths {} and {}",
+                a.len(),
+                b.len()
+            ));
+        }
+
+        return Ok(Value::Scalar(a.iter().zip(b).map(|(x, y)| x * y).sum()));
+    }
+
+    if a.len() != 3 || b.len() != 3 {
+        return Err(format!(
+            "Cross product needs vectors of length 3 but got lengths {} and {}",
+            a.len(),
+            b.len()
+        ));
+    }
+
+    return Ok(Value::Vector(vec![
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]));
+}
+
+/// Evaluate part of expression given in argument between positions given in argument,
+/// whose value must be a scalar, with variables given in argument.
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+fn evaluate_scalar(
+    expression: &str,
+    start: usize,
+    end: usize,
+    variables: &HashMap<String, f64>,
+) -> Result<f64, String> {
+    let part: String = " ".repeat(start) + &expression[start..end];
+
+    match evaluate_vector(&part, variables)? {
+        Value::Scalar(value) => Ok(value),
+        _ => Err(format!(
+            "Body of lambda must give a scalar at position {}",
+            part.len() - part.trim_start().len()
+        )),
+    }
+}
+
+/// Apply higher-order function whose name is given in argument, map or filter, on arguments of call
+/// given by their positions in expression given in argument. First argument is a vector and second one
+/// is a lambda like x -> x^2, whose body is evaluated for each element of vector bound to its parameter.
+/// Function map gives the values of body, while function filter keeps the elements for which
+/// body is true, like a comparison x > 0.
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+fn apply_higher_order_function(
+    name: &str,
+    expression: &str,
+    args: &[(usize, usize)],
+    variables: &HashMap<String, f64>,
+    position: usize,
+) -> Result<Value, String> {
+    let (vector, lambda): ((usize, usize), (usize, usize)) = match args {
+        &[vector, lambda] => (vector, lambda),
+        _ => {
+            return Err(format!(
+                "Function {name} expects 2 arguments but got {} at position {position}",
+                args.len()
+            ))
+        }
+    };
+
+    let values: Vec<f64> = match evaluate_vector(
+        &(" ".repeat(vector.0) + &expression[vector.0..vector.1]),
+        variables,
+    )? {
+        Value::Vector(values) => values,
+        _ => {
+            return Err(format!(
+                "First argument of {name} function must be a vector at position {position}"
+            ))
+        }
+    };
+
+    // Lambda is a parameter followed by an arrow and a body
+    let text: &str = &expression[lambda.0..lambda.1];
+    let (parameter, body): (&str, usize) = match text.split_once("->") {
+        Some((parameter, _))
+            if parameter.trim().starts_with(char::is_alphabetic)
+                && parameter.trim().chars().all(is_word_char) =>
+        {
+            (parameter.trim(), lambda.0 + parameter.len() + 2)
+        }
+        _ => {
+            return Err(format!(
+                "Second argument of {name} function must be a lambda like x -> x^2 at position {position}"
+            ))
+        }
+    };
+
+    let mut scope: HashMap<String, f64> = variables.clone();
+    let mut results: Vec<f64> = Vec::with_capacity(values.len());
+
+    for value in values {
+        scope.insert(String::from(parameter), value);
+
+        let result: f64 = evaluate_scalar(expression, body, lambda.1, &scope)?;
+
+        if name == "map" {
+            results.push(result);
+        } else if is_true(result) {
+            results.push(value);
+        }
+    }
+
+    return Ok(Value::Vector(results));
+}
+
+/// Replace each call of vector function of expression given in argument by a word of the same length,
+/// so positions of errors are kept, whose value is stored in calls given in argument.
+/// Arguments of calls are evaluated as whole vectors with variables given in argument.
+/// If error occurs during evaluation of a call, an error message is stored in string contained in Result output
+fn replace_vector_calls(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    calls: &mut VectorCalls,
+) -> Result<String, String> {
+    let mut replaced: String = String::with_capacity(expression.len());
+    let mut end: usize = 0;
+
+    for (start, c) in expression.char_indices() {
+        if start < end
+            || !c.is_alphabetic()
+            || expression[..start]
+                .chars()
+                .next_back()
+                .is_some_and(is_word_char)
+        {
+            continue;
+        }
+
+        let name: &str = expression[start..]
+            .split(|c: char| !is_word_char(c))
+            .next()
+            .unwrap_or_default();
+        let after_name: &str = &expression[start + name.len()..];
+
+        if !is_vector_function(name) || !after_name.trim_start().starts_with('(') {
+            continue;
+        }
+
+        // Arguments are separated by commas outside parenthesis and brackets
+        let open: usize = expression.len() - after_name.trim_start().len();
+        let mut args: Vec<(usize, usize)> = Vec::new();
+        let mut arg_start: usize = open + 1;
+        let mut depth: usize = 0;
+        let mut close: Option<usize> = None;
+
+        for (index, c) in expression[open + 1..].char_indices() {
+            let index: usize = open + 1 + index;
+
+            match c {
+                '(' | '[' => depth += 1,
+                ',' | ')' if depth == 0 => {
+                    args.push((arg_start, index));
+                    arg_start = index + 1;
+
+                    if c == ')' {
+                        close = Some(index);
+                        break;
+                    }
+                }
+                ')' | ']' => depth = depth.saturating_sub(1),
+                _ => (),
+            }
+        }
+
+        let close: usize =
+            close.ok_or_else(|| format!("Unclosed parenthesis at position {open}"))?;
+
+        let value: Value = if HIGHER_ORDER_FUNCTIONS.contains(&name) {
+            apply_higher_order_function(name, expression, &args, variables, start)?
+        } else if name == "minimize" {
+            let (argmin, min): (f64, f64) = calculus::evaluate_minimization(
+                expression,
+                &args,
+                start,
+                variables,
+                |bound: &str, variables: &HashMap<String, f64>| match evaluate_vector(
+                    bound, variables,
+                )? {
+                    Value::Scalar(bound) => Ok(bound),
+                    _ => Err(format!(
+                        "Bounds of minimize function must be scalars at position {start}"
+                    )),
+                },
+            )?;
+            Value::Vector(vec![argmin, min])
+        } else {
+            let args: Vec<Value> = args
+                .iter()
+                .map(|&(arg_start, arg_end)| {
+                    let arg: String = " ".repeat(arg_start) + &expression[arg_start..arg_end];
+                    return evaluate_vector(&arg, variables);
+                })
+                .collect::<Result<Vec<Value>, String>>()?;
+
+            apply_vector_function(name, &args)
+                .map_err(|message| format!("{message} at position {start}"))?
+        };
+
+        replaced.push_str(&expression[end..start]);
+        replaced.push_str(&calls.store(value, close + 1 - start));
+        end = close + 1;
+    }
+
+    replaced.push_str(&expression[end..]);
+    return Ok(replaced);
+}
+
+/// Check if a char can be part of an operand of range, which is a number or a name
+fn is_word_char(c: char) -> bool {
+    return c.is_alphanumeric() || c == '_';
+}
+
+/// Get position of the first char of operand ending at position given in argument.
+/// Operand is a number, a name, a vector literal or an expression in parenthesis
+fn operand_start(expression: &str, end: usize) -> usize {
+    let before: &str = expression[..end].trim_end();
+    let mut start: usize = before.len();
+
+    if before.ends_with([')', ']']) {
+        let mut depth: usize = 0;
+
+        for (index, c) in before.char_indices().rev() {
+            match c {
+                ')' | ']' => depth += 1,
+                '(' | '[' if depth == 1 => {
+                    start = index;
+                    break;
+                }
+                '(' | '[' => depth -= 1,
+                _ => (),
+            }
+        }
+    } else {
+        start = before
+            .trim_end_matches(|c: char| is_word_char(c) || c == '.')
+            .len();
+
+        // Operand stops at dots of a range
+        if let Some(dots) = before[start..].rfind("..") {
+            start += dots + 2;
+        }
+    }
+
+    return start;
+}
+
+/// Check if a char ends an operand of range, like a comma or a comparison operator
+fn is_range_delimiter(c: char) -> bool {
+    return matches!(c, ',' | ';' | '?' | ':' | '<' | '>' | '=' | '!' | '&' | '|');
+}
+
+/// Get position of the first char of operand of range ending at position given in argument.
+/// Ranges bind less tightly than arithmetic operators, so operand is the arithmetic expression
+/// following an opening parenthesis or bracket, a comma or a comparison operator
+fn range_operand_start(expression: &str, end: usize) -> usize {
+    let mut depth: usize = 0;
+
+    for (index, c) in expression[..end].char_indices().rev() {
+        match c {
+            ')' | ']' => depth += 1,
+            '(' | '[' if depth == 0 => return index + 1,
+            '(' | '[' => depth -= 1,
+            _ if depth == 0 && is_range_delimiter(c) => return index + 1,
+            _ => (),
+        }
+    }
+
+    return 0;
+}
+
+/// Get position following the last char of operand of range starting at position given in argument.
+/// Ranges bind less tightly than arithmetic operators, so operand is the arithmetic expression
+/// preceding a closing parenthesis or bracket, a comma or a comparison operator
+fn range_operand_end(expression: &str, start: usize) -> usize {
+    let mut depth: usize = 0;
+
+    for (index, c) in expression[start..].char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' if depth == 0 => return start + index,
+            ')' | ']' => depth -= 1,
+            _ if depth == 0 && is_range_delimiter(c) => return start + index,
+            _ => (),
+        }
+    }
+
+    return expression.len();
+}
+
+/// Replace each range of expression given in argument, like 1..10, by a word of the same length,
+/// so positions of errors are kept, whose values are stored in calls given in argument.
+/// Bounds of ranges are evaluated with variables and calls given in argument.
+/// If a bound is not a scalar or if range is empty, an error message is stored in string contained in Result output
+fn replace_ranges(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    calls: &mut VectorCalls,
+) -> Result<String, String> {
+    let mut replaced: String = String::with_capacity(expression.len());
+    let mut end: usize = 0;
+
+    for (dots, _) in expression.match_indices("..") {
+        if dots < end {
+            continue;
+        }
+
+        let start: usize = range_operand_start(expression, dots).max(end);
+        let stop: usize = range_operand_end(expression, dots + 2);
+        let mut bounds: [f64; 2] = [0.0; 2];
+
+        for (bound, (bound_start, bound_end)) in
+            bounds.iter_mut().zip([(start, dots), (dots + 2, stop)])
+        {
+            let operand: String = " ".repeat(bound_start) + &expression[bound_start..bound_end];
+
+            *bound = match evaluate_lane(&operand, variables, calls, 0)? {
+                (value, None) => value,
+                _ => {
+                    return Err(format!(
+                        "Bounds of range must be scalars at position {dots}"
+                    ))
+                }
+            };
+        }
+
+        // Range ends at the greatest integer offset from its start not exceeding its stop
+        let length: f64 = (bounds[1] - bounds[0]).floor() + 1.0;
+
+        if length.is_nan() || length < 1.0 {
+            return Err(format!(
+                "Range from {} to {} is empty at position {dots}",
+                bounds[0], bounds[1]
+            ));
+        }
+
+        let max_length: usize = Limits::default().max_tokens;
+
+        if length > max_length as f64 {
+            return Err(format!(
+                "Range from {} to {} exceeds limit of {max_length} elements at position {dots}",
+                bounds[0], bounds[1]
+            ));
+        }
+
+        let values: Vec<f64> = (0..length as usize)
+            .map(|index| bounds[0] + index as f64)
+            .collect();
+
+        replaced.push_str(&expression[end..start]);
+        replaced.push_str(&calls.store(Value::Vector(values), stop - start));
+        end = stop;
+    }
+
+    replaced.push_str(&expression[end..]);
+    return Ok(replaced);
+}
+
+/// Replace each indexing of a vector of expression given in argument, like v[2], by a word of the same length,
+/// so positions of errors are kept, whose value is stored in calls given in argument.
+/// Indexed vector and index are evaluated with variables and calls given in argument.
+/// If index is not valid for vector, an error message is stored in string contained in Result output
+fn replace_indexes(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    calls: &mut VectorCalls,
+) -> Result<String, String> {
+    let mut replaced: String = String::with_capacity(expression.len());
+    let mut end: usize = 0;
+
+    for (open, _) in expression.match_indices('[') {
+        // Bracket opens an index if it follows an operand, and a vector literal otherwise
+        let indexed: bool = expression[..open]
+            .trim_end()
+            .ends_with(|c: char| is_word_char(c) || c == ')' || c == ']');
+
+        if open < end || !indexed {
+            continue;
+        }
+
+        let start: usize = operand_start(expression, open).max(end);
+        let mut depth: usize = 0;
+        let close: usize = expression[open..]
+            .char_indices()
+            .find_map(|(index, c)| match c {
+                '(' | '[' => {
+                    depth += 1;
+                    None
+                }
+                ')' | ']' if depth == 1 => Some(open + index),
+                ')' | ']' => {
+                    depth -= 1;
+                    None
+                }
+                _ => None,
+            })
+            .ok_or_else(|| format!("Unclosed bracket at position {open}"))?;
+
+        let vector: String = " ".repeat(start) + &expression[start..open];
+        let index: String = " ".repeat(open + 1) + &expression[open + 1..close];
+
+        let value: f64 = match (
+            evaluate_replaced(&vector, variables, calls)?,
+            evaluate_replaced(&index, variables, calls)?,
+        ) {
+            (Value::Vector(values), Value::Scalar(index)) => vector_index(&values, index)
+                .map_err(|message| format!("{message} at position {open}"))?,
+            (Value::Vector(_), _) => {
+                return Err(format!(
+                    "Index of vector must be a scalar at position {open}"
+                ))
+            }
+            _ => return Err(format!("Only vectors can be indexed at position {open}")),
+        };
+
+        replaced.push_str(&expression[end..start]);
+        replaced.push_str(&calls.store(Value::Scalar(value), close + 1 - start));
+        end = close + 1;
+    }
+
+    replaced.push_str(&expression[end..]);
+    return Ok(replaced);
+}
+
+/// Replace each vector literal of expression given in argument, like [x, 2 * x], by a word of the same length,
+/// so positions of errors are kept, whose values are stored in calls given in argument.
+/// Elements of literal are evaluated as expressions with variables and calls given in argument.
+/// If an element is not a scalar, an error message is stored in string contained in Result output
+fn replace_vector_literals(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    calls: &mut VectorCalls,
+) -> Result<String, String> {
+    let mut replaced: String = String::with_capacity(expression.len());
+    let mut end: usize = 0;
+
+    for (open, _) in expression.match_indices('[') {
+        if open < end {
+            continue;
+        }
+
+        // Elements are separated by commas outside parenthesis and brackets
+        let mut elements: Vec<(usize, usize)> = Vec::new();
+        let mut element_start: usize = open + 1;
+        let mut depth: usize = 0;
+        let mut close: Option<usize> = None;
+
+        for (index, c) in expression[open + 1..].char_indices() {
+            let index: usize = open + 1 + index;
+
+            match c {
+                '(' | '[' => depth += 1,
+                ',' | ']' if depth == 0 => {
+                    elements.push((element_start, index));
+                    element_start = index + 1;
+
+                    if c == ']' {
+                        close = Some(index);
+                        break;
+                    }
+                }
+                ')' | ']' => depth = depth.saturating_sub(1),
+                _ => (),
+            }
+        }
+
+        let close: usize = close.ok_or_else(|| format!("Unclosed bracket at position {open}"))?;
+
+        // Empty literal has no element
+        if let [(start, end)] = elements[..] {
+            if expression[start..end].trim().is_empty() {
+                elements.clear();
+            }
+        }
+
+        let mut values: Vec<f64> = Vec::with_capacity(elements.len());
+
+        for (start, end) in elements {
+            let element: String = " ".repeat(start) + &expression[start..end];
+
+            match evaluate_replaced(&element, variables, calls)? {
+                Value::Scalar(value) => values.push(value),
+                _ => {
+                    return Err(format!(
+                        "Elements of vector must be scalars at position {}",
+                        element.len() - element.trim_start().len()
+                    ))
+                }
+            }
+        }
+
+        replaced.push_str(&expression[end..open]);
+        replaced.push_str(&calls.store(Value::Vector(values), close + 1 - open));
+        end = close + 1;
+    }
+
+    replaced.push_str(&expression[end..]);
+    return Ok(replaced);
+}
+
+/// Evaluate expression given in argument where each vector is replaced by its element in lane given in argument,
+/// giving its value with the length of its vectors, which is None if expression has no vector.
+/// Values of calls of vector functions are given by calls in argument.
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+fn evaluate_lane(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    calls: &VectorCalls,
+    lane: usize,
+) -> Result<(f64, Option<usize>), String> {
+    let infix_tokens = Infix::new(expression, |name: &str| {
+        calls
+            .scalars
+            .get(name)
+            .or_else(|| variables.get(name))
+            .map(|&value| Token::new_number(value))
+    })
+    .with_arrays(&calls.vectors)
+    .with_vector_lane(lane);
+    let mut postfix_tokens = Postfix::new(infix_tokens);
+    let values: OperandStack = evaluator::postfix_evaluation_stream(postfix_tokens.by_ref(), &[])
+        .map_err(|message| postfix_tokens.tokens().locate(message))?;
+
+    match values.first() {
+        Some(&value) => Ok((value, postfix_tokens.tokens().vector_length())),
+        None => Err(String::from("Empty expression")),
+    }
+}
+
+/// Evaluate an expression holding vector literals like [1, 2, 3] * 2 + [4, 5, 6],
+/// whose operators and functions apply to vectors element by element.
+/// Elements of vector literals are expressions, like [x, 2 * x].
+/// Scalars and vectors of a single element are broadcast to the length of other vectors,
+/// while vectors of different lengths cannot be combined.
+/// Arrays given as breakpoints of lookup functions are not vectors.
+/// Functions dot and cross give the dot product and the cross product of whole vectors,
+/// the cross product being only defined for vectors of length 3.
+/// Aggregation functions like sum or mean take whole vectors and give a scalar.
+/// Ranges like 1..10 give vectors from start by steps of 1 up to stop included, binding less tightly than
+/// arithmetic operators, and they cannot have more elements than the number of tokens of default limits,
+/// while function range(start, stop, step) gives a vector from start to stop excluded, step being 1 by default.
+/// Elements of vectors are indexed from 0, like v[2] or index(v, 2), an index out of range giving an error.
+/// Functions map and filter take a vector and a lambda like x -> x^2, map giving the values of the lambda
+/// for each element and filter keeping the elements for which the lambda holds, like x -> x > 0.
+/// Functions sort, cumsum and diff give the sorted elements, the cumulative sums and the differences
+/// between consecutive elements of a vector, the differences having one element less.
+/// Functions linspace(a, b, n) and logspace(a, b, n) give n values evenly spaced from a to b included,
+/// and their powers of 10 for logspace, n being limited like the length of ranges.
+/// Functions norm, norm1 and norminf give the euclidean norm, the sum of absolute values
+/// and the maximum of absolute values of a non-empty vector.
+/// Function minimize(expr, var, a, b) gives the point of minimum of subexpression between a and b
+/// and its value there, found by Brent method.
+/// Function roots(c0, c1, c2, ...) gives the real roots of polynomial c0 + c1 * x + c2 * x^2 + ...
+/// in increasing order, coefficients being scalars or vectors.
+///
+/// If error occurs during evaluation, an error message is stored in string contained in Result output.
+///
+/// # Example
+/// ```
+/// use taz::Value;
+/// use std::collections::HashMap;
+///
+/// let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 2.0)]);
+///
+/// let result: Result<Value, String> = taz::evaluate_vector("[1, 2, 3] * x + [4, 5, 6]", &variables);
+/// assert_eq!(result, Ok(Value::Vector(vec![6.0, 9.0, 12.0])));
+///
+/// let result: Result<Value, String> = taz::evaluate_vector("sum((1..100)^2)", &variables);
+/// assert_eq!(result, Ok(Value::Scalar(338350.0)));
+/// ```
+pub fn evaluate_vector(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+) -> Result<Value, String> {
+    let mut calls: VectorCalls = VectorCalls::default();
+    let expression: String = replace_vector_calls(expression, variables, &mut calls)?;
+
+    return evaluate_replaced(&expression, variables, &mut calls);
+}
+
+/// Evaluate expression given in argument whose calls of vector functions are already replaced,
+/// replacing its indexes and its ranges before evaluating it for each element of its vectors.
+/// Values of replaced words are given by calls in argument.
+/// If error occurs during evaluation, an error message is stored in string contained in Result output
+fn evaluate_replaced(
+    expression: &str,
+    variables: &HashMap<String, f64>,
+    calls: &mut VectorCalls,
+) -> Result<Value, String> {
+    let expression: String = replace_indexes(expression, variables, calls)?;
+    let expression: String = replace_ranges(&expression, variables, calls)?;
+    let expression: &str = &replace_vector_literals(&expression, variables, calls)?;
+    let calls: &VectorCalls = calls;
+
+    // A single vector is given as it is, so it can be empty
+    if let Some(values) = calls.vectors.get(expression.trim()) {
+        return Ok(Value::Vector(values.clone()));
+    }
+
+    let (first, length) = evaluate_lane(expression, variables, calls, 0)?;
+
+    let length: usize = match length {
+        Some(length) => length,
+        None => return Ok(Value::Scalar(first)),
+    };
+
+    let mut values: Vec<f64> = Vec::with_capacity(length);
+    values.push(first);
+
+    for lane in 1..length {
+        values.push(evaluate_lane(expression, variables, calls, lane)?.0);
+    }
+
+    return Ok(Value::Vector(values));
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_vector() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 3.0)]);
+
+        assert_eq!(
+            evaluate_vector("[1, 2, 3] * 2 + [4, 5, 6]", &variables),
+            Ok(Value::Vector(vec![6.0, 9.0, 12.0]))
+        );
+        assert_eq!(
+            evaluate_vector("clamp([1, 5, 9], 2, [4, 6, 8]) - [x]", &variables),
+            Ok(Value::Vector(vec![-1.0, 2.0, 5.0]))
+        );
+        assert_eq!(
+            evaluate_vector("[x, 2] + [1 + 1, max(x, 4)]", &variables),
+            Ok(Value::Vector(vec![5.0, 6.0]))
+        );
+        assert_eq!(
+            evaluate_vector("sin([0, pi / 2])", &variables),
+            Ok(Value::Vector(vec![0.0, 1.0]))
+        );
+        assert_eq!(evaluate_vector("[]", &variables), Ok(Value::Vector(vec![])));
+        assert_eq!(
+            evaluate_vector("[1, [2, 3]]", &variables),
+            Err(String::from(
+                "Elements of vector must be scalars at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("[1, y]", &variables),
+            Err(String::from("Unknown name y at position 4"))
+        );
+        assert_eq!(
+            evaluate_vector("clamp([1, 5, 9], 2, [4, 6, 8]) - [pi]", &variables),
+            Ok(Value::Vector(vec![
+                2.0 - std::f64::consts::PI,
+                5.0 - std::f64::consts::PI,
+                8.0 - std::f64::consts::PI
+            ]))
+        );
+        assert_eq!(
+            evaluate_vector("lookup([5, 15], [0, 10, 20], [1, 2, 4])", &variables),
+            Ok(Value::Vector(vec![1.5, 3.0]))
+        );
+        assert_eq!(
+            evaluate_vector("[1, 2, 3] - mean([1, 2, 3])", &variables),
+            Ok(Value::Vector(vec![-1.0, 0.0, 1.0]))
+        );
+        assert_eq!(evaluate_vector("x^2", &variables), Ok(Value::Scalar(9.0)));
+
+        assert_eq!(
+            evaluate_vector("[1, 2, 3] + [4, 5]", &variables),
+            Err(String::from(
+                "Vectors of lengths 3 and 2 cannot be broadcast at position 12"
+            ))
+        );
+        assert!(evaluate_vector("ln([1, -1])", &variables).is_err());
+    }
+
+    #[test]
+    fn test_evaluate_vector_functions() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 2.0)]);
+
+        assert_eq!(
+            evaluate_vector("dot([1, 2, 3], [4, 5, 6] * x) + 1", &variables),
+            Ok(Value::Scalar(65.0))
+        );
+        assert_eq!(
+            evaluate_vector("cross([1, 0, 0], [0, 1, 0]) * x - [1, 2, 3]", &variables),
+            Ok(Value::Vector(vec![-1.0, -2.0, -1.0]))
+        );
+        assert_eq!(
+            evaluate_vector("dot(cross([1, 2, 3], [4, 5, 6]), [1, 2, 3])", &variables),
+            Ok(Value::Scalar(0.0))
+        );
+        assert_eq!(
+            evaluate_vector("sum(cross([0, 0, 2], [2, 0, 0]))", &variables),
+            Ok(Value::Scalar(4.0))
+        );
+
+        assert_eq!(
+            evaluate_vector("1 + dot([1, 2, 3], [4, 5])", &variables),
+            Err(String::from(
+                "Dot product needs vectors of the same length but got lengths 3 and 2 at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("cross([1, 2], [3, 4])", &variables),
+            Err(String::from(
+                "Cross product needs vectors of length 3 but got lengths 2 and 2 at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("dot(x, [1])", &variables),
+            Err(String::from(
+                "Arguments of dot function must be vectors at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("dot([1], [2]", &variables),
+            Err(String::from("Unclosed parenthesis at position 3"))
+        );
+        assert_eq!(
+            evaluate_vector("dot([1], y)", &variables),
+            Err(String::from("Unknown name y at position 9"))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_vector_ranges() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 2.0)]);
+
+        assert_eq!(
+            evaluate_vector("sum((1..100)^2)", &variables),
+            Ok(Value::Scalar(338350.0))
+        );
+        assert_eq!(
+            evaluate_vector("(1..3) * x + 1", &variables),
+            Ok(Value::Vector(vec![3.0, 5.0, 7.0]))
+        );
+        assert_eq!(
+            evaluate_vector("(-1..x) + (x..(2 * x + 1))", &variables),
+            Ok(Value::Vector(vec![1.0, 3.0, 5.0, 7.0]))
+        );
+        assert_eq!(
+            evaluate_vector("0.5..3", &variables),
+            Ok(Value::Vector(vec![0.5, 1.5, 2.5]))
+        );
+        assert_eq!(
+            evaluate_vector("sum(1..3.5)", &variables),
+            Ok(Value::Scalar(6.0))
+        );
+        assert_eq!(
+            evaluate_vector("-x..x^2 - 1", &variables),
+            Ok(Value::Vector(vec![-2.0, -1.0, 0.0, 1.0, 2.0, 3.0]))
+        );
+        assert_eq!(
+            evaluate_vector("mean(1..4, 10) - max(range(0, 1, 0.25))", &variables),
+            Ok(Value::Scalar(3.25))
+        );
+        assert_eq!(
+            evaluate_vector("range(5, 0, -2)", &variables),
+            Ok(Value::Vector(vec![5.0, 3.0, 1.0]))
+        );
+        assert_eq!(
+            evaluate_vector("range(x, 5)", &variables),
+            Ok(Value::Vector(vec![2.0, 3.0, 4.0]))
+        );
+
+        assert_eq!(
+            evaluate_vector("1 + (3..1)", &variables),
+            Err(String::from("Range from 3 to 1 is empty at position 6"))
+        );
+        assert_eq!(
+            evaluate_vector("1..10^12", &variables),
+            Err(String::from(
+                "Range from 1 to 1000000000000 exceeds limit of 10000 elements at position 1"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("range(0, 1, 1 / 10^9)", &variables),
+            Err(String::from(
+                "Range from 0 to 1 by step 0.000000001 exceeds limit of 10000 elements at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("[1, 2]..3", &variables),
+            Err(String::from(
+                "Bounds of range must be scalars at position 6"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("range(0, 1, 0)", &variables),
+            Err(String::from("Step of range function is null at position 0"))
+        );
+        assert_eq!(
+            evaluate_vector("range(1)", &variables),
+            Err(String::from(
+                "Function range expects 2 or 3 arguments but got 1 at position 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_vector_indexes() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("x"), 2.0)]);
+
+        assert_eq!(
+            evaluate_vector("[4, 5, 6][x] * 2", &variables),
+            Ok(Value::Scalar(12.0))
+        );
+        assert_eq!(
+            evaluate_vector(
+                "-(1..10)[[0, 3][1]] + index(range(0, 1, 0.25), 1)",
+                &variables
+            ),
+            Ok(Value::Scalar(-3.75))
+        );
+        assert_eq!(
+            evaluate_vector("cross([1, 0, 0], [0, 1, 0])[2] + (x..5)[2]", &variables),
+            Ok(Value::Scalar(5.0))
+        );
+        assert_eq!(
+            evaluate_vector("1..[3, 4][0]", &variables),
+            Ok(Value::Vector(vec![1.0, 2.0, 3.0]))
+        );
+
+        assert_eq!(
+            evaluate_vector("1 + [1, 2, 3][3]", &variables),
+            Err(String::from(
+                "Index 3 is out of range of vector of length 3 at position 13"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("index([1, 2], -1)", &variables),
+            Err(String::from(
+                "Index -1 is out of range of vector of length 2 at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("[1, 2][0.5]", &variables),
+            Err(String::from(
+                "Index 0.5 of vector is not an integer at position 6"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("1..3[0]", &variables),
+            Err(String::from("Only vectors can be indexed at position 4"))
+        );
+        assert_eq!(
+            evaluate_vector("x[0]", &variables),
+            Err(String::from("Only vectors can be indexed at position 1"))
+        );
+        assert_eq!(
+            evaluate_vector("[1, 2][[0, 1]]", &variables),
+            Err(String::from(
+                "Index of vector must be a scalar at position 6"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("[1, 2][0", &variables),
+            Err(String::from("Unclosed bracket at position 6"))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_vector_higher_order_functions() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("k"), 2.0)]);
+
+        assert_eq!(
+            evaluate_vector("map([1, 2, 3], x -> x^2 + k)", &variables),
+            Ok(Value::Vector(vec![3.0, 6.0, 11.0]))
+        );
+        assert_eq!(
+            evaluate_vector("filter(-2..3, x -> x > 0) * k", &variables),
+            Ok(Value::Vector(vec![2.0, 4.0, 6.0]))
+        );
+        assert_eq!(
+            evaluate_vector("sum(filter(1..10, n -> abs(n - 5) <= k))", &variables),
+            Ok(Value::Scalar(25.0))
+        );
+        assert_eq!(
+            evaluate_vector("filter(map(1..4, x -> x - k), x -> x)", &variables),
+            Ok(Value::Vector(vec![-1.0, 1.0, 2.0]))
+        );
+        assert_eq!(
+            evaluate_vector("filter([1, 2, 1], x -> [5, 6, 7][x] != 6)", &variables),
+            Ok(Value::Vector(vec![2.0]))
+        );
+        assert_eq!(
+            evaluate_vector("filter(1..3, x -> x > 5)", &variables),
+            Ok(Value::Vector(vec![]))
+        );
+
+        assert_eq!(
+            evaluate_vector("map([1, 2], x ^ 2)", &variables),
+            Err(String::from(
+                "Second argument of map function must be a lambda like x -> x^2 at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("1 + map(k, x -> x)", &variables),
+            Err(String::from(
+                "First argument of map function must be a vector at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("map([1, 2], x -> x + [0, 1])", &variables),
+            Err(String::from(
+                "Body of lambda must give a scalar at position 17"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("map([1, 2], x -> ln(x - 1))", &variables),
+            Err(String::from(
+                "Argument of ln function is negative or null at position 25"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("filter([1, 2])", &variables),
+            Err(String::from(
+                "Function filter expects 2 arguments but got 1 at position 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_vector_list_operations() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("k"), 2.0)]);
+
+        assert_eq!(
+            evaluate_vector("sort([3, -1, 2, 0]) * k", &variables),
+            Ok(Value::Vector(vec![-2.0, 0.0, 4.0, 6.0]))
+        );
+        assert_eq!(
+            evaluate_vector("cumsum(1..4)", &variables),
+            Ok(Value::Vector(vec![1.0, 3.0, 6.0, 10.0]))
+        );
+        assert_eq!(
+            evaluate_vector("diff([1, 4, 9, 16])", &variables),
+            Ok(Value::Vector(vec![3.0, 5.0, 7.0]))
+        );
+        assert_eq!(
+            evaluate_vector("diff(cumsum([5, 1, 2]))[0] + sort([2, 1])[1]", &variables),
+            Ok(Value::Scalar(3.0))
+        );
+        assert_eq!(
+            evaluate_vector("diff([3])", &variables),
+            Ok(Value::Vector(vec![]))
+        );
+
+        assert_eq!(
+            evaluate_vector("1 + sort(k)", &variables),
+            Err(String::from(
+                "Argument of sort function must be a vector at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("cumsum([1], [2])", &variables),
+            Err(String::from(
+                "Function cumsum expects 1 argument but got 2 at position 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_vector_spaces() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("n"), 5.0)]);
+
+        assert_eq!(
+            evaluate_vector("linspace(0, 1, n)", &variables),
+            Ok(Value::Vector(vec![0.0, 0.25, 0.5, 0.75, 1.0]))
+        );
+        assert_eq!(
+            evaluate_vector("logspace(0, 3, 4)", &variables),
+            Ok(Value::Vector(vec![1.0, 10.0, 100.0, 1000.0]))
+        );
+        assert_eq!(
+            evaluate_vector(
+                "sum(linspace(2, -2, n)^2) + linspace(7, 9, 1)[0]",
+                &variables
+            ),
+            Ok(Value::Scalar(17.0))
+        );
+
+        assert_eq!(
+            evaluate_vector("linspace(0, 1, 2.5)", &variables),
+            Err(String::from(
+                "Number of points of linspace function must be a positive integer but got 2.5 at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("1 + logspace(0, 1, 0)", &variables),
+            Err(String::from(
+                "Number of points of logspace function must be a positive integer but got 0 at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("linspace(0, 1, 10^9)", &variables),
+            Err(String::from(
+                "Number of points of linspace function exceeds limit of 10000 elements at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("linspace(0, 1)", &variables),
+            Err(String::from(
+                "Function linspace expects 3 arguments but got 2 at position 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_vector_norms() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("k"), 2.0)]);
+
+        assert_eq!(
+            evaluate_vector("norm([3, -4])", &variables),
+            Ok(Value::Scalar(5.0))
+        );
+        assert_eq!(
+            evaluate_vector("norm1([3, -4]) * k", &variables),
+            Ok(Value::Scalar(14.0))
+        );
+        assert_eq!(
+            evaluate_vector("norminf([3, -4, 1] * k)", &variables),
+            Ok(Value::Scalar(8.0))
+        );
+        assert_eq!(
+            evaluate_vector("[3, 4] / norm([3, 4])", &variables),
+            Ok(Value::Vector(vec![0.6, 0.8]))
+        );
+
+        assert_eq!(
+            evaluate_vector("1 + norm(filter([1, 2], x -> x > 2))", &variables),
+            Err(String::from(
+                "Function norm needs a non-empty vector at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("norm1(k)", &variables),
+            Err(String::from(
+                "Argument of norm1 function must be a vector at position 0"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_vector_minimize() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("k"), 2.0)]);
+
+        match evaluate_vector("minimize(x^2 - k * x, x, 0, 3)", &variables) {
+            Ok(Value::Vector(values)) => {
+                assert!((values[0] - 1.0).abs() < 1e-7);
+                assert!((values[1] + 1.0).abs() < 1e-12);
+            }
+            _ => assert!(false),
+        }
+        match evaluate_vector("minimize(cos(t), t, k, 2 * k)[0] - pi", &variables) {
+            Ok(Value::Scalar(value)) => assert!(value.abs() < 1e-7),
+            _ => assert!(false),
+        }
+
+        // Minimum of a monotonic function is at a bound
+        match evaluate_vector("minimize(exp(x), x, -1, k)", &variables) {
+            Ok(Value::Vector(values)) => assert!((values[0] + 1.0).abs() < 1e-7),
+            _ => assert!(false),
+        }
+
+        assert_eq!(
+            evaluate_vector("minimize(x^2, x, [0, 1], 3)", &variables),
+            Err(String::from(
+                "Bounds of minimize function must be scalars at position 0"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("1 + minimize(x^2, 2, 0, 3)", &variables),
+            Err(String::from(
+                "Second argument of minimize function must be a variable at position 4"
+            ))
+        );
+        assert_eq!(
+            evaluate_vector("minimize(x^2 + y, x, 0, 3)", &variables),
+            Err(String::from("Missing value of variable y at position 9"))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_vector_roots() {
+        let variables: HashMap<String, f64> = HashMap::from([(String::from("k"), 6.0)]);
+
+        match evaluate_vector("roots(k, -5, 1)", &variables) {
+            Ok(Value::Vector(roots)) => {
+                assert_eq!(roots.len(), 2);
+                assert!((roots[0] - 2.0).abs() < 1e-12);
+                assert!((roots[1] - 3.0).abs() < 1e-12);
+            }
+            _ => assert!(false),
+        }
+        match evaluate_vector("roots([-6, 11, -6, 1])[2] * k", &variables) {
+            Ok(Value::Scalar(root)) => assert!((root - 18.0).abs() < 1e-10),
+            _ => assert!(false),
+        }
+
+        assert_eq!(
+            evaluate_vector("roots(1, 0, 1)", &variables),
+            Ok(Value::Vector(Vec::new()))
+        );
+        assert_eq!(
+            evaluate_vector("1 + sum(roots(0, 0, 0))", &variables),
+            Err(String::from(
+                "Polynomial with null coefficients has infinitely many roots at position 8"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_value_display() {
+        assert_eq!(Value::Scalar(2.5).to_string(), "2.5");
+        assert_eq!(Value::Vector(vec![1.0, -2.0]).to_string(), "[1, -2]");
+    }
+}
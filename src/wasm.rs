@@ -0,0 +1,136 @@
+#![allow(clippy::needless_return)]
+
+use super::compiler::CompiledExpression;
+use super::error::Error;
+
+use js_sys::{Array, Object, Reflect};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// Get kind of error given in argument, as given to JavaScript in kind property of error objects
+fn error_kind(error: &Error) -> &'static str {
+    match error {
+        Error::Message(_) => "message",
+        Error::LimitExceeded(_) => "limit",
+        Error::BudgetExceeded => "budget",
+        Error::Overflow(_) => "overflow",
+    }
+}
+
+/// Create JavaScript error object from error given in argument.
+/// Its name is TazError, its message is the message of error and its kind property is the kind of error,
+/// so JavaScript code can catch it like any error and tell kinds of errors apart
+fn js_error(error: Error) -> JsValue {
+    let object: js_sys::Error = js_sys::Error::new(&error.to_string());
+    object.set_name("TazError");
+
+    // Setting a property of a freshly created error object cannot fail
+    let _ = Reflect::set(&object, &"kind".into(), &error_kind(&error).into());
+
+    return object.into();
+}
+
+/// Read values of variables from JavaScript object given in argument, like { x: 1, y: 2 },
+/// undefined or null giving no variable.
+/// If a value is not a number, an error is given in Result output
+fn read_variables(variables: &JsValue) -> Result<HashMap<String, f64>, Error> {
+    let mut values: HashMap<String, f64> = HashMap::new();
+
+    if variables.is_undefined() || variables.is_null() {
+        return Ok(values);
+    }
+
+    for entry in Object::entries(&Object::from(variables.clone())).iter() {
+        let entry: Array = Array::from(&entry);
+        let name: String = entry.get(0).as_string().unwrap_or_default();
+
+        match entry.get(1).as_f64() {
+            Some(value) => values.insert(name, value),
+            None => {
+                return Err(Error::Message(format!(
+                    "Value of variable {name} is not a number"
+                )))
+            }
+        };
+    }
+
+    return Ok(values);
+}
+
+/// Evaluate an expression from JavaScript, values of variables being given by an object like { x: 1 }.
+/// If error occurs during evaluation, a TazError is thrown
+#[wasm_bindgen(js_name = evaluate)]
+pub fn evaluate(expression: &str, variables: JsValue) -> Result<f64, JsValue> {
+    let variables: HashMap<String, f64> = read_variables(&variables).map_err(js_error)?;
+
+    return super::evaluate(expression, &variables)
+        .map_err(|message| js_error(Error::Message(message)));
+}
+
+/// Expression compiled from JavaScript, to be evaluated many times with different values of variables
+#[wasm_bindgen(js_name = CompiledExpression)]
+pub struct WasmExpression {
+    compiled: CompiledExpression,
+}
+
+#[wasm_bindgen(js_class = CompiledExpression)]
+impl WasmExpression {
+    /// Get names of variables of expression, ordered by slot
+    #[wasm_bindgen(getter)]
+    pub fn variables(&self) -> Vec<String> {
+        return self.compiled.variables().to_vec();
+    }
+
+    /// Evaluate expression with values of variables given in array, ordered by slot.
+    /// If error occurs during evaluation, a TazError is thrown
+    pub fn evaluate(&self, values: &[f64]) -> Result<f64, JsValue> {
+        return self
+            .compiled
+            .evaluate(values)
+            .map_err(|message| js_error(Error::Message(message)));
+    }
+
+    /// Evaluate expression with values of variables given by an object like { x: 1 }.
+    /// If error occurs during evaluation, a TazError is thrown
+    #[wasm_bindgen(js_name = evaluateWithVariables)]
+    pub fn evaluate_with_variables(&self, variables: JsValue) -> Result<f64, JsValue> {
+        let variables: HashMap<String, f64> = read_variables(&variables).map_err(js_error)?;
+
+        return self
+            .compiled
+            .evaluate_with_variables(&variables)
+            .map_err(|message| js_error(Error::Message(message)));
+    }
+}
+
+/// Compile an expression from JavaScript.
+/// If expression is invalid, a TazError is thrown
+#[wasm_bindgen(js_name = compile)]
+pub fn compile(expression: &str) -> Result<WasmExpression, JsValue> {
+    match CompiledExpression::new(expression) {
+        Ok(compiled) => Ok(WasmExpression { compiled }),
+        Err(message) => Err(js_error(Error::Message(message))),
+    }
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use super::super::limits::Limit;
+
+    #[test]
+    fn test_error_kind() {
+        assert_eq!(
+            error_kind(&Error::Message(String::from("Division by zero"))),
+            "message"
+        );
+        assert_eq!(
+            error_kind(&Error::LimitExceeded(Limit::Nesting(2))),
+            "limit"
+        );
+        assert_eq!(error_kind(&Error::BudgetExceeded), "budget");
+        assert_eq!(error_kind(&Error::Overflow(String::new())), "overflow");
+    }
+}
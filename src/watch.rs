@@ -0,0 +1,214 @@
+#![allow(clippy::needless_return)]
+
+use super::ast::{Ast, Node};
+
+use std::collections::HashMap;
+
+/// Subexpression whose value changed between two sets of values of variables
+#[derive(Debug, PartialEq, Clone)]
+pub struct Change {
+    /// Subexpression as written by syntax tree, like sqrt(x^2 + y^2)
+    pub expression: String,
+    /// Value of subexpression with old values of variables
+    pub old: f64,
+    /// Value of subexpression with new values of variables
+    pub new: f64,
+}
+
+impl Change {
+    /// Get difference between new and old values of subexpression
+    pub fn difference(&self) -> f64 {
+        return self.new - self.old;
+    }
+}
+
+/// Check if two values of a subexpression are different, NaN values being considered equal
+fn has_changed(old: f64, new: f64) -> bool {
+    return old != new && !(old.is_nan() && new.is_nan());
+}
+
+/// Evaluate node given in argument with both sets of values of variables,
+/// pushing in changes the node and its children whose value changed
+fn watch_node(
+    node: &Node,
+    contexts: [&HashMap<String, f64>; 2],
+    changes: &mut Vec<Change>,
+) -> Result<[f64; 2], String> {
+    let values: [f64; 2] = match node {
+        Node::Number(number) => [*number; 2],
+        Node::Constant(_, value) => [*value; 2],
+        Node::Variable(name) => {
+            let mut values: [f64; 2] = [0.0; 2];
+
+            for (value, variables) in values.iter_mut().zip(contexts) {
+                match variables.get(name) {
+                    Some(&variable) => *value = variable,
+                    None => return Err(format!("Missing value of variable {name}")),
+                }
+            }
+
+            values
+        }
+        Node::Unary(ops, operand) => watch_node(operand, contexts, changes)?.map(|v| ops.apply(v)),
+        Node::Binary(ops, left, right) => {
+            let [left_old, left_new] = watch_node(left, contexts, changes)?;
+            let [right_old, right_new] = watch_node(right, contexts, changes)?;
+
+            [
+                ops.apply(left_old, right_old)?,
+                ops.apply(left_new, right_new)?,
+            ]
+        }
+        Node::Call(fun, args) => {
+            let mut args_values: [Vec<f64>; 2] = [Vec::new(), Vec::new()];
+
+            for arg in args {
+                match arg {
+                    // Breakpoint arrays are given as their values followed by their length
+                    Node::Array(array) => {
+                        for values in args_values.iter_mut() {
+                            values.extend_from_slice(array);
+                            values.push(array.len() as f64);
+                        }
+                    }
+                    _ => {
+                        let arg_values: [f64; 2] = watch_node(arg, contexts, changes)?;
+
+                        for (values, value) in args_values.iter_mut().zip(arg_values) {
+                            values.push(value);
+                        }
+                    }
+                }
+            }
+
+            // Arguments of aggregation functions are given followed by their number
+            if fun.is_variadic() {
+                for values in args_values.iter_mut() {
+                    values.push(args.len() as f64);
+                }
+            }
+
+            [fun.apply(&args_values[0])?, fun.apply(&args_values[1])?]
+        }
+        Node::Array(_) | Node::Tuple(_) => {
+            return Err(String::from("Node cannot be evaluated as a single value"));
+        }
+    };
+
+    if !matches!(node, Node::Number(_) | Node::Constant(_, _)) && has_changed(values[0], values[1])
+    {
+        changes.push(Change {
+            expression: node.to_string(),
+            old: values[0],
+            new: values[1],
+        });
+    }
+
+    return Ok(values);
+}
+
+/// Evaluate an expression with two sets of values of variables, like two snapshots of inputs,
+/// and report each subexpression whose value changed between them with its old and new values.
+/// Subexpressions are given from innermost to outermost, the whole expression being the last one
+/// if its value changed, so the changes explaining why the value of expression moved can be followed.
+/// Each element of a tuple like (a, b) is watched in turn.
+///
+/// If error occurs during evaluation, an error message is stored in string contained in Result output.
+///
+/// # Example
+/// ```
+/// use taz;
+/// use std::collections::HashMap;
+///
+/// let old: HashMap<String, f64> = HashMap::from([(String::from("x"), 3.0), (String::from("y"), 4.0)]);
+/// let new: HashMap<String, f64> = HashMap::from([(String::from("x"), 6.0), (String::from("y"), 4.0)]);
+///
+/// let changes: Vec<taz::Change> = taz::watch("sqrt(x^2 + y^2) + 1", &old, &new).unwrap();
+/// let last: &taz::Change = changes.last().unwrap();
+///
+/// assert_eq!(last.expression, "sqrt(x^2 + y^2) + 1");
+/// assert_eq!((last.old, last.new), (6.0, 1.0 + 52.0_f64.sqrt()));
+/// ```
+pub fn watch(
+    expression: &str,
+    old: &HashMap<String, f64>,
+    new: &HashMap<String, f64>,
+) -> Result<Vec<Change>, String> {
+    let ast: Ast = Ast::new(expression)?;
+    let mut changes: Vec<Change> = Vec::new();
+
+    match &ast.root {
+        Node::Tuple(elements) => {
+            for element in elements {
+                watch_node(element, [old, new], &mut changes)?;
+            }
+        }
+        root => {
+            watch_node(root, [old, new], &mut changes)?;
+        }
+    }
+
+    return Ok(changes);
+}
+
+// Units tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(x: f64, y: f64) -> HashMap<String, f64> {
+        return HashMap::from([(String::from("x"), x), (String::from("y"), y)]);
+    }
+
+    #[test]
+    fn test_watch_changes() {
+        match watch("2 * x + y * pi", &variables(1.0, 2.0), &variables(4.0, 2.0)) {
+            Ok(changes) => {
+                let expressions: Vec<&str> = changes
+                    .iter()
+                    .map(|change| change.expression.as_str())
+                    .collect();
+
+                assert_eq!(expressions, vec!["x", "2 * x", "2 * x + y * pi"]);
+                assert_eq!(changes[1].old, 2.0);
+                assert_eq!(changes[1].new, 8.0);
+                assert_eq!(changes[2].difference(), 6.0);
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_watch_tuple_and_lookup() {
+        match watch(
+            "(lookup(x, [0, 10], [0, 1]), y)",
+            &variables(5.0, 1.0),
+            &variables(5.0, 1.0),
+        ) {
+            Ok(changes) => assert!(changes.is_empty()),
+            Err(_) => assert!(false),
+        }
+
+        match watch(
+            "(lookup(x, [0, 10], [0, 1]), y)",
+            &variables(5.0, 1.0),
+            &variables(10.0, 1.0),
+        ) {
+            Ok(changes) => {
+                assert_eq!(changes.len(), 2);
+                assert_eq!(changes[1].expression, "lookup(x, [0, 10], [0, 1])");
+                assert_eq!(changes[1].difference(), 0.5);
+            }
+            Err(_) => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_watch_errors() {
+        assert_eq!(
+            watch("x + z", &variables(1.0, 2.0), &variables(1.0, 2.0)),
+            Err(String::from("Missing value of variable z"))
+        );
+        assert!(watch("x / (y - 2)", &variables(1.0, 3.0), &variables(1.0, 2.0)).is_err());
+    }
+}
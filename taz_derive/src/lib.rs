@@ -0,0 +1,130 @@
+#![allow(clippy::needless_return)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Field, Fields, LitStr, Type};
+
+/// Names of numeric types whose fields are bound as variables
+const NUMERIC_TYPES: [&str; 14] = [
+    "f64", "f32", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128",
+    "usize",
+];
+
+/// Check if type given in argument is a numeric type
+fn is_numeric(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => match type_path.path.get_ident() {
+            Some(ident) => NUMERIC_TYPES.contains(&ident.to_string().as_str()),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Options of field given by `#[taz(...)]` attributes
+struct FieldOptions {
+    skip: bool,
+    rename: Option<String>,
+}
+
+/// Read options of field from its `#[taz(...)]` attributes.
+/// If an attribute is not valid, a compilation error is returned
+fn field_options(field: &Field) -> syn::Result<FieldOptions> {
+    let mut options: FieldOptions = FieldOptions {
+        skip: false,
+        rename: None,
+    };
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("taz"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                options.skip = true;
+                return Ok(());
+            }
+
+            if meta.path.is_ident("rename") {
+                let name: LitStr = meta.value()?.parse()?;
+                options.rename = Some(name.value());
+                return Ok(());
+            }
+
+            return Err(meta.error("unknown taz attribute, expected `skip` or `rename`"));
+        })?;
+    }
+
+    return Ok(options);
+}
+
+/// Generate implementation of TazVariables trait for struct given in argument
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "TazVariables can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "TazVariables can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut insertions: Vec<TokenStream2> = Vec::with_capacity(fields.len());
+
+    for field in fields.iter() {
+        let options: FieldOptions = field_options(field)?;
+
+        if options.skip || !is_numeric(&field.ty) {
+            continue;
+        }
+
+        let ident = field.ident.as_ref().unwrap();
+        let name: String = options
+            .rename
+            .unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_string());
+
+        insertions.push(quote! {
+            variables.insert(::std::string::String::from(#name), self.#ident as f64);
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    return Ok(quote! {
+        impl #impl_generics ::taz::TazVariables for #ident #ty_generics #where_clause {
+            fn bind_variables(
+                &self,
+                variables: &mut ::std::collections::HashMap<::std::string::String, f64>,
+            ) {
+                #(#insertions)*
+            }
+        }
+    });
+}
+
+/// Derive TazVariables trait to bind numeric fields of a struct as variables of expressions.
+///
+/// Each numeric field is bound to a variable with the name of the field,
+/// other fields are ignored. A field can be renamed with `#[taz(rename = "name")]`
+/// or ignored with `#[taz(skip)]`.
+#[proc_macro_derive(TazVariables, attributes(taz))]
+pub fn derive_taz_variables(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input as DeriveInput);
+
+    match expand(&input) {
+        Ok(output) => output.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}